@@ -0,0 +1,48 @@
+use std::env;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Bakes the commit and build time into the binary for `/api/version`. Docker builds do
+// not ship `.git`, so CI passes the commit in through the GIT_COMMIT build argument.
+fn main() {
+    println!("cargo:rerun-if-env-changed=GIT_COMMIT");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+
+    let git_commit = env::var("GIT_COMMIT")
+        .ok()
+        .filter(|commit| !commit.trim().is_empty())
+        .or_else(|| {
+            Command::new("git")
+                .args(["rev-parse", "--short=12", "HEAD"])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .and_then(|output| String::from_utf8(output.stdout).ok())
+        })
+        .map(|commit| commit.trim().chars().take(12).collect::<String>())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=EAS_LISTENER_GIT_COMMIT={git_commit}");
+
+    let build_epoch = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.trim().parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or(0)
+        });
+    println!("cargo:rustc-env=EAS_LISTENER_BUILD_EPOCH={build_epoch}");
+
+    let mut features: Vec<String> = env::vars()
+        .filter_map(|(key, _)| key.strip_prefix("CARGO_FEATURE_").map(str::to_owned))
+        .map(|feature| feature.to_ascii_lowercase().replace('_', "-"))
+        .collect();
+    features.sort();
+    println!(
+        "cargo:rustc-env=EAS_LISTENER_FEATURES={}",
+        features.join(",")
+    );
+}
@@ -130,4 +130,5 @@ fi
 
 php-fpm8.4 -R
 nginx
-eas_listener
+# exec, so docker stop's SIGTERM reaches eas_listener and it can finish its recordings.
+exec eas_listener
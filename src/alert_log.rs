@@ -0,0 +1,1092 @@
+use crate::config::Config;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tokio::fs::{self, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+const GENESIS_PREV_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+const MAX_ROTATIONS_PER_MONTH: u32 = 10_000;
+
+static CHAIN_WRITE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+static DEDICATED_WRITE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct AlertLogRotation {
+    pub monthly: bool,
+    pub max_bytes: u64,
+    pub gzip: bool,
+    pub keep: usize,
+}
+
+impl Default for AlertLogRotation {
+    fn default() -> Self {
+        Self {
+            monthly: false,
+            max_bytes: 0,
+            gzip: false,
+            keep: 12,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChainEntryKind {
+    Genesis,
+    Alert,
+    Seal,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainEntry {
+    pub seq: u64,
+    pub kind: ChainEntryKind,
+    pub recorded_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_header: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eas_text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_file: Option<String>,
+    pub prev_hash: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub hash: String,
+}
+
+impl ChainEntry {
+    fn new(seq: u64, kind: ChainEntryKind, prev_hash: &str) -> Self {
+        Self {
+            seq,
+            kind,
+            recorded_at: Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            raw_header: None,
+            eas_text: None,
+            source: None,
+            previous_file: None,
+            prev_hash: prev_hash.to_string(),
+            hash: String::new(),
+        }
+    }
+
+    fn compute_hash(&self) -> String {
+        let mut unhashed = self.clone();
+        unhashed.hash.clear();
+        let bytes = serde_json::to_vec(&unhashed).unwrap_or_default();
+        Sha256::digest(&bytes)
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    fn sealed(mut self) -> Self {
+        self.hash = self.compute_hash();
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct ChainHead {
+    file: String,
+    seq: u64,
+    hash: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct PrunedChain {
+    segments: u64,
+    last_file: String,
+    last_hash: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainDivergence {
+    pub file: String,
+    pub line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainVerification {
+    pub valid: bool,
+    pub files_checked: Vec<String>,
+    pub entries_checked: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pruned: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_divergence: Option<ChainDivergence>,
+}
+
+pub fn chain_log_path(dedicated_alert_log_file: &Path) -> PathBuf {
+    dedicated_alert_log_file.with_extension("jsonl")
+}
+
+fn head_path(chain_path: &Path) -> PathBuf {
+    chain_sidecar_path(chain_path, ".head")
+}
+
+fn pruned_path(chain_path: &Path) -> PathBuf {
+    chain_sidecar_path(chain_path, ".pruned")
+}
+
+fn chain_sidecar_path(chain_path: &Path, suffix: &str) -> PathBuf {
+    let mut name = chain_path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(suffix);
+    chain_path.with_file_name(name)
+}
+
+fn file_name_of(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+async fn read_head(chain_path: &Path) -> Option<ChainHead> {
+    let contents = fs::read_to_string(head_path(chain_path)).await.ok()?;
+    serde_json::from_str(contents.trim()).ok()
+}
+
+async fn write_head(chain_path: &Path, head: &ChainHead) -> Result<()> {
+    let path = head_path(chain_path);
+    let tmp_path = path.with_extension("head.tmp");
+    fs::write(&tmp_path, serde_json::to_vec(head)?).await?;
+    fs::rename(&tmp_path, &path)
+        .await
+        .with_context(|| format!("failed to update chain head {}", path.display()))
+}
+
+async fn read_pruned(chain_path: &Path) -> Option<PrunedChain> {
+    let contents = fs::read_to_string(pruned_path(chain_path)).await.ok()?;
+    serde_json::from_str(contents.trim()).ok()
+}
+
+async fn write_pruned(chain_path: &Path, pruned: &PrunedChain) -> Result<()> {
+    let path = pruned_path(chain_path);
+    let tmp_path = path.with_extension("pruned.tmp");
+    fs::write(&tmp_path, serde_json::to_vec(pruned)?).await?;
+    fs::rename(&tmp_path, &path)
+        .await
+        .with_context(|| format!("failed to record pruned chain files in {}", path.display()))
+}
+
+async fn last_entry_in(chain_path: &Path) -> Result<Option<ChainEntry>> {
+    let contents = match fs::read_to_string(chain_path).await {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let Some(line) = contents.lines().rev().find(|line| !line.trim().is_empty()) else {
+        return Ok(None);
+    };
+    serde_json::from_str(line)
+        .map(Some)
+        .with_context(|| format!("last entry of {} is not valid JSON", chain_path.display()))
+}
+
+async fn append_entries(chain_path: &Path, entries: &[ChainEntry]) -> Result<()> {
+    let mut payload = Vec::new();
+    for entry in entries {
+        serde_json::to_writer(&mut payload, entry)?;
+        payload.push(b'\n');
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(chain_path)
+        .await
+        .with_context(|| format!("failed to open {}", chain_path.display()))?;
+    file.write_all(&payload).await?;
+    file.sync_data().await?;
+    Ok(())
+}
+
+async fn rotate(
+    chain_path: &Path,
+    head: &ChainHead,
+    rotation: &AlertLogRotation,
+    month: &str,
+) -> Result<ChainEntry> {
+    let seal = ChainEntry::new(head.seq + 1, ChainEntryKind::Seal, &head.hash).sealed();
+    append_entries(chain_path, std::slice::from_ref(&seal)).await?;
+
+    let mut rotated_path = rotated_path(chain_path, month).await?;
+    fs::rename(chain_path, &rotated_path)
+        .await
+        .with_context(|| format!("failed to rotate {}", chain_path.display()))?;
+    if rotation.gzip {
+        let plain = rotated_path.clone();
+        match tokio::task::spawn_blocking(move || gzip_file(&plain)).await? {
+            Ok(gz_path) => rotated_path = gz_path,
+            Err(err) => warn!("Failed to compress {}: {:#}", rotated_path.display(), err),
+        }
+    }
+    info!(
+        "Sealed alert log chain at {} and started a new chain",
+        rotated_path.display()
+    );
+    prune_chain(chain_path, rotation.keep).await;
+
+    let mut genesis = ChainEntry::new(seal.seq + 1, ChainEntryKind::Genesis, &seal.hash);
+    genesis.previous_file = Some(file_name_of(&rotated_path));
+    Ok(genesis.sealed())
+}
+
+async fn chain_rotation_month(
+    chain_path: &Path,
+    rotation: &AlertLogRotation,
+    now: DateTime<Tz>,
+) -> Result<Option<String>> {
+    let metadata = match fs::metadata(chain_path).await {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let last_written = DateTime::<Utc>::from(metadata.modified()?).with_timezone(&now.timezone());
+    let month = last_written.format("%Y-%m").to_string();
+    let size = metadata.len();
+    let due = (rotation.max_bytes > 0 && size >= rotation.max_bytes)
+        || (rotation.monthly && month != now.format("%Y-%m").to_string());
+    Ok(due.then_some(month))
+}
+
+fn follows_head(entry: &ChainEntry, head: &ChainHead) -> bool {
+    entry.seq == head.seq + 1 && entry.prev_hash == head.hash && entry.compute_hash() == entry.hash
+}
+
+async fn append_alert_entry(
+    chain_path: &Path,
+    rotation: &AlertLogRotation,
+    now: DateTime<Tz>,
+    raw_header: &str,
+    eas_text: &str,
+    source: Option<&str>,
+    received_at: DateTime<Utc>,
+) -> Result<()> {
+    let _guard = CHAIN_WRITE_LOCK.lock().await;
+
+    let head = match read_head(chain_path).await {
+        Some(head) => match last_entry_in(chain_path).await {
+            Ok(Some(entry)) if follows_head(&entry, &head) => {
+                warn!(
+                    "Alert log chain head is behind the log; resuming from last entry of {}",
+                    chain_path.display()
+                );
+                Some(ChainHead {
+                    file: file_name_of(chain_path),
+                    seq: entry.seq,
+                    hash: entry.hash,
+                })
+            }
+            _ => Some(head),
+        },
+        None => last_entry_in(chain_path).await?.map(|entry| {
+            warn!(
+                "Alert log chain head missing; resuming from last entry of {}",
+                chain_path.display()
+            );
+            ChainHead {
+                file: file_name_of(chain_path),
+                seq: entry.seq,
+                hash: entry.hash,
+            }
+        }),
+    };
+
+    let mut pending = Vec::new();
+    let (seq, prev_hash) = match head {
+        None => {
+            let genesis = ChainEntry::new(0, ChainEntryKind::Genesis, GENESIS_PREV_HASH).sealed();
+            let link = (genesis.seq + 1, genesis.hash.clone());
+            pending.push(genesis);
+            link
+        }
+        Some(head) => match chain_rotation_month(chain_path, rotation, now).await? {
+            Some(month) => {
+                let genesis = rotate(chain_path, &head, rotation, &month).await?;
+                let link = (genesis.seq + 1, genesis.hash.clone());
+                pending.push(genesis);
+                link
+            }
+            None => (head.seq + 1, head.hash),
+        },
+    };
+
+    let mut entry = ChainEntry::new(seq, ChainEntryKind::Alert, &prev_hash);
+    entry.recorded_at = received_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    entry.raw_header = Some(raw_header.to_string());
+    entry.eas_text = Some(eas_text.trim_end().to_string());
+    entry.source = source.map(str::to_string);
+    let entry = entry.sealed();
+    pending.push(entry.clone());
+
+    append_entries(chain_path, &pending).await?;
+    write_head(
+        chain_path,
+        &ChainHead {
+            file: file_name_of(chain_path),
+            seq: entry.seq,
+            hash: entry.hash,
+        },
+    )
+    .await
+}
+
+pub async fn append_chained_alert(
+    config: &Config,
+    raw_header: &str,
+    eas_text: &str,
+    source: Option<&str>,
+    received_at: DateTime<Utc>,
+) -> Result<()> {
+    let rotation = AlertLogRotation {
+        max_bytes: [
+            config.alert_log_chain_max_bytes,
+            config.alert_log_rotation.max_bytes,
+        ]
+        .into_iter()
+        .filter(|max| *max > 0)
+        .min()
+        .unwrap_or(0),
+        ..config.alert_log_rotation
+    };
+    append_alert_entry(
+        &chain_log_path(&config.dedicated_alert_log_file),
+        &rotation,
+        Utc::now().with_timezone(&config.timezone),
+        raw_header,
+        eas_text,
+        source,
+        received_at,
+    )
+    .await
+}
+
+async fn prune_rotated(
+    current: &Path,
+    keep: usize,
+    rotated_key: impl Fn(&str) -> Option<(String, u32)>,
+) {
+    remove_rotated(rotated_excess(current, keep, rotated_key).await).await;
+}
+
+async fn prune_chain(chain_path: &Path, keep: usize) {
+    let doomed = rotated_excess(chain_path, keep, |name| rotated_chain_key(chain_path, name)).await;
+    let Some(newest) = doomed.last() else {
+        return;
+    };
+    let seal = match read_chain_file(newest).await {
+        Ok(contents) => contents
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .and_then(|line| serde_json::from_str::<ChainEntry>(line).ok())
+            .filter(|entry| entry.kind == ChainEntryKind::Seal),
+        Err(_) => None,
+    };
+    let Some(seal) = seal else {
+        warn!(
+            "Keeping old alert log chain files; {} does not end with a seal record",
+            newest.display()
+        );
+        return;
+    };
+    let pruned = PrunedChain {
+        segments: read_pruned(chain_path)
+            .await
+            .map_or(0, |pruned| pruned.segments)
+            + doomed.len() as u64,
+        last_file: file_name_of(newest),
+        last_hash: seal.hash,
+    };
+    if let Err(err) = write_pruned(chain_path, &pruned).await {
+        warn!("Keeping old alert log chain files: {:#}", err);
+        return;
+    }
+    remove_rotated(doomed).await;
+}
+
+async fn rotated_excess(
+    current: &Path,
+    keep: usize,
+    rotated_key: impl Fn(&str) -> Option<(String, u32)>,
+) -> Vec<PathBuf> {
+    let Some(dir) = current.parent() else {
+        return Vec::new();
+    };
+    if keep == 0 {
+        return Vec::new();
+    }
+    let Ok(mut entries) = fs::read_dir(dir).await else {
+        return Vec::new();
+    };
+    let mut rotated = Vec::new();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        if path == current {
+            continue;
+        }
+        if let Some(key) = path
+            .file_name()
+            .and_then(|name| rotated_key(&name.to_string_lossy()))
+        {
+            rotated.push((key, path));
+        }
+    }
+    rotated.sort();
+    let excess = rotated.len().saturating_sub(keep);
+    rotated
+        .into_iter()
+        .take(excess)
+        .map(|(_, path)| path)
+        .collect()
+}
+
+async fn remove_rotated(paths: Vec<PathBuf>) {
+    for path in paths {
+        match fs::remove_file(&path).await {
+            Ok(()) => info!("Deleted old rotated alert log {}", path.display()),
+            Err(err) => warn!(
+                "Failed to delete rotated alert log {}: {}",
+                path.display(),
+                err
+            ),
+        }
+    }
+}
+
+fn rotated_key(log_path: &Path, name: &str) -> Option<(String, u32)> {
+    let stem = log_path.file_stem()?.to_string_lossy();
+    let ext = log_path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    let rest = name.strip_prefix(stem.as_ref())?.strip_prefix('-')?;
+    let rest = rest.strip_suffix(".gz").unwrap_or(rest);
+    let rest = rest.strip_suffix(ext.as_str())?;
+    let (month, number) = match rest.split_once('.') {
+        Some((month, number)) => (month, number.parse().ok()?),
+        None => (rest, 0),
+    };
+    chrono::NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d").ok()?;
+    Some((month.to_string(), number))
+}
+
+fn rotated_chain_key(chain_path: &Path, name: &str) -> Option<(String, u32)> {
+    rotated_key(chain_path, name).or_else(|| {
+        let stem = chain_path.file_stem()?.to_string_lossy();
+        let stamp = name
+            .strip_prefix(stem.as_ref())?
+            .strip_prefix('.')?
+            .strip_suffix(".jsonl")?;
+        let stamped = chrono::NaiveDateTime::parse_from_str(stamp, "%Y%m%dT%H%M%SZ").ok()?;
+        Some((stamped.format("%Y-%m").to_string(), 0))
+    })
+}
+
+async fn rotated_path(log_path: &Path, month: &str) -> Result<PathBuf> {
+    let stem = log_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "alerts".to_string());
+    let ext = log_path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    let dir = log_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let mut entries = fs::read_dir(dir)
+        .await
+        .with_context(|| format!("failed to list {}", dir.display()))?;
+    let mut number = 0;
+    while let Some(entry) = entries.next_entry().await? {
+        if let Some((rotated_month, rotated_number)) =
+            rotated_key(log_path, &entry.file_name().to_string_lossy())
+        {
+            if rotated_month == month {
+                number = number.max(rotated_number + 1);
+            }
+        }
+    }
+    if number >= MAX_ROTATIONS_PER_MONTH {
+        return Err(anyhow!(
+            "no unused rotated name for {} in {}",
+            log_path.display(),
+            month
+        ));
+    }
+    let name = if number == 0 {
+        format!("{stem}-{month}{ext}")
+    } else {
+        format!("{stem}-{month}.{number}{ext}")
+    };
+    Ok(log_path.with_file_name(name))
+}
+
+fn gzip_file(path: &Path) -> Result<PathBuf> {
+    let mut gz_path = path.as_os_str().to_owned();
+    gz_path.push(".gz");
+    let gz_path = PathBuf::from(gz_path);
+    let mut input = std::fs::File::open(path)?;
+    let mut encoder = flate2::write::GzEncoder::new(
+        std::fs::File::create(&gz_path)?,
+        flate2::Compression::default(),
+    );
+    std::io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?.sync_all()?;
+    std::fs::remove_file(path)?;
+    Ok(gz_path)
+}
+
+async fn rotate_dedicated_if_due(
+    log_path: &Path,
+    rotation: &AlertLogRotation,
+    incoming: u64,
+    now: DateTime<Tz>,
+) -> Result<Option<PathBuf>> {
+    if !rotation.monthly && rotation.max_bytes == 0 {
+        return Ok(None);
+    }
+    let metadata = match fs::metadata(log_path).await {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let last_written = DateTime::<Utc>::from(metadata.modified()?).with_timezone(&now.timezone());
+    let month = last_written.format("%Y-%m").to_string();
+    let new_month = month != now.format("%Y-%m").to_string();
+    let too_big = rotation.max_bytes > 0
+        && metadata.len() > 0
+        && metadata.len() + incoming > rotation.max_bytes;
+    let due = too_big || (rotation.monthly && new_month);
+    if !due {
+        return Ok(None);
+    }
+
+    let mut rotated = rotated_path(log_path, &month).await?;
+    fs::rename(log_path, &rotated)
+        .await
+        .with_context(|| format!("failed to rotate {}", log_path.display()))?;
+    if rotation.gzip {
+        let plain = rotated.clone();
+        match tokio::task::spawn_blocking(move || gzip_file(&plain)).await? {
+            Ok(gz_path) => rotated = gz_path,
+            Err(err) => warn!("Failed to compress {}: {:#}", rotated.display(), err),
+        }
+    }
+    info!("Rotated the dedicated alert log to {}", rotated.display());
+    prune_rotated(log_path, rotation.keep, |name| rotated_key(log_path, name)).await;
+    Ok(Some(rotated))
+}
+
+pub async fn append_dedicated(config: &Config, text: &str) -> Result<()> {
+    let _guard = DEDICATED_WRITE_LOCK.lock().await;
+    let log_path = &config.dedicated_alert_log_file;
+    let now = Utc::now().with_timezone(&config.timezone);
+    if let Err(err) =
+        rotate_dedicated_if_due(log_path, &config.alert_log_rotation, text.len() as u64, now).await
+    {
+        warn!("Failed to rotate the dedicated alert log: {:#}", err);
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path)
+        .await
+        .with_context(|| format!("failed to open {}", log_path.display()))?;
+    file.write_all(text.as_bytes()).await?;
+    Ok(())
+}
+
+async fn read_chain_file(path: &Path) -> std::io::Result<String> {
+    if path.extension().is_some_and(|ext| ext == "gz") {
+        let path = path.to_path_buf();
+        return tokio::task::spawn_blocking(move || {
+            let mut contents = String::new();
+            flate2::read::GzDecoder::new(std::fs::File::open(&path)?)
+                .read_to_string(&mut contents)?;
+            Ok(contents)
+        })
+        .await
+        .map_err(std::io::Error::other)?;
+    }
+    fs::read_to_string(path).await
+}
+
+async fn chain_files(chain_path: &Path) -> Vec<PathBuf> {
+    let mut files = vec![chain_path.to_path_buf()];
+    loop {
+        let Ok(contents) = read_chain_file(&files[0]).await else {
+            break;
+        };
+        let previous = contents
+            .lines()
+            .next()
+            .and_then(|line| serde_json::from_str::<ChainEntry>(line).ok())
+            .and_then(|entry| entry.previous_file)
+            .map(|name| chain_path.with_file_name(name));
+        match previous {
+            Some(path) if !files.contains(&path) && fs::metadata(&path).await.is_ok() => {
+                files.insert(0, path)
+            }
+            _ => break,
+        }
+    }
+    files
+}
+
+async fn verify_chain_at(chain_path: &Path) -> Result<ChainVerification> {
+    let _guard = CHAIN_WRITE_LOCK.lock().await;
+    let files = chain_files(chain_path).await;
+    let mut report = ChainVerification {
+        valid: true,
+        files_checked: files.iter().map(|path| file_name_of(path)).collect(),
+        entries_checked: 0,
+        head_hash: None,
+        pruned: None,
+        first_divergence: None,
+    };
+    let mut previous: Option<ChainEntry> = None;
+    let head = read_head(chain_path).await;
+    let pruned = read_pruned(chain_path).await;
+
+    'files: for (file_idx, path) in files.iter().enumerate() {
+        let contents = match read_chain_file(path).await {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(anyhow!("failed to read {}: {}", path.display(), err)),
+        };
+        let file = file_name_of(path);
+        let mut first_in_file = true;
+
+        for (line_idx, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let diverge = |seq: Option<u64>, reason: String| ChainDivergence {
+                file: file.clone(),
+                line: line_idx + 1,
+                seq,
+                reason,
+            };
+
+            let entry: ChainEntry = match serde_json::from_str(line) {
+                Ok(entry) => entry,
+                Err(err) => {
+                    report.first_divergence =
+                        Some(diverge(None, format!("entry is not valid JSON: {err}")));
+                    break 'files;
+                }
+            };
+            let seq = Some(entry.seq);
+
+            let problem = if entry.compute_hash() != entry.hash {
+                Some("entry contents do not match its hash".to_string())
+            } else if let Some(prev) = previous.as_ref() {
+                if entry.prev_hash != prev.hash {
+                    Some(format!(
+                        "prev_hash does not match the hash of entry {}",
+                        prev.seq
+                    ))
+                } else if entry.seq != prev.seq + 1 {
+                    Some(format!("sequence jumps from {} to {}", prev.seq, entry.seq))
+                } else if first_in_file
+                    && (entry.kind != ChainEntryKind::Genesis || prev.kind != ChainEntryKind::Seal)
+                {
+                    Some("file does not continue from a sealed previous file".to_string())
+                } else if prev.kind == ChainEntryKind::Seal && !first_in_file {
+                    Some("entries follow a seal record".to_string())
+                } else {
+                    None
+                }
+            } else if file_idx == 0 && line_idx == 0 && entry.kind != ChainEntryKind::Genesis {
+                Some("chain does not start with a genesis record".to_string())
+            } else if file_idx == 0 && line_idx == 0 {
+                match (&entry.previous_file, &pruned) {
+                    (None, _) if entry.prev_hash != GENESIS_PREV_HASH => {
+                        Some("genesis record does not start a new chain".to_string())
+                    }
+                    (None, _) => None,
+                    (Some(name), Some(pruned))
+                        if *name == pruned.last_file && entry.prev_hash == pruned.last_hash =>
+                    {
+                        report.pruned = Some(format!(
+                            "{} segments pruned, continuing from hash {}",
+                            pruned.segments, pruned.last_hash
+                        ));
+                        None
+                    }
+                    (Some(name), _) => Some(format!(
+                        "previous file {name} is missing and was not recorded as pruned"
+                    )),
+                }
+            } else {
+                None
+            };
+
+            if let Some(reason) = problem {
+                report.first_divergence = Some(diverge(seq, reason));
+                break 'files;
+            }
+
+            first_in_file = false;
+            report.entries_checked += 1;
+            previous = Some(entry);
+        }
+    }
+
+    if report.first_divergence.is_none() {
+        report.head_hash = head.as_ref().map(|head| head.hash.clone());
+        let head_matches = match (&head, &previous) {
+            (None, None) => true,
+            (Some(head), Some(last)) => head.hash == last.hash || follows_head(last, head),
+            _ => false,
+        };
+        if !head_matches {
+            report.first_divergence = Some(ChainDivergence {
+                file: file_name_of(chain_path),
+                line: 0,
+                seq: previous.as_ref().map(|entry| entry.seq),
+                reason: "chain head does not match the last entry (log truncated or head edited)"
+                    .to_string(),
+            });
+        }
+    }
+
+    report.valid = report.first_divergence.is_none();
+    Ok(report)
+}
+
+pub async fn verify_chain(config: &Config) -> Result<ChainVerification> {
+    verify_chain_at(&chain_log_path(&config.dedicated_alert_log_file)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn append(chain_path: &Path, max_bytes: u64, header: &str) {
+        append_alert_entry(
+            chain_path,
+            &AlertLogRotation {
+                max_bytes,
+                keep: 0,
+                ..AlertLogRotation::default()
+            },
+            Utc::now().with_timezone(&Tz::UTC),
+            header,
+            "Text",
+            Some("stream"),
+            Utc::now(),
+        )
+        .await
+        .expect("append");
+    }
+
+    #[tokio::test]
+    async fn chain_verifies_and_detects_edits() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let chain_path = chain_log_path(&dir.path().join("dedicated-alerts.log"));
+
+        append(&chain_path, 0, "ZCZC-WXR-TOR-031055+0030-1231645-KWO35-").await;
+        append(&chain_path, 0, "ZCZC-WXR-SVR-031055+0030-1231650-KWO35-").await;
+
+        let report = verify_chain_at(&chain_path).await.expect("verify");
+        assert!(report.valid, "{:?}", report.first_divergence);
+        assert_eq!(report.entries_checked, 3);
+
+        let contents = fs::read_to_string(&chain_path).await.expect("read");
+        fs::write(&chain_path, contents.replace("SVR", "TOR"))
+            .await
+            .expect("write");
+        let report = verify_chain_at(&chain_path).await.expect("verify");
+        assert!(!report.valid);
+        let divergence = report.first_divergence.expect("divergence");
+        assert_eq!(divergence.line, 3);
+        assert_eq!(divergence.seq, Some(2));
+    }
+
+    #[tokio::test]
+    async fn a_head_left_behind_by_a_crash_is_caught_up_from_the_log() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let chain_path = chain_log_path(&dir.path().join("dedicated-alerts.log"));
+
+        append(&chain_path, 0, "ZCZC-WXR-TOR-031055+0030-1231645-KWO35-").await;
+        let stale_head = fs::read(head_path(&chain_path)).await.expect("head");
+        append(&chain_path, 0, "ZCZC-WXR-SVR-031055+0030-1231650-KWO35-").await;
+        fs::write(head_path(&chain_path), &stale_head)
+            .await
+            .expect("restore head");
+        let report = verify_chain_at(&chain_path).await.expect("verify");
+        assert!(report.valid, "{:?}", report.first_divergence);
+
+        append(&chain_path, 0, "ZCZC-WXR-FFW-031055+0030-1231655-KWO35-").await;
+        let report = verify_chain_at(&chain_path).await.expect("verify");
+        assert!(report.valid, "{:?}", report.first_divergence);
+        assert_eq!(report.entries_checked, 4);
+
+        let contents = fs::read_to_string(&chain_path).await.expect("read");
+        let truncated: String = contents
+            .lines()
+            .take(3)
+            .map(|line| format!("{line}\n"))
+            .collect();
+        fs::write(&chain_path, truncated).await.expect("truncate");
+        let report = verify_chain_at(&chain_path).await.expect("verify");
+        assert!(!report.valid);
+
+        let chain_path = chain_log_path(&dir.path().join("other-alerts.log"));
+        append(&chain_path, 0, "ZCZC-WXR-TOR-031055+0030-1231645-KWO35-").await;
+        let pinned_head = fs::read(head_path(&chain_path)).await.expect("head");
+        append(&chain_path, 0, "ZCZC-WXR-SVR-031055+0030-1231650-KWO35-").await;
+        append(&chain_path, 0, "ZCZC-WXR-FFW-031055+0030-1231655-KWO35-").await;
+        fs::write(head_path(&chain_path), &pinned_head)
+            .await
+            .expect("restore head");
+        let report = verify_chain_at(&chain_path).await.expect("verify");
+        assert!(!report.valid);
+
+        append(&chain_path, 0, "ZCZC-WXR-EVI-031055+0030-1231700-KWO35-").await;
+        let report = verify_chain_at(&chain_path).await.expect("verify");
+        assert!(!report.valid);
+    }
+
+    #[tokio::test]
+    async fn rotation_seals_file_and_links_new_chain() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let chain_path = chain_log_path(&dir.path().join("dedicated-alerts.log"));
+
+        append(&chain_path, 1, "ZCZC-WXR-TOR-031055+0030-1231645-KWO35-").await;
+        append(&chain_path, 1, "ZCZC-WXR-SVR-031055+0030-1231650-KWO35-").await;
+
+        let report = verify_chain_at(&chain_path).await.expect("verify");
+        assert!(report.valid, "{:?}", report.first_divergence);
+        assert_eq!(report.files_checked.len(), 2);
+        assert_eq!(report.entries_checked, 5);
+
+        let first = fs::read_to_string(&chain_path).await.expect("read");
+        let genesis: ChainEntry =
+            serde_json::from_str(first.lines().next().unwrap()).expect("genesis");
+        assert_eq!(genesis.kind, ChainEntryKind::Genesis);
+        assert_eq!(
+            genesis.previous_file.as_deref(),
+            Some(report.files_checked[0].as_str())
+        );
+
+        fs::remove_file(head_path(&chain_path))
+            .await
+            .expect("remove head");
+        let report = verify_chain_at(&chain_path).await.expect("verify");
+        assert!(!report.valid);
+    }
+
+    #[tokio::test]
+    async fn chain_rotates_by_month_into_gzipped_files_that_still_verify() {
+        use chrono::TimeZone;
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        let chain_path = chain_log_path(&dir.path().join("dedicated-alerts.log"));
+        let september = Utc.with_ymd_and_hms(2026, 9, 30, 12, 0, 0).unwrap();
+        let rotation = AlertLogRotation {
+            monthly: true,
+            gzip: true,
+            ..AlertLogRotation::default()
+        };
+        let append = |header: &'static str| {
+            append_alert_entry(
+                &chain_path,
+                &rotation,
+                Utc::now().with_timezone(&Tz::UTC),
+                header,
+                "Text",
+                None,
+                Utc::now(),
+            )
+        };
+
+        append("ZCZC-WXR-TOR-031055+0030-2731200-KWO35-")
+            .await
+            .expect("append");
+        std::fs::File::options()
+            .write(true)
+            .open(&chain_path)
+            .and_then(|file| file.set_modified(september.into()))
+            .expect("set mtime");
+        append("ZCZC-WXR-SVR-031055+0030-2731205-KWO35-")
+            .await
+            .expect("append");
+
+        assert!(dir
+            .path()
+            .join("dedicated-alerts-2026-09.jsonl.gz")
+            .exists());
+        let report = verify_chain_at(&chain_path).await.expect("verify");
+        assert!(report.valid, "{:?}", report.first_divergence);
+        assert_eq!(
+            report.files_checked,
+            [
+                "dedicated-alerts-2026-09.jsonl.gz",
+                "dedicated-alerts.jsonl"
+            ]
+        );
+        assert_eq!(report.entries_checked, 5);
+    }
+
+    #[tokio::test]
+    async fn pruned_chain_files_are_recorded_and_reported_by_verification() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let chain_path = chain_log_path(&dir.path().join("dedicated-alerts.log"));
+        let rotation = AlertLogRotation {
+            max_bytes: 1,
+            keep: 1,
+            ..AlertLogRotation::default()
+        };
+        for header in [
+            "ZCZC-WXR-TOR-031055+0030-1231645-KWO35-",
+            "ZCZC-WXR-SVR-031055+0030-1231650-KWO35-",
+            "ZCZC-WXR-FFW-031055+0030-1231655-KWO35-",
+            "ZCZC-WXR-EVI-031055+0030-1231700-KWO35-",
+        ] {
+            append_alert_entry(
+                &chain_path,
+                &rotation,
+                Utc::now().with_timezone(&Tz::UTC),
+                header,
+                "Text",
+                None,
+                Utc::now(),
+            )
+            .await
+            .expect("append");
+        }
+
+        let pruned = read_pruned(&chain_path).await.expect("pruned record");
+        assert_eq!(pruned.segments, 2);
+        let report = verify_chain_at(&chain_path).await.expect("verify");
+        assert!(report.valid, "{:?}", report.first_divergence);
+        assert_eq!(report.files_checked.len(), 2);
+        assert_eq!(
+            report.pruned,
+            Some(format!(
+                "2 segments pruned, continuing from hash {}",
+                pruned.last_hash
+            ))
+        );
+
+        fs::remove_file(pruned_path(&chain_path))
+            .await
+            .expect("remove pruned record");
+        let report = verify_chain_at(&chain_path).await.expect("verify");
+        assert!(!report.valid);
+        assert!(report
+            .first_divergence
+            .expect("divergence")
+            .reason
+            .contains("not recorded as pruned"));
+    }
+
+    #[tokio::test]
+    async fn dedicated_log_rotates_by_month_and_size_and_prunes_old_rotations() {
+        use chrono::TimeZone;
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        let log_path = dir.path().join("dedicated-alerts.log");
+        let september = Utc.with_ymd_and_hms(2026, 9, 30, 12, 0, 0).unwrap();
+        let october = Utc
+            .with_ymd_and_hms(2026, 10, 16, 12, 0, 0)
+            .unwrap()
+            .with_timezone(&Tz::UTC);
+        let write_log = |modified: DateTime<Utc>| {
+            std::fs::write(
+                &log_path,
+                "ZCZC-WXR-TOR-031055+0030-2731200-KWO35-: Text\n\n",
+            )
+            .expect("write");
+            std::fs::File::options()
+                .write(true)
+                .open(&log_path)
+                .and_then(|file| file.set_modified(modified.into()))
+                .expect("set mtime");
+        };
+        let monthly = AlertLogRotation {
+            monthly: true,
+            ..AlertLogRotation::default()
+        };
+
+        write_log(october.with_timezone(&Utc));
+        assert_eq!(
+            rotate_dedicated_if_due(&log_path, &monthly, 10, october)
+                .await
+                .expect("rotate"),
+            None
+        );
+
+        write_log(september);
+        let rotated = rotate_dedicated_if_due(&log_path, &monthly, 10, october)
+            .await
+            .expect("rotate");
+        assert_eq!(
+            rotated,
+            Some(dir.path().join("dedicated-alerts-2026-09.log"))
+        );
+        assert!(!log_path.exists());
+
+        write_log(september);
+        let gzipped = AlertLogRotation {
+            max_bytes: 50,
+            gzip: true,
+            keep: 2,
+            ..AlertLogRotation::default()
+        };
+        let rotated = rotate_dedicated_if_due(&log_path, &gzipped, 10, october)
+            .await
+            .expect("rotate")
+            .expect("rotated");
+        assert_eq!(
+            rotated,
+            dir.path().join("dedicated-alerts-2026-09.1.log.gz")
+        );
+        let mut text = String::new();
+        flate2::read::GzDecoder::new(std::fs::File::open(&rotated).expect("open"))
+            .read_to_string(&mut text)
+            .expect("gunzip");
+        assert!(text.starts_with("ZCZC-WXR-TOR"));
+
+        write_log(october.with_timezone(&Utc));
+        rotate_dedicated_if_due(&log_path, &gzipped, 10, october)
+            .await
+            .expect("rotate")
+            .expect("rotated");
+        let mut left: Vec<_> = std::fs::read_dir(dir.path())
+            .expect("read dir")
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+        left.sort();
+        assert_eq!(
+            left,
+            [
+                "dedicated-alerts-2026-09.1.log.gz",
+                "dedicated-alerts-2026-10.log.gz"
+            ]
+        );
+    }
+}
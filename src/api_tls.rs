@@ -0,0 +1,110 @@
+use crate::config::Config;
+use anyhow::{anyhow, Context, Result};
+use axum_server::tls_rustls::RustlsConfig;
+use rustls::ServerConfig;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::broadcast::{self, Receiver as BroadcastReceiver};
+use tracing::{info, warn};
+
+fn load_server_config(cert_path: &Path, key_path: &Path) -> Result<ServerConfig> {
+    let cert_file = File::open(cert_path)
+        .with_context(|| format!("Failed to open API_TLS_CERT_PATH {}", cert_path.display()))?;
+    let certs = rustls_pemfile::certs(&mut BufReader::new(cert_file))
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("Failed to read certificates from {}", cert_path.display()))?;
+    if certs.is_empty() {
+        return Err(anyhow!(
+            "API_TLS_CERT_PATH {} contains no PEM certificates",
+            cert_path.display()
+        ));
+    }
+
+    let key_file = File::open(key_path)
+        .with_context(|| format!("Failed to open API_TLS_KEY_PATH {}", key_path.display()))?;
+    let key = rustls_pemfile::private_key(&mut BufReader::new(key_file))
+        .with_context(|| format!("Failed to read the private key from {}", key_path.display()))?
+        .ok_or_else(|| {
+            anyhow!(
+                "API_TLS_KEY_PATH {} contains no PEM private key",
+                key_path.display()
+            )
+        })?;
+
+    let mut server_config =
+        ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()?
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .context("The API TLS certificate and private key do not match")?;
+    server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(server_config)
+}
+
+pub fn load(config: &Config) -> Result<Option<RustlsConfig>> {
+    let (Some(cert_path), Some(key_path)) = (&config.api_tls_cert_path, &config.api_tls_key_path)
+    else {
+        return Ok(None);
+    };
+    let server_config = load_server_config(cert_path, key_path)?;
+    Ok(Some(RustlsConfig::from_config(Arc::new(server_config))))
+}
+
+pub async fn run_tls_reloader(
+    tls: RustlsConfig,
+    mut reload_rx: BroadcastReceiver<Config>,
+) -> Result<()> {
+    crate::reload::register("api_tls");
+    loop {
+        let config = match reload_rx.recv().await {
+            Ok(config) => config,
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => return Ok(()),
+        };
+        let (Some(cert_path), Some(key_path)) =
+            (&config.api_tls_cert_path, &config.api_tls_key_path)
+        else {
+            warn!("API TLS was turned off in the configuration; restart to serve plain HTTP.");
+            crate::reload::acknowledge("api_tls");
+            continue;
+        };
+        match load_server_config(cert_path, key_path) {
+            Ok(server_config) => {
+                tls.reload_from_config(Arc::new(server_config));
+                info!("Reloaded the monitoring API TLS certificate.");
+            }
+            Err(err) => warn!(
+                "Keeping the current API TLS certificate; reloading failed: {:#}",
+                err
+            ),
+        }
+        crate::reload::acknowledge("api_tls");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tls_files_that_cannot_be_used_are_errors() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let cert = dir.path().join("cert.pem");
+        let key = dir.path().join("key.pem");
+
+        let mut config = Config::safe_internal_defaults();
+        assert!(load(&config).expect("plain HTTP").is_none());
+
+        config.api_tls_cert_path = Some(cert.clone());
+        config.api_tls_key_path = Some(key.clone());
+        let err = load(&config).expect_err("missing files").to_string();
+        assert!(err.contains("API_TLS_CERT_PATH"), "{err}");
+
+        std::fs::write(&cert, "not a certificate\n").expect("write cert");
+        std::fs::write(&key, "not a key\n").expect("write key");
+        let err = load(&config).expect_err("no certificates").to_string();
+        assert!(err.contains("no PEM certificates"), "{err}");
+    }
+}
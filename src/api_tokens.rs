@@ -0,0 +1,146 @@
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ApiTokenScope {
+    Read,
+    Admin,
+}
+
+impl ApiTokenScope {
+    pub fn allows(self, required: ApiTokenScope) -> bool {
+        self >= required
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ApiToken {
+    pub name: String,
+    pub token: String,
+    pub scope: ApiTokenScope,
+}
+
+impl std::fmt::Debug for ApiToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiToken")
+            .field("name", &self.name)
+            .field("token", &"<redacted>")
+            .field("scope", &self.scope)
+            .finish()
+    }
+}
+
+fn parse_api_token(index: usize, entry: &Value) -> Result<ApiToken> {
+    let key = format!("API_TOKENS[{index}]");
+    let name = entry
+        .get("name")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| anyhow!("{key}.name must be a non-empty string in your config.json file"))?;
+    let token = entry
+        .get("token")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .ok_or_else(|| anyhow!("{key}.token must be a string in your config.json file"))?;
+    if token.len() < 16 {
+        return Err(anyhow!(
+            "{key}.token must be at least 16 characters in your config.json file"
+        ));
+    }
+    let scope = match entry.get("scope") {
+        None | Some(Value::Null) => ApiTokenScope::Read,
+        Some(Value::String(scope)) => match scope.trim().to_ascii_lowercase().as_str() {
+            "read" => ApiTokenScope::Read,
+            "admin" => ApiTokenScope::Admin,
+            _ => {
+                return Err(anyhow!(
+                    "{key}.scope must be \"read\" or \"admin\" in your config.json file"
+                ))
+            }
+        },
+        Some(_) => {
+            return Err(anyhow!(
+                "{key}.scope must be \"read\" or \"admin\" in your config.json file"
+            ))
+        }
+    };
+
+    Ok(ApiToken {
+        name: name.to_string(),
+        token: token.to_string(),
+        scope,
+    })
+}
+
+pub fn parse_api_tokens(config_json: &Value) -> Result<Vec<ApiToken>> {
+    let Some(value) = config_json.get("API_TOKENS") else {
+        return Ok(Vec::new());
+    };
+    let Some(entries) = value.as_array() else {
+        return Err(anyhow!(
+            "API_TOKENS must be an array in your config.json file"
+        ));
+    };
+
+    let tokens = entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| parse_api_token(index, entry))
+        .collect::<Result<Vec<_>>>()?;
+    for (index, token) in tokens.iter().enumerate() {
+        if tokens[..index].iter().any(|other| other.name == token.name) {
+            return Err(anyhow!(
+                "API_TOKENS has more than one token named {:?} in your config.json file",
+                token.name
+            ));
+        }
+    }
+    Ok(tokens)
+}
+
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+pub fn find_api_token<'a>(tokens: &'a [ApiToken], presented: &str) -> Option<&'a ApiToken> {
+    tokens.iter().fold(None, |found, token| {
+        let matches = constant_time_eq(token.token.as_bytes(), presented.as_bytes());
+        found.or(matches.then_some(token))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn api_tokens_parse_with_read_default_and_match_exactly() {
+        let tokens = parse_api_tokens(&json!({
+            "API_TOKENS": [
+                { "name": "grafana", "token": "read-token-0123456789" },
+                { "name": "node-red", "token": "admin-token-0123456789", "scope": "Admin" }
+            ]
+        }))
+        .expect("tokens");
+        assert_eq!(tokens[0].scope, ApiTokenScope::Read);
+        assert_eq!(tokens[1].scope, ApiTokenScope::Admin);
+        assert!(!format!("{tokens:?}").contains("admin-token"));
+
+        let found = find_api_token(&tokens, "admin-token-0123456789").expect("match");
+        assert_eq!(found.name, "node-red");
+        assert!(find_api_token(&tokens, "admin-token-012345678").is_none());
+        assert!(ApiTokenScope::Admin.allows(ApiTokenScope::Read));
+        assert!(!ApiTokenScope::Read.allows(ApiTokenScope::Admin));
+
+        let err = parse_api_tokens(&json!({
+            "API_TOKENS": [{ "name": "short", "token": "abc" }]
+        }))
+        .expect_err("short token");
+        assert!(err.to_string().contains("API_TOKENS[0].token"));
+    }
+}
@@ -1,5 +1,5 @@
-use crate::config::Config;
-use crate::monitoring::MonitoringHub;
+use crate::config::{Config, DEFAULT_STREAM_INACTIVITY_TIMEOUT_SECS};
+use crate::monitoring::{AlertsReason, MonitoringHub};
 use crate::recording::{self, RecordingState};
 use crate::relay::RelayState;
 use crate::state::{ActiveAlert, AppState, EasAlertData};
@@ -22,8 +22,6 @@ use symphonia::core::formats::FormatOptions;
 use symphonia::core::io::{MediaSourceStream, ReadOnlySource};
 use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
-use tokio::fs::OpenOptions;
-use tokio::io::AsyncWriteExt;
 use tokio::sync::broadcast::Receiver as BroadcastReceiver;
 use tokio::sync::broadcast::Sender as BroadcastSender;
 use tokio::sync::mpsc::error::TrySendError;
@@ -40,20 +38,17 @@ const NWR_TONE_MIN_DURATION: Duration = Duration::from_secs(5);
 const NWR_TONE_RECORDING_DURATION: Duration = Duration::from_secs(120);
 const SAME_TONE_SUPPRESSION_DURATION: Duration = Duration::from_secs(300);
 
-fn stream_inactivity_timeout() -> std::time::Duration {
-    std::time::Duration::from_secs(120)
-}
-
 fn nwr_tone_header_for_recording(
     current_same_header: Option<&str>,
     julian_timestamp: &str,
+    station_id: &str,
 ) -> String {
     if let Some(header) =
         current_same_header.filter(|header| header.starts_with("ZCZC-") && header.ends_with('-'))
     {
         header.to_string()
     } else {
-        format!("ZCZC-WXR-??S-099999+0015-{julian_timestamp}-NOAA1050-")
+        format!("ZCZC-WXR-??S-099999+0015-{julian_timestamp}-{station_id}-")
     }
 }
 
@@ -164,7 +159,7 @@ pub async fn run_audio_processor(
 
     let current_config = Arc::new(RwLock::new(config.clone()));
     let mut stream_tasks: HashMap<String, StreamWorkerHandle> = HashMap::new();
-    for stream_url in config.icecast_stream_urls.clone() {
+    for stream_url in config.streams.iter().map(|stream| stream.id.clone()) {
         if stream_tasks.contains_key(&stream_url) {
             warn!(
                 stream = %stream_url,
@@ -186,19 +181,28 @@ pub async fn run_audio_processor(
         stream_tasks.insert(stream_url, handle);
     }
 
+    crate::reload::register("audio");
     let mut reload_enabled = true;
-    while reload_enabled {
-        match reload_rx.recv().await {
+    let mut heartbeat = tokio::time::interval(crate::lifecycle::HEARTBEAT_INTERVAL);
+    loop {
+        let reload_result = tokio::select! {
+            _ = heartbeat.tick() => {
+                crate::lifecycle::AUDIO_PROCESSOR_HEARTBEAT.beat();
+                continue;
+            }
+            reload_result = reload_rx.recv(), if reload_enabled => reload_result,
+        };
+        match reload_result {
             Ok(new_config) => {
-                let old_stream_urls = current_config
+                let old_stream_set: HashSet<String> = current_config
                     .read()
                     .expect("audio config lock poisoned")
-                    .icecast_stream_urls
-                    .clone();
-
-                let old_stream_set: HashSet<String> = old_stream_urls.into_iter().collect();
+                    .streams
+                    .iter()
+                    .map(|stream| stream.id.clone())
+                    .collect();
                 let mut new_stream_set: HashSet<String> = HashSet::new();
-                for stream_url in &new_config.icecast_stream_urls {
+                for stream_url in new_config.streams.iter().map(|stream| &stream.id) {
                     if !new_stream_set.insert(stream_url.clone()) {
                         warn!(
                             stream = %stream_url,
@@ -281,6 +285,7 @@ pub async fn run_audio_processor(
                 }
 
                 info!("Audio processor loaded updated configuration.");
+                crate::reload::acknowledge("audio");
             }
             Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
                 warn!(
@@ -294,10 +299,6 @@ pub async fn run_audio_processor(
             }
         }
     }
-
-    std::future::pending::<()>().await;
-    #[allow(unreachable_code)]
-    Ok(())
 }
 
 fn spawn_stream_worker(
@@ -412,6 +413,15 @@ async fn run_stream_task(
 
                 let (byte_tx, byte_rx) = crossbeam_channel::bounded::<Bytes>(256);
 
+                let inactivity_timeout = Duration::from_secs(
+                    config
+                        .read()
+                        .expect("audio config lock poisoned")
+                        .stream(&stream_url)
+                        .map_or(DEFAULT_STREAM_INACTIVITY_TIMEOUT_SECS, |stream| {
+                            stream.inactivity_timeout_secs
+                        }),
+                );
                 let stream_for_reader = stream_url.clone();
                 let monitoring_reader = monitoring.clone();
                 let stop_signal_for_reader = Arc::clone(&stop_signal);
@@ -425,9 +435,7 @@ async fn run_stream_task(
                             break;
                         }
 
-                        match tokio::time::timeout(stream_inactivity_timeout(), response.chunk())
-                            .await
-                        {
+                        match tokio::time::timeout(inactivity_timeout, response.chunk()).await {
                             Ok(Ok(Some(chunk))) => match byte_tx.try_send(chunk) {
                                 Ok(_) => {
                                     monitoring_reader.note_activity(&stream_for_reader);
@@ -473,6 +481,7 @@ async fn run_stream_task(
                 let app_state_for_decode = app_state.clone();
                 let monitoring_for_decode = monitoring.clone();
                 let decoding_task = tokio::task::spawn_blocking(move || {
+                    let _active = crate::resources::track(&crate::resources::DECODE_TASKS);
                     let reader = ChannelReader {
                         rx: byte_rx,
                         buffer: Bytes::new(),
@@ -589,6 +598,7 @@ fn process_stream(
     let min_tone_samples_required =
         (TARGET_SAMPLE_RATE as f64 * NWR_TONE_MIN_DURATION.as_secs_f64()) as usize;
     let mut sustained_tone_samples: usize = 0;
+    let mut was_detecting = true;
     const MAX_CONSECUTIVE_DECODE_ERRORS: u32 = 8;
     let mut consecutive_decode_errors: u32 = 0;
 
@@ -702,7 +712,26 @@ fn process_stream(
                     let chunk_to_process = audio_buffer[..CHUNK_SIZE].to_vec();
                     let resampled = rs.process(&[chunk_to_process], None)?;
                     let samples_f32 = resampled[0].clone();
-                    let tone_present = tone_detector.detect(&samples_f32);
+
+                    let detecting =
+                        current_same_header.is_some() || !monitoring.is_stream_paused(stream_label);
+                    if detecting != was_detecting {
+                        if detecting {
+                            info!(stream = %stream_label, "Alert detection resumed.");
+                        } else {
+                            same_receiver.reset();
+                            sustained_tone_samples = 0;
+                            info!(stream = %stream_label, "Alert detection paused.");
+                        }
+                        was_detecting = detecting;
+                    }
+                    let tone_detection = config
+                        .read()
+                        .expect("audio config lock poisoned")
+                        .stream(stream_label)
+                        .is_none_or(|stream| stream.nwr_tone_detection);
+                    let tone_present =
+                        detecting && tone_detection && tone_detector.detect(&samples_f32);
 
                     if let Some(audio_tx) = {
                         let recorder = recording_state.blocking_lock();
@@ -721,7 +750,14 @@ fn process_stream(
                     }
 
                     let now = std::time::Instant::now();
-                    for msg in same_receiver.iter_messages(samples_f32.iter().copied()) {
+                    let messages: Vec<SameMessage> = if detecting {
+                        same_receiver
+                            .iter_messages(samples_f32.iter().copied())
+                            .collect()
+                    } else {
+                        Vec::new()
+                    };
+                    for msg in messages {
                         match msg {
                             SameMessage::StartOfMessage(header) => {
                                 same_tone_suppression_until =
@@ -793,12 +829,15 @@ fn process_stream(
                                 let tone_header = nwr_tone_header_for_recording(
                                     current_same_header.as_deref(),
                                     &julian_timestamp,
+                                    &config_snapshot.same_station_id,
                                 );
                                 match recording::start_encoding_task_with_timestamp(
                                     &config_snapshot,
                                     &tone_header,
+                                    None,
                                     stream_label,
                                     Some(&full_timestamp),
+                                    &recorder,
                                 ) {
                                     Ok((handle, new_state)) => {
                                         let output_path = new_state.output_path.clone();
@@ -830,12 +869,8 @@ fn process_stream(
 
                             let recording_state_for_timeout = Arc::clone(recording_state);
                             let stream_for_timeout = stream_label.to_string();
-                            let (config_for_relay, filters_for_relay) = {
-                                let config_snapshot =
-                                    config.read().expect("audio config lock poisoned").clone();
-                                let filters = config_snapshot.filters.clone();
-                                (config_snapshot, filters)
-                            };
+                            let config_for_relay =
+                                config.read().expect("audio config lock poisoned").clone();
                             let same_header_for_relay = current_same_header.clone();
                             let app_state_for_tone = Arc::clone(app_state);
                             let monitoring_for_tone = monitoring.clone();
@@ -888,6 +923,7 @@ fn process_stream(
                                 let raw_header = nwr_tone_header_for_recording(
                                     same_header_for_relay.as_deref(),
                                     &julian_timestamp,
+                                    &config_for_relay.same_station_id,
                                 );
 
                                 let parsed_header =
@@ -923,27 +959,25 @@ fn process_stream(
                                 )
                                 .with_source_stream_url(stream_for_timeout.clone());
 
+                                let filters = app_state_for_tone.lock().await.filters();
                                 send_alert_webhook(
                                     &stream_for_timeout,
                                     &tone_alert,
                                     &tone_details,
                                     &raw_header,
                                     Some(output_path.clone()),
+                                    &filters,
                                 )
                                 .await;
 
                                 crate::icecast::enqueue_alert_audio(output_path.clone());
 
                                 {
-                                    let active_snapshot = {
+                                    let (active_snapshot, evicted) = {
                                         let mut app_state_guard =
                                             app_state_for_tone.lock().await;
-                                        let now_utc = Utc::now();
-                                        app_state_guard.active_alerts.retain(|existing| {
-                                            existing.expires_at > now_utc
-                                                && existing.raw_header != raw_header
-                                        });
-                                        app_state_guard.active_alerts.push(tone_alert.clone());
+                                        let evicted =
+                                            app_state_guard.upsert_active_alert(tone_alert.clone());
 
                                         if let Err(e) = crate::alerts::update_alert_files(
                                             &config_for_relay.shared_state_dir,
@@ -958,13 +992,16 @@ fn process_stream(
                                             );
                                         }
 
-                                        app_state_guard.active_alerts.clone()
+                                        (app_state_guard.active_alerts.clone(), evicted)
                                     };
                                     monitoring_for_tone.broadcast_alerts(
-                                        active_snapshot,
+                                        active_snapshot.clone(),
+                                        AlertsReason::New,
                                         Some(stream_for_timeout.as_str()),
                                         Some(tone_alert.data.event_code.as_str()),
                                     );
+                                    monitoring_for_tone
+                                        .broadcast_evicted_alerts(active_snapshot, &evicted);
                                 }
 
                                 {
@@ -976,34 +1013,40 @@ fn process_stream(
                                         raw_header, tone_details, timestamp
                                     );
 
-                                    match OpenOptions::new()
-                                        .create(true)
-                                        .append(true)
-                                        .open(&config_for_relay.dedicated_alert_log_file)
-                                        .await
+                                    if let Err(e) = crate::alert_log::append_dedicated(
+                                        &config_for_relay,
+                                        &log_line,
+                                    )
+                                    .await
                                     {
-                                        Ok(mut file) => {
-                                            if let Err(e) = file.write_all(log_line.as_bytes()).await {
-                                                warn!(
-                                                    stream = %stream_for_timeout,
-                                                    "Failed to write 1050 Hz tone to dedicated alert log: {}",
-                                                    e
-                                                );
-                                            }
-                                        }
-                                        Err(e) => {
-                                            warn!(
-                                                stream = %stream_for_timeout,
-                                                "Failed to open dedicated alert log for 1050 Hz tone: {}",
-                                                e
-                                            );
-                                        }
+                                        warn!(
+                                            stream = %stream_for_timeout,
+                                            "Failed to write 1050 Hz tone to dedicated alert log: {:#}",
+                                            e
+                                        );
+                                    }
+
+                                    if let Err(e) = crate::alert_log::append_chained_alert(
+                                        &config_for_relay,
+                                        &raw_header,
+                                        &tone_details,
+                                        Some(stream_for_timeout.as_str()),
+                                        received_at,
+                                    )
+                                    .await
+                                    {
+                                        warn!(
+                                            stream = %stream_for_timeout,
+                                            "Failed to append 1050 Hz tone to chained alert log: {}",
+                                            e
+                                        );
                                     }
                                 }
 
                                 if config_for_relay.should_relay
                                     && (config_for_relay.should_relay_icecast
-                                        || config_for_relay.should_relay_dasdec)
+                                        || config_for_relay.should_relay_dasdec
+                                        || config_for_relay.should_relay_myod)
                                 {
                                     let relay_state =
                                         match RelayState::new(config_for_relay).await {
@@ -1021,7 +1064,8 @@ fn process_stream(
                                     if let Err(err) = relay_state
                                         .start_relay(
                                             "??W",
-                                            filters_for_relay.as_slice(),
+                                            &[],
+                                            &filters.snapshot(),
                                             &output_path,
                                             Some(stream_for_timeout.as_str()),
                                             &raw_header,
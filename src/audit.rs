@@ -0,0 +1,175 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::fs::{self, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tracing::{info, warn};
+
+pub const AUDIT_LOG_FILE: &str = "audit.jsonl";
+
+#[derive(Debug, Clone)]
+pub struct AuditIdentity {
+    pub who: String,
+    pub source_ip: Option<IpAddr>,
+}
+
+impl fmt::Display for AuditIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.source_ip {
+            Some(ip) => write!(f, "{} from {}", self.who, ip),
+            None => f.write_str(&self.who),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AuditNote(Arc<Mutex<Option<String>>>);
+
+impl AuditNote {
+    pub fn set(&self, summary: impl Into<String>) {
+        *self.0.lock().expect("audit note lock poisoned") = Some(summary.into());
+    }
+
+    pub fn take(&self) -> Option<String> {
+        self.0.lock().expect("audit note lock poisoned").take()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub identity: String,
+    pub source_ip: Option<IpAddr>,
+    pub method: String,
+    pub endpoint: String,
+    pub status: u16,
+    pub succeeded: bool,
+    pub summary: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+pub struct AuditLog {
+    path: PathBuf,
+    write_lock: tokio::sync::Mutex<()>,
+}
+
+impl AuditLog {
+    pub fn new(shared_state_dir: &Path) -> Self {
+        Self {
+            path: shared_state_dir.join(AUDIT_LOG_FILE),
+            write_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    pub async fn record(&self, entry: &AuditEntry) {
+        let who = AuditIdentity {
+            who: entry.identity.clone(),
+            source_ip: entry.source_ip,
+        };
+        match &entry.error {
+            None => info!(
+                target: "audit",
+                "{} by {} ({} {})",
+                entry.summary,
+                who,
+                entry.method,
+                entry.endpoint
+            ),
+            Some(error) => warn!(
+                target: "audit",
+                "{} by {} ({} {}) failed with {}: {}",
+                entry.summary,
+                who,
+                entry.method,
+                entry.endpoint,
+                entry.status,
+                error
+            ),
+        }
+
+        if let Err(err) = self.append(entry).await {
+            warn!("Failed to write the API audit log: {:#}", err);
+        }
+    }
+
+    async fn append(&self, entry: &AuditEntry) -> Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        let _guard = self.write_lock.lock().await;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .with_context(|| format!("failed to open {}", self.path.display()))?;
+        file.write_all(&line).await?;
+        file.sync_data().await?;
+        Ok(())
+    }
+
+    pub async fn recent(&self, limit: usize) -> Result<Vec<AuditEntry>> {
+        let contents = match fs::read_to_string(&self.path).await {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to read {}", self.path.display()))
+            }
+        };
+        Ok(contents
+            .lines()
+            .rev()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .take(limit)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(summary: &str, error: Option<&str>) -> AuditEntry {
+        AuditEntry {
+            timestamp: Utc::now(),
+            identity: "API token \"ci\"".to_string(),
+            source_ip: Some("192.0.2.10".parse().expect("ip")),
+            method: "DELETE".to_string(),
+            endpoint: "/api/recordings/a.wav".to_string(),
+            status: if error.is_some() { 409 } else { 200 },
+            succeeded: error.is_none(),
+            summary: summary.to_string(),
+            error: error.map(str::to_string),
+        }
+    }
+
+    #[tokio::test]
+    async fn entries_are_appended_and_read_back_newest_first() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let log = AuditLog::new(dir.path());
+        assert!(log.recent(10).await.expect("empty").is_empty());
+
+        let first = entry("Recording a.wav deleted", None);
+        let second = entry(
+            "DELETE /api/recordings/b.wav",
+            Some("Recording is still being written"),
+        );
+        log.record(&first).await;
+        log.record(&second).await;
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(dir.path().join(AUDIT_LOG_FILE))
+            .and_then(|mut file| std::io::Write::write_all(&mut file, b"not json\n"))
+            .expect("append garbage");
+
+        assert_eq!(
+            log.recent(10).await.expect("read"),
+            vec![second.clone(), first]
+        );
+        assert_eq!(log.recent(1).await.expect("read"), vec![second]);
+    }
+}
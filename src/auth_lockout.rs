@@ -0,0 +1,162 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+#[derive(Debug, Clone, Copy)]
+pub struct LockoutSettings {
+    pub threshold: u32,
+    pub window: Duration,
+    pub lockout: Duration,
+}
+
+#[derive(Debug)]
+struct ClientFailures {
+    failures: u32,
+    window_started: Instant,
+    locked_until: Option<Instant>,
+    lockouts: u32,
+    last_failure: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LockoutStatus {
+    pub ip: IpAddr,
+    pub failures: u32,
+    pub lockouts: u32,
+    pub last_failure: DateTime<Utc>,
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+pub struct AuthLimiter {
+    settings: LockoutSettings,
+    clients: Mutex<HashMap<IpAddr, ClientFailures>>,
+}
+
+impl AuthLimiter {
+    pub fn new(settings: LockoutSettings) -> Self {
+        Self {
+            settings,
+            clients: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn locked_for(&self, ip: IpAddr, now: Instant) -> Option<Duration> {
+        let clients = self.clients.lock().expect("auth limiter lock poisoned");
+        clients
+            .get(&ip)
+            .and_then(|client| client.locked_until)
+            .and_then(|until| until.checked_duration_since(now))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    pub fn record_failure(&self, ip: IpAddr, now: Instant) -> bool {
+        let settings = self.settings;
+        let mut clients = self.clients.lock().expect("auth limiter lock poisoned");
+        clients.retain(|_, client| {
+            client.locked_until.is_some_and(|until| until > now)
+                || now.duration_since(client.window_started) < settings.window
+        });
+
+        let client = clients.entry(ip).or_insert_with(|| ClientFailures {
+            failures: 0,
+            window_started: now,
+            locked_until: None,
+            lockouts: 0,
+            last_failure: Utc::now(),
+        });
+        if client.locked_until.is_some_and(|until| until <= now) {
+            client.locked_until = None;
+            client.failures = 0;
+            client.window_started = now;
+        }
+        if now.duration_since(client.window_started) >= settings.window {
+            client.failures = 0;
+            client.window_started = now;
+        }
+        client.failures = client.failures.saturating_add(1);
+        client.last_failure = Utc::now();
+
+        if client.locked_until.is_some() || client.failures < settings.threshold {
+            return false;
+        }
+        client.locked_until = Some(now + settings.lockout);
+        client.lockouts = client.lockouts.saturating_add(1);
+        warn!(
+            "Locking out {} for {}s after {} failed authentication attempts (lockout #{})",
+            ip,
+            settings.lockout.as_secs(),
+            client.failures,
+            client.lockouts
+        );
+        true
+    }
+
+    pub fn record_success(&self, ip: IpAddr) {
+        self.clients
+            .lock()
+            .expect("auth limiter lock poisoned")
+            .remove(&ip);
+    }
+
+    pub fn statuses(&self, now: Instant) -> Vec<LockoutStatus> {
+        let clients = self.clients.lock().expect("auth limiter lock poisoned");
+        let wall_now = Utc::now();
+        let mut statuses: Vec<_> = clients
+            .iter()
+            .map(|(ip, client)| LockoutStatus {
+                ip: *ip,
+                failures: client.failures,
+                lockouts: client.lockouts,
+                last_failure: client.last_failure,
+                locked_until: client
+                    .locked_until
+                    .and_then(|until| until.checked_duration_since(now))
+                    .and_then(|remaining| chrono::Duration::from_std(remaining).ok())
+                    .map(|remaining| wall_now + remaining),
+            })
+            .collect();
+        statuses.sort_by_key(|status| std::cmp::Reverse(status.last_failure));
+        statuses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_failures_lock_out_until_expiry_and_success_resets() {
+        let limiter = AuthLimiter::new(LockoutSettings {
+            threshold: 3,
+            window: Duration::from_secs(60),
+            lockout: Duration::from_secs(300),
+        });
+        let ip: IpAddr = "203.0.113.7".parse().expect("ip");
+        let other: IpAddr = "198.51.100.1".parse().expect("ip");
+        let start = Instant::now();
+
+        assert!(!limiter.record_failure(ip, start));
+        assert!(!limiter.record_failure(ip, start));
+        limiter.record_success(ip);
+        assert!(!limiter.record_failure(ip, start));
+        assert!(!limiter.record_failure(ip, start));
+        assert!(limiter.record_failure(ip, start));
+        assert!(!limiter.record_failure(ip, start + Duration::from_secs(1)));
+        assert!(limiter.locked_for(ip, start).is_some());
+        assert!(limiter.locked_for(other, start).is_none());
+
+        let statuses = limiter.statuses(start);
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].lockouts, 1);
+        assert!(statuses[0].locked_until.is_some());
+
+        let later = start + Duration::from_secs(301);
+        assert!(limiter.locked_for(ip, later).is_none());
+        assert!(!limiter.record_failure(ip, later));
+        assert_eq!(limiter.statuses(later)[0].failures, 1);
+    }
+}
@@ -1,35 +1,76 @@
-use crate::monitoring::{LogEntry, MonitoringEvent, MonitoringHub, StreamStatusPayload};
-use crate::state::{ActiveAlert, AppState, CapRuntimeStatus};
+use crate::alert_log::{self, ChainVerification};
+use crate::alerts::{self, INJECTED_STREAM_ID};
+use crate::api_tls;
+use crate::api_tokens::ApiTokenScope;
+use crate::audit::{AuditEntry, AuditIdentity, AuditLog, AuditNote};
+use crate::auth_lockout::{AuthLimiter, LockoutSettings, LockoutStatus};
+use crate::build_info::{self, BuildInfo};
+use crate::cap_xml;
+use crate::cleanup::{self, RecordingSweep};
+use crate::config;
+use crate::credentials::ApiCredentials;
+use crate::dashboard::{self, DashboardFiles};
+use crate::db::DbHandle;
+use crate::dependencies::{self, DependencyReport};
+use crate::e2t_ng;
+use crate::file_stream;
+use crate::filter;
+use crate::lifecycle::{self, CrashRecord, ShutdownRecord};
+use crate::monitoring::{
+    AlertStats, AlertsUpdate, EventChannelStats, LogEntry, LogFilter, MonitoringEvent,
+    MonitoringHub, StreamHistory, StreamStatusPayload,
+};
+use crate::recording::{self, RecordingFile, RecordingIndexEntry, RecordingState};
+use crate::relay::RelayState;
+use crate::resources::{self, ResourceSnapshot};
+use crate::sessions::{SessionCheck, SessionStore, SESSION_TOKEN_PREFIX};
+use crate::share::{self, ShareError, ShareRecord, ShareStore};
+use crate::state::{ActiveAlert, AppState, CapRuntimeStatus, RecentAlert};
+use crate::supervisor::{self, TaskStatus};
+use crate::webhook;
+use crate::webhook_capabilities::{self, DestinationStatus};
 use crate::Config;
 use anyhow::Result;
 use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
-use axum::extract::{Query, Request, State};
+use axum::extract::{ConnectInfo, Path, Query, Request, State};
 use axum::http::HeaderMap;
+use axum::http::Uri;
 use axum::middleware;
 use axum::middleware::Next;
 use axum::response::{IntoResponse, Response};
-use axum::routing::get;
-use axum::{Json, Router};
+use axum::routing::{delete, get, post};
+use axum::{Extension, Json, Router};
+use axum_server::tls_rustls::RustlsConfig;
 use base64::Engine;
 use once_cell::sync::Lazy;
+use parking_lot::RwLock;
 use reqwest::header;
 use reqwest::header::HeaderValue;
 use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
 use reqwest::Method;
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
-use std::net::SocketAddr;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Write;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::net::TcpListener;
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, mpsc, Mutex};
+use tokio::task::JoinSet;
 use tokio::time::{self, Duration, MissedTickBehavior};
-use tower_http::cors::CorsLayer;
-use tracing::{error, info, warn};
+use tower_http::compression::predicate::{DefaultPredicate, NotForContentType, Predicate};
+use tower_http::compression::CompressionLayer;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tracing::{debug, error, info, warn};
 
-const DEEPLINK_HOST_CACHE_FILE: &str = "deeplink_host.txt";
-const DEEPLINK_HOST_LAST_SEEN_CACHE_FILE: &str = "deeplink_host_last_seen.txt";
+pub(crate) const DEEPLINK_HOST_CACHE_FILE: &str = "deeplink_host.txt";
+pub(crate) const DEEPLINK_HOST_LAST_SEEN_CACHE_FILE: &str = "deeplink_host_last_seen.txt";
 const CAP_HEADER_SOURCE_MARKER: &str = "IPAWS";
+const RECORDINGS_DEFAULT_PER_PAGE: usize = 50;
+const RECORDINGS_MAX_PER_PAGE: usize = 500;
 static SAME_US_LOOKUP_JSON: Lazy<serde_json::Value> = Lazy::new(|| {
     serde_json::from_str(include_str!("../include/same-us.json")).expect("parse same-us.json")
 });
@@ -39,14 +80,198 @@ struct ApiState {
     app_state: Arc<Mutex<AppState>>,
     monitoring: MonitoringHub,
     cap_stream_urls: Arc<HashSet<String>>,
-    config: Config,
+    config: Arc<RwLock<Arc<Config>>>,
     deeplink_host_cache: Arc<Mutex<Option<String>>>,
     last_seen_host_cache: Arc<Mutex<Option<String>>>,
+    shares: Option<Arc<ShareStore>>,
+    config_path: PathBuf,
+    reload_tx: broadcast::Sender<Config>,
+    alert_tx: mpsc::Sender<(String, String, String, String, Duration, String)>,
+    nnnn_tx: broadcast::Sender<String>,
+    auth_limiter: Arc<AuthLimiter>,
+    db: DbHandle,
+    recording_state: Arc<Mutex<HashMap<String, RecordingState>>>,
+    credentials: Arc<RwLock<Arc<ApiCredentials>>>,
+    sessions: Arc<SessionStore>,
+    audit: Arc<AuditLog>,
+    dashboard: DashboardFiles,
+}
+
+impl ApiState {
+    fn config(&self) -> Arc<Config> {
+        self.config.read().clone()
+    }
+
+    fn credentials(&self) -> Arc<ApiCredentials> {
+        self.credentials.read().clone()
+    }
+}
+
+#[derive(Clone)]
+pub struct PipelineHandles {
+    pub alert_tx: mpsc::Sender<(String, String, String, String, Duration, String)>,
+    pub nnnn_tx: broadcast::Sender<String>,
+    pub recording_state: Arc<Mutex<HashMap<String, RecordingState>>>,
+}
+
+#[derive(Clone)]
+pub struct ConfigFile {
+    pub path: PathBuf,
+    pub reload_tx: broadcast::Sender<Config>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct ShareRequest {
+    ttl_secs: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct ShareResponse {
+    url: String,
+    #[serde(flatten)]
+    share: ShareRecord,
+}
+
+#[derive(Debug, Serialize)]
+struct SharesResponse {
+    shares: Vec<ShareRecord>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct TestNotificationRequest {
+    #[serde(default)]
+    attach_recording: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct TestNotificationResponse {
+    delivered: usize,
+    total: usize,
+    #[serde(flatten)]
+    summary: webhook::DeliverySummary,
+}
+
+#[derive(Debug, Serialize)]
+struct FiltersResponse {
+    default_action: filter::FilterAction,
+    unmatched: filter::RuleHits,
+    rules: Vec<filter::RuleWithHits>,
+    streams: BTreeMap<String, Vec<filter::RuleWithHits>>,
+    sets: BTreeMap<String, Vec<filter::RuleWithHits>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FilterTestQuery {
+    event_code: String,
+    #[serde(default)]
+    originator: Option<String>,
+    #[serde(default)]
+    fips: Option<String>,
+    #[serde(default)]
+    duration_minutes: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FilterTestRequest {
+    raw_header: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct InjectAlertRequest {
+    raw_header: String,
+    #[serde(default)]
+    simulate_audio: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct InjectAlertResponse {
+    event_code: String,
+    originator: String,
+    raw_header: String,
+    stream_id: &'static str,
+    simulate_audio: bool,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct RelayRecordingRequest {
+    raw_header: Option<String>,
+    #[serde(default)]
+    force: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct RelayRecordingResponse {
+    job_id: u64,
+    status: &'static str,
+    file: String,
+    event_code: String,
+    raw_header: String,
+    filter: Option<String>,
+    action: filter::FilterAction,
+    forced: bool,
+}
+
+#[derive(Deserialize)]
+struct LoginRequest {
+    username: String,
+    password: String,
+}
+
+#[derive(Serialize)]
+struct LoginResponse {
+    token: String,
+    token_type: &'static str,
+    expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct PauseStreamRequest {
+    duration_secs: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct StreamReadiness {
+    stream_url: String,
+    is_connected: bool,
+    is_receiving_audio: bool,
+    is_paused: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ReadinessResponse {
+    status: &'static str,
+    ready: bool,
+    healthy_streams: usize,
+    total_streams: usize,
+    min_healthy_streams: usize,
+    streams: Vec<StreamReadiness>,
+}
+
+#[derive(Debug, Serialize)]
+struct DiscordDeliveryStats {
+    deferred: u64,
+    dropped: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct WebhookStatsResponse {
+    discord: DiscordDeliveryStats,
+    apprise_destinations: Vec<DestinationStatus>,
+}
+
+#[derive(Debug, Serialize)]
+struct CapabilityResetResponse {
+    reset: usize,
 }
 
 #[derive(Debug, Deserialize, Default)]
 struct LogsQuery {
     tail: Option<usize>,
+    level: Option<String>,
+    target: Option<String>,
+    contains: Option<String>,
+    since: Option<i64>,
+    until: Option<i64>,
 }
 
 #[derive(Debug, Serialize)]
@@ -54,6 +279,57 @@ struct LogsResponse {
     logs: Vec<LogEntry>,
 }
 
+#[derive(Debug, Deserialize)]
+struct AuditQuery {
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct AuditResponse {
+    entries: Vec<AuditEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AlertStatsQuery {
+    days: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RecordingsQuery {
+    page: Option<usize>,
+    per_page: Option<usize>,
+    event_code: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeleteRecordingsQuery {
+    older_than_days: Option<u64>,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct DeletedRecording {
+    file: String,
+    size_bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct DeleteRecordingsResponse {
+    dry_run: bool,
+    deleted: Vec<DeletedRecording>,
+    skipped_active: Vec<String>,
+    freed_bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct RecordingsResponse {
+    page: usize,
+    per_page: usize,
+    total: usize,
+    recordings: Vec<RecordingIndexEntry>,
+}
+
 #[derive(Debug, Serialize)]
 struct HealthResponse {
     status: String,
@@ -63,7 +339,18 @@ struct HealthResponse {
 struct StatusResponse {
     streams: Vec<StreamStatusPayload>,
     active_alerts: Vec<ActiveAlert>,
+    active_alert_limit: usize,
+    evicted_alerts: u64,
     cap_status: CapStatusPayload,
+    resources: Option<ResourceSnapshot>,
+    monitoring: EventChannelStats,
+    started_at: chrono::DateTime<chrono::Utc>,
+    uptime_seconds: u64,
+    previous_shutdown: Option<ShutdownRecord>,
+    previous_crash: Option<CrashRecord>,
+    recording_sweep: Option<RecordingSweep>,
+    tasks: BTreeMap<&'static str, TaskStatus>,
+    dependencies: Option<DependencyReport>,
 }
 
 #[derive(Debug, Serialize)]
@@ -75,7 +362,7 @@ struct CapStatusPayload {
 
 #[derive(Debug, Deserialize)]
 struct Params {
-    auth: String,
+    auth: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -84,14 +371,102 @@ enum WsMessage {
     Snapshot(SnapshotPayload),
     Log(LogEntry),
     Stream(StreamStatusPayload),
-    Alerts(Vec<ActiveAlert>),
+    Alerts(AlertsUpdate),
     CapStatus(CapStatusPayload),
+    Resources(Box<ResourceSnapshot>),
+    Error(String),
+}
+
+const WS_SNAPSHOT_LOGS: usize = 100;
+const WS_SUBSCRIBE_GRACE: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct WsSubscription {
+    logs: bool,
+    streams: bool,
+    alerts: bool,
+    cap_status: bool,
+    resources: bool,
+}
+
+impl Default for WsSubscription {
+    fn default() -> Self {
+        Self {
+            logs: true,
+            streams: true,
+            alerts: true,
+            cap_status: true,
+            resources: true,
+        }
+    }
+}
+
+impl WsSubscription {
+    fn from_topics(topics: &[String]) -> Result<Self, String> {
+        let mut subscription = Self {
+            logs: false,
+            streams: false,
+            alerts: false,
+            cap_status: false,
+            resources: false,
+        };
+        for topic in topics {
+            match topic.trim().to_ascii_lowercase().as_str() {
+                "logs" => subscription.logs = true,
+                "streams" => subscription.streams = true,
+                "alerts" => subscription.alerts = true,
+                "cap_status" => subscription.cap_status = true,
+                "resources" => subscription.resources = true,
+                other => {
+                    return Err(format!(
+                        "Unknown subscription {other:?}; use logs, streams, alerts, cap_status or resources"
+                    ))
+                }
+            }
+        }
+        Ok(subscription)
+    }
+
+    fn allows(&self, message: &WsMessage) -> bool {
+        match message {
+            WsMessage::Log(_) => self.logs,
+            WsMessage::Stream(_) => self.streams,
+            WsMessage::Alerts(_) => self.alerts,
+            WsMessage::CapStatus(_) => self.cap_status,
+            WsMessage::Resources(_) => self.resources,
+            WsMessage::Snapshot(_) | WsMessage::Error(_) => true,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WsClientMessage {
+    subscribe: Option<Vec<String>>,
+    tail: Option<usize>,
+}
+
+fn apply_ws_client_message(
+    text: &str,
+    subscription: &mut WsSubscription,
+    tail: &mut usize,
+) -> Result<(), String> {
+    let message: WsClientMessage =
+        serde_json::from_str(text).map_err(|err| format!("Invalid message: {err}"))?;
+    if let Some(topics) = &message.subscribe {
+        *subscription = WsSubscription::from_topics(topics)?;
+    }
+    if let Some(requested) = message.tail {
+        *tail = requested.min(WS_SNAPSHOT_LOGS);
+    }
+    Ok(())
 }
 
 #[derive(Debug, Serialize)]
 struct SnapshotPayload {
     streams: Vec<StreamStatusPayload>,
     active_alerts: Vec<ActiveAlert>,
+    recent_alerts: Vec<RecentAlert>,
     cap_status: CapStatusPayload,
     logs: Vec<LogEntry>,
 }
@@ -101,57 +476,97 @@ impl From<MonitoringEvent> for WsMessage {
         match event {
             MonitoringEvent::Log(entry) => WsMessage::Log(entry),
             MonitoringEvent::Stream(status) => WsMessage::Stream(status),
-            MonitoringEvent::Alerts(alerts) => WsMessage::Alerts(alerts),
+            MonitoringEvent::Alerts(update) => WsMessage::Alerts(update),
+            MonitoringEvent::Resources(snapshot) => WsMessage::Resources(snapshot),
         }
     }
 }
 
 fn cors_layer(config: &Config) -> CorsLayer {
-    if !config.use_reverse_proxy {
-        let origin: HeaderValue =
-            format!("http://{}:{}/", "localhost", config.monitoring_bind_port)
-                .parse()
-                .unwrap_or_else(|_| HeaderValue::from_static("http://localhost:8080"));
-
-        CorsLayer::new()
-            .allow_origin(origin)
-            .allow_methods([
-                Method::GET,
-                Method::POST,
-                Method::PUT,
-                Method::PATCH,
-                Method::DELETE,
-                Method::OPTIONS,
-            ])
-            .allow_headers([AUTHORIZATION, CONTENT_TYPE])
-            .max_age(Duration::from_secs(86400))
+    let allow_origin = if config.allowed_origins.iter().any(|origin| origin == "*") {
+        AllowOrigin::any()
+    } else {
+        let allowed = config.allowed_origins.clone();
+        let proxy_hosts: Vec<String> = if config.use_reverse_proxy {
+            [&config.reverse_proxy_url, &config.ws_reverse_proxy_url]
+                .into_iter()
+                .filter_map(|url| url_host(url))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        AllowOrigin::predicate(move |origin: &HeaderValue, _| {
+            origin
+                .to_str()
+                .is_ok_and(|origin| origin_allowed(origin, &allowed, &proxy_hosts))
+        })
+    };
+
+    CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+            Method::OPTIONS,
+        ])
+        .allow_headers([AUTHORIZATION, CONTENT_TYPE])
+        .max_age(Duration::from_secs(86400))
+}
+
+fn url_host(value: &str) -> Option<String> {
+    let value = value.trim();
+    let with_scheme = if value.contains("://") {
+        value.to_string()
     } else {
-        let origin: HeaderValue = format!("http://{}/", config.ws_reverse_proxy_url)
-            .parse()
-            .unwrap_or_else(|_| HeaderValue::from_static("http://localhost"));
-
-        CorsLayer::new()
-            .allow_origin(origin)
-            .allow_methods([
-                Method::GET,
-                Method::POST,
-                Method::PUT,
-                Method::PATCH,
-                Method::DELETE,
-                Method::OPTIONS,
-            ])
-            .allow_headers([AUTHORIZATION, CONTENT_TYPE])
-            .max_age(Duration::from_secs(86400))
-    }
-}
-
-async fn auth(
-    State(state): State<ApiState>,
-    req: Request,
-    next: Next,
-) -> Result<Response, StatusCode> {
+        format!("http://{value}")
+    };
+    reqwest::Url::parse(&with_scheme)
+        .ok()?
+        .host_str()
+        .map(|host| host.trim_matches(['[', ']']).to_ascii_lowercase())
+}
+
+fn is_private_network_host(host: &str) -> bool {
+    if host == "localhost" || host.ends_with(".local") {
+        return true;
+    }
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => ip.is_loopback() || ip.is_private() || ip.is_link_local(),
+        Ok(IpAddr::V6(ip)) => {
+            let first = ip.segments()[0];
+            ip.is_loopback() || (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80
+        }
+        Err(_) => false,
+    }
+}
+
+fn origin_allowed(origin: &str, allowed: &[String], proxy_hosts: &[String]) -> bool {
+    if !allowed.is_empty() {
+        return allowed
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(origin));
+    }
+    let Some(host) = url_host(origin).filter(|_| origin.contains("://")) else {
+        return false;
+    };
+    is_private_network_host(&host) || proxy_hosts.contains(&host)
+}
+
+async fn auth(State(state): State<ApiState>, mut req: Request, next: Next) -> Response {
     if req.method() == Method::OPTIONS {
-        return Ok(next.run(req).await);
+        return next.run(req).await;
+    }
+
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| *addr);
+    let ip = client_ip(req.headers(), peer, state.config().use_reverse_proxy);
+    if let Some(response) = lockout_response(&state, ip) {
+        return response;
     }
 
     let auth_header = req
@@ -159,33 +574,257 @@ async fn auth(
         .get(header::AUTHORIZATION)
         .and_then(|header| header.to_str().ok());
 
-    match auth_header {
-        Some(auth_header) if token_is_valid(auth_header, &state.config) => Ok(next.run(req).await),
-        _ => Err(StatusCode::UNAUTHORIZED),
+    match authorize(&state, auth_header) {
+        Ok((scope, who)) => {
+            if let Some(ip) = ip {
+                state.auth_limiter.record_success(ip);
+            }
+            let identity = AuditIdentity { who, source_ip: ip };
+            let mutating = !is_read_request(req.method(), req.uri().path());
+            let method = req.method().to_string();
+            let endpoint = req.uri().path().to_string();
+            let note = AuditNote::default();
+            let response = if scope.allows(required_scope(req.method(), req.uri().path())) {
+                req.extensions_mut().insert(identity.clone());
+                req.extensions_mut().insert(note.clone());
+                next.run(req).await
+            } else {
+                StatusCode::FORBIDDEN.into_response()
+            };
+            if !mutating {
+                return response;
+            }
+            audit_response(&state, identity, method, endpoint, note, response).await
+        }
+        Err(AuthFailure::SessionExpired) => session_expired_response(),
+        Err(AuthFailure::Invalid) => {
+            if let Some(ip) = ip {
+                state
+                    .auth_limiter
+                    .record_failure(ip, std::time::Instant::now());
+            }
+            StatusCode::UNAUTHORIZED.into_response()
+        }
     }
 }
 
-fn token_is_valid(auth_header: &str, config: &Config) -> bool {
-    if !auth_header.starts_with("Bearer ") {
-        info!("Auth header does not start with 'Bearer '");
-        return false;
+fn client_ip(headers: &HeaderMap, peer: Option<SocketAddr>, behind_proxy: bool) -> Option<IpAddr> {
+    if behind_proxy {
+        if let Ok(ip) = forwarded_for(headers).parse::<IpAddr>() {
+            return Some(ip);
+        }
     }
+    peer.map(|addr| addr.ip())
+}
+
+fn lockout_response(state: &ApiState, ip: Option<IpAddr>) -> Option<Response> {
+    let remaining = state
+        .auth_limiter
+        .locked_for(ip?, std::time::Instant::now())?;
+    Some(
+        (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, remaining.as_secs().max(1).to_string())],
+            "Too many failed authentication attempts",
+        )
+            .into_response(),
+    )
+}
 
-    let token = &auth_header[7..];
-    let username = config.dashboard_username.clone();
-    let password = config.dashboard_password.clone();
+const ADMIN_ONLY_PATHS: &[&str] = &["/api/auth/lockouts", "/api/audit"];
+const READ_ONLY_POST_PATHS: &[&str] = &["/api/filters/test"];
 
-    if username.is_empty() || password.is_empty() || username == "admin" || password == "password" {
-        info!("Default or empty username/password in use, rejecting token");
-        return false;
+fn is_read_request(method: &Method, path: &str) -> bool {
+    method == Method::GET
+        || method == Method::HEAD
+        || (method == Method::POST && READ_ONLY_POST_PATHS.contains(&path))
+}
+
+fn required_scope(method: &Method, path: &str) -> ApiTokenScope {
+    if is_read_request(method, path) && !ADMIN_ONLY_PATHS.contains(&path) {
+        ApiTokenScope::Read
+    } else {
+        ApiTokenScope::Admin
     }
+}
 
-    let expected_token = Engine::encode(
-        &base64::engine::general_purpose::STANDARD,
-        format!("{}:{}", username, password),
-    );
+const AUDIT_ERROR_BODY_LIMIT: usize = 64 * 1024;
+const AUDIT_ERROR_MAX_CHARS: usize = 300;
+
+async fn audit_response(
+    state: &ApiState,
+    identity: AuditIdentity,
+    method: String,
+    endpoint: String,
+    note: AuditNote,
+    response: Response,
+) -> Response {
+    let status = response.status();
+    let succeeded = !(status.is_client_error() || status.is_server_error());
+    let (response, error) = if succeeded {
+        (response, None)
+    } else {
+        let (parts, body) = response.into_parts();
+        let bytes = axum::body::to_bytes(body, AUDIT_ERROR_BODY_LIMIT)
+            .await
+            .unwrap_or_default();
+        let text = String::from_utf8_lossy(&bytes);
+        let text = text.trim();
+        let error = if text.is_empty() {
+            status.canonical_reason().unwrap_or("error").to_string()
+        } else {
+            text.chars().take(AUDIT_ERROR_MAX_CHARS).collect()
+        };
+        (
+            Response::from_parts(parts, axum::body::Body::from(bytes)),
+            Some(error),
+        )
+    };
+
+    let entry = AuditEntry {
+        timestamp: chrono::Utc::now(),
+        identity: identity.who,
+        source_ip: identity.source_ip,
+        summary: note
+            .take()
+            .unwrap_or_else(|| format!("{method} {endpoint}")),
+        method,
+        endpoint,
+        status: status.as_u16(),
+        succeeded,
+        error,
+    };
+    state.audit.record(&entry).await;
+    response
+}
+
+async fn refresh_api_config(
+    config: Arc<RwLock<Arc<Config>>>,
+    credentials: Arc<RwLock<Arc<ApiCredentials>>>,
+    mut reload_rx: broadcast::Receiver<Config>,
+) {
+    crate::reload::register("api");
+    loop {
+        match reload_rx.recv().await {
+            Ok(new_config) => {
+                *credentials.write() = Arc::new(ApiCredentials::from_config(&new_config));
+                *config.write() = Arc::new(new_config);
+                debug!("Reloaded the monitoring API configuration and credentials.");
+                crate::reload::acknowledge("api");
+            }
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => return,
+        }
+    }
+}
+
+enum AuthFailure {
+    Invalid,
+    SessionExpired,
+}
+
+fn authorize(
+    state: &ApiState,
+    auth_header: Option<&str>,
+) -> Result<(ApiTokenScope, String), AuthFailure> {
+    let auth_header = auth_header.ok_or(AuthFailure::Invalid)?;
+    if let Some(token) = auth_header.strip_prefix("Bearer ") {
+        match state.sessions.check(token, std::time::Instant::now()) {
+            SessionCheck::Valid(username) => {
+                return Ok((
+                    ApiTokenScope::Admin,
+                    format!("dashboard session for {username}"),
+                ))
+            }
+            SessionCheck::Expired => return Err(AuthFailure::SessionExpired),
+            SessionCheck::NotASession => {}
+        }
+    }
+    state
+        .credentials()
+        .principal(auth_header)
+        .ok_or(AuthFailure::Invalid)
+}
+
+fn session_expired_response() -> Response {
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(serde_json::json!({
+            "error": "session_expired",
+            "message": "The dashboard session has expired; log in again."
+        })),
+    )
+        .into_response()
+}
+
+async fn login_handler(
+    State(state): State<ApiState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(request): Json<LoginRequest>,
+) -> Response {
+    let ip = client_ip(&headers, Some(peer), state.config().use_reverse_proxy);
+    if let Some(response) = lockout_response(&state, ip) {
+        return response;
+    }
+    if !state
+        .credentials()
+        .verify_login(&request.username, &request.password)
+    {
+        if let Some(ip) = ip {
+            state
+                .auth_limiter
+                .record_failure(ip, std::time::Instant::now());
+        }
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    if let Some(ip) = ip {
+        state.auth_limiter.record_success(ip);
+    }
+
+    match state
+        .sessions
+        .issue(&request.username, std::time::Instant::now())
+    {
+        Ok((token, expires_at)) => Json(LoginResponse {
+            token,
+            token_type: "Bearer",
+            expires_at,
+        })
+        .into_response(),
+        Err(err) => {
+            error!("Failed to issue a dashboard session: {:#}", err);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+async fn logout_handler(
+    State(state): State<ApiState>,
+    Extension(note): Extension<AuditNote>,
+    headers: HeaderMap,
+) -> StatusCode {
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match token {
+        Some(token) if token.starts_with(SESSION_TOKEN_PREFIX) => {
+            state.sessions.revoke(token);
+            note.set("Dashboard session logged out");
+            StatusCode::NO_CONTENT
+        }
+        _ => StatusCode::BAD_REQUEST,
+    }
+}
 
-    token == expected_token
+async fn sweep_sessions(sessions: Arc<SessionStore>) {
+    let mut timer = time::interval(Duration::from_secs(60));
+    timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    loop {
+        timer.tick().await;
+        sessions.sweep(std::time::Instant::now());
+    }
 }
 
 fn sanitize_host_header(raw: &str) -> Option<String> {
@@ -244,7 +883,7 @@ async fn maybe_persist_deeplink_host(headers: &HeaderMap, state: &ApiState) {
 
     if should_write_last_seen {
         let last_seen_file = state
-            .config
+            .config()
             .shared_state_dir
             .join(DEEPLINK_HOST_LAST_SEEN_CACHE_FILE);
         match tokio::fs::write(&last_seen_file, &host).await {
@@ -259,149 +898,1857 @@ async fn maybe_persist_deeplink_host(headers: &HeaderMap, state: &ApiState) {
         }
     }
 
-    if is_loopback_host(&host) {
-        return;
+    if is_loopback_host(&host) {
+        return;
+    }
+
+    let should_write_preferred = {
+        let guard = state.deeplink_host_cache.lock().await;
+        guard.as_deref() != Some(host.as_str())
+    };
+
+    if !should_write_preferred {
+        return;
+    }
+
+    let host_file = state
+        .config()
+        .shared_state_dir
+        .join(DEEPLINK_HOST_CACHE_FILE);
+    match tokio::fs::write(&host_file, &host).await {
+        Ok(_) => {
+            let mut guard = state.deeplink_host_cache.lock().await;
+            *guard = Some(host);
+        }
+        Err(err) => warn!(
+            "Failed to persist deeplink host '{}' to {:?}: {}",
+            host, host_file, err
+        ),
+    }
+}
+
+pub async fn run_server(
+    tls: Option<RustlsConfig>,
+    app_state: Arc<Mutex<AppState>>,
+    monitoring: MonitoringHub,
+    config: Config,
+    config_file: ConfigFile,
+    db: DbHandle,
+    pipeline: PipelineHandles,
+) -> Result<()> {
+    let bind_addr = config.monitoring_bind_addr;
+    let mut tasks = JoinSet::new();
+    let state = api_state(
+        app_state,
+        monitoring,
+        &config,
+        config_file,
+        db,
+        pipeline,
+        &mut tasks,
+    );
+    let router = api_router(&state, &config);
+
+    let service = router.into_make_service_with_connect_info::<SocketAddr>();
+    match tls {
+        Some(tls) => {
+            let reloader = api_tls::run_tls_reloader(tls.clone(), state.reload_tx.subscribe());
+            tasks.spawn(async move {
+                if let Err(err) = reloader.await {
+                    warn!("The API TLS reloader stopped: {:#}", err);
+                }
+            });
+            info!(%bind_addr, "Monitoring API listening over HTTPS");
+            let handle = axum_server::Handle::new();
+            let bound = handle.clone();
+            tasks.spawn(async move {
+                if bound.listening().await.is_some() {
+                    crate::lifecycle::note_api_listening();
+                }
+            });
+            axum_server::bind_rustls(bind_addr, tls)
+                .handle(handle)
+                .serve(service)
+                .await?;
+        }
+        None => {
+            let listener = TcpListener::bind(bind_addr).await?;
+            info!(%bind_addr, "Monitoring API listening");
+            crate::lifecycle::note_api_listening();
+            axum::serve(listener, service).await?;
+        }
+    }
+    Ok(())
+}
+
+fn api_state(
+    app_state: Arc<Mutex<AppState>>,
+    monitoring: MonitoringHub,
+    config: &Config,
+    config_file: ConfigFile,
+    db: DbHandle,
+    pipeline: PipelineHandles,
+    tasks: &mut JoinSet<()>,
+) -> ApiState {
+    let ConfigFile {
+        path: config_path,
+        reload_tx,
+    } = config_file;
+    let cap_stream_urls = Arc::new(
+        config
+            .cap_endpoints
+            .iter()
+            .map(|endpoint| endpoint.url.clone())
+            .collect(),
+    );
+    let auth_limiter = Arc::new(AuthLimiter::new(LockoutSettings {
+        threshold: config.auth_failure_threshold,
+        window: Duration::from_secs(config.auth_failure_window_secs),
+        lockout: Duration::from_secs(config.auth_lockout_secs),
+    }));
+    let initial_credentials = ApiCredentials::from_config(config);
+    if initial_credentials.uses_plaintext_password() {
+        warn!(
+            "DASHBOARD_PASSWORD is stored in plain text, which is deprecated. Set DASHBOARD_PASSWORD_HASH to an argon2 or bcrypt hash of it and clear DASHBOARD_PASSWORD."
+        );
+    }
+    let credentials = Arc::new(RwLock::new(Arc::new(initial_credentials)));
+    let current_config = Arc::new(RwLock::new(Arc::new(config.clone())));
+    tasks.spawn(refresh_api_config(
+        current_config.clone(),
+        credentials.clone(),
+        reload_tx.subscribe(),
+    ));
+    let sessions = Arc::new(SessionStore::new(Duration::from_secs(
+        config.session_ttl_secs,
+    )));
+    tasks.spawn(sweep_sessions(sessions.clone()));
+    let audit = Arc::new(AuditLog::new(&config.shared_state_dir));
+    ApiState {
+        app_state,
+        monitoring,
+        cap_stream_urls,
+        deeplink_host_cache: Arc::new(Mutex::new(None)),
+        last_seen_host_cache: Arc::new(Mutex::new(None)),
+        shares: match ShareStore::load(config) {
+            Ok(store) => Some(Arc::new(store)),
+            Err(err) => {
+                warn!("Recording share links are unavailable: {:#}", err);
+                None
+            }
+        },
+        config_path,
+        reload_tx,
+        auth_limiter,
+        alert_tx: pipeline.alert_tx,
+        nnnn_tx: pipeline.nnnn_tx,
+        db,
+        recording_state: pipeline.recording_state,
+        credentials,
+        sessions,
+        audit,
+        dashboard: DashboardFiles::from_config(config),
+        config: current_config,
+    }
+}
+
+fn api_router(state: &ApiState, config: &Config) -> Router {
+    let protected_router = Router::new()
+        .route("/api/logs", get(logs_handler))
+        .route("/api/logout", post(logout_handler))
+        .route("/api/status", get(status_handler))
+        .route("/api/auth/lockouts", get(auth_lockouts_handler))
+        .route("/api/audit", get(audit_handler))
+        .route("/api/stats/alerts", get(alert_stats_handler))
+        .route(
+            "/api/config",
+            get(config_handler).put(update_config_handler),
+        )
+        .route("/api/reload", post(reload_config_handler))
+        .route(
+            "/api/logging",
+            get(logging_handler).put(update_logging_handler),
+        )
+        .route("/metrics", get(metrics_handler))
+        .route("/api/cap-status", get(cap_status_handler))
+        .route("/api/same-us", get(same_us_lookup_handler))
+        .route("/api/alerts/recent", get(recent_alerts_handler))
+        .route("/api/alerts/:id/cap.xml", get(alert_cap_handler))
+        .route("/api/alerts/log/verify", get(alert_log_verify_handler))
+        .route("/api/alerts/inject", post(inject_alert_handler))
+        .route("/api/filters", get(filters_handler))
+        .route(
+            "/api/filters/test",
+            get(filter_test_handler).post(filter_test_header_handler),
+        )
+        .route("/api/streams/:index/pause", post(pause_stream_handler))
+        .route("/api/streams/:index/resume", post(resume_stream_handler))
+        .route("/api/streams/:index/history", get(stream_history_handler))
+        .route(
+            "/api/recordings",
+            get(recordings_handler).delete(delete_old_recordings_handler),
+        )
+        .route(
+            "/api/recordings/:file",
+            get(recording_file_handler).delete(delete_recording_handler),
+        )
+        .route("/api/recordings/:file/share", post(share_recording_handler))
+        .route("/api/recordings/:file/relay", post(relay_recording_handler))
+        .route("/api/shares", get(list_shares_handler))
+        .route("/api/shares/:id", delete(revoke_share_handler))
+        .route("/api/webhooks/stats", get(webhook_stats_handler))
+        .route("/api/notify/test", post(test_notification_handler))
+        .route(
+            "/api/webhooks/capabilities",
+            delete(reset_all_capabilities_handler),
+        )
+        .route(
+            "/api/webhooks/capabilities/:id",
+            delete(reset_capability_handler),
+        )
+        .layer(cors_layer(config))
+        .with_state(state.clone())
+        .route_layer(middleware::from_fn_with_state(state.clone(), auth));
+
+    let compression = CompressionLayer::new()
+        .compress_when(DefaultPredicate::new().and(NotForContentType::const_new("audio/")));
+    let router = Router::new()
+        .route("/api/health", get(health_handler))
+        .route("/api/login", post(login_handler))
+        .route("/api/ready", get(ready_handler))
+        .route("/api/version", get(version_handler))
+        .route("/api/shared/:token", get(shared_recording_handler))
+        .layer(cors_layer(config))
+        .merge(protected_router);
+    let router = if config.serve_dashboard {
+        router.merge(
+            Router::new()
+                .route("/config.js", get(dashboard_config_handler))
+                .fallback(dashboard_handler)
+                .with_state(state.clone()),
+        )
+    } else {
+        router
+    };
+    router
+        .layer(compression)
+        .route("/ws", get(ws_handler).layer(cors_layer(config)))
+        .with_state(state.clone())
+}
+
+async fn health_handler() -> Json<HealthResponse> {
+    Json(HealthResponse {
+        status: "OK".to_string(),
+    })
+}
+
+fn readiness(streams: Vec<StreamStatusPayload>, min_healthy_streams: usize) -> ReadinessResponse {
+    let streams: Vec<_> = streams
+        .into_iter()
+        .map(|stream| StreamReadiness {
+            stream_url: stream.stream_url,
+            is_connected: stream.is_connected,
+            is_receiving_audio: stream.is_receiving_audio,
+            is_paused: stream.is_paused,
+        })
+        .collect();
+    let total_streams = streams.len();
+    let healthy_streams = streams
+        .iter()
+        .filter(|stream| stream.is_connected && stream.is_receiving_audio)
+        .count();
+    let min_healthy_streams = min_healthy_streams.min(total_streams);
+    let ready = healthy_streams >= min_healthy_streams;
+    let status = if healthy_streams == total_streams {
+        "ok"
+    } else if ready {
+        "degraded"
+    } else {
+        "down"
+    };
+    ReadinessResponse {
+        status,
+        ready,
+        healthy_streams,
+        total_streams,
+        min_healthy_streams,
+        streams,
+    }
+}
+
+async fn ready_handler(State(state): State<ApiState>) -> (StatusCode, Json<ReadinessResponse>) {
+    let streams = filter_non_cap_streams(state.monitoring.stream_snapshots(), &state);
+    let response = readiness(streams, state.config().ready_min_healthy_streams);
+    let status = if response.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(response))
+}
+
+fn request_host(headers: &HeaderMap, behind_proxy: bool) -> Option<&str> {
+    let forwarded = behind_proxy
+        .then(|| headers.get("x-forwarded-host"))
+        .flatten();
+    forwarded
+        .or_else(|| headers.get(header::HOST))
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').next())
+        .map(str::trim)
+}
+
+async fn dashboard_config_handler(State(state): State<ApiState>, headers: HeaderMap) -> Response {
+    let format = config::ConfigFormat::from_path(&state.config_path);
+    let raw_config = tokio::fs::read_to_string(&state.config_path)
+        .await
+        .ok()
+        .and_then(|contents| format.parse(&contents).ok());
+    let web_config = crate::build_web_runtime_config_payload(&state.config(), raw_config.as_ref());
+    let alert_sound = state
+        .dashboard
+        .alert_sound_src(web_config.get("ALERT_SOUND_SRC").and_then(|v| v.as_str()))
+        .await;
+    let script = dashboard::config_script(
+        request_host(&headers, state.config().use_reverse_proxy),
+        &web_config,
+        &alert_sound,
+    );
+    (
+        [
+            (CONTENT_TYPE, "text/javascript; charset=utf-8"),
+            (header::CACHE_CONTROL, "no-store"),
+        ],
+        script,
+    )
+        .into_response()
+}
+
+const NON_DASHBOARD_PREFIXES: &[&str] = &["/api/", "/ws", "/metrics"];
+
+async fn dashboard_handler(
+    State(state): State<ApiState>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+) -> Response {
+    let path = uri.path();
+    if !(method == Method::GET || method == Method::HEAD)
+        || NON_DASHBOARD_PREFIXES
+            .iter()
+            .any(|prefix| path.starts_with(prefix))
+    {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let Some(asset) = state.dashboard.get(path).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if etag_matches(&headers, &asset.etag) {
+        return not_modified(&asset.etag);
+    }
+    let cache_control = if asset.is_page {
+        "no-cache"
+    } else {
+        "public, max-age=3600"
+    };
+    (
+        [
+            (CONTENT_TYPE, asset.content_type),
+            (header::ETAG, asset.etag),
+            (header::CACHE_CONTROL, cache_control.to_string()),
+        ],
+        asset.body,
+    )
+        .into_response()
+}
+
+async fn version_handler() -> Json<BuildInfo> {
+    Json(build_info::build_info())
+}
+
+async fn same_us_lookup_handler(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+) -> Json<serde_json::Value> {
+    maybe_persist_deeplink_host(&headers, &state).await;
+    Json(SAME_US_LOOKUP_JSON.clone())
+}
+
+async fn logs_handler(
+    Query(params): Query<LogsQuery>,
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    maybe_persist_deeplink_host(&headers, &state).await;
+    let max_logs = state.monitoring.max_logs();
+    let tail = params.tail.unwrap_or(100).clamp(1, max_logs);
+    let filter = log_filter(&params).map_err(|err| (StatusCode::BAD_REQUEST, err))?;
+    let etag = format!("W/\"logs-{}\"", state.monitoring.latest_log_id());
+    if etag_matches(&headers, &etag) {
+        return Ok(not_modified(&etag));
+    }
+    let logs = state.monitoring.filtered_logs(tail, &filter);
+    Ok(tagged_json(&etag, &LogsResponse { logs }))
+}
+
+fn etag_matches(request_headers: &HeaderMap, etag: &str) -> bool {
+    let bare = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    request_headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| {
+            value.trim() == "*" || value.split(',').any(|tag| bare(tag) == bare(etag))
+        })
+}
+
+fn not_modified(etag: &str) -> Response {
+    (StatusCode::NOT_MODIFIED, [(header::ETAG, etag.to_string())]).into_response()
+}
+
+fn tagged_json<T: Serialize>(etag: &str, body: &T) -> Response {
+    (
+        [
+            (header::ETAG, etag.to_string()),
+            (header::CACHE_CONTROL, "no-cache".to_string()),
+        ],
+        Json(body),
+    )
+        .into_response()
+}
+
+fn status_etag(revision: u64, body: &[u8]) -> String {
+    let digest = Sha256::digest(body);
+    let short: String = digest[..8]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    format!("W/\"status-{revision}-{short}\"")
+}
+
+fn log_filter(params: &LogsQuery) -> Result<LogFilter, String> {
+    let min_level = params
+        .level
+        .as_deref()
+        .map(|level| {
+            level.trim().parse::<tracing::Level>().map_err(|_| {
+                format!("Invalid log level {level:?}; use trace, debug, info, warn or error")
+            })
+        })
+        .transpose()?;
+    let timestamp = |name: &str, millis: Option<i64>| {
+        millis
+            .map(|millis| {
+                chrono::DateTime::from_timestamp_millis(millis)
+                    .ok_or_else(|| format!("{name} is not a valid millisecond timestamp"))
+            })
+            .transpose()
+    };
+    let non_empty = |value: &Option<String>| value.clone().filter(|value| !value.is_empty());
+
+    Ok(LogFilter {
+        min_level,
+        target_prefix: non_empty(&params.target),
+        contains: non_empty(&params.contains),
+        since: timestamp("since", params.since)?,
+        until: timestamp("until", params.until)?,
+    })
+}
+
+async fn recordings_handler(
+    Query(params): Query<RecordingsQuery>,
+    State(state): State<ApiState>,
+) -> Result<Json<RecordingsResponse>, (StatusCode, String)> {
+    let recording_dir = state.config().recording_dir.clone();
+    let mut recordings =
+        tokio::task::spawn_blocking(move || recording::list_recordings(&recording_dir))
+            .await
+            .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?
+            .map_err(|err| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to read the recording directory: {err}"),
+                )
+            })?;
+    if let Some(event_code) = params
+        .event_code
+        .as_deref()
+        .map(str::trim)
+        .filter(|code| !code.is_empty())
+    {
+        recordings.retain(|entry| {
+            entry
+                .recording
+                .name
+                .event_code
+                .eq_ignore_ascii_case(event_code)
+        });
+    }
+
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = params
+        .per_page
+        .unwrap_or(RECORDINGS_DEFAULT_PER_PAGE)
+        .clamp(1, RECORDINGS_MAX_PER_PAGE);
+    let total = recordings.len();
+    let recordings = recordings
+        .into_iter()
+        .skip((page - 1).saturating_mul(per_page))
+        .take(per_page)
+        .collect();
+    Ok(Json(RecordingsResponse {
+        page,
+        per_page,
+        total,
+        recordings,
+    }))
+}
+
+async fn recording_file_handler(
+    State(state): State<ApiState>,
+    Path(file): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    match share::resolve_recording_path(&state.config(), &file).await {
+        Ok(Some(path)) => file_stream::file_response(&path, &headers).await,
+        Ok(None) => (StatusCode::BAD_REQUEST, "Invalid recording name").into_response(),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            (StatusCode::NOT_FOUND, "Recording not found").into_response()
+        }
+        Err(err) => {
+            warn!("Failed to resolve recording '{}': {}", file, err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to open recording",
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn read_config_document(
+    path: &std::path::Path,
+) -> Result<serde_json::Value, (StatusCode, String)> {
+    let format = config::ConfigFormat::from_path(path);
+    match tokio::fs::read_to_string(path).await {
+        Ok(contents) => format.parse(&contents).map_err(|err| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!(
+                    "The current configuration file is not valid {}: {err:#}",
+                    format.name()
+                ),
+            )
+        }),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(serde_json::json!({})),
+        Err(err) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to read the configuration file: {err}"),
+        )),
+    }
+}
+
+fn write_validated_config(
+    path: &std::path::Path,
+    document: &serde_json::Value,
+) -> Result<Config, (StatusCode, String)> {
+    let internal = |err: std::io::Error| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to write the configuration file: {err}"),
+        )
+    };
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(std::path::Path::new("."));
+    let format = config::ConfigFormat::from_path(path);
+    let rendered = format.render(document).map_err(|err| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!(
+                "The configuration cannot be written as {}: {err:#}",
+                format.name()
+            ),
+        )
+    })?;
+    let suffix = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    let mut temp = tempfile::Builder::new()
+        .suffix(&suffix)
+        .tempfile_in(dir)
+        .map_err(internal)?;
+    temp.write_all(rendered.as_bytes()).map_err(internal)?;
+    if let Ok(metadata) = std::fs::metadata(path) {
+        std::fs::set_permissions(temp.path(), metadata.permissions()).map_err(internal)?;
+    }
+
+    let validated = Config::from_file(temp.path())
+        .map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, format!("{err:#}")))?;
+    temp.persist(path).map_err(|err| internal(err.error))?;
+    Ok(validated)
+}
+
+fn changed_config_keys(before: &serde_json::Value, after: &serde_json::Value) -> Vec<String> {
+    let empty = serde_json::Map::new();
+    let before = before.as_object().unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+    let mut keys: Vec<String> = before
+        .keys()
+        .chain(after.keys())
+        .filter(|key| before.get(*key) != after.get(*key))
+        .cloned()
+        .collect();
+    keys.sort();
+    keys.dedup();
+    keys
+}
+
+async fn config_handler(
+    State(state): State<ApiState>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let current = read_config_document(&state.config_path).await?;
+    Ok(Json(config::redact_config_secrets(&current)))
+}
+
+async fn update_config_handler(
+    State(state): State<ApiState>,
+    Extension(note): Extension<AuditNote>,
+    Json(mut edited): Json<serde_json::Value>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    if !edited.is_object() {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "The configuration must be a JSON object".to_string(),
+        ));
+    }
+    let current = read_config_document(&state.config_path).await?;
+    config::restore_config_secrets(&mut edited, &current);
+
+    let config_path = state.config_path.clone();
+    let document = edited.clone();
+    let new_config =
+        tokio::task::spawn_blocking(move || write_validated_config(&config_path, &document))
+            .await
+            .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))??;
+    crate::apply_config_reload(
+        new_config,
+        &state.config_path,
+        &state.app_state,
+        &state.reload_tx,
+    )
+    .await;
+    note.set(format!(
+        "Configuration updated (keys: {})",
+        changed_config_keys(&current, &edited).join(", ")
+    ));
+
+    Ok(Json(config::redact_config_secrets(&edited)))
+}
+
+const RELOAD_ACK_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Serialize)]
+struct ReloadResponse {
+    generation: u64,
+    #[serde(flatten)]
+    acks: crate::reload::ReloadAcks,
+}
+
+async fn reload_config_handler(
+    State(state): State<ApiState>,
+    Extension(note): Extension<AuditNote>,
+) -> Result<Json<ReloadResponse>, (StatusCode, String)> {
+    let config_path = state.config_path.clone();
+    let new_config = tokio::task::spawn_blocking(move || Config::from_file(&config_path))
+        .await
+        .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?
+        .map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, format!("{err:#}")))?;
+
+    let generation = crate::apply_config_reload(
+        new_config,
+        &state.config_path,
+        &state.app_state,
+        &state.reload_tx,
+    )
+    .await;
+    let acks = crate::reload::wait_for_acks(generation, RELOAD_ACK_TIMEOUT).await;
+    if acks.pending.is_empty() {
+        note.set("Configuration reloaded from disk");
+    } else {
+        note.set(format!(
+            "Configuration reloaded from disk (not yet applied by: {})",
+            acks.pending.join(", ")
+        ));
+    }
+
+    Ok(Json(ReloadResponse { generation, acks }))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LoggingUpdate {
+    levels: BTreeMap<String, Option<String>>,
+    #[serde(default)]
+    persist: bool,
+    #[serde(default)]
+    output: Option<crate::logging::LogOutput>,
+}
+
+async fn logging_handler() -> Json<crate::logging::LoggingStatus> {
+    Json(crate::logging::status())
+}
+
+async fn update_logging_handler(
+    State(state): State<ApiState>,
+    Extension(note): Extension<AuditNote>,
+    Json(update): Json<LoggingUpdate>,
+) -> Result<Json<crate::logging::LoggingStatus>, (StatusCode, String)> {
+    let mut changes = BTreeMap::new();
+    for (target, level) in &update.levels {
+        let change = match level {
+            Some(level) => {
+                let (target, level) = crate::logging::parse_target_level(target, level)
+                    .map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+                (target, Some(level))
+            }
+            None => (target.trim().to_string(), None),
+        };
+        changes.insert(change.0, change.1);
+    }
+    if changes.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "levels must name at least one log target".to_string(),
+        ));
+    }
+    let summary = changes
+        .iter()
+        .map(|(target, level)| match level {
+            Some(level) => format!("{target}={level}"),
+            None => format!("{target}=default"),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    if let Some(output) = update.output {
+        if update.persist {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "LOG_TARGET_LEVELS applies to every output; persist levels without an output"
+                    .to_string(),
+            ));
+        }
+        let status = crate::logging::set_runtime_levels(Some(output), &changes);
+        note.set(format!(
+            "{output:?} log levels set until restart: {summary}"
+        ));
+        return Ok(Json(status));
+    }
+    if !update.persist {
+        let status = crate::logging::set_runtime_levels(None, &changes);
+        note.set(format!("Log levels set until restart: {summary}"));
+        return Ok(Json(status));
+    }
+
+    let mut document = read_config_document(&state.config_path).await?;
+    let Some(config_object) = document.as_object_mut() else {
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "The current configuration file is not a JSON object".to_string(),
+        ));
+    };
+    let levels = config_object
+        .entry("LOG_TARGET_LEVELS")
+        .or_insert_with(|| serde_json::json!({}));
+    let Some(levels) = levels.as_object_mut() else {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "LOG_TARGET_LEVELS in the configuration file is not an object".to_string(),
+        ));
+    };
+    for (target, level) in &changes {
+        match level {
+            Some(level) => {
+                levels.insert(target.clone(), serde_json::Value::String(level.to_string()));
+            }
+            None => {
+                levels.remove(target);
+            }
+        }
+    }
+
+    let config_path = state.config_path.clone();
+    let new_config =
+        tokio::task::spawn_blocking(move || write_validated_config(&config_path, &document))
+            .await
+            .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))??;
+    crate::apply_config_reload(
+        new_config,
+        &state.config_path,
+        &state.app_state,
+        &state.reload_tx,
+    )
+    .await;
+    let status = crate::logging::clear_runtime_levels(changes.keys());
+    note.set(format!("Log levels saved to the configuration: {summary}"));
+    Ok(Json(status))
+}
+
+async fn status_handler(State(state): State<ApiState>, headers: HeaderMap) -> Response {
+    maybe_persist_deeplink_host(&headers, &state).await;
+    let revision = state.monitoring.status_revision();
+    let streams = filter_non_cap_streams(state.monitoring.stream_snapshots(), &state);
+    let (active_alerts, active_alert_limit, evicted_alerts, cap_status) = {
+        let guard = state.app_state.lock().await;
+        (
+            guard.active_alerts.clone(),
+            guard.max_active_alerts(),
+            guard.alerts_evicted,
+            build_cap_status_payload(&guard.active_alerts, &guard.cap_status),
+        )
+    };
+    let mut status = StatusResponse {
+        streams,
+        active_alerts,
+        active_alert_limit,
+        evicted_alerts,
+        cap_status,
+        resources: resources::latest(),
+        monitoring: state.monitoring.event_channel_stats(),
+        started_at: state.monitoring.started_at(),
+        uptime_seconds: 0,
+        previous_shutdown: lifecycle::previous_shutdown(),
+        previous_crash: lifecycle::previous_crash(),
+        recording_sweep: cleanup::last_recording_sweep(),
+        tasks: supervisor::task_statuses(),
+        dependencies: dependencies::last_report(),
+    };
+    let etag = match serde_json::to_vec(&status) {
+        Ok(body) => status_etag(revision, &body),
+        Err(err) => return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    };
+    if etag_matches(&headers, &etag) {
+        return not_modified(&etag);
+    }
+    status.uptime_seconds = (chrono::Utc::now() - status.started_at)
+        .num_seconds()
+        .max(0) as u64;
+    let body = match serde_json::to_vec(&status) {
+        Ok(body) => body,
+        Err(err) => return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    };
+    (
+        [
+            (header::ETAG, etag),
+            (header::CACHE_CONTROL, "no-cache".to_string()),
+            (CONTENT_TYPE, "application/json".to_string()),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+async fn metrics_handler() -> Response {
+    let body = resources::latest()
+        .map(|snapshot| resources::prometheus_text(&snapshot))
+        .unwrap_or_default();
+    (
+        [(CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        body,
+    )
+        .into_response()
+}
+
+async fn cap_status_handler(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+) -> Json<CapStatusPayload> {
+    maybe_persist_deeplink_host(&headers, &state).await;
+    Json(cap_status_snapshot(&state).await)
+}
+
+async fn recent_alerts_handler(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+) -> Json<Vec<RecentAlert>> {
+    maybe_persist_deeplink_host(&headers, &state).await;
+    let guard = state.app_state.lock().await;
+    Json(guard.recent_alerts.iter().rev().cloned().collect())
+}
+
+async fn alert_cap_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+) -> Result<Response, (StatusCode, String)> {
+    let alert = {
+        let guard = state.app_state.lock().await;
+        guard
+            .recent_alerts
+            .iter()
+            .rev()
+            .find(|recent| recent.id == id)
+            .map(|recent| recent.alert.clone())
+            .or_else(|| {
+                guard
+                    .active_alerts
+                    .iter()
+                    .find(|alert| cap_xml::identifier(&alert.raw_header) == id)
+                    .cloned()
+            })
+    };
+    let alert = alert.ok_or_else(|| (StatusCode::NOT_FOUND, format!("No alert {id}")))?;
+    let document = cap_xml::document(&alert, &state.config().eas_relay_name);
+    Ok((
+        [(CONTENT_TYPE, "application/cap+xml; charset=utf-8")],
+        document,
+    )
+        .into_response())
+}
+
+async fn alert_log_verify_handler(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+) -> Result<Json<ChainVerification>, (StatusCode, String)> {
+    maybe_persist_deeplink_host(&headers, &state).await;
+    alert_log::verify_chain(&state.config())
+        .await
+        .map(Json)
+        .map_err(|err| {
+            error!("Alert log chain verification failed: {:#}", err);
+            (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+        })
+}
+
+fn share_store(state: &ApiState) -> Result<&Arc<ShareStore>, (StatusCode, String)> {
+    state.shares.as_ref().ok_or((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Share links are unavailable".to_string(),
+    ))
+}
+
+fn forwarded_for(headers: &HeaderMap) -> &str {
+    headers
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.rsplit(',').next())
+        .map(str::trim)
+        .unwrap_or("direct")
+}
+
+fn expired_recordings(
+    recordings: Vec<RecordingFile>,
+    cutoff: chrono::DateTime<chrono::Utc>,
+    active: &HashSet<String>,
+) -> (Vec<RecordingFile>, Vec<String>) {
+    let (active_old, expired): (Vec<_>, Vec<_>) = recordings
+        .into_iter()
+        .filter(|recording| recording.created_at.is_some_and(|created| created < cutoff))
+        .partition(|recording| active.contains(&recording.file));
+    (
+        expired,
+        active_old
+            .into_iter()
+            .map(|recording| recording.file)
+            .collect(),
+    )
+}
+
+async fn active_recording_files(state: &ApiState) -> HashSet<String> {
+    state
+        .recording_state
+        .lock()
+        .await
+        .values()
+        .filter_map(|recording| {
+            recording::recording_file_name(&state.config().recording_dir, &recording.output_path)
+        })
+        .collect()
+}
+
+async fn compact_recordings_index(state: &ApiState) {
+    let recording_dir = state.config().recording_dir.clone();
+    match tokio::task::spawn_blocking(move || recording::compact_index(&recording_dir)).await {
+        Ok(Err(err)) => warn!("Failed to compact the recordings index: {:#}", err),
+        Err(err) => warn!("Failed to compact the recordings index: {}", err),
+        Ok(Ok(_)) => {}
+    }
+}
+
+async fn delete_recording_handler(
+    State(state): State<ApiState>,
+    Extension(note): Extension<AuditNote>,
+    Path(file): Path<String>,
+) -> Result<Json<DeletedRecording>, (StatusCode, String)> {
+    let path = match share::resolve_recording_path(&state.config(), &file).await {
+        Ok(Some(path)) => path,
+        Ok(None) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Invalid recording name".to_string(),
+            ))
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Err((StatusCode::NOT_FOUND, "Recording not found".to_string()))
+        }
+        Err(err) => return Err((StatusCode::INTERNAL_SERVER_ERROR, err.to_string())),
+    };
+    if active_recording_files(&state).await.contains(file.trim()) {
+        return Err((
+            StatusCode::CONFLICT,
+            "Recording is still being written".to_string(),
+        ));
+    }
+
+    let size_bytes = tokio::fs::metadata(&path)
+        .await
+        .map(|metadata| metadata.len())
+        .unwrap_or(0);
+    tokio::task::spawn_blocking(move || recording::remove_recording_files(&path))
+        .await
+        .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?
+        .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+    compact_recordings_index(&state).await;
+    note.set(format!("Recording {file} deleted ({size_bytes} bytes)"));
+    Ok(Json(DeletedRecording { file, size_bytes }))
+}
+
+async fn delete_old_recordings_handler(
+    State(state): State<ApiState>,
+    Extension(identity): Extension<AuditIdentity>,
+    Extension(note): Extension<AuditNote>,
+    Query(params): Query<DeleteRecordingsQuery>,
+) -> Result<Json<DeleteRecordingsResponse>, (StatusCode, String)> {
+    let Some(days) = params.older_than_days.filter(|days| *days > 0) else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "older_than_days must be given and at least 1".to_string(),
+        ));
+    };
+    let cutoff = chrono::Utc::now()
+        - chrono::Duration::try_days(i64::try_from(days).unwrap_or(i64::MAX))
+            .unwrap_or(chrono::Duration::MAX);
+
+    let recording_dir = state.config().recording_dir.clone();
+    let recordings =
+        tokio::task::spawn_blocking(move || recording::scan_recordings(&recording_dir))
+            .await
+            .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?
+            .map_err(|err| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to read the recording directory: {err}"),
+                )
+            })?;
+    let active = active_recording_files(&state).await;
+
+    let (expired, skipped_active) = expired_recordings(recordings, cutoff, &active);
+    let mut response = DeleteRecordingsResponse {
+        dry_run: params.dry_run,
+        deleted: Vec::new(),
+        skipped_active,
+        freed_bytes: 0,
+    };
+    for recording in expired {
+        if !params.dry_run {
+            let Ok(Some(path)) =
+                share::resolve_recording_path(&state.config(), &recording.file).await
+            else {
+                continue;
+            };
+            if let Err(err) = recording::remove_recording_files(&path) {
+                warn!("Failed to delete recording {}: {}", recording.file, err);
+                continue;
+            }
+            info!(
+                "Recording {} deleted ({} bytes, older than {} days) by {}",
+                recording.file, recording.size_bytes, days, identity
+            );
+        }
+        response.freed_bytes += recording.size_bytes;
+        response.deleted.push(DeletedRecording {
+            file: recording.file,
+            size_bytes: recording.size_bytes,
+        });
+    }
+    if !params.dry_run && !response.deleted.is_empty() {
+        compact_recordings_index(&state).await;
+    }
+    note.set(format!(
+        "{} {} recording(s) older than {} days ({} bytes){}",
+        if params.dry_run {
+            "Would delete"
+        } else {
+            "Deleted"
+        },
+        response.deleted.len(),
+        days,
+        response.freed_bytes,
+        if response.skipped_active.is_empty() {
+            String::new()
+        } else {
+            format!(
+                ", skipped {} still being written",
+                response.skipped_active.len()
+            )
+        }
+    ));
+    Ok(Json(response))
+}
+
+static NEXT_RELAY_JOB_ID: AtomicU64 = AtomicU64::new(1);
+
+async fn relay_recording_handler(
+    State(state): State<ApiState>,
+    Extension(note): Extension<AuditNote>,
+    Path(file): Path<String>,
+    request: Option<Json<RelayRecordingRequest>>,
+) -> Result<Json<RelayRecordingResponse>, (StatusCode, String)> {
+    let request = request.map(|Json(request)| request).unwrap_or_default();
+    let config = Config::clone(&state.config());
+    if !(config.should_relay
+        && (config.should_relay_icecast || config.should_relay_dasdec || config.should_relay_myod))
+    {
+        return Err((StatusCode::CONFLICT, "Relaying is disabled".to_string()));
+    }
+    let Some(path) = share::recording_path(&config, &file) else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Invalid recording name".to_string(),
+        ));
+    };
+    if !tokio::fs::try_exists(&path).await.unwrap_or(false) {
+        return Err((StatusCode::NOT_FOUND, "Recording not found".to_string()));
+    }
+
+    let raw_header = match request
+        .raw_header
+        .as_deref()
+        .map(str::trim)
+        .filter(|header| !header.is_empty())
+    {
+        Some(header) => header.to_string(),
+        None => state
+            .db
+            .raw_header_for_recording(&file)
+            .await
+            .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, format!("{err:#}")))?
+            .ok_or_else(|| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    "No alert history for this recording; pass raw_header".to_string(),
+                )
+            })?,
+    };
+    let parsed =
+        e2t_ng::parse_header_checked(&raw_header).map_err(|err| (StatusCode::BAD_REQUEST, err))?;
+
+    let filters = state.app_state.lock().await.filters().snapshot();
+    let (action, filter_name) = filters
+        .matching_rule(
+            "",
+            &parsed.event_code,
+            &parsed.originator,
+            &parsed.fips_codes,
+            Some(parsed.duration.to_std()),
+        )
+        .map(|rule| (rule.action, Some(rule.name.clone())))
+        .unwrap_or((filters.default_action, None));
+    if action != filter::FilterAction::Relay && !request.force {
+        return Err((
+            StatusCode::CONFLICT,
+            format!(
+                "Filter {:?} does not relay {}; pass force=true to relay anyway",
+                filter_name.as_deref().unwrap_or("Default Filter"),
+                parsed.event_code
+            ),
+        ));
+    }
+
+    let job_id = NEXT_RELAY_JOB_ID.fetch_add(1, Ordering::Relaxed);
+    note.set(format!(
+        "Relay job {} started for recording {} ({}){}",
+        job_id,
+        file,
+        raw_header,
+        if request.force {
+            ", overriding filters"
+        } else {
+            ""
+        }
+    ));
+
+    let event_code = parsed.event_code.clone();
+    let fips = parsed.fips_codes.clone();
+    let header_for_relay = raw_header.clone();
+    tokio::spawn(async move {
+        let relay_state = match RelayState::new(config).await {
+            Ok(relay_state) => relay_state,
+            Err(err) => {
+                warn!("Relay job {} could not start: {:?}", job_id, err);
+                return;
+            }
+        };
+        let filters = if request.force {
+            Arc::new(filter::Filters::default())
+        } else {
+            filters
+        };
+        match relay_state
+            .start_relay(&event_code, &fips, &filters, &path, None, &header_for_relay)
+            .await
+        {
+            Ok(outcomes) => {
+                for outcome in outcomes.iter().filter(|outcome| !outcome.ok) {
+                    warn!(
+                        "Relay job {} gave up on {} after {} attempt(s): {}",
+                        job_id,
+                        outcome.target,
+                        outcome.attempts,
+                        outcome.detail.as_deref().unwrap_or("unknown error")
+                    );
+                }
+                info!(target: "audit", "Relay job {} finished", job_id)
+            }
+            Err(err) => warn!("Relay job {} failed: {:?}", job_id, err),
+        }
+    });
+
+    Ok(Json(RelayRecordingResponse {
+        job_id,
+        status: "started",
+        file,
+        event_code: parsed.event_code,
+        raw_header,
+        filter: filter_name,
+        action,
+        forced: request.force && action != filter::FilterAction::Relay,
+    }))
+}
+
+async fn share_recording_handler(
+    State(state): State<ApiState>,
+    Extension(note): Extension<AuditNote>,
+    Path(file): Path<String>,
+    request: Option<Json<ShareRequest>>,
+) -> Result<Json<ShareResponse>, (StatusCode, String)> {
+    let shares = share_store(&state)?;
+    match share::resolve_recording_path(&state.config(), &file).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err((StatusCode::FORBIDDEN, "Invalid recording name".to_string())),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Err((StatusCode::NOT_FOUND, "Recording not found".to_string()))
+        }
+        Err(err) => return Err((StatusCode::INTERNAL_SERVER_ERROR, err.to_string())),
+    }
+
+    let ttl_secs = request
+        .and_then(|Json(request)| request.ttl_secs)
+        .unwrap_or(state.config().share_link_ttl_secs)
+        .clamp(1, i64::MAX as u64 / 1000);
+    let (share, token) = shares
+        .mint(&file, chrono::Duration::seconds(ttl_secs as i64))
+        .await
+        .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+    note.set(format!(
+        "Share link {} minted for recording {} (expires {})",
+        share.id,
+        share.file,
+        share.expires_at.to_rfc3339()
+    ));
+
+    Ok(Json(ShareResponse {
+        url: format!("/api/shared/{token}"),
+        share,
+    }))
+}
+
+async fn list_shares_handler(
+    State(state): State<ApiState>,
+) -> Result<Json<SharesResponse>, (StatusCode, String)> {
+    let shares = share_store(&state)?.list().await;
+    Ok(Json(SharesResponse { shares }))
+}
+
+async fn revoke_share_handler(
+    State(state): State<ApiState>,
+    Extension(note): Extension<AuditNote>,
+    Path(id): Path<String>,
+) -> Result<Json<ShareRecord>, (StatusCode, String)> {
+    let Some(share) = share_store(&state)?.revoke(&id).await else {
+        return Err((StatusCode::NOT_FOUND, "Share not found".to_string()));
+    };
+    note.set(format!(
+        "Share link {} for recording {} revoked",
+        share.id, share.file
+    ));
+    Ok(Json(share))
+}
+
+fn apprise_destination_urls(config: &Config) -> Vec<String> {
+    webhook::read_apprise_urls(&config.apprise_config_path)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|url| url.contains("://") && !url.starts_with("discord://"))
+        .collect()
+}
+
+async fn webhook_stats_handler(State(state): State<ApiState>) -> Json<WebhookStatsResponse> {
+    let (deferred, dropped) = webhook::discord_delivery_counters();
+    let apprise_destinations = webhook_capabilities::destination_statuses(
+        &state.config().shared_state_dir,
+        &state.config().apprise_attachment_support,
+        &apprise_destination_urls(&state.config()),
+    );
+    Json(WebhookStatsResponse {
+        discord: DiscordDeliveryStats { deferred, dropped },
+        apprise_destinations,
+    })
+}
+
+async fn auth_lockouts_handler(State(state): State<ApiState>) -> Json<Vec<LockoutStatus>> {
+    Json(state.auth_limiter.statuses(std::time::Instant::now()))
+}
+
+const AUDIT_DEFAULT_LIMIT: usize = 100;
+const AUDIT_MAX_LIMIT: usize = 1000;
+
+async fn audit_handler(
+    State(state): State<ApiState>,
+    Query(params): Query<AuditQuery>,
+) -> Result<Json<AuditResponse>, (StatusCode, String)> {
+    let limit = params
+        .limit
+        .unwrap_or(AUDIT_DEFAULT_LIMIT)
+        .clamp(1, AUDIT_MAX_LIMIT);
+    let entries = state
+        .audit
+        .recent(limit)
+        .await
+        .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, format!("{err:#}")))?;
+    Ok(Json(AuditResponse { entries }))
+}
+
+const ALERT_STATS_DEFAULT_DAYS: u32 = 30;
+const ALERT_STATS_MAX_DAYS: u32 = 3660;
+
+async fn alert_stats_handler(
+    State(state): State<ApiState>,
+    Query(params): Query<AlertStatsQuery>,
+) -> Json<AlertStats> {
+    let max_days = u32::try_from(state.config().alert_stats_retention_days)
+        .unwrap_or(u32::MAX)
+        .clamp(1, ALERT_STATS_MAX_DAYS);
+    let days = params
+        .days
+        .unwrap_or(ALERT_STATS_DEFAULT_DAYS)
+        .clamp(1, max_days);
+    Json(
+        state
+            .monitoring
+            .alert_stats(days, chrono::Utc::now().date_naive()),
+    )
+}
+
+async fn test_notification_handler(
+    State(state): State<ApiState>,
+    Extension(note): Extension<AuditNote>,
+    request: Option<Json<TestNotificationRequest>>,
+) -> Json<TestNotificationResponse> {
+    let Json(request) = request.unwrap_or_default();
+    let filters = state.app_state.lock().await.filters();
+    let summary = webhook::send_test_notification(request.attach_recording, &filters).await;
+    note.set(format!(
+        "Test notification sent{}: {}",
+        if request.attach_recording {
+            " with a recording"
+        } else {
+            ""
+        },
+        summary
+    ));
+    Json(TestNotificationResponse {
+        delivered: summary.delivered(),
+        total: summary.results.len(),
+        summary,
+    })
+}
+
+async fn filters_handler(State(state): State<ApiState>) -> Json<FiltersResponse> {
+    let handle = state.app_state.lock().await.filters();
+    let filters = handle.snapshot();
+    Json(FiltersResponse {
+        default_action: filters.default_action,
+        unmatched: handle.unmatched_hits(),
+        rules: handle.rules_with_hits(&filters.global),
+        streams: filters
+            .streams
+            .iter()
+            .map(|(id, rules)| (id.clone(), handle.rules_with_hits(rules)))
+            .collect(),
+        sets: state
+            .config()
+            .filter_sets
+            .iter()
+            .map(|(name, rules)| (name.clone(), handle.rules_with_hits(rules)))
+            .collect(),
+    })
+}
+
+async fn evaluate_live_filters(
+    state: &ApiState,
+    event_code: &str,
+    originator: &str,
+    fips: &[String],
+    duration: Option<Duration>,
+) -> Response {
+    let filters = state.app_state.lock().await.filters().snapshot();
+    Json(filter::evaluate(
+        &filters.global,
+        filters.default_action,
+        event_code,
+        originator,
+        fips,
+        duration,
+    ))
+    .into_response()
+}
+
+async fn filter_test_handler(
+    State(state): State<ApiState>,
+    Query(query): Query<FilterTestQuery>,
+) -> Result<Response, (StatusCode, String)> {
+    let event_code = query.event_code.trim();
+    if event_code.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "event_code must not be empty".to_string(),
+        ));
+    }
+    let fips = query
+        .fips
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|code| !code.is_empty())
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    let originator = query.originator.as_deref().unwrap_or_default();
+    let duration = query
+        .duration_minutes
+        .map(|minutes| Duration::from_secs(minutes * 60));
+    Ok(evaluate_live_filters(&state, event_code, originator, &fips, duration).await)
+}
+
+async fn filter_test_header_handler(
+    State(state): State<ApiState>,
+    Json(request): Json<FilterTestRequest>,
+) -> Result<Response, (StatusCode, String)> {
+    let parsed = e2t_ng::parse_header_checked(request.raw_header.trim())
+        .map_err(|err| (StatusCode::BAD_REQUEST, err))?;
+    Ok(evaluate_live_filters(
+        &state,
+        &parsed.event_code,
+        &parsed.originator,
+        &parsed.fips_codes,
+        Some(parsed.duration.to_std()),
+    )
+    .await)
+}
+
+fn injected_alert(
+    raw_header: &str,
+) -> Result<(String, String, String, String, Duration, String), String> {
+    let parsed = e2t_ng::parse_header_checked(raw_header)?;
+    let purge_time = parsed.duration.to_std();
+    Ok((
+        parsed.event_code,
+        parsed.locations.join(", "),
+        parsed.originator,
+        raw_header.to_string(),
+        purge_time,
+        INJECTED_STREAM_ID.to_string(),
+    ))
+}
+
+async fn inject_alert_handler(
+    State(state): State<ApiState>,
+    Extension(note): Extension<AuditNote>,
+    Json(request): Json<InjectAlertRequest>,
+) -> Result<Json<InjectAlertResponse>, (StatusCode, String)> {
+    if !state.config().alert_injection_enabled {
+        return Err((
+            StatusCode::NOT_FOUND,
+            "Alert injection is disabled".to_string(),
+        ));
     }
 
-    let should_write_preferred = {
-        let guard = state.deeplink_host_cache.lock().await;
-        guard.as_deref() != Some(host.as_str())
+    let raw_header = request.raw_header.trim().to_string();
+    let alert = injected_alert(&raw_header).map_err(|err| (StatusCode::BAD_REQUEST, err))?;
+    let response = InjectAlertResponse {
+        event_code: alert.0.clone(),
+        originator: alert.2.clone(),
+        raw_header: raw_header.clone(),
+        stream_id: INJECTED_STREAM_ID,
+        simulate_audio: request.simulate_audio,
     };
 
-    if !should_write_preferred {
-        return;
+    if request.simulate_audio {
+        alerts::request_simulated_audio(&raw_header);
     }
-
-    let host_file = state.config.shared_state_dir.join(DEEPLINK_HOST_CACHE_FILE);
-    match tokio::fs::write(&host_file, &host).await {
-        Ok(_) => {
-            let mut guard = state.deeplink_host_cache.lock().await;
-            *guard = Some(host);
-        }
-        Err(err) => warn!(
-            "Failed to persist deeplink host '{}' to {:?}: {}",
-            host, host_file, err
-        ),
+    if state.alert_tx.send(alert).await.is_err() {
+        alerts::clear_simulated_audio(&raw_header);
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Alert manager is not running".to_string(),
+        ));
+    }
+    note.set(format!(
+        "Alert injected{}: {}",
+        if request.simulate_audio {
+            " with simulated audio"
+        } else {
+            ""
+        },
+        raw_header
+    ));
+
+    if request.simulate_audio {
+        let nnnn_tx = state.nnnn_tx.clone();
+        tokio::spawn(async move {
+            time::sleep(Duration::from_secs(crate::TEST_ALERT_RECORDING_SECS)).await;
+            if let Err(err) = nnnn_tx.send(INJECTED_STREAM_ID.to_string()) {
+                warn!(
+                    "Failed to broadcast synthetic NNNN for injected alert: {}",
+                    err
+                );
+            }
+            alerts::clear_simulated_audio(&raw_header);
+        });
     }
+
+    Ok(Json(response))
 }
 
-pub async fn run_server(
-    bind_addr: SocketAddr,
-    app_state: Arc<Mutex<AppState>>,
-    monitoring: MonitoringHub,
-    config: Config,
-) -> Result<()> {
-    let cap_stream_urls = Arc::new(
-        config
-            .cap_endpoints
-            .iter()
-            .map(|endpoint| endpoint.url.clone())
-            .collect(),
-    );
-    let state = ApiState {
-        app_state,
-        monitoring,
-        cap_stream_urls,
-        config,
-        deeplink_host_cache: Arc::new(Mutex::new(None)),
-        last_seen_host_cache: Arc::new(Mutex::new(None)),
+fn stream_at_index(state: &ApiState, index: usize) -> Result<String, (StatusCode, String)> {
+    filter_non_cap_streams(state.monitoring.stream_snapshots(), state)
+        .into_iter()
+        .nth(index)
+        .map(|stream| stream.stream_url)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("No stream at index {index}")))
+}
+
+fn stream_status(
+    state: &ApiState,
+    stream_url: &str,
+) -> Result<Json<StreamStatusPayload>, (StatusCode, String)> {
+    state
+        .monitoring
+        .stream_snapshot(stream_url)
+        .map(Json)
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Stream {stream_url} was removed"),
+            )
+        })
+}
+
+async fn stream_history_handler(
+    State(state): State<ApiState>,
+    Path(index): Path<usize>,
+) -> Result<Json<StreamHistory>, (StatusCode, String)> {
+    let stream_url = stream_at_index(&state, index)?;
+    state
+        .monitoring
+        .stream_history(&stream_url)
+        .map(Json)
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Stream {stream_url} was removed"),
+            )
+        })
+}
+
+async fn pause_stream_handler(
+    State(state): State<ApiState>,
+    Extension(note): Extension<AuditNote>,
+    Path(index): Path<usize>,
+    request: Option<Json<PauseStreamRequest>>,
+) -> Result<Json<StreamStatusPayload>, (StatusCode, String)> {
+    let stream_url = stream_at_index(&state, index)?;
+    let duration_secs = request.and_then(|Json(request)| request.duration_secs);
+    let until = match duration_secs {
+        None => None,
+        Some(0) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "duration_secs must be greater than zero".to_string(),
+            ))
+        }
+        Some(secs) => Some(
+            i64::try_from(secs)
+                .ok()
+                .and_then(chrono::Duration::try_seconds)
+                .and_then(|duration| chrono::Utc::now().checked_add_signed(duration))
+                .ok_or_else(|| {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        "duration_secs is too large".to_string(),
+                    )
+                })?,
+        ),
     };
 
-    let protected_router = Router::new()
-        .route("/api/logs", get(logs_handler))
-        .route("/api/status", get(status_handler))
-        .route("/api/cap-status", get(cap_status_handler))
-        .route("/api/same-us", get(same_us_lookup_handler))
-        .layer(cors_layer(&state.config))
-        .with_state(state.clone())
-        .route_layer(middleware::from_fn_with_state(state.clone(), auth));
+    state.monitoring.pause_stream(&stream_url, until);
+    note.set(match duration_secs {
+        Some(secs) => format!("Stream {stream_url} paused for {secs}s"),
+        None => format!("Stream {stream_url} paused until resumed"),
+    });
+    stream_status(&state, &stream_url)
+}
 
-    let router = Router::new()
-        .route("/api/health", get(health_handler))
-        .route("/ws", get(ws_handler))
-        .layer(cors_layer(&state.config))
-        .merge(protected_router)
-        .with_state(state.clone());
-
-    let listener = TcpListener::bind(bind_addr).await?;
-    info!(%bind_addr, "Monitoring API listening");
-    axum::serve(listener, router.into_make_service()).await?;
-    Ok(())
+async fn resume_stream_handler(
+    State(state): State<ApiState>,
+    Extension(note): Extension<AuditNote>,
+    Path(index): Path<usize>,
+) -> Result<Json<StreamStatusPayload>, (StatusCode, String)> {
+    let stream_url = stream_at_index(&state, index)?;
+    note.set(if state.monitoring.resume_stream(&stream_url) {
+        format!("Stream {stream_url} resumed")
+    } else {
+        format!("Stream {stream_url} was not paused")
+    });
+    stream_status(&state, &stream_url)
 }
 
-async fn health_handler() -> Json<HealthResponse> {
-    Json(HealthResponse {
-        status: "OK".to_string(),
-    })
+async fn reset_all_capabilities_handler(
+    State(state): State<ApiState>,
+    Extension(note): Extension<AuditNote>,
+) -> Json<CapabilityResetResponse> {
+    let reset = webhook_capabilities::reset_learned(&state.config().shared_state_dir, None);
+    note.set(format!(
+        "Learned attachment support reset for {reset} AppRise destination(s)"
+    ));
+    Json(CapabilityResetResponse { reset })
 }
 
-async fn same_us_lookup_handler(
+async fn reset_capability_handler(
     State(state): State<ApiState>,
-    headers: HeaderMap,
-) -> Json<serde_json::Value> {
-    maybe_persist_deeplink_host(&headers, &state).await;
-    Json(SAME_US_LOOKUP_JSON.clone())
+    Extension(note): Extension<AuditNote>,
+    Path(id): Path<String>,
+) -> Result<Json<CapabilityResetResponse>, (StatusCode, String)> {
+    let reset = webhook_capabilities::reset_learned(&state.config().shared_state_dir, Some(&id));
+    if reset == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            "Nothing has been learned about that destination".to_string(),
+        ));
+    }
+    note.set(format!(
+        "Learned attachment support reset for AppRise destination {id}"
+    ));
+    Ok(Json(CapabilityResetResponse { reset }))
 }
 
-async fn logs_handler(
-    Query(params): Query<LogsQuery>,
+async fn shared_recording_handler(
     State(state): State<ApiState>,
+    Path(token): Path<String>,
     headers: HeaderMap,
-) -> Json<LogsResponse> {
-    maybe_persist_deeplink_host(&headers, &state).await;
-    let max_logs = state.monitoring.max_logs();
-    let tail = params.tail.unwrap_or(100).clamp(1, max_logs);
-    let logs = state.monitoring.recent_logs(tail);
-    Json(LogsResponse { logs })
+) -> Response {
+    let shares = match share_store(&state) {
+        Ok(shares) => shares,
+        Err(err) => return err.into_response(),
+    };
+    let client = forwarded_for(&headers);
+    let share = match shares.redeem(&token).await {
+        Ok(share) => share,
+        Err(err) => {
+            warn!(
+                target: "audit",
+                "Rejected share link access from {}: {:?}",
+                client,
+                err
+            );
+            return match err {
+                ShareError::Expired | ShareError::Revoked => {
+                    (StatusCode::GONE, "This share link has expired").into_response()
+                }
+                ShareError::Malformed | ShareError::BadSignature => {
+                    (StatusCode::FORBIDDEN, "Invalid share link").into_response()
+                }
+            };
+        }
+    };
+    info!(
+        target: "audit",
+        "Share link {} used to fetch {} from {} (access #{})",
+        share.id,
+        share.file,
+        client,
+        share.access_count
+    );
+
+    match share::resolve_recording_path(&state.config(), &share.file).await {
+        Ok(Some(path)) => file_stream::file_response(&path, &headers).await,
+        Ok(None) => (StatusCode::FORBIDDEN, "Invalid share link").into_response(),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            (StatusCode::NOT_FOUND, "Recording not found").into_response()
+        }
+        Err(err) => {
+            warn!(
+                "Failed to resolve shared recording '{}': {}",
+                share.file, err
+            );
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to open recording",
+            )
+                .into_response()
+        }
+    }
 }
 
-async fn status_handler(State(state): State<ApiState>, headers: HeaderMap) -> Json<StatusResponse> {
-    maybe_persist_deeplink_host(&headers, &state).await;
-    let streams = filter_non_cap_streams(state.monitoring.stream_snapshots(), &state);
-    let (active_alerts, cap_status) = {
-        let guard = state.app_state.lock().await;
-        (
-            guard.active_alerts.clone(),
-            build_cap_status_payload(&guard.active_alerts, &guard.cap_status),
-        )
-    };
-    Json(StatusResponse {
-        streams,
-        active_alerts,
-        cap_status,
+const WS_PROTOCOL: &str = "eas-listener";
+const WS_AUTH_PROTOCOL_PREFIX: &str = "eas-auth.";
+
+static WS_QUERY_AUTH_WARNED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, PartialEq)]
+struct WsCredential {
+    auth_header: String,
+    protocol: Option<String>,
+}
+
+fn ws_credential(
+    headers: &HeaderMap,
+    query_auth: Option<&str>,
+    allow_query: bool,
+) -> Option<WsCredential> {
+    if let Some(value) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+    {
+        return Some(WsCredential {
+            auth_header: value.to_string(),
+            protocol: None,
+        });
+    }
+
+    let auth_protocol = headers
+        .get_all(header::SEC_WEBSOCKET_PROTOCOL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .find(|protocol| protocol.starts_with(WS_AUTH_PROTOCOL_PREFIX));
+    if let Some(protocol) = auth_protocol {
+        let token = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(&protocol[WS_AUTH_PROTOCOL_PREFIX.len()..])
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())?;
+        return Some(WsCredential {
+            auth_header: format!("Bearer {token}"),
+            protocol: Some(protocol.to_string()),
+        });
+    }
+
+    let token = query_auth.filter(|token| allow_query && !token.is_empty())?;
+    if !WS_QUERY_AUTH_WARNED.swap(true, Ordering::Relaxed) {
+        warn!("A WebSocket client authenticated with ?auth=, which exposes the token in proxy logs; send an Authorization header or the eas-auth subprotocol instead.");
+    }
+    Some(WsCredential {
+        auth_header: format!("Bearer {token}"),
+        protocol: None,
     })
 }
 
-async fn cap_status_handler(
-    State(state): State<ApiState>,
-    headers: HeaderMap,
-) -> Json<CapStatusPayload> {
-    maybe_persist_deeplink_host(&headers, &state).await;
-    Json(cap_status_snapshot(&state).await)
+fn ws_unauthorized(allow_query: bool) -> Response {
+    let mut accepted = vec![
+        "Authorization: Bearer <token> header".to_string(),
+        format!(
+            "Sec-WebSocket-Protocol: {WS_PROTOCOL}, {WS_AUTH_PROTOCOL_PREFIX}<unpadded base64url of the token>"
+        ),
+    ];
+    if allow_query {
+        accepted.push("?auth=<token> query parameter (deprecated)".to_string());
+    }
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(serde_json::json!({ "error": "Unauthorized", "accepted": accepted })),
+    )
+        .into_response()
 }
 
 async fn ws_handler(
     ws: WebSocketUpgrade,
     State(state): State<ApiState>,
     Query(params): Query<Params>,
+    headers: HeaderMap,
+    peer: Option<ConnectInfo<SocketAddr>>,
 ) -> impl IntoResponse {
-    let auth_header = format!("Bearer {}", params.auth);
+    let ip = client_ip(
+        &headers,
+        peer.map(|ConnectInfo(addr)| addr),
+        state.config().use_reverse_proxy,
+    );
+    if let Some(response) = lockout_response(&state, ip) {
+        return response;
+    }
 
-    if !token_is_valid(&auth_header, &state.config) {
-        (StatusCode::UNAUTHORIZED, "Unauthorized").into_response()
-    } else {
-        ws.on_upgrade(move |socket| ws_connection(socket, state))
+    let allow_query = state.config().ws_query_auth_enabled;
+    let credential = ws_credential(&headers, params.auth.as_deref(), allow_query);
+    let authorized = credential
+        .as_ref()
+        .map(|credential| authorize(&state, Some(&credential.auth_header)));
+    let credential = match (credential, authorized) {
+        (Some(credential), Some(Ok(_))) => credential,
+        (_, Some(Err(AuthFailure::SessionExpired))) => return session_expired_response(),
+        _ => {
+            if let Some(ip) = ip {
+                state
+                    .auth_limiter
+                    .record_failure(ip, std::time::Instant::now());
+            }
+            return ws_unauthorized(allow_query);
+        }
+    };
+
+    if let Some(ip) = ip {
+        state.auth_limiter.record_success(ip);
     }
+    let ws = match credential.protocol {
+        Some(protocol) => ws.protocols([WS_PROTOCOL.to_string(), protocol]),
+        None => ws,
+    };
+    ws.on_upgrade(move |socket| ws_connection(socket, state))
 }
 
 async fn ws_connection(mut socket: WebSocket, state: ApiState) {
-    if let Err(err) = send_snapshot(&mut socket, &state).await {
+    let mut subscription = WsSubscription::default();
+    let mut tail = WS_SNAPSHOT_LOGS;
+    if let Ok(Some(Ok(Message::Text(text)))) =
+        time::timeout(WS_SUBSCRIBE_GRACE, socket.recv()).await
+    {
+        if let Err(err) = apply_ws_client_message(&text, &mut subscription, &mut tail) {
+            let _ = send_ws_message(&mut socket, &WsMessage::Error(err)).await;
+        }
+    }
+    if !subscription.logs {
+        tail = 0;
+    }
+
+    if let Err(err) = send_snapshot(&mut socket, &state, tail).await {
         error!("Failed to send initial snapshot: {err}");
         let _ = socket.close().await;
         return;
     }
 
     let mut events = state.monitoring.subscribe();
+    let client = state.monitoring.register_ws_client();
     let mut heartbeat = time::interval(Duration::from_secs(30));
     heartbeat.set_missed_tick_behavior(MissedTickBehavior::Skip);
 
@@ -417,18 +2764,30 @@ async fn ws_connection(mut socket: WebSocket, state: ApiState) {
                             }
                         }
                         let message: WsMessage = event.into();
+                        if !subscription.allows(&message) {
+                            continue;
+                        }
                         if let Err(err) = send_ws_message(&mut socket, &message).await {
                             error!("Failed to send monitoring event: {err}");
                             break;
                         }
-                        if should_send_cap_status {
+                        client.note_sent();
+                        if should_send_cap_status && subscription.cap_status {
                             if let Err(err) = send_cap_status_update(&mut socket, &state).await {
                                 error!("Failed to send CAP status update: {err}");
                                 break;
                             }
+                            client.note_sent();
+                        }
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                        if client.note_lagged(skipped) {
+                            warn!(
+                                "A WebSocket client fell behind and skipped {} monitoring event(s); consider raising MONITORING_EVENT_CHANNEL_CAPACITY.",
+                                skipped
+                            );
                         }
                     }
-                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
                     Err(_) => break,
                 }
             }
@@ -440,7 +2799,15 @@ async fn ws_connection(mut socket: WebSocket, state: ApiState) {
                             break;
                         }
                     }
-                    Some(Ok(Message::Text(_))) | Some(Ok(Message::Binary(_))) | Some(Ok(Message::Pong(_))) => {}
+                    Some(Ok(Message::Text(text))) => {
+                        let mut ignored_tail = tail;
+                        if let Err(err) = apply_ws_client_message(&text, &mut subscription, &mut ignored_tail) {
+                            if send_ws_message(&mut socket, &WsMessage::Error(err)).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
+                    Some(Ok(Message::Binary(_))) | Some(Ok(Message::Pong(_))) => {}
                     Some(Err(_err)) => {
                         //error!("WebSocket receive error: {err}");
                         break;
@@ -448,9 +2815,11 @@ async fn ws_connection(mut socket: WebSocket, state: ApiState) {
                 }
             }
             _ = heartbeat.tick() => {
-                if let Err(err) = send_cap_status_update(&mut socket, &state).await {
-                    error!("Failed to send CAP status heartbeat update: {err}");
-                    break;
+                if subscription.cap_status {
+                    if let Err(err) = send_cap_status_update(&mut socket, &state).await {
+                        error!("Failed to send CAP status heartbeat update: {err}");
+                        break;
+                    }
                 }
                 if socket.send(Message::Ping(Vec::new())).await.is_err() {
                     break;
@@ -478,19 +2847,21 @@ fn filter_non_cap_streams(
     streams
 }
 
-async fn send_snapshot(socket: &mut WebSocket, state: &ApiState) -> Result<()> {
+async fn send_snapshot(socket: &mut WebSocket, state: &ApiState, log_tail: usize) -> Result<()> {
     let streams = filter_non_cap_streams(state.monitoring.stream_snapshots(), state);
-    let logs = state.monitoring.recent_logs(100);
-    let (active_alerts, cap_status) = {
+    let logs = state.monitoring.recent_logs(log_tail);
+    let (active_alerts, recent_alerts, cap_status) = {
         let guard = state.app_state.lock().await;
         (
             guard.active_alerts.clone(),
+            guard.recent_alerts.iter().rev().cloned().collect(),
             build_cap_status_payload(&guard.active_alerts, &guard.cap_status),
         )
     };
     let snapshot = WsMessage::Snapshot(SnapshotPayload {
         streams,
         active_alerts,
+        recent_alerts,
         cap_status,
         logs,
     });
@@ -531,8 +2902,42 @@ async fn send_ws_message(socket: &mut WebSocket, message: &WsMessage) -> Result<
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api_tokens::ApiToken;
+    use crate::monitoring::AlertsReason;
     use crate::state::EasAlertData;
 
+    #[test]
+    fn lockout_keys_on_the_proxy_appended_forwarded_for_entry() {
+        let peer: SocketAddr = "10.0.0.2:443".parse().expect("peer");
+        let limiter = AuthLimiter::new(LockoutSettings {
+            threshold: 3,
+            window: Duration::from_secs(60),
+            lockout: Duration::from_secs(300),
+        });
+        let now = std::time::Instant::now();
+        for spoofed in ["1.1.1.1", "2.2.2.2", "3.3.3.3"] {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                "x-forwarded-for",
+                format!("{spoofed}, 203.0.113.7").parse().expect("header"),
+            );
+            let ip = client_ip(&headers, Some(peer), true).expect("ip");
+            assert_eq!(ip, "203.0.113.7".parse::<IpAddr>().expect("ip"));
+            limiter.record_failure(ip, now);
+        }
+        assert!(limiter
+            .locked_for("203.0.113.7".parse().expect("ip"), now)
+            .is_some());
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", "not-an-ip".parse().expect("header"));
+        assert_eq!(client_ip(&headers, Some(peer), true), Some(peer.ip()));
+        assert_eq!(
+            client_ip(&HeaderMap::new(), Some(peer), false),
+            Some(peer.ip())
+        );
+    }
+
     fn sample_config(username: &str, password: &str) -> Config {
         let mut cfg = Config::safe_internal_defaults();
         cfg.dashboard_username = username.to_string();
@@ -554,18 +2959,215 @@ mod tests {
         ActiveAlert::new(data, raw_header.to_string(), Duration::from_secs(120))
     }
 
+    fn token_scope(auth_header: &str, credentials: &ApiCredentials) -> Option<ApiTokenScope> {
+        credentials.principal(auth_header).map(|(scope, _)| scope)
+    }
+
     #[test]
     fn token_validation_rejects_default_and_accepts_matching_bearer() {
         let default_cfg = sample_config("admin", "password");
-        assert!(!token_is_valid("Bearer abc", &default_cfg));
+        let credentials = ApiCredentials::from_config(&default_cfg);
+        assert!(token_scope("Bearer abc", &credentials).is_none());
 
         let cfg = sample_config("alice", "s3cret");
-        assert!(!token_is_valid("Basic abc", &cfg));
+        let credentials = ApiCredentials::from_config(&cfg);
+        assert!(token_scope("Basic abc", &credentials).is_none());
 
         let expected = base64::engine::general_purpose::STANDARD.encode("alice:s3cret");
         let auth_header = format!("Bearer {expected}");
-        assert!(token_is_valid(auth_header.as_str(), &cfg));
-        assert!(!token_is_valid("Bearer wrong", &cfg));
+        assert!(token_scope(auth_header.as_str(), &credentials).is_some());
+        assert!(token_scope("Bearer wrong", &credentials).is_none());
+    }
+
+    #[test]
+    fn api_tokens_work_alongside_dashboard_credentials_with_scopes() {
+        let mut cfg = sample_config("admin", "password");
+        cfg.api_tokens = vec![ApiToken {
+            name: "grafana".to_string(),
+            token: "grafana-read-token".to_string(),
+            scope: ApiTokenScope::Read,
+        }];
+
+        let scope = token_scope(
+            "Bearer grafana-read-token",
+            &ApiCredentials::from_config(&cfg),
+        )
+        .expect("api token");
+        assert_eq!(scope, ApiTokenScope::Read);
+        assert!(scope.allows(required_scope(&Method::GET, "/api/status")));
+        assert!(!scope.allows(required_scope(&Method::GET, "/api/auth/lockouts")));
+        assert!(!scope.allows(required_scope(&Method::PUT, "/api/config")));
+        assert!(!scope.allows(required_scope(&Method::POST, "/api/alerts/inject")));
+        assert!(scope.allows(required_scope(&Method::POST, "/api/filters/test")));
+        assert!(!scope.allows(required_scope(&Method::DELETE, "/api/shares/abc")));
+        assert!(token_scope(
+            "Bearer grafana-read-toke",
+            &ApiCredentials::from_config(&cfg)
+        )
+        .is_none());
+
+        cfg.dashboard_username = "alice".to_string();
+        cfg.dashboard_password = "s3cret".to_string();
+        let dashboard = base64::engine::general_purpose::STANDARD.encode("alice:s3cret");
+        assert_eq!(
+            token_scope(
+                &format!("Bearer {dashboard}"),
+                &ApiCredentials::from_config(&cfg)
+            ),
+            Some(ApiTokenScope::Admin)
+        );
+    }
+
+    #[test]
+    fn config_updates_are_validated_before_replacing_the_file() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("config.json");
+        std::fs::write(&path, "{}\n").expect("write config");
+
+        let (status, message) = write_validated_config(
+            &path,
+            &serde_json::json!({ "MONITORING_BIND_PORT": "not a port" }),
+        )
+        .expect_err("invalid config");
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(message.contains("MONITORING_BIND_PORT"), "{message}");
+        assert_eq!(std::fs::read_to_string(&path).expect("read"), "{}\n");
+
+        let config = write_validated_config(
+            &path,
+            &serde_json::json!({ "EAS_RELAY_NAME": "Test Relay", "SAME_STATION_ID": "TEST" }),
+        )
+        .expect("valid config");
+        assert_eq!(config.eas_relay_name, "Test Relay");
+        let written: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).expect("read")).expect("json");
+        assert_eq!(written["EAS_RELAY_NAME"], "Test Relay");
+        assert_eq!(std::fs::read_dir(dir.path()).expect("dir").count(), 1);
+
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "EAS_RELAY_NAME = \"Test Relay\"\nSAME_STATION_ID = \"TEST\"\n",
+        )
+        .expect("write config");
+        let config = write_validated_config(
+            &path,
+            &serde_json::json!({
+                "FILTERS": [{ "name": "Tests", "event_codes": ["RWT"], "action": "log" }],
+                "MQTT_URL": null,
+                "WATCHED_FIPS": "031055"
+            }),
+        )
+        .expect("valid config");
+        assert_eq!(config.filters.len(), 1);
+        let written = config::read_config_document(&path).expect("toml");
+        assert_eq!(written["WATCHED_FIPS"], "031055");
+        assert!(written.get("MQTT_URL").is_none());
+    }
+
+    #[test]
+    fn injected_alerts_are_tagged_and_bad_headers_are_explained() {
+        let (event, locations, originator, raw_header, purge, stream_id) =
+            injected_alert("ZCZC-CIV-DMO-031055-031153+0130-1231200-EASLSTNR-")
+                .expect("valid header");
+        assert_eq!(event, "DMO");
+        assert_eq!(locations, "031055, 031153");
+        assert_eq!(originator, "CIV");
+        assert!(raw_header.ends_with("-EASLSTNR-"));
+        assert_eq!(purge, Duration::from_secs(90 * 60));
+        assert_eq!(stream_id, INJECTED_STREAM_ID);
+
+        let err = injected_alert("ZCZC-CIV-DMO-031055+0015-1231200-").expect_err("no sender");
+        assert!(err.contains("sender id"), "{err}");
+    }
+
+    #[test]
+    fn websocket_credentials_prefer_header_then_subprotocol_then_query() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::SEC_WEBSOCKET_PROTOCOL,
+            "eas-listener, eas-auth.YWxpY2U6czNjcmV0"
+                .parse()
+                .expect("header"),
+        );
+        assert_eq!(
+            ws_credential(&headers, Some("ignored"), true),
+            Some(WsCredential {
+                auth_header: "Bearer alice:s3cret".to_string(),
+                protocol: Some("eas-auth.YWxpY2U6czNjcmV0".to_string()),
+            })
+        );
+
+        headers.insert(AUTHORIZATION, "Bearer from-header".parse().expect("header"));
+        let credential = ws_credential(&headers, None, false).expect("header credential");
+        assert_eq!(credential.auth_header, "Bearer from-header");
+        assert_eq!(credential.protocol, None);
+
+        let empty = HeaderMap::new();
+        assert_eq!(
+            ws_credential(&empty, Some("query-token"), true).map(|c| c.auth_header),
+            Some("Bearer query-token".to_string())
+        );
+        assert_eq!(ws_credential(&empty, Some("query-token"), false), None);
+    }
+
+    #[test]
+    fn cors_accepts_lan_origins_by_default_and_configured_origins_otherwise() {
+        let none: Vec<String> = Vec::new();
+        assert!(origin_allowed("http://192.168.1.209:8080", &none, &none));
+        assert!(origin_allowed("http://localhost:3010", &none, &none));
+        assert!(origin_allowed("http://[fd00::5]:3010", &none, &none));
+        assert!(!origin_allowed("https://evil.example", &none, &none));
+        assert!(!origin_allowed("null", &none, &none));
+
+        let proxy = vec!["eas.example.com".to_string()];
+        assert!(origin_allowed("https://eas.example.com", &none, &proxy));
+
+        let allowed = vec!["http://192.168.1.209:8080".to_string()];
+        assert!(origin_allowed("http://192.168.1.209:8080", &allowed, &none));
+        assert!(!origin_allowed(
+            "http://192.168.1.210:8080",
+            &allowed,
+            &none
+        ));
+    }
+
+    #[tokio::test]
+    async fn a_wildcard_allowed_origin_answers_any_origin_with_a_star() {
+        async fn allow_origin_header(config: &Config, origin: &str) -> Option<String> {
+            let app = Router::new()
+                .route("/", get(|| async { "ok" }))
+                .layer(cors_layer(config));
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+                .await
+                .expect("bind");
+            let addr = listener.local_addr().expect("addr");
+            tokio::spawn(async move {
+                axum::serve(listener, app).await.expect("serve");
+            });
+            reqwest::Client::new()
+                .get(format!("http://{addr}/"))
+                .header("origin", origin)
+                .send()
+                .await
+                .expect("request")
+                .headers()
+                .get("access-control-allow-origin")
+                .map(|value| value.to_str().expect("header").to_string())
+        }
+
+        let mut config = Config::safe_internal_defaults();
+        assert_eq!(
+            allow_origin_header(&config, "https://evil.example").await,
+            None
+        );
+        config.allowed_origins = vec!["*".to_string()];
+        assert_eq!(
+            allow_origin_header(&config, "https://evil.example")
+                .await
+                .as_deref(),
+            Some("*")
+        );
     }
 
     #[test]
@@ -585,6 +3187,93 @@ mod tests {
         assert_eq!(sanitize_host_header("  "), None);
     }
 
+    #[test]
+    fn log_queries_parse_levels_and_reject_bad_values() {
+        let mut params = LogsQuery {
+            tail: None,
+            level: Some("WARN".to_string()),
+            target: Some(String::new()),
+            contains: None,
+            since: Some(1_700_000_000_000),
+            until: None,
+        };
+        let filter = log_filter(&params).expect("filter");
+        assert_eq!(filter.min_level, Some(tracing::Level::WARN));
+        assert!(filter.target_prefix.is_none());
+        assert_eq!(
+            filter.since.map(|since| since.timestamp_millis()),
+            Some(1_700_000_000_000)
+        );
+
+        params.level = Some("loud".to_string());
+        assert!(log_filter(&params).unwrap_err().contains("loud"));
+        params.level = None;
+        params.until = Some(i64::MAX);
+        assert!(log_filter(&params).unwrap_err().contains("until"));
+    }
+
+    #[test]
+    fn readiness_degrades_then_fails_below_the_healthy_stream_threshold() {
+        let stream = |url: &str, healthy: bool| StreamStatusPayload {
+            stream_url: url.to_string(),
+            is_removed: false,
+            is_connected: true,
+            is_receiving_audio: healthy,
+            connection_attempts: 1,
+            alerts_received: 0,
+            connected_since: None,
+            last_activity: None,
+            last_disconnect: None,
+            last_alert_received_ts: None,
+            last_alert_received: None,
+            last_error: None,
+            uptime_seconds: None,
+            is_paused: false,
+            paused_until: None,
+            availability_percent_24h: None,
+        };
+
+        let all = readiness(vec![stream("a", true), stream("b", true)], 1);
+        assert_eq!((all.status, all.ready), ("ok", true));
+        let one = readiness(vec![stream("a", true), stream("b", false)], 1);
+        assert_eq!((one.status, one.ready), ("degraded", true));
+        let none = readiness(vec![stream("a", false), stream("b", false)], 1);
+        assert_eq!((none.status, none.ready), ("down", false));
+        assert!(!none.streams[0].is_receiving_audio);
+
+        let capped = readiness(vec![stream("a", true)], 3);
+        assert_eq!((capped.min_healthy_streams, capped.ready), (1, true));
+    }
+
+    #[test]
+    fn bulk_recording_cleanup_skips_new_and_active_recordings() {
+        let now = chrono::Utc::now();
+        let recording = |file: &str, age_days: i64| RecordingFile {
+            file: file.to_string(),
+            size_bytes: 10,
+            created_at: Some(now - chrono::Duration::days(age_days)),
+            name: recording::parse_recording_file_name(
+                "EAS_Recording_2024-01-02_03-04-05_TOR_WXYZ.wav",
+            )
+            .expect("recording name"),
+        };
+        let recordings = vec![
+            recording("old.wav", 40),
+            recording("writing.wav", 40),
+            recording("new.wav", 2),
+        ];
+        let active = HashSet::from(["writing.wav".to_string()]);
+
+        let (expired, skipped) =
+            expired_recordings(recordings, now - chrono::Duration::days(30), &active);
+        let expired: Vec<_> = expired
+            .into_iter()
+            .map(|recording| recording.file)
+            .collect();
+        assert_eq!(expired, ["old.wav"]);
+        assert_eq!(skipped, ["writing.wav"]);
+    }
+
     #[test]
     fn extract_deeplink_host_candidate_prefers_forwarded_host() {
         let mut headers = HeaderMap::new();
@@ -621,4 +3310,304 @@ mod tests {
         let payload = build_cap_status_payload(&alerts, &runtime);
         assert_eq!(payload.active_alerts, 1);
     }
+
+    #[test]
+    fn alerts_messages_carry_the_reason_and_source_beside_the_alert_list() {
+        let message = WsMessage::from(MonitoringEvent::Alerts(AlertsUpdate {
+            alerts: vec![make_alert("ZCZC-WXR-TOR-031055+0030-1231234-KOAX/NWS-")],
+            reason: AlertsReason::New,
+            source_stream: Some("http://radio/stream".to_string()),
+            triggering_event_code: Some("TOR".to_string()),
+            evicted: Vec::new(),
+        }));
+        let json = serde_json::to_value(&message).expect("serialize");
+        assert_eq!(json["type"], "Alerts");
+        assert_eq!(json["payload"]["reason"], "new");
+        assert_eq!(json["payload"]["source_stream"], "http://radio/stream");
+        assert_eq!(json["payload"]["triggering_event_code"], "TOR");
+        assert_eq!(
+            json["payload"]["alerts"][0]["raw_header"],
+            "ZCZC-WXR-TOR-031055+0030-1231234-KOAX/NWS-"
+        );
+
+        let expired = serde_json::to_value(WsMessage::Alerts(AlertsUpdate {
+            alerts: Vec::new(),
+            reason: AlertsReason::Expired,
+            source_stream: None,
+            triggering_event_code: None,
+            evicted: Vec::new(),
+        }))
+        .expect("serialize");
+        assert_eq!(
+            expired["payload"],
+            serde_json::json!({
+                "alerts": [],
+                "reason": "expired",
+                "source_stream": null,
+                "triggering_event_code": null,
+            })
+        );
+    }
+
+    #[test]
+    fn websocket_client_messages_update_the_subscription() {
+        let mut subscription = WsSubscription::default();
+        let mut tail = WS_SNAPSHOT_LOGS;
+        assert!(subscription.allows(&WsMessage::Alerts(AlertsUpdate {
+            alerts: Vec::new(),
+            reason: AlertsReason::Expired,
+            source_stream: None,
+            triggering_event_code: None,
+            evicted: Vec::new(),
+        })));
+
+        apply_ws_client_message(
+            r#"{"subscribe": ["alerts", "streams"], "tail": 0}"#,
+            &mut subscription,
+            &mut tail,
+        )
+        .expect("valid message");
+        assert_eq!(tail, 0);
+        assert!(subscription.alerts && subscription.streams);
+        assert!(!subscription.logs && !subscription.cap_status);
+        assert!(
+            !subscription.allows(&WsMessage::CapStatus(build_cap_status_payload(
+                &[],
+                &CapRuntimeStatus::default()
+            )))
+        );
+
+        let err = apply_ws_client_message(
+            r#"{"subscribe": ["weather"]}"#,
+            &mut subscription,
+            &mut tail,
+        )
+        .expect_err("unknown topic");
+        assert!(err.contains("weather"), "{err}");
+        assert!(
+            subscription.alerts,
+            "a rejected message keeps the subscription"
+        );
+        assert!(apply_ws_client_message("ping", &mut subscription, &mut tail).is_err());
+
+        apply_ws_client_message(r#"{"tail": 500}"#, &mut subscription, &mut tail).expect("tail");
+        assert_eq!(tail, WS_SNAPSHOT_LOGS);
+    }
+
+    #[tokio::test]
+    async fn unchanged_logs_and_status_answer_304() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let mut config = sample_config("alice", "s3cret");
+        config.shared_state_dir = dir.path().to_path_buf();
+        let monitoring = MonitoringHub::new(10, Duration::from_secs(30));
+        monitoring.record_log(
+            tracing::Level::INFO,
+            "eas_listener",
+            "first".into(),
+            serde_json::Map::new(),
+        );
+        let (reload_tx, _) = broadcast::channel(1);
+        let (alert_tx, _alert_rx) = mpsc::channel(1);
+        let (nnnn_tx, _) = broadcast::channel(1);
+        let mut tasks = JoinSet::new();
+        let state = api_state(
+            Arc::new(Mutex::new(AppState::new(crate::filter::FilterHandle::new(
+                crate::filter::Filters::default(),
+            )))),
+            monitoring.clone(),
+            &config,
+            ConfigFile {
+                path: dir.path().join("config.json"),
+                reload_tx,
+            },
+            DbHandle::open(&dir.path().join("alerts.db")).expect("db"),
+            PipelineHandles {
+                alert_tx,
+                nnnn_tx,
+                recording_state: Arc::new(Mutex::new(HashMap::new())),
+            },
+            &mut tasks,
+        );
+        let app = api_router(&state, &config);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+            .expect("serve");
+        });
+
+        let client = reqwest::Client::new();
+        let token = base64::engine::general_purpose::STANDARD.encode("alice:s3cret");
+        let get = |path: &str, etag: Option<&str>| {
+            let mut request = client
+                .get(format!("http://{addr}{path}"))
+                .bearer_auth(&token);
+            if let Some(etag) = etag {
+                request = request.header(header::IF_NONE_MATCH, etag);
+            }
+            request.send()
+        };
+
+        let mut etags = Vec::new();
+        for path in ["/api/logs", "/api/status"] {
+            let response = get(path, None).await.expect("request");
+            assert_eq!(response.status(), StatusCode::OK);
+            let etag = response.headers()[header::ETAG]
+                .to_str()
+                .expect("etag")
+                .to_string();
+
+            let response = get(path, Some(&etag)).await.expect("request");
+            assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+            assert_eq!(response.headers()[header::ETAG], etag.as_str());
+            assert!(response.bytes().await.expect("body").is_empty());
+            etags.push(etag);
+        }
+
+        monitoring.record_log(
+            tracing::Level::INFO,
+            "eas_listener",
+            "second".into(),
+            serde_json::Map::new(),
+        );
+        let response = get("/api/logs", Some(&etags[0])).await.expect("request");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        monitoring.note_connecting("http://example.com/stream");
+        let response = get("/api/status", Some(&etags[1])).await.expect("request");
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn share_links_refuse_symlinks_out_of_the_recording_dir() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let mut config = sample_config("alice", "s3cret");
+        config.shared_state_dir = dir.path().to_path_buf();
+        config.recording_dir = dir.path().join("recordings");
+        std::fs::create_dir_all(&config.recording_dir).expect("recording dir");
+        std::fs::write(dir.path().join("secret.txt"), b"secret").expect("secret");
+        std::os::unix::fs::symlink(
+            dir.path().join("secret.txt"),
+            config.recording_dir.join("EAS_Recording_1.wav"),
+        )
+        .expect("symlink");
+        let (reload_tx, _) = broadcast::channel(1);
+        let (alert_tx, _alert_rx) = mpsc::channel(1);
+        let (nnnn_tx, _) = broadcast::channel(1);
+        let mut tasks = JoinSet::new();
+        let state = api_state(
+            Arc::new(Mutex::new(AppState::new(crate::filter::FilterHandle::new(
+                crate::filter::Filters::default(),
+            )))),
+            MonitoringHub::new(10, Duration::from_secs(30)),
+            &config,
+            ConfigFile {
+                path: dir.path().join("config.json"),
+                reload_tx,
+            },
+            DbHandle::open(&dir.path().join("alerts.db")).expect("db"),
+            PipelineHandles {
+                alert_tx,
+                nnnn_tx,
+                recording_state: Arc::new(Mutex::new(HashMap::new())),
+            },
+            &mut tasks,
+        );
+        let (_, token) = state
+            .shares
+            .as_ref()
+            .expect("share store")
+            .mint("EAS_Recording_1.wav", chrono::Duration::hours(1))
+            .await
+            .expect("mint");
+        let app = api_router(&state, &config);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+            .expect("serve");
+        });
+
+        let client = reqwest::Client::new();
+        let response = client
+            .post(format!(
+                "http://{addr}/api/recordings/EAS_Recording_1.wav/share"
+            ))
+            .bearer_auth(base64::engine::general_purpose::STANDARD.encode("alice:s3cret"))
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = client
+            .get(format!("http://{addr}/api/shared/{token}"))
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_ne!(response.bytes().await.expect("body").as_ref(), b"secret");
+    }
+
+    #[tokio::test]
+    async fn restarted_servers_leave_one_set_of_background_tasks() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let occupied = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+        let mut config = sample_config("alice", "s3cret");
+        config.shared_state_dir = dir.path().to_path_buf();
+        config.monitoring_bind_addr = occupied.local_addr().expect("addr");
+        let (reload_tx, _) = broadcast::channel(1);
+        let (alert_tx, _alert_rx) = mpsc::channel(1);
+        let (nnnn_tx, _) = broadcast::channel(1);
+        let app_state = Arc::new(Mutex::new(AppState::new(crate::filter::FilterHandle::new(
+            crate::filter::Filters::default(),
+        ))));
+        let monitoring = MonitoringHub::new(10, Duration::from_secs(30));
+        let config_file = ConfigFile {
+            path: dir.path().join("config.json"),
+            reload_tx: reload_tx.clone(),
+        };
+        let db = DbHandle::open(&dir.path().join("alerts.db")).expect("db");
+        let pipeline = PipelineHandles {
+            alert_tx,
+            nnnn_tx,
+            recording_state: Arc::new(Mutex::new(HashMap::new())),
+        };
+        let server = |config: Config| {
+            run_server(
+                None,
+                app_state.clone(),
+                monitoring.clone(),
+                config,
+                config_file.clone(),
+                db.clone(),
+                pipeline.clone(),
+            )
+        };
+
+        for _ in 0..2 {
+            assert!(server(config.clone()).await.is_err());
+        }
+        config.monitoring_bind_addr = "127.0.0.1:0".parse().expect("addr");
+        let running = tokio::spawn(server(config));
+        time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(reload_tx.receiver_count(), 1);
+
+        running.abort();
+        assert!(running.await.is_err());
+        time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(reload_tx.receiver_count(), 0);
+    }
 }
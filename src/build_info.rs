@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    pub git_commit: &'static str,
+    pub build_timestamp: Option<DateTime<Utc>>,
+    pub features: Vec<&'static str>,
+    pub config_schema_version: u32,
+}
+
+pub fn build_info() -> BuildInfo {
+    BuildInfo {
+        version: env!("CARGO_PKG_VERSION"),
+        git_commit: env!("EAS_LISTENER_GIT_COMMIT"),
+        build_timestamp: env!("EAS_LISTENER_BUILD_EPOCH")
+            .parse::<i64>()
+            .ok()
+            .and_then(|secs| DateTime::from_timestamp(secs, 0)),
+        features: env!("EAS_LISTENER_FEATURES")
+            .split(',')
+            .filter(|feature| !feature.is_empty())
+            .collect(),
+        config_schema_version: crate::config::CONFIG_SCHEMA_VERSION,
+    }
+}
+
+impl std::fmt::Display for BuildInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "EAS Listener v{} (commit {}",
+            self.version, self.git_commit
+        )?;
+        if let Some(built) = self.build_timestamp {
+            write!(f, ", built {}", built.format("%Y-%m-%d %H:%M:%S UTC"))?;
+        }
+        if !self.features.is_empty() {
+            write!(f, ", features: {}", self.features.join(", "))?;
+        }
+        write!(f, ", config schema v{})", self.config_schema_version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_info_reports_the_crate_version_and_a_commit() {
+        let info = build_info();
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert!(!info.git_commit.is_empty());
+        assert!(info.build_timestamp.is_some());
+        assert!(info
+            .to_string()
+            .starts_with(&format!("EAS Listener v{}", info.version)));
+    }
+}
@@ -1,9 +1,10 @@
-use crate::alerts::update_alert_files;
+use crate::alerts::{record_relay_outcomes, update_alert_files};
 use crate::config::Config;
 use crate::db::DbHandle;
 use crate::filter::{self, FilterAction};
 use crate::header;
-use crate::monitoring::MonitoringHub;
+use crate::monitoring::{AlertsReason, MonitoringHub};
+use crate::recording;
 use crate::relay::RelayState;
 use crate::state::{ActiveAlert, AlertRecordingState, AppState, EasAlertData};
 use crate::webhook::send_alert_webhook;
@@ -18,7 +19,6 @@ use std::path::{Path, PathBuf};
 use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 use tokio::fs;
-use tokio::fs::OpenOptions;
 use tokio::io::AsyncWriteExt;
 use tokio::process::Command;
 use tokio::sync::{broadcast, Mutex};
@@ -112,6 +112,7 @@ pub async fn run_cap_supervisor(
         None
     };
 
+    crate::reload::register("cap");
     loop {
         match reload_rx.recv().await {
             Ok(new_config) => {
@@ -138,6 +139,7 @@ pub async fn run_cap_supervisor(
                 } else {
                     info!("CAP processor disabled by reloaded configuration.");
                 }
+                crate::reload::acknowledge("cap");
             }
             Err(broadcast::error::RecvError::Lagged(skipped)) => {
                 warn!(
@@ -501,12 +503,6 @@ async fn cap_alert_is_active(app_state: &Arc<Mutex<AppState>>, dedupe_key: &str)
     active_alert_has_dedupe_key(&guard.active_alerts, dedupe_key)
 }
 
-fn recording_file_name_from_path(path: &Path) -> Option<String> {
-    path.file_name()
-        .and_then(|name| name.to_str())
-        .map(|name| name.to_string())
-}
-
 async fn update_cap_alert_recording_metadata(
     config: &Config,
     app_state: &Arc<Mutex<AppState>>,
@@ -515,7 +511,7 @@ async fn update_cap_alert_recording_metadata(
     recording_state: AlertRecordingState,
     recording_file_name: Option<String>,
 ) {
-    let active_snapshot = {
+    let (active_snapshot, event_code) = {
         let mut guard = app_state.lock().await;
         if !guard.update_alert_recording_metadata(raw_header, recording_state, recording_file_name)
         {
@@ -529,10 +525,20 @@ async fn update_cap_alert_recording_metadata(
             );
         }
 
-        guard.active_alerts.clone()
+        let event_code = guard
+            .active_alerts
+            .iter()
+            .find(|alert| alert.raw_header == raw_header)
+            .map(|alert| alert.data.event_code.clone());
+        (guard.active_alerts.clone(), event_code)
     };
 
-    monitoring.broadcast_alerts(active_snapshot, None, None);
+    monitoring.broadcast_alerts(
+        active_snapshot,
+        AlertsReason::Updated,
+        None,
+        event_code.as_deref(),
+    );
 }
 
 async fn process_cap_alert(
@@ -546,11 +552,16 @@ async fn process_cap_alert(
 ) {
     let event_code = normalize_event_code(&alert.event_code);
 
-    let filters = {
-        let guard = app_state.lock().await;
-        guard.cloned_filters()
-    };
-    let action = filter::evaluate_action(filters.as_slice(), &event_code);
+    let filters = app_state.lock().await.filters();
+    let action = filters.evaluate_action(
+        source_stream,
+        &event_code,
+        &alert.originator_code,
+        &alert.fips,
+        alert
+            .expires
+            .map(|expires| (expires - Utc::now()).to_std().unwrap_or_default()),
+    );
     if action == FilterAction::Ignore {
         debug!(
             "Skipping CAP alert {} ({}) due to filter action=ignore",
@@ -665,22 +676,25 @@ async fn process_cap_alert(
     let active_alert = ActiveAlert::new(alert_data, raw_header.clone(), purge_time)
         .with_source_stream_url(source_stream.to_string());
 
-    let active_snapshot = {
+    let (active_snapshot, evicted) = {
         let mut guard = app_state.lock().await;
-        let now = Utc::now();
-        guard
-            .active_alerts
-            .retain(|existing| existing.expires_at > now && existing.raw_header != raw_header);
-        guard.active_alerts.push(active_alert.clone());
+        let evicted = guard.upsert_active_alert(active_alert.clone());
         guard.cap_status.last_alert_received_at = Some(active_alert.received_at);
         guard.cap_status.last_alert_event_code = Some(event_code.clone());
         guard.cap_status.last_alert_source = Some(source_stream.to_string());
         guard.cap_status.alerts_processed = guard.cap_status.alerts_processed.saturating_add(1);
-        guard.active_alerts.clone()
+        (guard.active_alerts.clone(), evicted)
     };
 
-    monitoring.broadcast_alerts(active_snapshot, Some(source_stream), Some(&event_code));
+    monitoring.broadcast_alerts(
+        active_snapshot.clone(),
+        AlertsReason::New,
+        Some(source_stream),
+        Some(&event_code),
+    );
+    monitoring.broadcast_evicted_alerts(active_snapshot, &evicted);
 
+    let recording_started_at = Utc::now();
     let cap_recording_path =
         match fetch_cap_audio_recording(client, config, &alert, &raw_header, &event_code).await {
             Ok(path) => path,
@@ -692,6 +706,30 @@ async fn process_cap_alert(
                 None
             }
         };
+    if let Some(path) = &cap_recording_path {
+        let metadata = recording::RecordingMetadata {
+            raw_header: raw_header.clone(),
+            alert: Some(active_alert.data.clone()),
+            source_stream: source_stream.to_string(),
+            started_at: recording_started_at,
+            ended_at: Utc::now(),
+            sample_rate: CAP_RECORDING_SAMPLE_RATE,
+            total_samples: None,
+            audio_start_sample: None,
+            audio_samples: None,
+            clipped_samples: None,
+            remote_url: None,
+        };
+        if let Err(err) = recording::write_sidecar(path, &metadata) {
+            warn!(
+                "Failed to write the metadata sidecar for {:?}: {:#}",
+                path, err
+            );
+        }
+        if let Err(err) = recording::index_recording(&config.recording_dir, path, Some(&metadata)) {
+            warn!("Failed to index recording {:?}: {:#}", path, err);
+        }
+    }
 
     let recording_state = if cap_recording_path.is_some() {
         AlertRecordingState::Ready
@@ -700,7 +738,7 @@ async fn process_cap_alert(
     };
     let recording_file_name = cap_recording_path
         .as_ref()
-        .and_then(|path| recording_file_name_from_path(path));
+        .and_then(|path| recording::recording_file_name(&config.recording_dir, path));
     if let Some(ref name) = recording_file_name {
         db.update_recording_name(&raw_header, name).await;
     }
@@ -731,26 +769,40 @@ async fn process_cap_alert(
             &eas_text,
             &raw_header,
             cap_recording_path.clone(),
+            &filters,
         )
         .await;
     }
 
-    if action == FilterAction::Relay && config.should_relay {
+    let forward_to_myod =
+        action == FilterAction::Forward && config.should_relay_myod && config.myod_forward;
+    if (action == FilterAction::Relay || forward_to_myod) && config.should_relay {
         info!("CAP alert for watched zone(s) received. Relaying...");
-        if let Some(recording_path) = cap_recording_path {
+        if let Some(recording_path) = &cap_recording_path {
             match RelayState::new(config.clone()).await {
                 Ok(relay_state) => {
-                    if let Err(err) = relay_state
+                    match relay_state
                         .start_relay(
                             event_code.as_str(),
-                            filters.as_slice(),
+                            &alert.fips,
+                            &filters.snapshot(),
                             &recording_path,
                             Some(source_stream),
                             &raw_header,
                         )
                         .await
                     {
-                        warn!("CAP relay failed for {}: {}", event_code, err);
+                        Ok(outcomes) => {
+                            record_relay_outcomes(
+                                config,
+                                app_state,
+                                monitoring,
+                                &raw_header,
+                                outcomes,
+                            )
+                            .await
+                        }
+                        Err(err) => warn!("CAP relay failed for {}: {}", event_code, err),
                     }
                 }
                 Err(err) => warn!("Skipping CAP relay due to config error: {}", err),
@@ -763,6 +815,12 @@ async fn process_cap_alert(
         }
     }
 
+    if config.upload.is_some() {
+        if let Some(path) = cap_recording_path {
+            crate::upload::enqueue(path);
+        }
+    }
+
     debug!(
         "CAP alert {} ({}) processing completed",
         alert.identifier, event_code
@@ -1739,12 +1797,19 @@ async fn append_cap_log(config: &Config, alert: &CapAlert) -> Result<()> {
         header_string, alert_desc, timestamp
     );
 
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&config.dedicated_alert_log_file)
-        .await?;
-    file.write_all(log_line.as_bytes()).await?;
+    crate::alert_log::append_dedicated(config, &log_line).await?;
+
+    if let Err(err) = crate::alert_log::append_chained_alert(
+        config,
+        &header_string,
+        &alert_desc,
+        Some(alert.source_url.as_str()),
+        received_at,
+    )
+    .await
+    {
+        warn!("Failed to append CAP alert to chained alert log: {}", err);
+    }
     Ok(())
 }
 
@@ -2099,18 +2164,20 @@ async fn build_recording_with_same_header(
         .recording_dir
         .join(format!("cap_nnnn_{}.wav", tmp_id));
 
-    let header_samples = header::generate_same_header_samples(
+    let header_samples = header::generate_same_header_samples_with_timing(
         raw_header,
         CAP_RECORDING_SAMPLE_RATE,
         CAP_HEADER_AMPLITUDE,
+        &config.same_timing,
     )?;
     let silence_samples = header::generate_silence_for_duration(CAP_RECORDING_SAMPLE_RATE, 1.0);
     let attn_samples =
         header::generate_attention_tone(CAP_RECORDING_SAMPLE_RATE, CAP_HEADER_AMPLITUDE)?;
-    let nnnn_samples = header::generate_same_header_samples(
+    let nnnn_samples = header::generate_same_header_samples_with_timing(
         "NNNN",
         CAP_RECORDING_SAMPLE_RATE,
         CAP_HEADER_AMPLITUDE,
+        &config.same_timing,
     )?;
 
     write_wav_i16(&header_path, CAP_RECORDING_SAMPLE_RATE, &header_samples).await?;
@@ -2118,33 +2185,35 @@ async fn build_recording_with_same_header(
     write_wav_i16(&silence_path, CAP_RECORDING_SAMPLE_RATE, &silence_samples).await?;
     write_wav_i16(&nnnn_path, CAP_RECORDING_SAMPLE_RATE, &nnnn_samples).await?;
 
-    let timestamp = Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
     let source_marker = if raw_header.contains(CAP_HEADER_SOURCE_MARKER_WEA) {
         CAP_HEADER_SOURCE_MARKER_WEA
     } else {
         CAP_HEADER_SOURCE_MARKER_CAP
     };
-    let storage_saver = config.storage_saver_mode;
-    let saver_format = config.storage_saver_ext;
-    let extension = if storage_saver {
-        saver_format.extension()
-    } else {
-        "wav"
-    };
-    let output_name = format!(
-        "EAS_Recording_{}_{}_{}.{}",
-        timestamp,
-        sanitize_filename_label(event_code),
+    let format = config.recording_format;
+    let mut fields = recording::RecordingNameFields::from_header(
+        raw_header,
         source_marker,
-        extension
+        Local::now().naive_local(),
     );
-    let output_path = config.recording_dir.join(output_name);
-    let ffmpeg_output_path = if storage_saver {
+    fields.event = sanitize_filename_label(event_code);
+    let output_path = recording::next_available_recording_path(
+        &config.recording_dir,
+        &config
+            .recording_filename_template
+            .with_subdirs(config.recording_subdir_scheme),
+        &fields,
+        format.extension(),
+    );
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent)
+            .await
+            .with_context(|| format!("Failed to create {:?}", parent))?;
+    }
+    let ffmpeg_output_path = {
         let mut partial = output_path.as_os_str().to_owned();
         partial.push(".partial");
         PathBuf::from(partial)
-    } else {
-        output_path.clone()
     };
 
     let mut ffmpeg = Command::new("ffmpeg");
@@ -2171,11 +2240,10 @@ async fn build_recording_with_same_header(
         .arg("-map")
         .arg("[outa]");
 
-    if storage_saver {
-        ffmpeg.args(saver_format.ffmpeg_codec_args());
-    } else {
-        ffmpeg.arg("-c:a").arg("pcm_s16le");
-    }
+    ffmpeg.args(format.ffmpeg_codec_args(config.recording_sample_format));
+    ffmpeg
+        .arg("-ar")
+        .arg(config.recording_sample_rate.to_string());
     ffmpeg.arg(&ffmpeg_output_path);
 
     let status = ffmpeg.status().await?;
@@ -2185,20 +2253,16 @@ async fn build_recording_with_same_header(
     let _ = fs::remove_file(&attn_tone_path).await;
 
     if !status.success() {
-        if storage_saver {
-            let _ = fs::remove_file(&ffmpeg_output_path).await;
-        }
+        let _ = fs::remove_file(&ffmpeg_output_path).await;
         return Err(anyhow!(
             "ffmpeg failed to build CAP recording with SAME header (status {:?})",
             status.code()
         ));
     }
 
-    if storage_saver {
-        fs::rename(&ffmpeg_output_path, &output_path)
-            .await
-            .with_context(|| format!("Failed to finalize CAP recording at {:?}", output_path))?;
-    }
+    fs::rename(&ffmpeg_output_path, &output_path)
+        .await
+        .with_context(|| format!("Failed to finalize CAP recording at {:?}", output_path))?;
 
     Ok(output_path)
 }
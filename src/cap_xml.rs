@@ -0,0 +1,366 @@
+use crate::state::ActiveAlert;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+const CAP_NAMESPACE: &str = "urn:oasis:names:tc:emergency:cap:1.2";
+const TEST_EVENT_CODES: &[&str] = &["DMO", "NPT", "RMT", "RWT"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapEventMapping {
+    pub category: &'static str,
+    pub urgency: &'static str,
+    pub severity: &'static str,
+    pub certainty: &'static str,
+}
+
+const fn mapping(
+    category: &'static str,
+    urgency: &'static str,
+    severity: &'static str,
+    certainty: &'static str,
+) -> CapEventMapping {
+    CapEventMapping {
+        category,
+        urgency,
+        severity,
+        certainty,
+    }
+}
+
+const UNKNOWN_EVENT: CapEventMapping = mapping("Other", "Unknown", "Unknown", "Unknown");
+
+const EVENT_MAPPINGS: &[(&str, CapEventMapping)] = &[
+    ("ADR", UNKNOWN_EVENT),
+    ("AVA", mapping("Geo", "Future", "Severe", "Possible")),
+    ("AVW", mapping("Geo", "Immediate", "Severe", "Likely")),
+    ("BLU", mapping("Security", "Immediate", "Severe", "Likely")),
+    ("BZW", mapping("Met", "Expected", "Severe", "Likely")),
+    ("CAE", mapping("Rescue", "Immediate", "Severe", "Likely")),
+    ("CDW", mapping("Safety", "Immediate", "Extreme", "Likely")),
+    ("CEM", mapping("Safety", "Immediate", "Severe", "Likely")),
+    ("CFA", mapping("Met", "Future", "Severe", "Possible")),
+    ("CFW", mapping("Met", "Expected", "Severe", "Likely")),
+    ("DSW", mapping("Met", "Immediate", "Severe", "Likely")),
+    (
+        "EAN",
+        mapping("Security", "Immediate", "Extreme", "Observed"),
+    ),
+    ("EQW", mapping("Geo", "Immediate", "Extreme", "Observed")),
+    ("EVI", mapping("Safety", "Immediate", "Extreme", "Observed")),
+    ("EWW", mapping("Met", "Immediate", "Extreme", "Observed")),
+    ("FFA", mapping("Met", "Future", "Severe", "Possible")),
+    ("FFS", mapping("Met", "Expected", "Moderate", "Likely")),
+    ("FFW", mapping("Met", "Immediate", "Severe", "Likely")),
+    ("FLA", mapping("Met", "Future", "Moderate", "Possible")),
+    ("FLS", mapping("Met", "Expected", "Minor", "Likely")),
+    ("FLW", mapping("Met", "Expected", "Severe", "Likely")),
+    ("FRW", mapping("Fire", "Immediate", "Severe", "Likely")),
+    ("HLS", mapping("Met", "Expected", "Severe", "Likely")),
+    ("HMW", mapping("CBRNE", "Immediate", "Severe", "Likely")),
+    ("HUA", mapping("Met", "Future", "Extreme", "Possible")),
+    ("HUW", mapping("Met", "Expected", "Extreme", "Likely")),
+    ("HWA", mapping("Met", "Future", "Severe", "Possible")),
+    ("HWW", mapping("Met", "Expected", "Severe", "Likely")),
+    ("LAE", mapping("Safety", "Immediate", "Severe", "Likely")),
+    ("LEW", mapping("Security", "Immediate", "Severe", "Likely")),
+    ("NIC", UNKNOWN_EVENT),
+    ("NMN", UNKNOWN_EVENT),
+    ("NUW", mapping("CBRNE", "Immediate", "Extreme", "Likely")),
+    ("RHW", mapping("CBRNE", "Immediate", "Extreme", "Likely")),
+    ("SMW", mapping("Met", "Immediate", "Severe", "Likely")),
+    ("SPS", mapping("Met", "Expected", "Moderate", "Likely")),
+    ("SPW", mapping("Safety", "Immediate", "Extreme", "Likely")),
+    ("SQW", mapping("Met", "Immediate", "Severe", "Likely")),
+    ("SSA", mapping("Met", "Future", "Extreme", "Possible")),
+    ("SSW", mapping("Met", "Expected", "Extreme", "Likely")),
+    ("SVA", mapping("Met", "Future", "Severe", "Possible")),
+    ("SVR", mapping("Met", "Immediate", "Severe", "Likely")),
+    ("SVS", mapping("Met", "Immediate", "Severe", "Likely")),
+    ("TOA", mapping("Met", "Future", "Extreme", "Possible")),
+    ("TOE", mapping("Infra", "Immediate", "Moderate", "Observed")),
+    ("TOR", mapping("Met", "Immediate", "Extreme", "Likely")),
+    ("TRA", mapping("Met", "Future", "Severe", "Possible")),
+    ("TRW", mapping("Met", "Expected", "Severe", "Likely")),
+    ("TSA", mapping("Geo", "Future", "Severe", "Possible")),
+    ("TSW", mapping("Geo", "Immediate", "Extreme", "Likely")),
+    ("VOW", mapping("Geo", "Immediate", "Severe", "Likely")),
+    ("WSA", mapping("Met", "Future", "Severe", "Possible")),
+    ("WSW", mapping("Met", "Expected", "Severe", "Likely")),
+];
+
+pub fn event_mapping(event_code: &str) -> CapEventMapping {
+    let code = event_code.trim().to_ascii_uppercase();
+    EVENT_MAPPINGS
+        .iter()
+        .find(|(listed, _)| *listed == code)
+        .map_or(UNKNOWN_EVENT, |(_, mapping)| *mapping)
+}
+
+pub fn is_test_event(event_code: &str) -> bool {
+    TEST_EVENT_CODES.contains(&event_code.trim().to_ascii_uppercase().as_str())
+}
+
+pub fn identifier(raw_header: &str) -> String {
+    let digest = Sha256::digest(raw_header.trim().as_bytes());
+    let hash: String = digest[..8]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    format!("EAS-{hash}")
+}
+
+fn cap_time(at: DateTime<Utc>) -> String {
+    at.format("%Y-%m-%dT%H:%M:%S-00:00").to_string()
+}
+
+fn sender(relay_name: &str) -> String {
+    let sender: String = relay_name
+        .trim()
+        .chars()
+        .map(|ch| {
+            if ch.is_whitespace() || ch == ',' {
+                '_'
+            } else {
+                ch
+            }
+        })
+        .collect();
+    if sender.is_empty() {
+        "EAS_Listener".to_string()
+    } else {
+        sender
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+fn element(xml: &mut String, indent: usize, name: &str, value: &str) {
+    let _ = writeln!(
+        xml,
+        "{:indent$}<{name}>{}</{name}>",
+        "",
+        escape(value),
+        indent = indent
+    );
+}
+
+fn value_pair(xml: &mut String, indent: usize, name: &str, value_name: &str, value: &str) {
+    let _ = writeln!(xml, "{:indent$}<{name}>", "", indent = indent);
+    element(xml, indent + 2, "valueName", value_name);
+    element(xml, indent + 2, "value", value);
+    let _ = writeln!(xml, "{:indent$}</{name}>", "", indent = indent);
+}
+
+fn alert_window(alert: &ActiveAlert) -> (DateTime<Utc>, DateTime<Utc>) {
+    let decoded = alert.data.parsed_header.as_ref().and_then(|header| {
+        let start = DateTime::parse_from_rfc3339(&header.start_time_utc).ok()?;
+        let start = start.with_timezone(&Utc);
+        let duration = ChronoDuration::hours(header.duration_hours)
+            + ChronoDuration::minutes(header.duration_minutes);
+        Some((start, start + duration))
+    });
+    decoded.unwrap_or((alert.received_at, alert.expires_at))
+}
+
+pub fn document(alert: &ActiveAlert, relay_name: &str) -> String {
+    let data = &alert.data;
+    let event_code = data.event_code.trim().to_ascii_uppercase();
+    let decoded = data.parsed_header.is_some();
+    let mapping = if decoded {
+        event_mapping(&event_code)
+    } else {
+        CapEventMapping {
+            category: event_mapping(&event_code).category,
+            ..UNKNOWN_EVENT
+        }
+    };
+    let status = if is_test_event(&event_code) {
+        "Test"
+    } else {
+        "Actual"
+    };
+    let event = [data.event_text.trim(), event_code.as_str()]
+        .into_iter()
+        .find(|text| !text.is_empty())
+        .unwrap_or("Unknown Event");
+    let (effective, expires) = alert_window(alert);
+    let area = if data.locations.trim().is_empty() {
+        "Unknown"
+    } else {
+        data.locations.trim()
+    };
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(xml, "<alert xmlns=\"{CAP_NAMESPACE}\">");
+    element(&mut xml, 2, "identifier", &identifier(&alert.raw_header));
+    element(&mut xml, 2, "sender", &sender(relay_name));
+    element(&mut xml, 2, "sent", &cap_time(alert.received_at));
+    element(&mut xml, 2, "status", status);
+    element(&mut xml, 2, "msgType", "Alert");
+    element(&mut xml, 2, "scope", "Public");
+    element(&mut xml, 2, "note", &alert.raw_header);
+    xml.push_str("  <info>\n");
+    element(&mut xml, 4, "language", "en-US");
+    element(&mut xml, 4, "category", mapping.category);
+    element(&mut xml, 4, "event", event);
+    element(&mut xml, 4, "urgency", mapping.urgency);
+    element(&mut xml, 4, "severity", mapping.severity);
+    element(&mut xml, 4, "certainty", mapping.certainty);
+    if !event_code.is_empty() {
+        value_pair(&mut xml, 4, "eventCode", "SAME", &event_code);
+    }
+    element(&mut xml, 4, "effective", &cap_time(effective));
+    element(&mut xml, 4, "expires", &cap_time(expires));
+    element(&mut xml, 4, "senderName", relay_name.trim());
+    element(&mut xml, 4, "headline", event);
+    if !data.eas_text.trim().is_empty() {
+        element(&mut xml, 4, "description", data.eas_text.trim());
+    }
+    let originator = data.originator_code().trim();
+    if !originator.is_empty() {
+        value_pair(&mut xml, 4, "parameter", "EAS-ORG", originator);
+    }
+    xml.push_str("    <area>\n");
+    element(&mut xml, 6, "areaDesc", area);
+    for fips in &data.fips {
+        value_pair(&mut xml, 6, "geocode", "SAME", fips);
+    }
+    xml.push_str("    </area>\n");
+    xml.push_str("  </info>\n");
+    xml.push_str("</alert>\n");
+    xml
+}
+
+pub async fn write_document(dir: &Path, alert: &ActiveAlert, relay_name: &str) -> Result<PathBuf> {
+    tokio::fs::create_dir_all(dir)
+        .await
+        .with_context(|| format!("create {}", dir.display()))?;
+    let path = dir.join(format!("{}.xml", identifier(&alert.raw_header)));
+    tokio::fs::write(&path, document(alert, relay_name))
+        .await
+        .with_context(|| format!("write {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::e2t_ng::ParsedEasSerialized;
+    use crate::state::EasAlertData;
+    use std::time::Duration;
+
+    fn alert(event_code: &str, decoded: bool) -> ActiveAlert {
+        let raw_header = format!("ZCZC-WXR-{event_code}-031055-031153+0030-3391200-KOAX/NWS-");
+        let data = EasAlertData {
+            eas_text: "The National Weather Service has issued a Tornado Warning & more."
+                .to_string(),
+            event_text: if decoded {
+                "Tornado Warning".to_string()
+            } else {
+                String::new()
+            },
+            event_code: event_code.to_string(),
+            fips: vec!["031055".to_string(), "031153".to_string()],
+            locations: "Douglas, NE; Sarpy, NE".to_string(),
+            originator: "WXR".to_string(),
+            description: None,
+            parsed_header: decoded.then(|| ParsedEasSerialized {
+                originator: "WXR".to_string(),
+                event_code: event_code.to_string(),
+                fips_codes: vec!["031055".to_string(), "031153".to_string()],
+                locations: Vec::new(),
+                duration_hours: 0,
+                duration_minutes: 30,
+                start_time_utc: "2024-12-04T12:00:00+00:00".to_string(),
+                sender_id: "KOAX/NWS".to_string(),
+            }),
+        };
+        ActiveAlert::new(data, raw_header, Duration::from_secs(1800))
+    }
+
+    fn text<'a>(doc: &'a roxmltree::Document, name: &str) -> Vec<&'a str> {
+        doc.descendants()
+            .filter(|node| node.has_tag_name((CAP_NAMESPACE, name)))
+            .filter_map(|node| node.text())
+            .collect()
+    }
+
+    #[test]
+    fn event_codes_map_to_cap_categories() {
+        assert_eq!(
+            event_mapping("tor"),
+            mapping("Met", "Immediate", "Extreme", "Likely")
+        );
+        assert_eq!(event_mapping("EQW").category, "Geo");
+        assert_eq!(event_mapping("HMW").category, "CBRNE");
+        assert_eq!(event_mapping("XYZ"), UNKNOWN_EVENT);
+        assert_eq!(event_mapping("RWT"), UNKNOWN_EVENT);
+        assert!(is_test_event("rmt") && !is_test_event("TOR"));
+        assert!(
+            EVENT_MAPPINGS.windows(2).all(|pair| pair[0].0 < pair[1].0),
+            "codes are sorted and unique"
+        );
+    }
+
+    #[test]
+    fn decoded_alerts_become_cap_documents() {
+        let alert = alert("TOR", true);
+        let xml = document(&alert, "Omaha Relay, Inc.");
+        let doc = roxmltree::Document::parse(&xml).expect("well-formed XML");
+        assert_eq!(
+            text(&doc, "identifier"),
+            vec![identifier(&alert.raw_header)]
+        );
+        assert_eq!(text(&doc, "sender"), vec!["Omaha_Relay__Inc."]);
+        assert_eq!(text(&doc, "status"), vec!["Actual"]);
+        assert_eq!(text(&doc, "category"), vec!["Met"]);
+        assert_eq!(text(&doc, "event"), vec!["Tornado Warning"]);
+        assert_eq!(text(&doc, "severity"), vec!["Extreme"]);
+        assert_eq!(text(&doc, "effective"), vec!["2024-12-04T12:00:00-00:00"]);
+        assert_eq!(text(&doc, "expires"), vec!["2024-12-04T12:30:00-00:00"]);
+        assert_eq!(
+            text(&doc, "description"),
+            vec!["The National Weather Service has issued a Tornado Warning & more."]
+        );
+        assert_eq!(text(&doc, "value"), vec!["TOR", "WXR", "031055", "031153"]);
+        assert!(text(&doc, "sent")[0].ends_with("-00:00"));
+    }
+
+    #[test]
+    fn undecoded_and_test_alerts_still_produce_valid_documents() {
+        let alert = alert("RWT", false);
+        let xml = document(&alert, "");
+        let doc = roxmltree::Document::parse(&xml).expect("well-formed XML");
+        assert_eq!(text(&doc, "status"), vec!["Test"]);
+        assert_eq!(text(&doc, "sender"), vec!["EAS_Listener"]);
+        assert_eq!(text(&doc, "event"), vec!["RWT"]);
+        assert_eq!(text(&doc, "urgency"), vec!["Unknown"]);
+        assert_eq!(text(&doc, "certainty"), vec!["Unknown"]);
+        assert_eq!(
+            text(&doc, "expires"),
+            vec![cap_time(alert.expires_at).as_str()]
+        );
+
+        let tornado = document(&self::alert("TOR", false), "Relay");
+        let doc = roxmltree::Document::parse(&tornado).expect("well-formed XML");
+        assert_eq!(text(&doc, "category"), vec!["Met"]);
+        assert_eq!(text(&doc, "severity"), vec!["Unknown"]);
+    }
+}
@@ -0,0 +1,197 @@
+use crate::config::{Config, ConfigFormat};
+use crate::filter;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CheckReport {
+    pub path: PathBuf,
+    pub valid: bool,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+pub fn check_file(path: &Path) -> CheckReport {
+    let format = ConfigFormat::from_path(path);
+    let (errors, warnings) = match std::fs::read_to_string(path) {
+        Err(err) => (
+            vec![format!("Cannot read {}: {}", path.display(), err)],
+            Vec::new(),
+        ),
+        Ok(payload) => match format.parse(&payload) {
+            Err(err) => (
+                vec![format!(
+                    "{} is not valid {}: {:#}",
+                    path.display(),
+                    format.name(),
+                    err
+                )],
+                Vec::new(),
+            ),
+            Ok(config_json) => check_value(&config_json),
+        },
+    };
+    CheckReport {
+        path: path.to_path_buf(),
+        valid: errors.is_empty(),
+        errors,
+        warnings,
+    }
+}
+
+fn check_value(config_json: &Value) -> (Vec<String>, Vec<String>) {
+    let (config, errors) = match Config::validate_config_value(config_json) {
+        Ok(converted) => converted,
+        Err(err) => return (vec![format!("{err:#}")], Vec::new()),
+    };
+    let mut errors = errors.into_vec();
+    errors.extend(filter::parse_filters_with_problems(config_json).1);
+    errors.extend(missing_files(&config));
+    errors.extend(
+        config
+            .streams
+            .iter()
+            .map(|stream| &stream.url)
+            .filter_map(|url| {
+                let parsed = reqwest::Url::parse(url)
+                    .ok()
+                    .filter(|parsed| matches!(parsed.scheme(), "http" | "https"))
+                    .filter(|parsed| parsed.host_str().is_some());
+                parsed.is_none().then(|| {
+                    format!(
+                        "ICECAST_STREAM_URL_ARRAY entry {url:?} must be an http:// or https:// URL"
+                    )
+                })
+            }),
+    );
+    (errors, config.warnings)
+}
+
+pub(crate) fn missing_files(config: &Config) -> Vec<String> {
+    let mut referenced: Vec<(&str, PathBuf)> = vec![(
+        "APPRISE_CONFIG_PATH",
+        PathBuf::from(&config.apprise_config_path),
+    )];
+    for (key, path) in [
+        ("ICECAST_INTRO", Some(&config.icecast_intro)),
+        ("ICECAST_OUTRO", Some(&config.icecast_outro)),
+        ("API_TLS_CERT_PATH", config.api_tls_cert_path.as_ref()),
+        ("API_TLS_KEY_PATH", config.api_tls_key_path.as_ref()),
+    ] {
+        if let Some(path) = path.filter(|path| !path.as_os_str().is_empty()) {
+            referenced.push((key, path.clone()));
+        }
+    }
+    if let Some(path) = &config.operational_apprise_config_path {
+        referenced.push(("OPERATIONAL_APPRISE_CONFIG_PATH", PathBuf::from(path)));
+    }
+
+    let mut problems: Vec<String> = referenced
+        .into_iter()
+        .filter_map(|(key, path)| {
+            std::fs::File::open(&path)
+                .err()
+                .map(|err| format!("{key} {} cannot be read: {err}", path.display()))
+        })
+        .collect();
+    if let Some(dir) = &config.dashboard_static_dir {
+        if let Err(err) = std::fs::read_dir(dir) {
+            problems.push(format!(
+                "DASHBOARD_STATIC_DIR {} cannot be read: {err}",
+                dir.display()
+            ));
+        }
+    }
+    problems
+}
+
+pub fn run(path: &Path, json: bool) -> bool {
+    let report = check_file(path);
+    if json {
+        match serde_json::to_string_pretty(&report) {
+            Ok(rendered) => println!("{rendered}"),
+            Err(err) => eprintln!("Failed to render the report: {err}"),
+        }
+        return report.valid;
+    }
+
+    println!("Checking {}", report.path.display());
+    for error in &report.errors {
+        println!("  error: {error}");
+    }
+    for warning in &report.warnings {
+        println!("  warning: {warning}");
+    }
+    if report.valid {
+        println!("The configuration is valid.");
+    } else {
+        println!(
+            "The configuration is invalid: {} error(s), {} warning(s).",
+            report.errors.len(),
+            report.warnings.len()
+        );
+    }
+    report.valid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn check_reports_every_problem_at_once() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let apprise = dir.path().join("apprise.yml");
+        std::fs::write(&apprise, "urls: []\n").expect("write");
+        let path = dir.path().join("config.json");
+        std::fs::write(
+            &path,
+            json!({
+                "APPRISE_CONFIG_PATH": apprise,
+                "ICECAST_STREAM_URL_ARRAY": ["http://example.com:8000/wxr", "not a url"],
+                "MONITORING_BIND_ADDR": "nowhere",
+                "PARTIAL_RECEPTION_THRESHOLD": 2,
+                "TZ": "Mars/Olympus_Mons",
+                "ICECAST_INTRO": dir.path().join("missing-intro.mp3"),
+                "FILTERS": [{ "name": "Tests", "event_codes": [] }],
+                "MONITORING_MAX_LOG": 5
+            })
+            .to_string(),
+        )
+        .expect("write");
+
+        let report = check_file(&path);
+        assert!(!report.valid);
+        let errors = report.errors.join("\n");
+        for expected in [
+            "MONITORING_BIND_ADDR",
+            "PARTIAL_RECEPTION_THRESHOLD",
+            "TZ \"Mars/Olympus_Mons\"",
+            "ICECAST_INTRO",
+            "Filter 'Tests' has no valid event codes",
+            "\"not a url\"",
+        ] {
+            assert!(
+                errors.contains(expected),
+                "{expected} missing from {errors}"
+            );
+        }
+        assert_eq!(report.errors.len(), 6, "{errors}");
+        assert!(report.warnings[0].contains("did you mean MONITORING_MAX_LOGS"));
+
+        std::fs::write(
+            &path,
+            json!({
+                "APPRISE_CONFIG_PATH": apprise,
+                "ICECAST_STREAM_URL_ARRAY": ["http://example.com:8000/wxr"],
+                "TZ": "America/Chicago"
+            })
+            .to_string(),
+        )
+        .expect("write");
+        let report = check_file(&path);
+        assert!(report.valid, "{:?}", report.errors);
+    }
+}
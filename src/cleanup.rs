@@ -1,15 +1,60 @@
 use crate::config::Config;
+use crate::recording::{self, RecordingFile, RecordingState};
 use anyhow::Result;
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::sync::broadcast;
 use tokio::time::interval;
 use tracing::{info, warn};
 
-pub async fn run_log_cleanup(config: Config) -> Result<()> {
+const RECORDING_SWEEP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+const ORPHAN_MIN_AGE: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RecordingSweep {
+    pub finished_at: DateTime<Utc>,
+    pub deleted: usize,
+    pub bytes_freed: u64,
+    pub skipped_active: usize,
+    pub remaining: usize,
+    pub remaining_bytes: u64,
+}
+
+static LAST_RECORDING_SWEEP: Lazy<Mutex<Option<RecordingSweep>>> = Lazy::new(|| Mutex::new(None));
+
+pub fn last_recording_sweep() -> Option<RecordingSweep> {
+    LAST_RECORDING_SWEEP.lock().clone()
+}
+
+pub async fn run_log_cleanup(
+    mut config: Config,
+    mut reload_rx: broadcast::Receiver<Config>,
+) -> Result<()> {
     info!("Log cleanup task started. Will run every 24 hours.");
     let mut timer = interval(std::time::Duration::from_secs(24 * 60 * 60));
+    crate::reload::register("log_cleanup");
 
     loop {
-        timer.tick().await;
+        tokio::select! {
+            _ = timer.tick() => {}
+            reload = reload_rx.recv() => {
+                match reload {
+                    Ok(new_config) => {
+                        config = new_config;
+                        crate::reload::acknowledge("log_cleanup");
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                }
+                continue;
+            }
+        }
         info!("Running daily log cleanup...");
 
         let retention_period = Duration::days(3);
@@ -48,3 +93,321 @@ pub async fn run_log_cleanup(config: Config) -> Result<()> {
         }
     }
 }
+
+fn recordings_to_sweep(
+    recordings: Vec<RecordingFile>,
+    cutoff: Option<DateTime<Utc>>,
+    max_total_bytes: Option<u64>,
+    active: &HashSet<String>,
+) -> (Vec<RecordingFile>, Vec<String>) {
+    let mut total: u64 = recordings
+        .iter()
+        .map(|recording| recording.size_bytes)
+        .sum();
+    let mut doomed = Vec::new();
+    let mut skipped_active = Vec::new();
+    for recording in recordings.into_iter().rev() {
+        let expired = cutoff
+            .is_some_and(|cutoff| recording.created_at.is_some_and(|created| created < cutoff));
+        let over_budget = max_total_bytes.is_some_and(|max| total > max);
+        if !expired && !over_budget {
+            continue;
+        }
+        if active.contains(&recording.file) {
+            skipped_active.push(recording.file);
+            continue;
+        }
+        total = total.saturating_sub(recording.size_bytes);
+        doomed.push(recording);
+    }
+    (doomed, skipped_active)
+}
+
+async fn sweep_recordings(
+    config: &Config,
+    recording_state: &Arc<tokio::sync::Mutex<HashMap<String, RecordingState>>>,
+) -> Result<RecordingSweep> {
+    let cutoff = (config.recording_retention_days > 0).then(|| {
+        Utc::now()
+            - Duration::try_days(i64::try_from(config.recording_retention_days).unwrap_or(i64::MAX))
+                .unwrap_or(Duration::MAX)
+    });
+    let max_total_bytes = (config.recording_max_total_mb > 0)
+        .then(|| config.recording_max_total_mb.saturating_mul(1024 * 1024));
+
+    let recording_dir = config.recording_dir.clone();
+    let recordings = tokio::task::spawn_blocking(move || {
+        recording::scan_recordings(&recording_dir).map(|mut recordings| {
+            for recording in &mut recordings {
+                let cache = crate::webhook::discord_attachment_cache_path(
+                    &recording_dir.join(&recording.file),
+                );
+                if let Some(metadata) = cache.and_then(|cache| std::fs::metadata(cache).ok()) {
+                    recording.size_bytes += metadata.len();
+                }
+            }
+            recordings
+        })
+    })
+    .await??;
+    let active: HashSet<String> = recording_state
+        .lock()
+        .await
+        .values()
+        .filter_map(|recording| {
+            recording::recording_file_name(&config.recording_dir, &recording.output_path)
+        })
+        .collect();
+
+    let remaining = recordings.len();
+    let remaining_bytes = recordings
+        .iter()
+        .map(|recording| recording.size_bytes)
+        .sum();
+    let (doomed, skipped_active) =
+        recordings_to_sweep(recordings, cutoff, max_total_bytes, &active);
+    let mut sweep = RecordingSweep {
+        finished_at: Utc::now(),
+        deleted: 0,
+        bytes_freed: 0,
+        skipped_active: skipped_active.len(),
+        remaining,
+        remaining_bytes,
+    };
+    for recording in doomed {
+        let path = config.recording_dir.join(&recording.file);
+        if let Err(err) = recording::remove_recording_files(&path) {
+            warn!("Failed to delete recording {}: {}", recording.file, err);
+            continue;
+        }
+        sweep.deleted += 1;
+        sweep.bytes_freed += recording.size_bytes;
+        sweep.remaining -= 1;
+        sweep.remaining_bytes = sweep.remaining_bytes.saturating_sub(recording.size_bytes);
+    }
+    if sweep.deleted > 0 {
+        let recording_dir = config.recording_dir.clone();
+        if let Err(err) =
+            tokio::task::spawn_blocking(move || recording::compact_index(&recording_dir)).await?
+        {
+            warn!("Failed to compact the recordings index: {:#}", err);
+        }
+    }
+    sweep.finished_at = Utc::now();
+    Ok(sweep)
+}
+
+pub async fn run_recording_cleanup(
+    mut config: Config,
+    recording_state: Arc<tokio::sync::Mutex<HashMap<String, RecordingState>>>,
+    mut reload_rx: broadcast::Receiver<Config>,
+) -> Result<()> {
+    let mut timer = interval(RECORDING_SWEEP_INTERVAL);
+    crate::reload::register("recording_cleanup");
+
+    loop {
+        tokio::select! {
+            _ = timer.tick() => {}
+            reload = reload_rx.recv() => {
+                match reload {
+                    Ok(new_config) => {
+                        config = new_config;
+                        crate::reload::acknowledge("recording_cleanup");
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                }
+                continue;
+            }
+        }
+        if config.recording_retention_days == 0 && config.recording_max_total_mb == 0 {
+            continue;
+        }
+
+        match sweep_recordings(&config, &recording_state).await {
+            Ok(sweep) => {
+                if sweep.deleted > 0 || sweep.skipped_active > 0 {
+                    info!(
+                        "Recording cleanup deleted {} recording(s), freeing {} bytes; {} recording(s) ({} bytes) remain{}",
+                        sweep.deleted,
+                        sweep.bytes_freed,
+                        sweep.remaining,
+                        sweep.remaining_bytes,
+                        if sweep.skipped_active > 0 {
+                            format!(", {} still being written were kept", sweep.skipped_active)
+                        } else {
+                            String::new()
+                        }
+                    );
+                }
+                *LAST_RECORDING_SWEEP.lock() = Some(sweep);
+            }
+            Err(err) => warn!("Recording cleanup failed: {:#}", err),
+        }
+    }
+}
+
+fn stale_files(
+    dir: &Path,
+    prefixes: &[&str],
+    min_age: std::time::Duration,
+    now: SystemTime,
+) -> Vec<(PathBuf, u64)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| prefixes.iter().any(|prefix| name.starts_with(prefix)))
+        })
+        .filter_map(|entry| {
+            let metadata = entry
+                .metadata()
+                .ok()
+                .filter(|metadata| metadata.is_file())?;
+            let age = now.duration_since(metadata.modified().ok()?).ok()?;
+            (age > min_age).then(|| (entry.path(), metadata.len()))
+        })
+        .collect()
+}
+
+pub fn sweep_orphaned_files(config: &Config) -> (usize, u64) {
+    let now = SystemTime::now();
+    let mut stale = stale_files(
+        &config.relay_tmp_dir,
+        &[
+            crate::relay::TONES_TEMP_PREFIX,
+            crate::relay::COMBINED_TEMP_PREFIX,
+        ],
+        ORPHAN_MIN_AGE,
+        now,
+    );
+    if config.deeplink_cache_max_age_days > 0 {
+        stale.extend(stale_files(
+            &config.shared_state_dir,
+            &[
+                crate::backend::DEEPLINK_HOST_CACHE_FILE,
+                crate::backend::DEEPLINK_HOST_LAST_SEEN_CACHE_FILE,
+            ],
+            std::time::Duration::from_secs(
+                config
+                    .deeplink_cache_max_age_days
+                    .saturating_mul(24 * 60 * 60),
+            ),
+            now,
+        ));
+    }
+
+    let (mut deleted, mut bytes_freed) = (0, 0);
+    for (path, size) in stale {
+        match std::fs::remove_file(&path) {
+            Ok(()) => {
+                info!("Deleted orphaned file {:?} ({} bytes)", path, size);
+                deleted += 1;
+                bytes_freed += size;
+            }
+            Err(err) => warn!("Failed to delete orphaned file {:?}: {}", path, err),
+        }
+    }
+    if deleted > 0 {
+        info!(
+            "Startup cleanup deleted {} orphaned file(s), freeing {} bytes",
+            deleted, bytes_freed
+        );
+    }
+    (deleted, bytes_freed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sweeps_drop_expired_then_oldest_recordings_but_never_active_ones() {
+        let now = Utc::now();
+        let recording = |file: &str, age_days: i64, size_bytes: u64| RecordingFile {
+            file: file.to_string(),
+            size_bytes,
+            created_at: Some(now - Duration::days(age_days)),
+            name: recording::parse_recording_file_name(
+                "EAS_Recording_2024-01-02_03-04-05_TOR_WXYZ.wav",
+            )
+            .expect("recording name"),
+        };
+        let recordings = || {
+            vec![
+                recording("newest.wav", 1, 40),
+                recording("newer.wav", 5, 40),
+                recording("writing.wav", 20, 40),
+                recording("old.wav", 40, 40),
+            ]
+        };
+        let active = HashSet::from(["writing.wav".to_string()]);
+        let names = |picked: Vec<RecordingFile>| {
+            picked
+                .into_iter()
+                .map(|recording| recording.file)
+                .collect::<Vec<_>>()
+        };
+
+        let (doomed, skipped) =
+            recordings_to_sweep(recordings(), Some(now - Duration::days(10)), None, &active);
+        assert_eq!(names(doomed), ["old.wav"]);
+        assert_eq!(skipped, ["writing.wav"]);
+
+        let (doomed, skipped) = recordings_to_sweep(recordings(), None, Some(90), &active);
+        assert_eq!(names(doomed), ["old.wav", "newer.wav"]);
+        assert_eq!(skipped, ["writing.wav"]);
+
+        let (doomed, skipped) = recordings_to_sweep(recordings(), None, None, &active);
+        assert!(doomed.is_empty() && skipped.is_empty());
+    }
+
+    #[test]
+    fn orphan_sweeps_only_take_old_relay_files_and_expired_deeplink_caches() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let tmp = dir.path().join("tmp");
+        std::fs::create_dir_all(&tmp).expect("tmp dir");
+        let two_hours_ago = SystemTime::now() - std::time::Duration::from_secs(2 * 60 * 60);
+        let two_days_ago = SystemTime::now() - std::time::Duration::from_secs(2 * 24 * 60 * 60);
+        let write = |path: PathBuf, modified: Option<SystemTime>| {
+            std::fs::write(&path, b"audio").expect("write");
+            if let Some(modified) = modified {
+                std::fs::File::options()
+                    .write(true)
+                    .open(&path)
+                    .and_then(|file| file.set_modified(modified))
+                    .expect("set mtime");
+            }
+        };
+        write(tmp.join("relay_combined_old.ogg"), Some(two_hours_ago));
+        write(tmp.join("relay_tones_old.wav"), Some(two_hours_ago));
+        write(tmp.join("relay_combined_live.ogg"), None);
+        write(tmp.join("unrelated.ogg"), Some(two_hours_ago));
+        write(dir.path().join("deeplink_host.txt"), Some(two_days_ago));
+        write(dir.path().join("deeplink_host_last_seen.txt"), None);
+
+        let mut config = Config::safe_internal_defaults();
+        config.shared_state_dir = dir.path().to_path_buf();
+        config.relay_tmp_dir = tmp.clone();
+        assert_eq!(sweep_orphaned_files(&config), (2, 10));
+        assert!(dir.path().join("deeplink_host.txt").exists());
+
+        config.deeplink_cache_max_age_days = 1;
+        assert_eq!(sweep_orphaned_files(&config), (1, 5));
+        assert!(!dir.path().join("deeplink_host.txt").exists());
+        assert!(dir.path().join("deeplink_host_last_seen.txt").exists());
+
+        let mut left: Vec<_> = std::fs::read_dir(&tmp)
+            .expect("read tmp")
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+        left.sort();
+        assert_eq!(left, ["relay_combined_live.ogg", "unrelated.ogg"]);
+    }
+}
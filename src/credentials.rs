@@ -0,0 +1,230 @@
+use crate::api_tokens::{self, ApiToken, ApiTokenScope};
+use crate::config::Config;
+use anyhow::{anyhow, Result};
+use argon2::password_hash::{PasswordHash, PasswordVerifier};
+use argon2::Argon2;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use tracing::debug;
+
+const MAX_VERIFIED_TOKENS: usize = 64;
+
+fn is_bcrypt_hash(hash: &str) -> bool {
+    ["$2a$", "$2b$", "$2y$"]
+        .iter()
+        .any(|prefix| hash.starts_with(prefix))
+}
+
+pub fn validate_password_hash(hash: &str) -> Result<()> {
+    let valid = if is_bcrypt_hash(hash) {
+        hash.len() == 60
+    } else {
+        PasswordHash::new(hash).is_ok_and(|parsed| parsed.algorithm.as_str().starts_with("argon2"))
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "DASHBOARD_PASSWORD_HASH must be an argon2 or bcrypt hash in your config.json file"
+        ))
+    }
+}
+
+fn verify_password(hash: &str, password: &str) -> bool {
+    if is_bcrypt_hash(hash) {
+        return bcrypt::verify(password, hash).unwrap_or(false);
+    }
+    PasswordHash::new(hash).is_ok_and(|parsed| {
+        Argon2::default()
+            .verify_password(password.as_bytes(), &parsed)
+            .is_ok()
+    })
+}
+
+enum DashboardSecret {
+    Plaintext {
+        username: String,
+        token: String,
+    },
+    Hash {
+        username: String,
+        hash: String,
+        verified: Mutex<HashSet<[u8; 32]>>,
+    },
+}
+
+pub struct ApiCredentials {
+    dashboard: Option<DashboardSecret>,
+    api_tokens: Vec<ApiToken>,
+}
+
+impl ApiCredentials {
+    pub fn from_config(config: &Config) -> Self {
+        let username = config.dashboard_username.as_str();
+        let dashboard = if username.is_empty() || username == "admin" {
+            None
+        } else if let Some(hash) = &config.dashboard_password_hash {
+            Some(DashboardSecret::Hash {
+                username: username.to_string(),
+                hash: hash.clone(),
+                verified: Mutex::new(HashSet::new()),
+            })
+        } else if config.dashboard_password.is_empty() || config.dashboard_password == "password" {
+            None
+        } else {
+            Some(DashboardSecret::Plaintext {
+                username: username.to_string(),
+                token: base64::engine::general_purpose::STANDARD
+                    .encode(format!("{}:{}", username, config.dashboard_password)),
+            })
+        };
+
+        Self {
+            dashboard,
+            api_tokens: config.api_tokens.clone(),
+        }
+    }
+
+    pub fn principal(&self, auth_header: &str) -> Option<(ApiTokenScope, String)> {
+        let Some(token) = auth_header.strip_prefix("Bearer ") else {
+            debug!("Auth header does not start with 'Bearer '");
+            return None;
+        };
+
+        if let Some(api_token) = api_tokens::find_api_token(&self.api_tokens, token) {
+            return Some((api_token.scope, format!("API token {:?}", api_token.name)));
+        }
+
+        if !self.dashboard_token_valid(token) {
+            return None;
+        }
+        let username = match &self.dashboard {
+            Some(DashboardSecret::Plaintext { username, .. })
+            | Some(DashboardSecret::Hash { username, .. }) => username.as_str(),
+            None => return None,
+        };
+        Some((ApiTokenScope::Admin, format!("dashboard user {username}")))
+    }
+
+    pub fn verify_login(&self, username: &str, password: &str) -> bool {
+        self.dashboard_token_valid(
+            &base64::engine::general_purpose::STANDARD.encode(format!("{username}:{password}")),
+        )
+    }
+
+    fn dashboard_token_valid(&self, token: &str) -> bool {
+        match &self.dashboard {
+            None => {
+                debug!("Default or empty username/password in use, rejecting token");
+                false
+            }
+            Some(DashboardSecret::Plaintext {
+                token: expected, ..
+            }) => api_tokens::constant_time_eq(token.as_bytes(), expected.as_bytes()),
+            Some(DashboardSecret::Hash {
+                username,
+                hash,
+                verified,
+            }) => Self::check_hashed(token, username, hash, verified),
+        }
+    }
+
+    fn check_hashed(
+        token: &str,
+        username: &str,
+        hash: &str,
+        verified: &Mutex<HashSet<[u8; 32]>>,
+    ) -> bool {
+        let digest: [u8; 32] = Sha256::digest(token.as_bytes()).into();
+        {
+            let verified = verified.lock().expect("credential cache lock poisoned");
+            let cached = verified.iter().fold(false, |found, known| {
+                found | api_tokens::constant_time_eq(known, &digest)
+            });
+            if cached {
+                return true;
+            }
+        }
+
+        let Some((presented_user, password)) = base64::engine::general_purpose::STANDARD
+            .decode(token)
+            .ok()
+            .and_then(|decoded| String::from_utf8(decoded).ok())
+            .and_then(|decoded| {
+                decoded
+                    .split_once(':')
+                    .map(|(user, password)| (user.to_string(), password.to_string()))
+            })
+        else {
+            return false;
+        };
+        let user_matches =
+            api_tokens::constant_time_eq(presented_user.as_bytes(), username.as_bytes());
+        let password_matches = verify_password(hash, &password);
+        if !(user_matches && password_matches) || password == "password" {
+            return false;
+        }
+
+        let mut verified = verified.lock().expect("credential cache lock poisoned");
+        if verified.len() >= MAX_VERIFIED_TOKENS {
+            verified.clear();
+        }
+        verified.insert(digest);
+        true
+    }
+
+    pub fn uses_plaintext_password(&self) -> bool {
+        matches!(self.dashboard, Some(DashboardSecret::Plaintext { .. }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bearer(username: &str, password: &str) -> String {
+        format!(
+            "Bearer {}",
+            base64::engine::general_purpose::STANDARD.encode(format!("{username}:{password}"))
+        )
+    }
+
+    #[test]
+    fn hashed_dashboard_passwords_verify_with_argon2_and_bcrypt() {
+        use argon2::password_hash::{PasswordHasher, SaltString};
+
+        let salt = SaltString::encode_b64(b"fixed-test-salt!").expect("salt");
+        let argon_hash = Argon2::default()
+            .hash_password(b"s3cret-pass", &salt)
+            .expect("argon2 hash")
+            .to_string();
+        let bcrypt_hash = bcrypt::hash("s3cret-pass", 4).expect("bcrypt hash");
+        assert!(validate_password_hash(&argon_hash).is_ok());
+        assert!(validate_password_hash(&bcrypt_hash).is_ok());
+        assert!(validate_password_hash("s3cret-pass").is_err());
+
+        for hash in [argon_hash, bcrypt_hash] {
+            let mut config = Config::safe_internal_defaults();
+            config.dashboard_username = "operator".to_string();
+            config.dashboard_password = String::new();
+            config.dashboard_password_hash = Some(hash);
+            let credentials = ApiCredentials::from_config(&config);
+            assert!(!credentials.uses_plaintext_password());
+
+            let good = bearer("operator", "s3cret-pass");
+            assert!(credentials.principal(&good).is_some());
+            assert_eq!(
+                credentials.principal(&good),
+                Some((ApiTokenScope::Admin, "dashboard user operator".to_string()))
+            );
+            assert!(credentials
+                .principal(&bearer("operator", "wrong"))
+                .is_none());
+            assert!(credentials
+                .principal(&bearer("intruder", "s3cret-pass"))
+                .is_none());
+        }
+    }
+}
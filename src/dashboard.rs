@@ -0,0 +1,175 @@
+use crate::config::Config;
+use rust_embed::RustEmbed;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::path::{Component, Path, PathBuf};
+
+pub const DASHBOARD_INDEX: &str = "dashboard.html";
+const DEFAULT_ALERT_SOUND: &str = "iembot.mp3";
+
+#[derive(RustEmbed)]
+#[folder = "web_server/"]
+#[exclude = "*.php"]
+#[exclude = "web_config.json"]
+#[exclude = "image_info.json"]
+struct EmbeddedDashboard;
+
+pub struct DashboardAsset {
+    pub body: Cow<'static, [u8]>,
+    pub content_type: String,
+    pub etag: String,
+    pub is_page: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum DashboardFiles {
+    Embedded,
+    Directory(PathBuf),
+}
+
+pub fn asset_path(request_path: &str) -> Option<String> {
+    let trimmed = request_path.trim_start_matches('/');
+    if trimmed.is_empty() {
+        return Some(DASHBOARD_INDEX.to_string());
+    }
+    let path = Path::new(trimmed);
+    let safe = path.components().all(|component| match component {
+        Component::Normal(part) => !part.to_string_lossy().starts_with('.'),
+        _ => false,
+    });
+    if !safe {
+        return None;
+    }
+    match path.extension().and_then(|ext| ext.to_str()) {
+        None => Some(DASHBOARD_INDEX.to_string()),
+        Some(ext) if ext.eq_ignore_ascii_case("php") => None,
+        Some(_) => Some(trimmed.to_string()),
+    }
+}
+
+fn asset(name: &str, body: Cow<'static, [u8]>) -> DashboardAsset {
+    let digest = Sha256::digest(&body);
+    let etag: String = digest[..8]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    let content_type = mime_guess::from_path(name)
+        .first_or_octet_stream()
+        .essence_str()
+        .to_string();
+    let is_page = content_type == "text/html";
+    DashboardAsset {
+        body,
+        content_type,
+        etag: format!("\"{etag}\""),
+        is_page,
+    }
+}
+
+impl DashboardFiles {
+    pub fn from_config(config: &Config) -> Self {
+        match &config.dashboard_static_dir {
+            Some(dir) => Self::Directory(dir.clone()),
+            None => Self::Embedded,
+        }
+    }
+
+    pub async fn get(&self, request_path: &str) -> Option<DashboardAsset> {
+        let name = asset_path(request_path)?;
+        let body = match self {
+            Self::Embedded => EmbeddedDashboard::get(&name)?.data,
+            Self::Directory(dir) => Cow::Owned(tokio::fs::read(dir.join(&name)).await.ok()?),
+        };
+        Some(asset(&name, body))
+    }
+
+    pub async fn alert_sound_src(&self, configured: Option<&str>) -> String {
+        let configured = configured.map(str::trim).unwrap_or_default();
+        if !configured.is_empty()
+            && asset_path(configured).is_some_and(|name| name != DASHBOARD_INDEX)
+            && self.get(configured).await.is_some()
+        {
+            configured.trim_start_matches('/').to_string()
+        } else {
+            DEFAULT_ALERT_SOUND.to_string()
+        }
+    }
+}
+
+pub fn config_script(
+    host: Option<&str>,
+    web_config: &serde_json::Value,
+    alert_sound_src: &str,
+) -> String {
+    let api_base = host
+        .filter(|host| {
+            !host.is_empty()
+                && host
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || ".-:[]".contains(c))
+        })
+        .map(|host| serde_json::Value::String(host.to_string()).to_string())
+        .unwrap_or_else(|| "window.location.host".to_string());
+    let value = |key: &str, default: serde_json::Value| {
+        web_config.get(key).cloned().unwrap_or(default).to_string()
+    };
+    format!(
+        "window.API_BASE = {api_base};\n\
+         window.MONITORING_MAX_LOGS = {};\n\
+         window.ALERTSOUNDDATA = {};\n\
+         window.ALERTSOUNDENABLED = {};\n\
+         window.ICECAST_STREAM_URL_MAPPING = {};\n\
+         window.EAS_LISTENER_VERSION = {};\n",
+        value("MONITORING_MAX_LOGS", serde_json::json!(500)),
+        serde_json::Value::String(alert_sound_src.to_string()),
+        value("ALERT_SOUND_ENABLED", serde_json::json!(false)),
+        value("ICECAST_STREAM_URL_MAPPING", serde_json::json!({})),
+        serde_json::Value::String(env!("CARGO_PKG_VERSION").to_string()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn dashboard_paths_resolve_to_assets_or_the_index() {
+        assert_eq!(asset_path("/").as_deref(), Some(DASHBOARD_INDEX));
+        assert_eq!(asset_path("/streams").as_deref(), Some(DASHBOARD_INDEX));
+        assert_eq!(asset_path("/style.css").as_deref(), Some("style.css"));
+        assert_eq!(
+            asset_path("/assets/favicon.svg").as_deref(),
+            Some("assets/favicon.svg")
+        );
+        assert!(asset_path("/config.php").is_none());
+        assert!(asset_path("/../Cargo.toml").is_none());
+        assert!(asset_path("/.htaccess").is_none());
+
+        let files = DashboardFiles::Embedded;
+        let index = files.get("/").await.expect("index");
+        assert_eq!(index.content_type, "text/html");
+        assert!(index.is_page);
+        let script = files.get("/index.js").await.expect("script");
+        assert!(script.content_type.ends_with("javascript"));
+        assert!(!script.is_page);
+        assert!(files.get("/missing.js").await.is_none());
+        assert_eq!(
+            files.alert_sound_src(Some("/etc/passwd")).await,
+            DEFAULT_ALERT_SOUND
+        );
+
+        let script = config_script(
+            Some("192.168.1.20:8080"),
+            &serde_json::json!({ "ALERT_SOUND_ENABLED": true }),
+            DEFAULT_ALERT_SOUND,
+        );
+        assert!(script.contains("window.API_BASE = \"192.168.1.20:8080\";"));
+        assert!(script.contains("window.ALERTSOUNDENABLED = true;"));
+        let script = config_script(
+            Some("evil\";alert(1)//"),
+            &serde_json::json!({}),
+            DEFAULT_ALERT_SOUND,
+        );
+        assert!(script.contains("window.API_BASE = window.location.host;"));
+    }
+}
@@ -1,6 +1,7 @@
+use crate::state::AlsoHeard;
 use anyhow::{Context, Result};
 use regex::Regex;
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
@@ -30,6 +31,8 @@ CREATE TABLE IF NOT EXISTS alerts (
     duration_hhmm   TEXT,
     received_at     TEXT    NOT NULL,
     expires_at      TEXT,
+    also_heard_on   TEXT,
+    partially_received INTEGER NOT NULL DEFAULT 0,
     created_at      TEXT    NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
 );
 
@@ -38,6 +41,29 @@ CREATE INDEX IF NOT EXISTS idx_alerts_event_code  ON alerts(event_code);
 CREATE INDEX IF NOT EXISTS idx_alerts_raw_zczc    ON alerts(raw_zczc);
 "#;
 
+const ADDED_COLUMNS: &[(&str, &str)] = &[
+    ("also_heard_on", "TEXT"),
+    ("partially_received", "INTEGER NOT NULL DEFAULT 0"),
+    ("recording_url", "TEXT"),
+];
+
+fn add_missing_columns(conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare("PRAGMA table_info(alerts)")?;
+    let existing = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+
+    for (column, definition) in ADDED_COLUMNS {
+        if !existing.iter().any(|name| name == column) {
+            conn.execute_batch(&format!(
+                "ALTER TABLE alerts ADD COLUMN {column} {definition};"
+            ))
+            .with_context(|| format!("Failed to add column {column} to alerts table"))?;
+        }
+    }
+    Ok(())
+}
+
 #[derive(Clone)]
 pub struct DbHandle {
     conn: Arc<std::sync::Mutex<Connection>>,
@@ -54,6 +80,7 @@ impl DbHandle {
             .context("Failed to set busy timeout")?;
         conn.execute_batch(SCHEMA_SQL)
             .context("Failed to initialize database schema")?;
+        add_missing_columns(&conn).context("Failed to migrate database schema")?;
 
         info!("Alert database opened at {}", path.display());
 
@@ -223,6 +250,103 @@ impl DbHandle {
         }
     }
 
+    pub async fn update_recording_url(&self, recording_name: &str, url: &str) {
+        let conn = self.conn.clone();
+        let recording_name = recording_name.to_string();
+        let url = url.to_string();
+        let result = tokio::task::spawn_blocking(move || {
+            let guard = conn
+                .lock()
+                .map_err(|e| anyhow::anyhow!("DB mutex poisoned: {}", e))?;
+            let updated = guard.execute(
+                "UPDATE alerts SET recording_url = ?1 WHERE recording_name = ?2",
+                params![url, recording_name],
+            )?;
+            Ok::<usize, anyhow::Error>(updated)
+        })
+        .await;
+
+        match result {
+            Ok(Ok(_)) => {}
+            Ok(Err(err)) => warn!("Failed to update recording_url in DB: {}", err),
+            Err(err) => warn!("Recording URL update task panicked: {}", err),
+        }
+    }
+
+    pub async fn raw_header_for_recording(&self, recording_name: &str) -> Result<Option<String>> {
+        let conn = self.conn.clone();
+        let recording_name = recording_name.to_string();
+        tokio::task::spawn_blocking(move || {
+            let guard = conn.lock().map_err(|e| anyhow::anyhow!("DB mutex poisoned: {}", e))?;
+            let raw_zczc = guard
+                .query_row(
+                    "SELECT raw_zczc FROM alerts WHERE recording_name = ?1 ORDER BY id DESC LIMIT 1",
+                    params![recording_name],
+                    |row| row.get::<_, String>(0),
+                )
+                .optional()?;
+            Ok(raw_zczc)
+        })
+        .await
+        .context("DB lookup task panicked")?
+    }
+
+    pub async fn update_reception(
+        &self,
+        raw_zczc: &str,
+        also_heard_on: &[AlsoHeard],
+        partially_received: bool,
+    ) {
+        let conn = self.conn.clone();
+        let raw_zczc = raw_zczc.to_string();
+        let also_heard_json =
+            serde_json::to_string(also_heard_on).unwrap_or_else(|_| "[]".to_string());
+
+        let result = tokio::task::spawn_blocking(move || {
+            let guard = conn.lock().map_err(|e| anyhow::anyhow!("DB mutex poisoned: {}", e))?;
+            let updated = guard.execute(
+                "UPDATE alerts SET also_heard_on = ?1, partially_received = ?2 WHERE id = (SELECT id FROM alerts WHERE raw_zczc = ?3 ORDER BY id DESC LIMIT 1)",
+                params![also_heard_json, partially_received, raw_zczc],
+            )?;
+            Ok::<usize, anyhow::Error>(updated)
+        })
+        .await;
+
+        match result {
+            Ok(Ok(_)) => {}
+            Ok(Err(err)) => warn!("Failed to update alert reception in DB: {}", err),
+            Err(err) => warn!("Alert reception update task panicked: {}", err),
+        }
+    }
+
+    pub async fn update_description(
+        &self,
+        raw_zczc: &str,
+        description: &str,
+        instructions: Option<&str>,
+    ) {
+        let conn = self.conn.clone();
+        let raw_zczc = raw_zczc.to_string();
+        let description = description.to_string();
+        let instructions = instructions.map(str::to_string);
+
+        let result = tokio::task::spawn_blocking(move || {
+            let guard = conn.lock().map_err(|e| anyhow::anyhow!("DB mutex poisoned: {}", e))?;
+            let updated = guard.execute(
+                "UPDATE alerts SET description = ?1, instructions = ?2 WHERE id = (SELECT id FROM alerts WHERE raw_zczc = ?3 ORDER BY id DESC LIMIT 1)",
+                params![description, instructions, raw_zczc],
+            )?;
+            Ok::<usize, anyhow::Error>(updated)
+        })
+        .await;
+
+        match result {
+            Ok(Ok(_)) => {}
+            Ok(Err(err)) => warn!("Failed to update alert description in DB: {}", err),
+            Err(err) => warn!("Alert description update task panicked: {}", err),
+        }
+    }
+
     pub fn migrate_legacy_log(
         &self,
         legacy_log_path: &Path,
@@ -561,6 +685,74 @@ mod tests {
         assert_eq!(sev.as_deref(), Some("Extreme"));
     }
 
+    #[test]
+    fn test_open_adds_columns_to_legacy_schema() {
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("legacy.db");
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE alerts (id INTEGER PRIMARY KEY AUTOINCREMENT, raw_zczc TEXT NOT NULL, eas_text TEXT NOT NULL, event_code TEXT NOT NULL, event_text TEXT NOT NULL, received_at TEXT NOT NULL);",
+            )
+            .unwrap();
+        }
+
+        let handle = DbHandle::open(&db_path).unwrap();
+        let conn = handle.conn.lock().unwrap();
+        let columns: Vec<String> = conn
+            .prepare("PRAGMA table_info(alerts)")
+            .unwrap()
+            .query_map([], |row| row.get(1))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert!(columns.iter().any(|name| name == "also_heard_on"));
+        assert!(columns.iter().any(|name| name == "partially_received"));
+    }
+
+    #[tokio::test]
+    async fn test_update_reception() {
+        let (handle, _dir) = test_db();
+        handle
+            .insert_same_alert(
+                "ZCZC-WXR-TOR-031055+0030-1231645-KWO35-",
+                "Tornado Warning text.",
+                "TOR",
+                "Tornado Warning",
+                "WXR",
+                "NWS",
+                &["031055".to_string()],
+                "Douglas County",
+                Some("http://stream-a.example.com"),
+                Some("0030"),
+                "2024-12-04T17:58:45Z",
+                None,
+            )
+            .await
+            .unwrap();
+
+        let heard = vec![AlsoHeard {
+            stream_url: "http://stream-b.example.com".to_string(),
+            heard_at: chrono::Utc::now(),
+        }];
+        handle
+            .update_reception("ZCZC-WXR-TOR-031055+0030-1231645-KWO35-", &heard, true)
+            .await;
+
+        let conn = handle.conn.lock().unwrap();
+        let (also_heard_json, partial): (String, bool) = conn
+            .query_row(
+                "SELECT also_heard_on, partially_received FROM alerts ORDER BY id DESC LIMIT 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        let stored: Vec<AlsoHeard> = serde_json::from_str(&also_heard_json).unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].stream_url, "http://stream-b.example.com");
+        assert!(partial);
+    }
+
     #[tokio::test]
     async fn test_update_recording_name() {
         let (handle, _dir) = test_db();
@@ -589,18 +781,66 @@ mod tests {
             )
             .await;
 
+        handle
+            .update_recording_url(
+                "EAS_Recording_TOR_20241204_175845.wav",
+                "https://cdn.example.com/EAS_Recording_TOR_20241204_175845.wav",
+            )
+            .await;
+
         let conn = handle.conn.lock().unwrap();
-        let name: Option<String> = conn
+        let (name, url): (Option<String>, Option<String>) = conn
             .query_row(
-                "SELECT recording_name FROM alerts WHERE raw_zczc = ?1",
+                "SELECT recording_name, recording_url FROM alerts WHERE raw_zczc = ?1",
                 params!["ZCZC-WXR-TOR-031055+0030-1231645-KWO35-"],
-                |row| row.get(0),
+                |row| Ok((row.get(0)?, row.get(1)?)),
             )
             .unwrap();
         assert_eq!(
             name.as_deref(),
             Some("EAS_Recording_TOR_20241204_175845.wav")
         );
+        assert_eq!(
+            url.as_deref(),
+            Some("https://cdn.example.com/EAS_Recording_TOR_20241204_175845.wav")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_raw_header_for_recording() {
+        let (handle, _dir) = test_db();
+        let header = "ZCZC-WXR-SVR-031055+0100-1231700-KWO35-";
+        handle
+            .insert_same_alert(
+                header,
+                "Severe Thunderstorm Warning text.",
+                "SVR",
+                "Severe Thunderstorm Warning",
+                "WXR",
+                "NWS",
+                &["031055".to_string()],
+                "Douglas County",
+                None,
+                Some("0100"),
+                "2024-12-04T17:00:00Z",
+                None,
+            )
+            .await
+            .unwrap();
+        handle
+            .update_recording_name(header, "EAS_Recording_SVR.wav")
+            .await;
+
+        let found = handle
+            .raw_header_for_recording("EAS_Recording_SVR.wav")
+            .await
+            .unwrap();
+        assert_eq!(found.as_deref(), Some(header));
+        assert!(handle
+            .raw_header_for_recording("EAS_Recording_other.wav")
+            .await
+            .unwrap()
+            .is_none());
     }
 
     #[tokio::test]
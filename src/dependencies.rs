@@ -0,0 +1,145 @@
+use crate::config::Config;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::time::Duration;
+use tokio::process::Command;
+use tracing::{info, warn};
+
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+const PROBE_HEADER: &str = "ZCZC-WXR-RWT-031055+0015-1231645-KOAX/NWS-";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DependencyCheck {
+    pub name: &'static str,
+    pub ok: bool,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DependencyReport {
+    pub checked_at: DateTime<Utc>,
+    pub checks: Vec<DependencyCheck>,
+}
+
+impl DependencyReport {
+    pub fn failures(&self) -> impl Iterator<Item = &DependencyCheck> {
+        self.checks.iter().filter(|check| !check.ok)
+    }
+}
+
+static LAST_REPORT: Lazy<Mutex<Option<DependencyReport>>> = Lazy::new(|| Mutex::new(None));
+
+pub fn last_report() -> Option<DependencyReport> {
+    LAST_REPORT.lock().clone()
+}
+
+async fn first_output_line(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program).args(args).kill_on_drop(true).output();
+    let output = match tokio::time::timeout(COMMAND_TIMEOUT, output).await {
+        Err(_) => {
+            return Err(format!(
+                "did not finish within {} seconds",
+                COMMAND_TIMEOUT.as_secs()
+            ))
+        }
+        Ok(Err(err)) => return Err(format!("cannot be run: {err}")),
+        Ok(Ok(output)) => output,
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let first_line = stdout.lines().next().unwrap_or_default().trim().to_string();
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "exited with {}: {}",
+            output.status,
+            stderr.lines().next().unwrap_or_default().trim()
+        ));
+    }
+    Ok(first_line)
+}
+
+fn check(name: &'static str, result: Result<String, String>) -> DependencyCheck {
+    let (ok, detail) = match result {
+        Ok(detail) => (true, detail),
+        Err(detail) => (false, detail),
+    };
+    DependencyCheck { name, ok, detail }
+}
+
+fn check_e2t_ng() -> Result<String, String> {
+    let json = crate::e2t_ng::parse_header_json(PROBE_HEADER)?;
+    serde_json::from_str::<serde_json::Value>(&json)
+        .map_err(|err| format!("returned unparseable JSON: {err}"))?;
+    Ok(format!("decoded {PROBE_HEADER}"))
+}
+
+pub async fn probe(config: &Config) -> DependencyReport {
+    let mut checks = vec![
+        check("ffmpeg", first_output_line("ffmpeg", &["-version"]).await),
+        check("ffprobe", first_output_line("ffprobe", &["-version"]).await),
+        check("e2t-ng", check_e2t_ng()),
+    ];
+    if config.apprise_api_url.is_none() {
+        checks.push(check(
+            "apprise",
+            first_output_line("apprise", &["--version"]).await,
+        ));
+    }
+    let missing = crate::check_config::missing_files(config);
+    checks.push(check(
+        "files",
+        if missing.is_empty() {
+            Ok("every configured file can be read".to_string())
+        } else {
+            Err(missing.join("; "))
+        },
+    ));
+
+    for check in &checks {
+        if check.ok {
+            info!("Dependency check: {} OK ({})", check.name, check.detail);
+        } else {
+            warn!("Dependency check: {} FAILED: {}", check.name, check.detail);
+        }
+    }
+    let report = DependencyReport {
+        checked_at: Utc::now(),
+        checks,
+    };
+    *LAST_REPORT.lock() = Some(report.clone());
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn probes_report_missing_programs_and_files() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let mut config = Config::safe_internal_defaults();
+        config.apprise_config_path = dir
+            .path()
+            .join("missing-apprise.yml")
+            .to_string_lossy()
+            .into_owned();
+
+        let missing = first_output_line("eas-listener-no-such-program", &["--version"]).await;
+        assert!(missing.is_err_and(|err| err.starts_with("cannot be run")));
+        assert!(check_e2t_ng().is_ok());
+
+        let report = probe(&config).await;
+        let files = report
+            .checks
+            .iter()
+            .find(|check| check.name == "files")
+            .expect("files check");
+        assert!(!files.ok);
+        assert!(files.detail.contains("APPRISE_CONFIG_PATH"));
+        assert!(report.checks.iter().any(|check| check.name == "apprise"));
+        assert!(report.failures().any(|check| check.name == "files"));
+        assert_eq!(last_report(), Some(report));
+    }
+}
@@ -95,6 +95,12 @@ pub struct DurationParts {
     pub minutes: i64,
 }
 
+impl DurationParts {
+    pub fn to_std(&self) -> std::time::Duration {
+        std::time::Duration::from_secs((self.hours * 3600 + self.minutes * 60).max(0) as u64)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ParsedEas {
     pub originator: String,
@@ -305,6 +311,67 @@ pub fn parse_header_json(header: &str) -> Result<String, String> {
     parsed.to_json().map_err(|error| error.to_string())
 }
 
+pub fn parse_header_checked(header: &str) -> Result<ParsedEas, String> {
+    let invalid = |reason: &str| format!("{INVALID_HEADER_FORMAT}: {reason}");
+    let body = header
+        .strip_prefix("ZCZC-")
+        .ok_or_else(|| invalid("must start with ZCZC-"))?;
+    let body = body
+        .strip_suffix('-')
+        .ok_or_else(|| invalid("must end with '-' after the sender id"))?;
+    let (codes, trailer) = body
+        .split_once('+')
+        .ok_or_else(|| invalid("missing '+' before the purge time"))?;
+
+    let mut fields = codes.split('-');
+    let originator = fields.next().unwrap_or_default();
+    if originator.len() != 3 || !originator.bytes().all(|b| b.is_ascii_uppercase()) {
+        return Err(invalid(&format!(
+            "originator {originator:?} is not three letters"
+        )));
+    }
+    let event_code = fields.next().unwrap_or_default();
+    if event_code.len() != 3
+        || !event_code
+            .bytes()
+            .all(|b| b.is_ascii_uppercase() || b == b'?')
+    {
+        return Err(invalid(&format!(
+            "event code {event_code:?} is not three letters"
+        )));
+    }
+    let locations = fields.collect::<Vec<_>>();
+    if locations.is_empty() || locations.len() > 31 {
+        return Err(invalid("expected between 1 and 31 location codes"));
+    }
+    if let Some(location) = locations
+        .iter()
+        .find(|location| location.len() != 6 || !location.bytes().all(|b| b.is_ascii_digit()))
+    {
+        return Err(invalid(&format!(
+            "location code {location:?} is not six digits"
+        )));
+    }
+
+    let mut fields = trailer.splitn(3, '-');
+    let purge = fields.next().unwrap_or_default();
+    if parse_eas_duration(purge).is_none() {
+        return Err(invalid(&format!("purge time {purge:?} is not HHMM")));
+    }
+    let issued = fields.next().unwrap_or_default();
+    if parse_eas_time(issued).is_none() {
+        return Err(invalid(&format!("issue time {issued:?} is not JJJHHMM")));
+    }
+    let sender = fields.next().unwrap_or_default();
+    if sender.is_empty() || sender.len() > 8 {
+        return Err(invalid(&format!(
+            "sender id {sender:?} must be 1 to 8 characters"
+        )));
+    }
+
+    parse_header(header).ok_or_else(|| INVALID_HEADER_FORMAT.to_string())
+}
+
 #[allow(dead_code)]
 pub fn parse_header_pretty_json(header: &str) -> Result<String, String> {
     let parsed = parse_header(header).ok_or_else(|| INVALID_HEADER_FORMAT.to_string())?;
@@ -360,6 +427,19 @@ fn lookup_same_us(section_key: &str, item_key: &str) -> Option<String> {
     lookup_section(&SAME_US, section_key, item_key)
 }
 
+pub fn us_location_name(code: &str) -> Option<String> {
+    if code.len() != 6 || !code.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    let subdiv = lookup_same_us("SUBDIV", &code[0..1])?;
+    let name = lookup_same_us("SAME", &code[1..6])?;
+    Some(if subdiv.is_empty() {
+        name
+    } else {
+        format!("{} {}", subdiv, name)
+    })
+}
+
 fn apply_mode_template(mode_key: &str, replacements: &[(&str, String)]) -> String {
     let mut template = match ENDEC_MODES.templates.get(mode_key) {
         Some(value) => value.clone(),
@@ -1544,6 +1624,24 @@ mod tests {
         assert_eq!(err, "Invalid EAS header format");
     }
 
+    #[test]
+    fn parse_header_checked_names_the_malformed_field() {
+        assert!(parse_header_checked(valid_header()).is_ok());
+        let err = |header: &str| parse_header_checked(header).expect_err("invalid header");
+        assert_eq!(
+            err("NNNN"),
+            "Invalid EAS header format: must start with ZCZC-"
+        );
+        assert_eq!(
+            err("ZCZC-CIV-DMO-12345+0015-1231200-EASLSTNR-"),
+            "Invalid EAS header format: location code \"12345\" is not six digits"
+        );
+        assert_eq!(
+            err("ZCZC-CIV-DMO-000000+15-1231200-EASLSTNR-"),
+            "Invalid EAS header format: purge time \"15\" is not HHMM"
+        );
+    }
+
     #[test]
     fn e2t_returns_error_for_invalid_header() {
         let text = E2T("not-a-header", "", false, None);
@@ -0,0 +1,351 @@
+use crate::config::{optional_string, optional_u16, optional_u64};
+use crate::webhook::RecordingAttachment;
+use anyhow::{anyhow, Context, Result};
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde_json::Value;
+use std::time::Duration;
+use tracing::{info, warn};
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(30);
+const SMTP_RETRY_BASE_DELAY: Duration = Duration::from_secs(2);
+const SMTP_RETRY_MAX_DELAY: Duration = Duration::from_secs(60);
+const DEFAULT_ATTACHMENT_MAX_BYTES: u64 = 15 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpSecurity {
+    StartTls,
+    Tls,
+    None,
+}
+
+impl SmtpSecurity {
+    fn default_port(self) -> u16 {
+        match self {
+            Self::StartTls => 587,
+            Self::Tls => 465,
+            Self::None => 25,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: Mailbox,
+    pub to: Vec<Mailbox>,
+    pub attachment_max_bytes: u64,
+}
+
+impl std::fmt::Debug for SmtpConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SmtpConfig")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("security", &self.security)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("from", &self.from.to_string())
+            .field("to", &self.to.len())
+            .field("attachment_max_bytes", &self.attachment_max_bytes)
+            .finish()
+    }
+}
+
+impl SmtpConfig {
+    pub fn endpoint_id(&self) -> String {
+        crate::webhook_capabilities::destination_id(&format!("smtp://{}:{}", self.host, self.port))
+    }
+}
+
+fn parse_mailbox(key: &str, address: &str) -> Result<Mailbox> {
+    address
+        .trim()
+        .parse()
+        .with_context(|| format!("{key} has an invalid email address '{}'", address.trim()))
+}
+
+pub fn parse_smtp_config(config_json: &Value) -> Result<Option<SmtpConfig>> {
+    let Some(host) = optional_string(config_json, "SMTP_HOST")?
+        .map(|host| host.trim().to_string())
+        .filter(|host| !host.is_empty())
+    else {
+        return Ok(None);
+    };
+
+    let security = match optional_string(config_json, "SMTP_SECURITY")?
+        .map(|value| value.trim().to_ascii_lowercase())
+        .as_deref()
+    {
+        None | Some("") | Some("starttls") => SmtpSecurity::StartTls,
+        Some("tls") | Some("ssl") => SmtpSecurity::Tls,
+        Some("none") => SmtpSecurity::None,
+        Some(other) => {
+            return Err(anyhow!(
+                "SMTP_SECURITY must be one of starttls, tls or none (got '{other}') in your config.json file"
+            ))
+        }
+    };
+    let port = optional_u16(config_json, "SMTP_PORT")?
+        .filter(|port| *port != 0)
+        .unwrap_or_else(|| security.default_port());
+
+    let username = optional_string(config_json, "SMTP_USERNAME")?
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    let password = optional_string(config_json, "SMTP_PASSWORD")?.filter(|value| !value.is_empty());
+    if username.is_some() != password.is_some() {
+        return Err(anyhow!(
+            "SMTP_USERNAME and SMTP_PASSWORD must be set together in your config.json file"
+        ));
+    }
+
+    let from = optional_string(config_json, "SMTP_FROM")?
+        .filter(|value| !value.trim().is_empty())
+        .ok_or_else(|| {
+            anyhow!("SMTP_FROM must be set when SMTP_HOST is set in your config.json file")
+        })?;
+    let from = parse_mailbox("SMTP_FROM", &from)?;
+
+    let recipients: Vec<String> = match config_json.get("SMTP_TO") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::String(list)) => list.split(',').map(str::to_string).collect(),
+        Some(Value::Array(entries)) => entries
+            .iter()
+            .map(|entry| {
+                entry.as_str().map(str::to_string).ok_or_else(|| {
+                    anyhow!("SMTP_TO must contain only strings in your config.json file")
+                })
+            })
+            .collect::<Result<_>>()?,
+        Some(_) => {
+            return Err(anyhow!(
+                "SMTP_TO must be a string or an array of strings in your config.json file"
+            ))
+        }
+    };
+    let to = recipients
+        .iter()
+        .filter(|address| !address.trim().is_empty())
+        .map(|address| parse_mailbox("SMTP_TO", address))
+        .collect::<Result<Vec<_>>>()?;
+    if to.is_empty() {
+        return Err(anyhow!(
+            "SMTP_TO must list at least one recipient when SMTP_HOST is set in your config.json file"
+        ));
+    }
+
+    let attachment_max_bytes = optional_u64(config_json, "SMTP_ATTACHMENT_MAX_BYTES")?
+        .unwrap_or(DEFAULT_ATTACHMENT_MAX_BYTES);
+
+    Ok(Some(SmtpConfig {
+        host,
+        port,
+        security,
+        username,
+        password,
+        from,
+        to,
+        attachment_max_bytes,
+    }))
+}
+
+fn build_alert_message(
+    smtp: &SmtpConfig,
+    subject: &str,
+    plain_body: &str,
+    html_body: &str,
+    attachment: Option<&RecordingAttachment>,
+) -> Result<Message> {
+    let mut builder = Message::builder().from(smtp.from.clone()).subject(subject);
+    for recipient in &smtp.to {
+        builder = builder.to(recipient.clone());
+    }
+
+    let body = MultiPart::alternative_plain_html(plain_body.to_string(), html_body.to_string());
+    let message = match attachment {
+        Some(attachment) => {
+            let content_type = ContentType::parse(attachment.mime)
+                .unwrap_or_else(|_| ContentType::parse("application/octet-stream").expect("valid"));
+            builder.multipart(
+                MultiPart::mixed().multipart(body).singlepart(
+                    Attachment::new(attachment.file_name.clone())
+                        .body(attachment.bytes.clone(), content_type),
+                ),
+            )
+        }
+        None => builder.multipart(body),
+    };
+    message.context("failed to build alert email")
+}
+
+fn build_transport(smtp: &SmtpConfig) -> Result<AsyncSmtpTransport<Tokio1Executor>> {
+    let builder = match smtp.security {
+        SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&smtp.host)?,
+        SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&smtp.host)?,
+        SmtpSecurity::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&smtp.host),
+    };
+    let mut builder = builder.port(smtp.port).timeout(Some(SMTP_TIMEOUT));
+    if let (Some(username), Some(password)) = (&smtp.username, &smtp.password) {
+        builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+    }
+    Ok(builder.build())
+}
+
+fn retry_delay(attempt: u32) -> Duration {
+    SMTP_RETRY_BASE_DELAY
+        .saturating_mul(1 << attempt.min(16))
+        .min(SMTP_RETRY_MAX_DELAY)
+}
+
+pub async fn send_alert_email(
+    smtp: &SmtpConfig,
+    max_retries: u32,
+    subject: &str,
+    plain_body: &str,
+    html_body: &str,
+    attachment: Option<&RecordingAttachment>,
+) -> bool {
+    let message = match build_alert_message(smtp, subject, plain_body, html_body, attachment) {
+        Ok(message) => message,
+        Err(err) => {
+            warn!("Failed to build alert email: {:#}", err);
+            return false;
+        }
+    };
+    let transport = match build_transport(smtp) {
+        Ok(transport) => transport,
+        Err(err) => {
+            warn!(
+                "Failed to set up SMTP transport for '{}': {}",
+                smtp.host, err
+            );
+            return false;
+        }
+    };
+
+    let mut attempt = 0;
+    loop {
+        match transport.send(message.clone()).await {
+            Ok(response) => {
+                info!(
+                    "Delivered alert email to {} recipient(s) via '{}' (SMTP {})",
+                    smtp.to.len(),
+                    smtp.host,
+                    response.code()
+                );
+                return true;
+            }
+            Err(err) => {
+                let code = err
+                    .status()
+                    .map_or_else(|| "no response code".to_string(), |code| code.to_string());
+                if err.is_permanent() || attempt >= max_retries {
+                    warn!(
+                        "Failed to send alert email via '{}' after {} attempt(s) (SMTP {}): {}",
+                        smtp.host,
+                        attempt + 1,
+                        code,
+                        err
+                    );
+                    return false;
+                }
+                let delay = retry_delay(attempt);
+                attempt += 1;
+                warn!(
+                    "Alert email via '{}' failed (SMTP {}): {}; retrying in {}s (attempt {}/{})",
+                    smtp.host,
+                    code,
+                    err,
+                    delay.as_secs(),
+                    attempt,
+                    max_retries
+                );
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn smtp_config_is_optional_and_validated() {
+        assert!(parse_smtp_config(&json!({})).unwrap().is_none());
+        assert!(parse_smtp_config(&json!({ "SMTP_HOST": " " }))
+            .unwrap()
+            .is_none());
+
+        let smtp = parse_smtp_config(&json!({
+            "SMTP_HOST": "smtp.example.com",
+            "SMTP_SECURITY": "tls",
+            "SMTP_USERNAME": "relay",
+            "SMTP_PASSWORD": "hunter2",
+            "SMTP_FROM": "EAS Relay <relay@example.com>",
+            "SMTP_TO": "eoc@example.com, duty@example.com",
+        }))
+        .unwrap()
+        .expect("smtp enabled");
+        assert_eq!(smtp.port, 465);
+        assert_eq!(smtp.security, SmtpSecurity::Tls);
+        assert_eq!(smtp.to.len(), 2);
+        assert!(!format!("{smtp:?}").contains("hunter2"));
+
+        assert!(parse_smtp_config(&json!({
+            "SMTP_HOST": "smtp.example.com",
+            "SMTP_FROM": "relay@example.com",
+            "SMTP_TO": ["not an address"],
+        }))
+        .is_err());
+        assert!(parse_smtp_config(&json!({
+            "SMTP_HOST": "smtp.example.com",
+            "SMTP_FROM": "relay@example.com",
+            "SMTP_TO": [],
+        }))
+        .is_err());
+        assert_eq!(retry_delay(0), Duration::from_secs(2));
+        assert_eq!(retry_delay(10), SMTP_RETRY_MAX_DELAY);
+    }
+
+    #[test]
+    fn alert_email_carries_both_bodies_and_the_recording() {
+        let smtp = parse_smtp_config(&json!({
+            "SMTP_HOST": "smtp.example.com",
+            "SMTP_FROM": "relay@example.com",
+            "SMTP_TO": ["eoc@example.com"],
+        }))
+        .unwrap()
+        .expect("smtp enabled");
+        assert_eq!(smtp.port, 587);
+
+        let attachment = RecordingAttachment {
+            bytes: b"RIFF....WAVE".to_vec(),
+            file_name: "EAS_Recording_test.wav".to_string(),
+            mime: "audio/wav",
+        };
+        let message = build_alert_message(
+            &smtp,
+            "A Tornado Warning has just been issued/received",
+            "plain body",
+            "<p>html body</p>",
+            Some(&attachment),
+        )
+        .expect("message");
+        let formatted = String::from_utf8(message.formatted()).expect("utf8");
+        assert!(formatted.contains("multipart/mixed"));
+        assert!(formatted.contains("multipart/alternative"));
+        assert!(formatted.contains("text/plain"));
+        assert!(formatted.contains("text/html"));
+        assert!(formatted.contains("filename=\"EAS_Recording_test.wav\""));
+        assert!(formatted.contains("To: eoc@example.com"));
+    }
+}
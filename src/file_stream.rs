@@ -0,0 +1,168 @@
+use axum::body::Body;
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use std::io::SeekFrom;
+use std::path::Path;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+use tracing::warn;
+
+pub fn content_type_for(path: &Path) -> &'static str {
+    match path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase)
+        .as_deref()
+    {
+        Some("wav") => "audio/wav",
+        Some("mp3") => "audio/mpeg",
+        Some("ogg") | Some("opus") => "audio/ogg",
+        Some("flac") => "audio/flac",
+        _ => "application/octet-stream",
+    }
+}
+
+fn parse_range(value: &str, len: u64) -> Result<Option<(u64, u64)>, ()> {
+    let Some(spec) = value.trim().strip_prefix("bytes=") else {
+        return Ok(None);
+    };
+    if spec.contains(',') {
+        return Ok(None);
+    }
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return Ok(None);
+    };
+
+    let range = match (start.trim(), end.trim()) {
+        ("", "") => return Ok(None),
+        ("", suffix) => {
+            let Ok(suffix) = suffix.parse::<u64>() else {
+                return Ok(None);
+            };
+            if suffix == 0 || len == 0 {
+                return Err(());
+            }
+            (len.saturating_sub(suffix), len - 1)
+        }
+        (start, end) => {
+            let Ok(start) = start.parse::<u64>() else {
+                return Ok(None);
+            };
+            let end = if end.is_empty() {
+                len.saturating_sub(1)
+            } else {
+                match end.parse::<u64>() {
+                    Ok(end) => end.min(len.saturating_sub(1)),
+                    Err(_) => return Ok(None),
+                }
+            };
+            if start >= len || start > end {
+                return Err(());
+            }
+            (start, end)
+        }
+    };
+
+    Ok(Some(range))
+}
+
+pub async fn file_response(path: &Path, request_headers: &HeaderMap) -> Response {
+    let mut file = match tokio::fs::File::open(path).await {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return (StatusCode::NOT_FOUND, "Recording not found").into_response();
+        }
+        Err(err) => {
+            warn!("Failed to open {} for streaming: {}", path.display(), err);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to open recording",
+            )
+                .into_response();
+        }
+    };
+    let len = match file.metadata().await {
+        Ok(metadata) => metadata.len(),
+        Err(err) => {
+            warn!("Failed to stat {} for streaming: {}", path.display(), err);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to read recording",
+            )
+                .into_response();
+        }
+    };
+
+    let range = match request_headers
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| parse_range(value, len))
+    {
+        Some(Ok(range)) => range,
+        None => None,
+        Some(Err(())) => {
+            let mut response = StatusCode::RANGE_NOT_SATISFIABLE.into_response();
+            if let Ok(value) = HeaderValue::from_str(&format!("bytes */{len}")) {
+                response.headers_mut().insert(header::CONTENT_RANGE, value);
+            }
+            return response;
+        }
+    };
+
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().replace('"', ""))
+        .unwrap_or_default();
+    let mut builder = Response::builder()
+        .header(header::CONTENT_TYPE, content_type_for(path))
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("inline; filename=\"{file_name}\""),
+        );
+
+    let body = match range {
+        Some((start, end)) => {
+            if let Err(err) = file.seek(SeekFrom::Start(start)).await {
+                warn!("Failed to seek in {}: {}", path.display(), err);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to read recording",
+                )
+                    .into_response();
+            }
+            let length = end - start + 1;
+            builder = builder
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(header::CONTENT_RANGE, format!("bytes {start}-{end}/{len}"))
+                .header(header::CONTENT_LENGTH, length);
+            Body::from_stream(ReaderStream::new(file.take(length)))
+        }
+        None => {
+            builder = builder
+                .status(StatusCode::OK)
+                .header(header::CONTENT_LENGTH, len);
+            Body::from_stream(ReaderStream::new(file))
+        }
+    };
+
+    builder
+        .body(body)
+        .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_range_handles_common_forms() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Ok(Some((0, 99))));
+        assert_eq!(parse_range("bytes=900-", 1000), Ok(Some((900, 999))));
+        assert_eq!(parse_range("bytes=-100", 1000), Ok(Some((900, 999))));
+        assert_eq!(parse_range("bytes=500-5000", 1000), Ok(Some((500, 999))));
+        assert_eq!(parse_range("bytes=1000-", 1000), Err(()));
+        assert_eq!(parse_range("bytes=0-1,5-9", 1000), Ok(None));
+        assert_eq!(parse_range("items=0-1", 1000), Ok(None));
+    }
+}
@@ -1,171 +1,863 @@
-use lazy_static::lazy_static;
-use parking_lot::RwLock;
+use crate::config::Config;
+use chrono::{DateTime, Utc};
+use parking_lot::{Mutex, RwLock};
+use serde::{Serialize, Serializer};
 use serde_json::Value;
-use tracing::{error, warn};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum FilterAction {
     Ignore,
+    #[default]
     Relay,
     Log,
     Forward,
 }
 
+impl FilterAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FilterAction::Ignore => "ignore",
+            FilterAction::Relay => "relay",
+            FilterAction::Log => "log",
+            FilterAction::Forward => "forward",
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum EventCodeMatcher {
     Exact(String),
+    Prefix(String),
+    Suffix(String),
     Wildcard,
+    Not(Box<EventCodeMatcher>),
 }
 
-#[derive(Debug, Clone)]
+impl EventCodeMatcher {
+    fn parse(pattern: &str) -> Result<Self, String> {
+        let pattern = normalize_event_code(pattern);
+        if let Some(excluded) = pattern.strip_prefix('!') {
+            return match Self::parse(excluded)? {
+                EventCodeMatcher::Wildcard => Err("!* excludes every event code".to_string()),
+                EventCodeMatcher::Not(_) => Err(format!("{pattern} excludes twice")),
+                matcher => Ok(EventCodeMatcher::Not(Box::new(matcher))),
+            };
+        }
+        if pattern == "*" {
+            return Ok(EventCodeMatcher::Wildcard);
+        }
+        let matcher = if let Some(suffix) = pattern.strip_prefix('*') {
+            EventCodeMatcher::Suffix(suffix.to_string())
+        } else if let Some(prefix) = pattern.strip_suffix('*') {
+            EventCodeMatcher::Prefix(prefix.to_string())
+        } else {
+            EventCodeMatcher::Exact(pattern.clone())
+        };
+        match &matcher {
+            EventCodeMatcher::Exact(code)
+            | EventCodeMatcher::Prefix(code)
+            | EventCodeMatcher::Suffix(code)
+                if code.is_empty() || code.contains(['*', '!']) =>
+            {
+                Err(format!(
+                    "{pattern} is not an event code, a code with a single * at its start or end, or *"
+                ))
+            }
+            _ => Ok(matcher),
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            EventCodeMatcher::Exact(_) => 0,
+            EventCodeMatcher::Prefix(_) | EventCodeMatcher::Suffix(_) => 1,
+            EventCodeMatcher::Wildcard | EventCodeMatcher::Not(_) => 2,
+        }
+    }
+
+    fn matches(&self, normalized_code: &str) -> bool {
+        match self {
+            EventCodeMatcher::Exact(code) => code == normalized_code,
+            EventCodeMatcher::Prefix(prefix) => normalized_code.starts_with(prefix.as_str()),
+            EventCodeMatcher::Suffix(suffix) => normalized_code.ends_with(suffix.as_str()),
+            EventCodeMatcher::Wildcard => true,
+            EventCodeMatcher::Not(matcher) => !matcher.matches(normalized_code),
+        }
+    }
+}
+
+impl fmt::Display for EventCodeMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventCodeMatcher::Exact(code) => write!(f, "{code}"),
+            EventCodeMatcher::Prefix(prefix) => write!(f, "{prefix}*"),
+            EventCodeMatcher::Suffix(suffix) => write!(f, "*{suffix}"),
+            EventCodeMatcher::Wildcard => write!(f, "*"),
+            EventCodeMatcher::Not(matcher) => write!(f, "!{matcher}"),
+        }
+    }
+}
+
+impl Serialize for EventCodeMatcher {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct FilterRule {
     pub name: String,
     pub action: FilterAction,
+    #[serde(skip_serializing_if = "is_zero")]
+    pub priority: i64,
+    #[serde(rename = "event_codes")]
     matchers: Vec<EventCodeMatcher>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    originators: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fips: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_duration_minutes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_duration_minutes: Option<u64>,
 }
 
 impl FilterRule {
-    fn matches_exact(&self, normalized_code: &str) -> bool {
-        self.matchers.iter().any(|matcher| match matcher {
-            EventCodeMatcher::Exact(expected) => expected == normalized_code,
-            EventCodeMatcher::Wildcard => false,
+    fn positive_matchers(&self) -> impl Iterator<Item = &EventCodeMatcher> {
+        self.matchers
+            .iter()
+            .filter(|matcher| !matches!(matcher, EventCodeMatcher::Not(_)))
+    }
+
+    fn has_exclusions(&self) -> bool {
+        self.matchers.len() != self.positive_matchers().count()
+    }
+
+    fn match_rank(&self, normalized_code: &str) -> Option<u8> {
+        let excluded = self.matchers.iter().any(|matcher| {
+            matches!(matcher, EventCodeMatcher::Not(_)) && !matcher.matches(normalized_code)
+        });
+        if excluded {
+            return None;
+        }
+        let mut positives = self.positive_matchers().peekable();
+        if positives.peek().is_none() {
+            return Some(EventCodeMatcher::Wildcard.rank());
+        }
+        positives
+            .filter(|matcher| matcher.matches(normalized_code))
+            .map(EventCodeMatcher::rank)
+            .min()
+    }
+
+    fn matches_originator(&self, originator: &str) -> bool {
+        self.originators.is_empty()
+            || self
+                .originators
+                .iter()
+                .any(|expected| expected.eq_ignore_ascii_case(originator.trim()))
+    }
+
+    fn is_limited(&self) -> bool {
+        !self.originators.is_empty()
+            || !self.fips.is_empty()
+            || self.min_duration_minutes.is_some()
+            || self.max_duration_minutes.is_some()
+    }
+
+    fn matches_duration(&self, duration: Option<Duration>) -> bool {
+        if self.min_duration_minutes.is_none() && self.max_duration_minutes.is_none() {
+            return true;
+        }
+        let Some(minutes) = duration.map(|duration| duration.as_secs() / 60) else {
+            debug!(
+                filter = %self.name,
+                "Skipping filter: it has duration bounds and the alert's purge time is unknown"
+            );
+            return false;
+        };
+        if let Some(min) = self.min_duration_minutes.filter(|min| minutes < *min) {
+            debug!(
+                filter = %self.name,
+                "Skipping filter: purge time of {} minutes is below min_duration_minutes {}",
+                minutes,
+                min
+            );
+            return false;
+        }
+        if let Some(max) = self.max_duration_minutes.filter(|max| minutes > *max) {
+            debug!(
+                filter = %self.name,
+                "Skipping filter: purge time of {} minutes is above max_duration_minutes {}",
+                minutes,
+                max
+            );
+            return false;
+        }
+        true
+    }
+
+    fn is_shadowed_by(&self, earlier: &FilterRule) -> bool {
+        if earlier.is_limited() || earlier.has_exclusions() {
+            return false;
+        }
+        let mut positives = self.positive_matchers().peekable();
+        if positives.peek().is_none() {
+            return earlier.matchers.contains(&EventCodeMatcher::Wildcard);
+        }
+        positives.all(|matcher| match matcher {
+            EventCodeMatcher::Exact(code) => earlier.match_rank(code) == Some(0),
+            pattern => earlier.matchers.contains(pattern),
         })
     }
 
-    fn has_wildcard(&self) -> bool {
-        self.matchers
+    fn matches_fips(&self, fips: &[String]) -> bool {
+        self.fips.is_empty()
+            || self.fips.iter().any(|code| code == "000000")
+            || fips
+                .iter()
+                .any(|code| code == "000000" || self.fips.contains(code))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RuleHits {
+    pub hits: u64,
+    pub last_hit: Option<DateTime<Utc>>,
+}
+
+impl RuleHits {
+    fn record(&mut self) {
+        self.hits += 1;
+        self.last_hit = Some(Utc::now());
+    }
+}
+
+#[derive(Debug, Default)]
+struct FilterHits {
+    rules: HashMap<String, RuleHits>,
+    unmatched: RuleHits,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleWithHits {
+    #[serde(flatten)]
+    pub rule: FilterRule,
+    #[serde(flatten)]
+    pub hits: RuleHits,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Filters {
+    pub global: Vec<FilterRule>,
+    pub streams: HashMap<String, Vec<FilterRule>>,
+    pub default_action: FilterAction,
+}
+
+impl Filters {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            global: config.filters.clone(),
+            streams: config
+                .streams
+                .iter()
+                .filter_map(|stream| Some((stream.id.clone(), stream.filters.clone()?)))
+                .collect(),
+            default_action: config.default_filter_action,
+        }
+    }
+
+    pub fn rules_for(&self, stream_id: &str) -> &[FilterRule] {
+        self.streams
+            .get(stream_id)
+            .map_or(self.global.as_slice(), Vec::as_slice)
+    }
+
+    pub fn matching_rule(
+        &self,
+        stream_id: &str,
+        event_code: &str,
+        originator: &str,
+        fips: &[String],
+        duration: Option<Duration>,
+    ) -> Option<&FilterRule> {
+        match_filter(
+            self.rules_for(stream_id),
+            event_code,
+            originator,
+            fips,
+            duration,
+        )
+    }
+
+    pub fn rule_name(
+        &self,
+        stream_id: &str,
+        event_code: &str,
+        originator: &str,
+        fips: &[String],
+        duration: Option<Duration>,
+    ) -> String {
+        self.matching_rule(stream_id, event_code, originator, fips, duration)
+            .map_or_else(|| "Default Filter".to_string(), |rule| rule.name.clone())
+    }
+
+    fn rule_names(&self) -> HashSet<&str> {
+        self.global
             .iter()
-            .any(|matcher| matches!(matcher, EventCodeMatcher::Wildcard))
+            .chain(self.streams.values().flatten())
+            .map(|rule| rule.name.as_str())
+            .collect()
     }
 }
 
-lazy_static! {
-    static ref GLOBAL_FILTERS: RwLock<Vec<FilterRule>> = RwLock::new(Vec::new());
+#[derive(Debug, Clone)]
+pub struct FilterHandle {
+    current: Arc<RwLock<Arc<Filters>>>,
+    hits: Arc<Mutex<FilterHits>>,
+}
+
+impl FilterHandle {
+    pub fn new(filters: Filters) -> Self {
+        Self {
+            current: Arc::new(RwLock::new(Arc::new(filters))),
+            hits: Arc::new(Mutex::new(FilterHits::default())),
+        }
+    }
+
+    pub fn snapshot(&self) -> Arc<Filters> {
+        Arc::clone(&self.current.read())
+    }
+
+    pub fn replace(&self, filters: Filters) {
+        {
+            let names = filters.rule_names();
+            self.hits
+                .lock()
+                .rules
+                .retain(|name, _| names.contains(name.as_str()));
+        }
+        *self.current.write() = Arc::new(filters);
+    }
+
+    pub fn evaluate_action(
+        &self,
+        stream_id: &str,
+        event_code: &str,
+        originator: &str,
+        fips: &[String],
+        duration: Option<Duration>,
+    ) -> FilterAction {
+        let filters = self.snapshot();
+        let matched = filters.matching_rule(stream_id, event_code, originator, fips, duration);
+        let mut hits = self.hits.lock();
+        match matched {
+            Some(rule) => {
+                hits.rules.entry(rule.name.clone()).or_default().record();
+                rule.action
+            }
+            None => {
+                hits.unmatched.record();
+                filters.default_action
+            }
+        }
+    }
+
+    pub fn rules_with_hits(&self, rules: &[FilterRule]) -> Vec<RuleWithHits> {
+        let hits = self.hits.lock();
+        rules
+            .iter()
+            .map(|rule| RuleWithHits {
+                rule: rule.clone(),
+                hits: hits.rules.get(&rule.name).cloned().unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    pub fn unmatched_hits(&self) -> RuleHits {
+        self.hits.lock().unmatched.clone()
+    }
 }
 
 pub fn parse_filters(config_json: &Value) -> Vec<FilterRule> {
-    let mut filters = Vec::new();
+    let (filters, problems) = parse_filters_with_problems(config_json);
+    for problem in problems {
+        warn!("{}", problem);
+    }
+    filters
+}
 
-    let filters_enabled = config_json
-        .get("ENABLE_FILTERS")
-        .and_then(Value::as_bool)
-        .unwrap_or(true);
-    if !filters_enabled {
-        return filters;
+pub fn parse_filters_with_problems(config_json: &Value) -> (Vec<FilterRule>, Vec<String>) {
+    if !filters_enabled(config_json) {
+        return (Vec::new(), Vec::new());
+    }
+    match config_json.get("FILTERS").and_then(Value::as_array) {
+        Some(entries) => parse_filter_rules(entries),
+        None => (Vec::new(), Vec::new()),
     }
+}
 
-    let Some(entries) = config_json.get("FILTERS").and_then(Value::as_array) else {
-        return filters;
+pub fn parse_filter_sets(config_json: &Value) -> (BTreeMap<String, Vec<FilterRule>>, Vec<String>) {
+    let mut sets = BTreeMap::new();
+    let mut problems = Vec::new();
+    let Some(value) = config_json
+        .get("FILTER_SETS")
+        .filter(|_| filters_enabled(config_json))
+    else {
+        return (sets, problems);
+    };
+    let Some(entries) = value.as_object() else {
+        problems
+            .push("FILTER_SETS must be an object of named filter lists; ignoring it".to_string());
+        return (sets, problems);
     };
 
+    for (name, entry) in entries {
+        let name = name.trim();
+        if name.is_empty() {
+            problems.push("Skipping a filter set without a name".to_string());
+            continue;
+        }
+        let Some(rules) = entry.as_array() else {
+            problems.push(format!(
+                "Skipping filter set '{name}' because it is not a list of filters"
+            ));
+            continue;
+        };
+        let (rules, rule_problems) = parse_filter_rules(rules);
+        problems.extend(
+            rule_problems
+                .into_iter()
+                .map(|problem| format!("Filter set '{name}': {problem}")),
+        );
+        sets.insert(name.to_string(), rules);
+    }
+    (sets, problems)
+}
+
+pub fn filters_enabled(config_json: &Value) -> bool {
+    config_json
+        .get("ENABLE_FILTERS")
+        .and_then(Value::as_bool)
+        .unwrap_or(true)
+}
+
+pub fn parse_filter_rules(entries: &[Value]) -> (Vec<FilterRule>, Vec<String>) {
+    let mut filters = Vec::new();
+    let mut problems = Vec::new();
+
     for entry in entries {
         let Some(name) = entry.get("name").and_then(Value::as_str).map(str::trim) else {
-            warn!("Skipping filter without a valid name: {:?}", entry);
+            problems.push(format!("Skipping filter without a valid name: {:?}", entry));
             continue;
         };
 
-        let Some(codes_value) = entry.get("event_codes").and_then(Value::as_array) else {
-            warn!("Skipping filter '{}' due to missing event_codes", name);
-            continue;
+        let limited = entry.get("originators").is_some() || entry.get("fips").is_some();
+        let wildcard = [Value::from("*")];
+        let codes_value = match entry.get("event_codes").and_then(Value::as_array) {
+            Some(codes) => codes.as_slice(),
+            None if limited => &wildcard[..],
+            None => {
+                problems.push(format!(
+                    "Skipping filter '{}' due to missing event_codes",
+                    name
+                ));
+                continue;
+            }
         };
 
         let mut matchers = Vec::with_capacity(codes_value.len());
         for code_value in codes_value {
-            if let Some(pattern) = code_value.as_str() {
-                let pattern = pattern.trim();
-                if pattern == "*" {
-                    matchers.push(EventCodeMatcher::Wildcard);
-                } else if !pattern.is_empty() {
-                    matchers.push(EventCodeMatcher::Exact(normalize_event_code(pattern)));
+            if let Some(pattern) = code_value.as_str().map(str::trim) {
+                if pattern.is_empty() {
+                    continue;
+                }
+                match EventCodeMatcher::parse(pattern) {
+                    Ok(matcher) => matchers.push(matcher),
+                    Err(err) => problems.push(format!(
+                        "Filter '{}' has an unusable event code pattern: {}; ignoring it",
+                        name, err
+                    )),
                 }
             }
         }
 
         if matchers.is_empty() {
-            warn!("Filter '{}' has no valid event codes; skipping", name);
+            problems.push(format!(
+                "Filter '{}' has no valid event codes; skipping",
+                name
+            ));
             continue;
         }
+        problems.extend(
+            exclusion_problems(&matchers)
+                .into_iter()
+                .map(|problem| format!("Filter '{}' {}", name, problem)),
+        );
 
-        let Some(action_str) = entry.get("action").and_then(Value::as_str) else {
-            warn!("Filter '{}' missing action field; defaulting to log", name);
-            filters.push(FilterRule {
-                name: name.to_string(),
-                action: FilterAction::Log,
-                matchers,
-            });
-            continue;
+        let originators = match entry.get("originators") {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::Array(codes)) => {
+                let originators = codes
+                    .iter()
+                    .filter_map(|value| {
+                        let code = value
+                            .as_str()
+                            .map(str::trim)
+                            .filter(|code| code.len() == 3)
+                            .filter(|code| code.bytes().all(|b| b.is_ascii_alphabetic()));
+                        if code.is_none() {
+                            problems.push(format!(
+                                "Filter '{}' has invalid originator {}; ignoring it",
+                                name, value
+                            ));
+                        }
+                        code.map(str::to_ascii_uppercase)
+                    })
+                    .collect::<Vec<_>>();
+                if originators.is_empty() {
+                    problems.push(format!(
+                        "Filter '{}' has no valid originators; skipping",
+                        name
+                    ));
+                    continue;
+                }
+                originators
+            }
+            Some(other) => {
+                problems.push(format!(
+                    "Skipping filter '{}' because originators must be a list of codes, not {}",
+                    name, other
+                ));
+                continue;
+            }
+        };
+
+        let fips = match entry.get("fips") {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::Array(codes)) => {
+                let fips = codes
+                    .iter()
+                    .filter_map(|value| {
+                        let code = value
+                            .as_str()
+                            .map(str::trim)
+                            .filter(|code| code.len() == 6)
+                            .filter(|code| code.bytes().all(|b| b.is_ascii_digit()));
+                        if code.is_none() {
+                            problems.push(format!(
+                                "Filter '{}' has invalid FIPS code {}; ignoring it",
+                                name, value
+                            ));
+                        }
+                        code.map(str::to_string)
+                    })
+                    .collect::<Vec<_>>();
+                if fips.is_empty() {
+                    problems.push(format!(
+                        "Filter '{}' has no valid FIPS codes; skipping",
+                        name
+                    ));
+                    continue;
+                }
+                fips
+            }
+            Some(other) => {
+                problems.push(format!(
+                    "Skipping filter '{}' because fips must be a list of codes, not {}",
+                    name, other
+                ));
+                continue;
+            }
+        };
+
+        let mut duration_bound = |key: &str| match entry.get(key) {
+            None | Some(Value::Null) => None,
+            Some(value) => {
+                let minutes = value.as_u64();
+                if minutes.is_none() {
+                    problems.push(format!(
+                        "Filter '{}' has {} {}, which is not a whole number of minutes; ignoring it",
+                        name, key, value
+                    ));
+                }
+                minutes
+            }
+        };
+        let min_duration_minutes = duration_bound("min_duration_minutes");
+        let max_duration_minutes = duration_bound("max_duration_minutes");
+        if let (Some(min), Some(max)) = (min_duration_minutes, max_duration_minutes) {
+            if min > max {
+                problems.push(format!(
+                    "Skipping filter '{}' because min_duration_minutes {} is above max_duration_minutes {}",
+                    name, min, max
+                ));
+                continue;
+            }
+        }
+
+        let priority = match entry.get("priority") {
+            None | Some(Value::Null) => 0,
+            Some(value) => match value.as_i64() {
+                Some(priority) => priority,
+                None => {
+                    problems.push(format!(
+                        "Filter '{}' has priority {}, which is not a whole number; using 0",
+                        name, value
+                    ));
+                    0
+                }
+            },
         };
 
-        let action = parse_action(action_str, name);
+        let action = match entry.get("action").and_then(Value::as_str) {
+            Some(action_str) => parse_action(action_str).unwrap_or_else(|| {
+                problems.push(format!(
+                    "Filter '{}' has unsupported action '{}'; defaulting to relay",
+                    name,
+                    action_str.trim()
+                ));
+                FilterAction::Relay
+            }),
+            None => {
+                problems.push(format!(
+                    "Filter '{}' missing action field; defaulting to log",
+                    name
+                ));
+                FilterAction::Log
+            }
+        };
 
         filters.push(FilterRule {
             name: name.to_string(),
             action,
+            priority,
             matchers,
+            originators,
+            fips,
+            min_duration_minutes,
+            max_duration_minutes,
         });
     }
 
-    filters
+    filters.sort_by_key(|rule| Reverse(rule.priority));
+    for (index, rule) in filters.iter().enumerate() {
+        if let Some(earlier) = filters[..index]
+            .iter()
+            .find(|earlier| rule.is_shadowed_by(earlier))
+        {
+            problems.push(format!(
+                "Filter '{}' never matches because '{}' is evaluated before it and matches the same alerts",
+                rule.name, earlier.name
+            ));
+        }
+    }
+
+    (filters, problems)
 }
 
-pub fn install_filters(filters: Vec<FilterRule>) {
-    let mut global_filters = GLOBAL_FILTERS.write();
-    *global_filters = filters;
+fn exclusion_problems(matchers: &[EventCodeMatcher]) -> Vec<String> {
+    let (excluded, included): (Vec<_>, Vec<_>) = matchers
+        .iter()
+        .partition(|matcher| matches!(matcher, EventCodeMatcher::Not(_)));
+    let mut problems = Vec::new();
+    if excluded.is_empty() {
+        return problems;
+    }
+    if included.contains(&&EventCodeMatcher::Wildcard) {
+        problems.push(
+            "mixes * with exclusions; list only the exclusions to match every other event code"
+                .to_string(),
+        );
+    }
+    for exclusion in &excluded {
+        let EventCodeMatcher::Not(inner) = exclusion else {
+            continue;
+        };
+        for matcher in &included {
+            if let EventCodeMatcher::Exact(code) = matcher {
+                if inner.matches(code) {
+                    problems.push(format!(
+                        "lists {} but also excludes it with {}",
+                        code, exclusion
+                    ));
+                }
+            }
+        }
+        if let EventCodeMatcher::Exact(code) = inner.as_ref() {
+            let listed =
+                included.is_empty() || included.iter().any(|matcher| matcher.matches(code));
+            if !listed {
+                problems.push(format!(
+                    "excludes {} although none of its event codes match it",
+                    code
+                ));
+            }
+        }
+    }
+    problems
 }
 
-#[allow(dead_code)]
-pub fn evaluate_action(filters: &[FilterRule], event_code: &str) -> FilterAction {
-    match_filter(filters, event_code)
-        .map(|rule| rule.action)
-        .unwrap_or(FilterAction::Relay)
+fn is_zero(value: &i64) -> bool {
+    *value == 0
 }
 
-pub fn determine_filter_name(event_code: &str) -> String {
-    let filters = GLOBAL_FILTERS.read();
-    match_filter(&filters, event_code)
-        .map(|rule| rule.name.clone())
-        .unwrap_or_else(|| "Default Filter".to_string())
+pub fn log_effective_filters(label: &str, filters: &[FilterRule], default_action: FilterAction) {
+    let rules = filters
+        .iter()
+        .enumerate()
+        .map(|(index, rule)| {
+            let mut limits = Vec::new();
+            if !rule.originators.is_empty() {
+                limits.push(format!("originators {}", rule.originators.join(",")));
+            }
+            if !rule.fips.is_empty() {
+                limits.push(format!("FIPS {}", rule.fips.join(",")));
+            }
+            match (rule.min_duration_minutes, rule.max_duration_minutes) {
+                (Some(min), Some(max)) => {
+                    limits.push(format!("purge times of {min}-{max} minutes"))
+                }
+                (Some(min), None) => limits.push(format!("purge times of at least {min} minutes")),
+                (None, Some(max)) => limits.push(format!("purge times of at most {max} minutes")),
+                (None, None) => {}
+            }
+            let codes = rule
+                .matchers
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(",");
+            let limits = if limits.is_empty() {
+                String::new()
+            } else {
+                format!(" for {}", limits.join(" and "))
+            };
+            format!(
+                "{}. '{}' {}{} -> {}",
+                index + 1,
+                rule.name,
+                codes,
+                limits,
+                rule.action.as_str()
+            )
+        })
+        .collect::<Vec<_>>();
+    info!(
+        "{} filters in evaluation order: {}; unmatched alerts -> {}",
+        label,
+        if rules.is_empty() {
+            "none".to_string()
+        } else {
+            rules.join("; ")
+        },
+        default_action.as_str()
+    );
 }
 
-pub fn match_filter<'a>(filters: &'a [FilterRule], event_code: &str) -> Option<&'a FilterRule> {
+pub fn match_filter<'a>(
+    filters: &'a [FilterRule],
+    event_code: &str,
+    originator: &str,
+    fips: &[String],
+    duration: Option<Duration>,
+) -> Option<&'a FilterRule> {
     let normalized = normalize_event_code(event_code);
-    let mut wildcard_match: Option<&FilterRule> = None;
+    let mut best: Option<(u8, &FilterRule)> = None;
 
-    for rule in filters {
-        if rule.matches_exact(&normalized) {
+    for rule in filters
+        .iter()
+        .filter(|rule| rule.matches_originator(originator) && rule.matches_fips(fips))
+    {
+        let Some(rank) = rule.match_rank(&normalized) else {
+            continue;
+        };
+        if best.is_some_and(|(best_rank, _)| rank >= best_rank) || !rule.matches_duration(duration)
+        {
+            continue;
+        }
+        if rank == 0 {
             return Some(rule);
         }
+        best = Some((rank, rule));
+    }
+
+    best.map(|(_, rule)| rule)
+}
 
-        if wildcard_match.is_none() && rule.has_wildcard() {
-            wildcard_match = Some(rule);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FilterBehavior {
+    pub log: bool,
+    pub forward: bool,
+    pub relay: bool,
+}
+
+impl From<FilterAction> for FilterBehavior {
+    fn from(action: FilterAction) -> Self {
+        Self {
+            log: should_log_action(action),
+            forward: should_forward_action(action),
+            relay: action == FilterAction::Relay,
         }
     }
-
-    wildcard_match
 }
 
-#[allow(dead_code)]
-pub fn should_relay_alert(event_code: &str) -> bool {
-    let filters = GLOBAL_FILTERS.read();
-    matches!(resolve_action(&filters, event_code), FilterAction::Relay)
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleEvaluation<'a> {
+    #[serde(flatten)]
+    pub rule: &'a FilterRule,
+    pub matched: bool,
 }
 
-#[allow(dead_code)]
-pub fn should_log_alert(event_code: &str) -> bool {
-    let filters = GLOBAL_FILTERS.read();
-    matches!(
-        resolve_action(&filters, event_code),
-        FilterAction::Log | FilterAction::Forward | FilterAction::Relay
-    )
+#[derive(Debug, Clone, Serialize)]
+pub struct FilterEvaluation<'a> {
+    pub event_code: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub originator: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fips: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_minutes: Option<u64>,
+    pub matched_rule: String,
+    pub action: FilterAction,
+    pub behavior: FilterBehavior,
+    pub rules: Vec<RuleEvaluation<'a>>,
 }
 
-#[allow(dead_code)]
-pub fn should_forward_alert(event_code: &str) -> bool {
-    let filters = GLOBAL_FILTERS.read();
-    matches!(
-        resolve_action(&filters, event_code),
-        FilterAction::Forward | FilterAction::Relay
-    )
+pub fn evaluate<'a>(
+    filters: &'a [FilterRule],
+    default_action: FilterAction,
+    event_code: &str,
+    originator: &str,
+    fips: &[String],
+    duration: Option<Duration>,
+) -> FilterEvaluation<'a> {
+    let matched = match_filter(filters, event_code, originator, fips, duration);
+    let action = matched.map_or(default_action, |rule| rule.action);
+    FilterEvaluation {
+        event_code: normalize_event_code(event_code),
+        originator: originator.trim().to_ascii_uppercase(),
+        fips: fips.to_vec(),
+        duration_minutes: duration.map(|duration| duration.as_secs() / 60),
+        matched_rule: matched
+            .map_or_else(|| "Default Filter".to_string(), |rule| rule.name.clone()),
+        action,
+        behavior: action.into(),
+        rules: filters
+            .iter()
+            .map(|rule| RuleEvaluation {
+                rule,
+                matched: matched.is_some_and(|matched| std::ptr::eq(matched, rule)),
+            })
+            .collect(),
+    }
 }
 
 pub fn should_log_action(action: FilterAction) -> bool {
@@ -179,25 +871,13 @@ pub fn should_forward_action(action: FilterAction) -> bool {
     matches!(action, FilterAction::Forward | FilterAction::Relay)
 }
 
-fn resolve_action(filters: &[FilterRule], event_code: &str) -> FilterAction {
-    match_filter(filters, event_code)
-        .map(|rule| rule.action)
-        .unwrap_or(FilterAction::Relay)
-}
-
-fn parse_action(action: &str, filter_name: &str) -> FilterAction {
+pub fn parse_action(action: &str) -> Option<FilterAction> {
     match action.trim().to_ascii_lowercase().as_str() {
-        "ignore" => FilterAction::Ignore,
-        "relay" => FilterAction::Relay,
-        "log" => FilterAction::Log,
-        "forward" => FilterAction::Forward,
-        other => {
-            error!(
-                "Filter '{}' has unsupported action '{}'; defaulting to relay",
-                filter_name, other
-            );
-            FilterAction::Relay
-        }
+        "ignore" => Some(FilterAction::Ignore),
+        "relay" | "relay_and_forward" => Some(FilterAction::Relay),
+        "log" => Some(FilterAction::Log),
+        "forward" => Some(FilterAction::Forward),
+        _ => None,
     }
 }
 
@@ -212,6 +892,22 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    fn evaluate_action(
+        rules: &[FilterRule],
+        default_action: FilterAction,
+        event_code: &str,
+        originator: &str,
+        fips: &[String],
+        duration: Option<Duration>,
+    ) -> FilterAction {
+        let handle = FilterHandle::new(Filters {
+            global: rules.to_vec(),
+            default_action,
+            ..Filters::default()
+        });
+        handle.evaluate_action("", event_code, originator, fips, duration)
+    }
+
     #[test]
     fn parse_filters_returns_empty_when_disabled() {
         let cfg = json!({
@@ -245,10 +941,16 @@ mod tests {
             ]
         });
         let filters = parse_filters(&cfg);
-        let matched = match_filter(&filters, "TOR").expect("match");
+        let matched = match_filter(&filters, "TOR", "WXR", &[], None).expect("match");
         assert_eq!(matched.name, "Tornado");
-        assert_eq!(evaluate_action(&filters, "TOR"), FilterAction::Ignore);
-        assert_eq!(evaluate_action(&filters, "SVR"), FilterAction::Relay);
+        assert_eq!(
+            evaluate_action(&filters, FilterAction::Relay, "TOR", "WXR", &[], None),
+            FilterAction::Ignore
+        );
+        assert_eq!(
+            evaluate_action(&filters, FilterAction::Relay, "SVR", "WXR", &[], None),
+            FilterAction::Relay
+        );
     }
 
     #[test]
@@ -268,7 +970,7 @@ mod tests {
     }
 
     #[test]
-    fn global_filters_drive_helper_functions() {
+    fn global_filters_decide_names_and_behavior() {
         let cfg = json!({
             "FILTERS": [
                 {
@@ -283,20 +985,520 @@ mod tests {
                 }
             ]
         });
-        let filters = parse_filters(&cfg);
-        install_filters(filters.clone());
+        let handle = FilterHandle::new(Filters {
+            global: parse_filters(&cfg),
+            ..Filters::default()
+        });
+        let behavior = |event_code| {
+            FilterBehavior::from(handle.evaluate_action("", event_code, "WXR", &[], None))
+        };
 
-        assert_eq!(determine_filter_name("RWT"), "RWT ignore");
-        assert!(!should_relay_alert("RWT"));
-        assert!(!should_log_alert("RWT"));
-        assert!(!should_forward_alert("RWT"));
+        assert_eq!(
+            handle.snapshot().rule_name("", "RWT", "WXR", &[], None),
+            "RWT ignore"
+        );
+        assert_eq!(
+            behavior("RWT"),
+            FilterBehavior {
+                log: false,
+                forward: false,
+                relay: false
+            }
+        );
 
-        assert_eq!(determine_filter_name("TOR"), "Fallback");
-        assert!(!should_relay_alert("TOR"));
-        assert!(should_log_alert("TOR"));
-        assert!(should_forward_alert("TOR"));
+        assert_eq!(
+            handle.snapshot().rule_name("", "TOR", "WXR", &[], None),
+            "Fallback"
+        );
+        assert_eq!(
+            behavior("TOR"),
+            FilterBehavior {
+                log: true,
+                forward: true,
+                relay: false
+            }
+        );
 
         assert!(should_log_action(FilterAction::Relay));
         assert!(should_forward_action(FilterAction::Forward));
     }
+
+    #[test]
+    fn filter_names_come_from_the_stream_rules_when_it_has_them() {
+        let (sets, problems) = parse_filter_sets(&json!({
+            "FILTER_SETS": {
+                "nwr_rules": [{ "name": "NWR tests", "event_codes": ["RWT"], "action": "log" }],
+                "bad": "relay"
+            }
+        }));
+        assert_eq!(problems.len(), 1, "{problems:?}");
+        assert!(problems[0].contains("'bad'"));
+        let filters = Filters {
+            global: parse_filters(&json!({
+                "FILTERS": [{ "name": "Everything", "event_codes": ["*"], "action": "relay" }]
+            })),
+            streams: HashMap::from([(
+                "http://example.local/nwr.mp3".to_string(),
+                sets["nwr_rules"].clone(),
+            )]),
+            ..Filters::default()
+        };
+
+        assert_eq!(
+            filters.rule_name("http://example.local/nwr.mp3", "RWT", "WXR", &[], None),
+            "NWR tests"
+        );
+        assert_eq!(
+            filters.rule_name("http://example.local/nwr.mp3", "TOR", "WXR", &[], None),
+            "Default Filter"
+        );
+        assert_eq!(
+            filters.rule_name("http://example.local/other.mp3", "TOR", "WXR", &[], None),
+            "Everything"
+        );
+    }
+
+    #[test]
+    fn evaluate_reports_the_winning_rule_and_behavior() {
+        let cfg = json!({
+            "FILTERS": [
+                { "name": "Fallback", "event_codes": ["*"], "action": "log" },
+                { "name": "Tornado", "event_codes": ["tor", "SVR"], "action": "forward" }
+            ]
+        });
+        let filters = parse_filters(&cfg);
+
+        let evaluation = evaluate(&filters, FilterAction::Relay, "tor", "WXR", &[], None);
+        assert_eq!(evaluation.event_code, "TOR");
+        assert_eq!(evaluation.matched_rule, "Tornado");
+        assert_eq!(
+            evaluation.behavior,
+            FilterBehavior {
+                log: true,
+                forward: true,
+                relay: false
+            }
+        );
+        let value = serde_json::to_value(&evaluation).expect("json");
+        assert_eq!(value["action"], "forward");
+        assert_eq!(value["rules"][0]["event_codes"], json!(["*"]));
+        assert_eq!(value["rules"][0]["matched"], false);
+        assert_eq!(value["rules"][1]["matched"], true);
+
+        let unmatched = evaluate(&[], FilterAction::Relay, "RWT", "WXR", &[], None);
+        assert_eq!(unmatched.matched_rule, "Default Filter");
+        assert_eq!(unmatched.action, FilterAction::Relay);
+    }
+
+    #[test]
+    fn fips_limited_rules_need_an_overlapping_county() {
+        let cfg = json!({
+            "FILTERS": [
+                {
+                    "name": "Home SVR",
+                    "event_codes": ["SVR"],
+                    "fips": ["031055", "031153"],
+                    "action": "relay"
+                },
+                { "name": "Neighbor SVR", "event_codes": ["SVR"], "action": "log" },
+                { "name": "Typo", "event_codes": ["TOR"], "fips": ["31055"], "action": "relay" },
+                { "name": "Not a list", "event_codes": ["TOR"], "fips": "031055", "action": "relay" }
+            ]
+        });
+        let (filters, problems) = parse_filters_with_problems(&cfg);
+        assert_eq!(filters.len(), 2);
+        assert_eq!(problems.len(), 3, "{problems:?}");
+        let fips = |codes: &[&str]| {
+            codes
+                .iter()
+                .map(|code| code.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let partial = fips(&["031153", "019085"]);
+        assert_eq!(
+            evaluate_action(&filters, FilterAction::Relay, "SVR", "WXR", &partial, None),
+            FilterAction::Relay
+        );
+        let neighbors = fips(&["019085", "019129"]);
+        assert_eq!(
+            evaluate_action(
+                &filters,
+                FilterAction::Relay,
+                "SVR",
+                "WXR",
+                &neighbors,
+                None
+            ),
+            FilterAction::Log
+        );
+        let everywhere = fips(&["000000"]);
+        assert_eq!(
+            evaluate_action(
+                &filters,
+                FilterAction::Relay,
+                "SVR",
+                "WXR",
+                &everywhere,
+                None
+            ),
+            FilterAction::Relay
+        );
+        assert_eq!(
+            evaluate_action(&filters, FilterAction::Relay, "SVR", "WXR", &[], None),
+            FilterAction::Log
+        );
+
+        let evaluation = evaluate(
+            &filters,
+            FilterAction::Relay,
+            "SVR",
+            "WXR",
+            &neighbors,
+            None,
+        );
+        assert_eq!(evaluation.matched_rule, "Neighbor SVR");
+        let value = serde_json::to_value(&evaluation).expect("json");
+        assert_eq!(value["rules"][0]["fips"], json!(["031055", "031153"]));
+        assert!(value["rules"][1].get("fips").is_none());
+    }
+
+    #[test]
+    fn originator_rules_match_with_or_without_event_codes() {
+        let cfg = json!({
+            "FILTERS": [
+                { "name": "Unverified civil", "originators": ["civ"], "action": "log" },
+                {
+                    "name": "Weather service",
+                    "event_codes": ["*"],
+                    "originators": ["WXR"],
+                    "action": "relay"
+                },
+                { "name": "Typo", "originators": ["CIVIL"], "action": "ignore" }
+            ]
+        });
+        let (filters, problems) = parse_filters_with_problems(&cfg);
+        assert_eq!(filters.len(), 2);
+        assert_eq!(problems.len(), 2, "{problems:?}");
+
+        assert_eq!(
+            evaluate_action(&filters, FilterAction::Relay, "TOR", "CIV", &[], None),
+            FilterAction::Log
+        );
+        assert_eq!(
+            evaluate_action(&filters, FilterAction::Relay, "TOR", "WXR", &[], None),
+            FilterAction::Relay
+        );
+        let evaluation = evaluate(&filters, FilterAction::Relay, "??W", "WXR", &[], None);
+        assert_eq!(evaluation.matched_rule, "Weather service");
+        assert_eq!(
+            evaluate(&filters, FilterAction::Relay, "TOR", "PEP", &[], None).matched_rule,
+            "Default Filter"
+        );
+        let value = serde_json::to_value(&evaluation).expect("json");
+        assert_eq!(value["rules"][0]["originators"], json!(["CIV"]));
+        assert_eq!(value["rules"][0]["event_codes"], json!(["*"]));
+    }
+
+    #[test]
+    fn priorities_order_rules_and_shadowed_rules_are_reported() {
+        let cfg = json!({
+            "FILTERS": [
+                { "name": "Everything", "event_codes": ["*"], "action": "relay" },
+                { "name": "Civil", "originators": ["CIV"], "action": "log" },
+                { "name": "Tests", "event_codes": ["RWT", "RMT"], "action": "log" },
+                { "name": "Weekly tests", "event_codes": ["RWT"], "action": "ignore" }
+            ]
+        });
+        let (filters, problems) = parse_filters_with_problems(&cfg);
+        assert_eq!(problems.len(), 2, "{problems:?}");
+        assert!(problems[0].contains("'Civil' never matches because 'Everything'"));
+        assert!(problems[1].contains("'Weekly tests' never matches because 'Tests'"));
+        assert_eq!(
+            evaluate_action(&filters, FilterAction::Relay, "TOR", "CIV", &[], None),
+            FilterAction::Relay
+        );
+
+        let mut cfg = cfg;
+        cfg["FILTERS"][1]["priority"] = json!(10);
+        cfg["FILTERS"][3]["priority"] = json!(5);
+        let (filters, problems) = parse_filters_with_problems(&cfg);
+        assert!(problems.is_empty(), "{problems:?}");
+        let names = filters
+            .iter()
+            .map(|rule| rule.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Civil", "Weekly tests", "Everything", "Tests"]);
+        assert_eq!(
+            evaluate_action(&filters, FilterAction::Relay, "TOR", "CIV", &[], None),
+            FilterAction::Log
+        );
+        assert_eq!(
+            evaluate_action(&filters, FilterAction::Relay, "RWT", "WXR", &[], None),
+            FilterAction::Ignore
+        );
+        let value = serde_json::to_value(&filters).expect("json");
+        assert_eq!(value[0]["priority"], 10);
+        assert!(value[2].get("priority").is_none());
+    }
+
+    #[test]
+    fn every_action_has_a_fixed_behavior() {
+        let table = [
+            ("ignore", FilterAction::Ignore, false, false, false),
+            ("log", FilterAction::Log, true, false, false),
+            ("forward", FilterAction::Forward, true, true, false),
+            ("relay", FilterAction::Relay, true, true, true),
+            ("relay_and_forward", FilterAction::Relay, true, true, true),
+        ];
+        for (name, action, log, forward, relay) in table {
+            assert_eq!(parse_action(name), Some(action), "{name}");
+            assert_eq!(
+                FilterBehavior::from(action),
+                FilterBehavior {
+                    log,
+                    forward,
+                    relay
+                }
+            );
+            assert_eq!(should_log_action(action), log, "{name}");
+            assert_eq!(should_forward_action(action), forward, "{name}");
+        }
+        assert_eq!(
+            parse_action(" Relay_And_Forward "),
+            Some(FilterAction::Relay)
+        );
+        assert_eq!(parse_action("drop"), None);
+    }
+
+    #[test]
+    fn event_code_patterns_parse_and_round_trip() {
+        for (pattern, expected) in [
+            ("tor", "TOR"),
+            (" *w ", "*W"),
+            ("to*", "TO*"),
+            ("*", "*"),
+            ("!rwt", "!RWT"),
+            ("! *A", "!*A"),
+            ("!SV*", "!SV*"),
+        ] {
+            let matcher = EventCodeMatcher::parse(pattern).expect(pattern);
+            assert_eq!(matcher.to_string(), expected);
+            assert_eq!(EventCodeMatcher::parse(expected), Ok(matcher));
+        }
+        for pattern in ["!*", "!!RWT", "T*R", "**", "*W*", "!", "*!W"] {
+            assert!(EventCodeMatcher::parse(pattern).is_err(), "{pattern}");
+        }
+    }
+
+    #[test]
+    fn event_code_patterns_match_by_prefix_suffix_and_exclusion() {
+        let matcher = |pattern| EventCodeMatcher::parse(pattern).expect("pattern");
+        assert!(matcher("*W").matches("TOW"));
+        assert!(matcher("*W").matches("??W"));
+        assert!(!matcher("*W").matches("TOA"));
+        assert!(matcher("TO*").matches("TOR"));
+        assert!(matcher("TO*").matches("TOA"));
+        assert!(!matcher("TO*").matches("SVR"));
+        assert!(matcher("!RWT").matches("RMT"));
+        assert!(!matcher("!RWT").matches("RWT"));
+        assert!(!matcher("!*T").matches("RMT"));
+        assert_eq!(matcher("RWT").rank(), 0);
+        assert_eq!(matcher("*W").rank(), 1);
+        assert_eq!(matcher("*").rank(), 2);
+    }
+
+    #[test]
+    fn rules_need_a_matching_entry_and_no_matching_exclusion() {
+        let cfg = json!({
+            "FILTERS": [
+                { "name": "Warnings", "event_codes": ["*W"], "action": "relay" },
+                { "name": "Not tests", "event_codes": ["!RWT", "!RMT"], "action": "forward" },
+                {
+                    "name": "Watches but not TOA",
+                    "event_codes": ["*A", "!TOA"],
+                    "action": "log"
+                },
+                { "name": "Tornado warning", "event_codes": ["TOR"], "action": "ignore" }
+            ]
+        });
+        let (filters, problems) = parse_filters_with_problems(&cfg);
+        assert!(problems.is_empty(), "{problems:?}");
+        let name = |code: &str| {
+            match_filter(&filters, code, "WXR", &[], None).map(|rule| rule.name.clone())
+        };
+        assert_eq!(name("TOR").as_deref(), Some("Tornado warning"));
+        assert_eq!(name("SVW").as_deref(), Some("Warnings"));
+        assert_eq!(name("SVA").as_deref(), Some("Watches but not TOA"));
+        assert_eq!(name("TOA").as_deref(), Some("Not tests"));
+        assert_eq!(name("CEM").as_deref(), Some("Not tests"));
+        assert_eq!(name("RWT"), None);
+        assert_eq!(name("rmt"), None);
+
+        let value = serde_json::to_value(&filters).expect("json");
+        assert_eq!(value[1]["event_codes"], json!(["!RWT", "!RMT"]));
+        assert_eq!(value[2]["event_codes"], json!(["*A", "!TOA"]));
+    }
+
+    #[test]
+    fn confusing_exclusions_are_reported() {
+        let cfg = json!({
+            "FILTERS": [
+                { "name": "Star", "event_codes": ["*", "!RWT"], "action": "relay" },
+                { "name": "Both", "event_codes": ["RWT", "RMT", "!RWT"], "action": "log" },
+                { "name": "Missed", "event_codes": ["*W", "!RMT"], "action": "log" },
+                { "name": "Broken", "event_codes": ["!*", "T*R"], "action": "log" }
+            ]
+        });
+        let (filters, problems) = parse_filters_with_problems(&cfg);
+        assert_eq!(filters.len(), 3);
+        let problems = problems.join("\n");
+        for expected in [
+            "Filter 'Star' mixes * with exclusions",
+            "Filter 'Both' lists RWT but also excludes it with !RWT",
+            "Filter 'Missed' excludes RMT although none of its event codes match it",
+            "Filter 'Broken' has an unusable event code pattern: !* excludes every event code",
+            "Filter 'Broken' has an unusable event code pattern: T*R is not an event code",
+            "Filter 'Broken' has no valid event codes; skipping",
+        ] {
+            assert!(
+                problems.contains(expected),
+                "{expected} missing from {problems}"
+            );
+        }
+    }
+
+    #[test]
+    fn pattern_rules_are_shadowed_only_by_the_same_pattern() {
+        let cfg = json!({
+            "FILTERS": [
+                { "name": "Warnings", "event_codes": ["*W"], "action": "relay" },
+                { "name": "Everything", "event_codes": ["*"], "action": "log" },
+                { "name": "Warnings again", "event_codes": ["*W"], "action": "log" },
+                { "name": "Tornado", "event_codes": ["TOR"], "action": "log" },
+                { "name": "Everything else", "event_codes": ["!RWT"], "action": "log" }
+            ]
+        });
+        let (_, problems) = parse_filters_with_problems(&cfg);
+        assert_eq!(problems.len(), 2, "{problems:?}");
+        assert!(problems[0].contains("'Warnings again' never matches because 'Warnings'"));
+        assert!(problems[1].contains("'Everything else' never matches because 'Everything'"));
+    }
+
+    #[test]
+    fn evaluating_an_alert_counts_a_hit_for_the_deciding_rule() {
+        let cfg = json!({
+            "FILTERS": [
+                { "name": "Hit counter tornado", "event_codes": ["TOR"], "action": "relay" },
+                { "name": "Hit counter tests", "event_codes": ["RWT"], "action": "log" }
+            ]
+        });
+        let filters = parse_filters(&cfg);
+        let handle = FilterHandle::new(Filters {
+            global: filters.clone(),
+            default_action: FilterAction::Ignore,
+            ..Filters::default()
+        });
+
+        assert!(match_filter(&filters, "TOR", "WXR", &[], None).is_some());
+        evaluate(&filters, FilterAction::Relay, "TOR", "WXR", &[], None);
+        assert_eq!(
+            handle.rules_with_hits(&filters)[0].hits,
+            RuleHits::default()
+        );
+
+        for _ in 0..2 {
+            handle.evaluate_action("", "TOR", "WXR", &[], None);
+        }
+        assert_eq!(
+            handle.evaluate_action("", "SVR", "WXR", &[], None),
+            FilterAction::Ignore
+        );
+        let counted = handle.rules_with_hits(&filters);
+        assert_eq!(counted[0].hits.hits, 2);
+        assert!(counted[0].hits.last_hit.is_some());
+        assert_eq!(counted[1].hits.hits, 0);
+        assert_eq!(handle.unmatched_hits().hits, 1);
+
+        let rendered = serde_json::to_value(&counted[0]).expect("serialize");
+        assert_eq!(rendered["name"], "Hit counter tornado");
+        assert_eq!(rendered["hits"], 2);
+
+        handle.replace(Filters {
+            global: filters[1..].to_vec(),
+            ..Filters::default()
+        });
+        assert_eq!(handle.rules_with_hits(&filters)[0].hits.hits, 0);
+    }
+
+    #[test]
+    fn evaluations_during_a_reload_see_one_whole_set_of_filters() {
+        let set = |action: &str, default_action| Filters {
+            global: parse_filters(&json!({
+                "FILTERS": [{ "name": "Tornado", "event_codes": ["TOR"], "action": action }]
+            })),
+            default_action,
+            ..Filters::default()
+        };
+        let handle = FilterHandle::new(set("relay", FilterAction::Relay));
+
+        std::thread::scope(|scope| {
+            let reloader = handle.clone();
+            scope.spawn(move || {
+                for round in 0..500 {
+                    reloader.replace(if round % 2 == 0 {
+                        set("ignore", FilterAction::Ignore)
+                    } else {
+                        set("relay", FilterAction::Relay)
+                    });
+                }
+            });
+            for _ in 0..4 {
+                let evaluator = handle.clone();
+                scope.spawn(move || {
+                    for _ in 0..500 {
+                        let filters = evaluator.snapshot();
+                        assert_eq!(filters.global[0].action, filters.default_action);
+                        evaluator.evaluate_action("", "TOR", "WXR", &[], None);
+                    }
+                });
+            }
+        });
+        assert_eq!(
+            handle.rules_with_hits(&handle.snapshot().global)[0]
+                .hits
+                .hits,
+            2000
+        );
+    }
+
+    #[test]
+    fn alerts_outside_a_rules_duration_bounds_fall_through() {
+        let cfg = json!({
+            "FILTERS": [
+                { "name": "Junk", "event_codes": ["*"], "max_duration_minutes": 0, "action": "ignore" },
+                { "name": "Review long", "event_codes": ["*"], "min_duration_minutes": 361, "action": "log" },
+                { "name": "Relay", "event_codes": ["*"], "action": "relay" },
+                { "name": "Backwards", "event_codes": ["TOR"], "min_duration_minutes": 60, "max_duration_minutes": 30 },
+                { "name": "Fractional", "event_codes": ["TOR"], "max_duration_minutes": 1.5, "action": "log" }
+            ]
+        });
+        let (filters, problems) = parse_filters_with_problems(&cfg);
+        assert_eq!(problems.len(), 2, "{problems:?}");
+        assert!(problems[0].contains("'Backwards' because min_duration_minutes 60"));
+        assert!(problems[1].contains("max_duration_minutes 1.5"));
+
+        let name = |duration: Option<Duration>| {
+            match_filter(&filters, "SVR", "WXR", &[], duration).map(|rule| rule.name.clone())
+        };
+        let minutes = |minutes: u64| Some(Duration::from_secs(minutes * 60));
+        assert_eq!(name(minutes(0)).as_deref(), Some("Junk"));
+        assert_eq!(name(minutes(360)).as_deref(), Some("Relay"));
+        assert_eq!(name(minutes(361)).as_deref(), Some("Review long"));
+        assert_eq!(name(None).as_deref(), Some("Relay"));
+
+        let evaluation = evaluate(&filters, FilterAction::Relay, "TOR", "WXR", &[], minutes(0));
+        assert_eq!(evaluation.matched_rule, "Fractional");
+        assert_eq!(evaluation.duration_minutes, Some(0));
+    }
 }
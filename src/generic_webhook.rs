@@ -0,0 +1,416 @@
+use anyhow::{anyhow, Context, Result};
+use hmac::{Hmac, Mac};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use reqwest::Client;
+use serde_json::Value;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{info, warn};
+
+const GENERIC_WEBHOOK_TIMEOUT_SECS: u64 = 15;
+const RESPONSE_LOG_LIMIT: usize = 500;
+const SIGNATURE_HEADER: &str = "X-EAS-Signature";
+const TIMESTAMP_HEADER: &str = "X-EAS-Timestamp";
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const TEMPLATE_VARIABLES: &[&str] = &[
+    "event_code",
+    "event_text",
+    "originator",
+    "originator_code",
+    "fips",
+    "locations",
+    "eas_text",
+    "raw_header",
+    "received_at",
+    "expires_at",
+    "stream_name",
+    "monitor",
+    "recording_deeplink",
+    "matched_filter",
+    "station_name",
+];
+
+const DEFAULT_BODY_TEMPLATE: &str = r#"{
+    "event_code": "{event_code}",
+    "event_text": "{event_text}",
+    "originator": "{originator}",
+    "originator_code": "{originator_code}",
+    "fips": "{fips}",
+    "locations": "{locations}",
+    "eas_text": "{eas_text}",
+    "raw_header": "{raw_header}",
+    "received_at": "{received_at}",
+    "expires_at": "{expires_at}",
+    "stream_name": "{stream_name}",
+    "monitor": "{monitor}",
+    "recording_deeplink": "{recording_deeplink}",
+    "matched_filter": "{matched_filter}",
+    "station_name": "{station_name}"
+}"#;
+
+#[derive(Debug, Clone, PartialEq)]
+enum TemplateSegment {
+    Literal(String),
+    Variable(&'static str),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BodyTemplate {
+    segments: Vec<TemplateSegment>,
+}
+
+impl BodyTemplate {
+    pub fn parse(template: &str) -> Result<Self> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut rest = template;
+
+        while let Some(start) = rest.find('{') {
+            literal.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+            let name_len = after
+                .find(|c: char| !(c.is_ascii_lowercase() || c == '_'))
+                .unwrap_or(after.len());
+
+            if name_len > 0 && after[name_len..].starts_with('}') {
+                let name = &after[..name_len];
+                let Some(variable) = TEMPLATE_VARIABLES.iter().find(|known| **known == name) else {
+                    return Err(anyhow!(
+                        "unknown placeholder '{{{name}}}' (available: {})",
+                        TEMPLATE_VARIABLES.join(", ")
+                    ));
+                };
+                if !literal.is_empty() {
+                    segments.push(TemplateSegment::Literal(std::mem::take(&mut literal)));
+                }
+                segments.push(TemplateSegment::Variable(variable));
+                rest = &after[name_len + 1..];
+            } else {
+                literal.push('{');
+                rest = after;
+            }
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            segments.push(TemplateSegment::Literal(literal));
+        }
+
+        Ok(Self { segments })
+    }
+
+    pub fn render(&self, values: &HashMap<&'static str, String>) -> String {
+        let mut rendered = String::new();
+        for segment in &self.segments {
+            match segment {
+                TemplateSegment::Literal(text) => rendered.push_str(text),
+                TemplateSegment::Variable(name) => {
+                    let value = values.get(name).map(String::as_str).unwrap_or_default();
+                    let quoted = serde_json::to_string(value).unwrap_or_default();
+                    rendered.push_str(&quoted[1..quoted.len().saturating_sub(1)]);
+                }
+            }
+        }
+        rendered
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GenericWebhook {
+    pub name: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body_template: BodyTemplate,
+    pub signing_secret: Option<SigningSecret>,
+}
+
+#[derive(Clone)]
+pub struct SigningSecret(String);
+
+impl std::fmt::Debug for SigningSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SigningSecret(<redacted>)")
+    }
+}
+
+impl SigningSecret {
+    /// HMAC-SHA256 over `<X-EAS-Timestamp as decimal>.<raw body bytes>`, as lowercase hex.
+    pub fn sign(&self, timestamp: i64, body: &[u8]) -> String {
+        let mut mac =
+            HmacSha256::new_from_slice(self.0.as_bytes()).expect("HMAC accepts keys of any length");
+        mac.update(timestamp.to_string().as_bytes());
+        mac.update(b".");
+        mac.update(body);
+        mac.finalize()
+            .into_bytes()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+}
+
+fn sample_template_values() -> HashMap<&'static str, String> {
+    TEMPLATE_VARIABLES
+        .iter()
+        .map(|name| (*name, format!("sample \"{name}\" value")))
+        .collect()
+}
+
+fn parse_generic_webhook(index: usize, entry: &Value) -> Result<GenericWebhook> {
+    let key = format!("GENERIC_WEBHOOKS[{index}]");
+    let url = entry
+        .get("url")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .ok_or_else(|| anyhow!("{key}.url must be a non-empty string in your config.json file"))?;
+    reqwest::Url::parse(url).with_context(|| format!("{key}.url is not a valid URL"))?;
+
+    let name = entry
+        .get("name")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("webhook #{}", index + 1));
+
+    let mut headers = Vec::new();
+    if let Some(header_value) = entry.get("headers") {
+        let Some(header_map) = header_value.as_object() else {
+            return Err(anyhow!(
+                "{key}.headers must be an object of header names to values in your config.json file"
+            ));
+        };
+        for (header_name, value) in header_map {
+            let Some(value) = value.as_str() else {
+                return Err(anyhow!("{key}.headers.{header_name} must be a string"));
+            };
+            HeaderName::from_bytes(header_name.as_bytes())
+                .with_context(|| format!("{key}.headers has an invalid header name"))?;
+            HeaderValue::from_str(value)
+                .with_context(|| format!("{key}.headers.{header_name} has an invalid value"))?;
+            headers.push((header_name.clone(), value.to_string()));
+        }
+    }
+
+    let template_source = match entry.get("body_template") {
+        None | Some(Value::Null) => DEFAULT_BODY_TEMPLATE.to_string(),
+        Some(Value::String(template)) => template.clone(),
+        Some(template @ (Value::Object(_) | Value::Array(_))) => template.to_string(),
+        Some(_) => {
+            return Err(anyhow!(
+                "{key}.body_template must be a string or a JSON object in your config.json file"
+            ))
+        }
+    };
+    let signing_secret = match entry.get("signing_secret") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(secret)) if secret.is_empty() => None,
+        Some(Value::String(secret)) => Some(SigningSecret(secret.clone())),
+        Some(_) => {
+            return Err(anyhow!(
+                "{key}.signing_secret must be a string in your config.json file"
+            ))
+        }
+    };
+
+    let body_template = BodyTemplate::parse(&template_source)
+        .with_context(|| format!("{key}.body_template is invalid"))?;
+    serde_json::from_str::<Value>(&body_template.render(&sample_template_values()))
+        .with_context(|| format!("{key}.body_template does not render to valid JSON"))?;
+
+    Ok(GenericWebhook {
+        name,
+        url: url.to_string(),
+        headers,
+        body_template,
+        signing_secret,
+    })
+}
+
+pub fn parse_generic_webhooks(config_json: &Value) -> Result<Vec<GenericWebhook>> {
+    let Some(value) = config_json.get("GENERIC_WEBHOOKS") else {
+        return Ok(Vec::new());
+    };
+    let Some(entries) = value.as_array() else {
+        return Err(anyhow!(
+            "GENERIC_WEBHOOKS must be an array in your config.json file"
+        ));
+    };
+
+    entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| parse_generic_webhook(index, entry))
+        .collect()
+}
+
+async fn post_generic_webhook(
+    client: &Client,
+    webhook: &GenericWebhook,
+    values: &HashMap<&'static str, String>,
+) {
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    for (name, value) in &webhook.headers {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            headers.insert(name, value);
+        }
+    }
+
+    let body = webhook.body_template.render(values);
+    if let Some(secret) = &webhook.signing_secret {
+        let timestamp = chrono::Utc::now().timestamp();
+        let signature = format!("sha256={}", secret.sign(timestamp, body.as_bytes()));
+        if let (Ok(timestamp), Ok(signature)) = (
+            HeaderValue::from_str(&timestamp.to_string()),
+            HeaderValue::from_str(&signature),
+        ) {
+            headers.insert(TIMESTAMP_HEADER, timestamp);
+            headers.insert(SIGNATURE_HEADER, signature);
+        }
+    }
+    match client
+        .post(&webhook.url)
+        .headers(headers)
+        .body(body)
+        .send()
+        .await
+    {
+        Ok(response) if response.status().is_success() => {
+            info!(
+                "Delivered alert to generic webhook '{}' (HTTP {})",
+                webhook.name,
+                response.status()
+            );
+        }
+        Ok(response) => {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            let clipped: String = body.trim().chars().take(RESPONSE_LOG_LIMIT).collect();
+            warn!(
+                "Generic webhook '{}' rejected alert (HTTP {}): {}",
+                webhook.name, status, clipped
+            );
+        }
+        Err(err) => {
+            warn!(
+                "Failed to send alert to generic webhook '{}': {}",
+                webhook.name, err
+            );
+        }
+    }
+}
+
+pub async fn send_generic_webhooks(
+    webhooks: &[GenericWebhook],
+    values: &HashMap<&'static str, String>,
+) {
+    if webhooks.is_empty() {
+        return;
+    }
+
+    let client = match Client::builder()
+        .timeout(Duration::from_secs(GENERIC_WEBHOOK_TIMEOUT_SECS))
+        .build()
+    {
+        Ok(client) => client,
+        Err(err) => {
+            warn!("Failed to build HTTP client for generic webhooks: {}", err);
+            return;
+        }
+    };
+
+    for webhook in webhooks {
+        post_generic_webhook(&client, webhook, values).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn template_substitutes_escaped_values_and_keeps_json_braces() {
+        let template =
+            BodyTemplate::parse(r#"{"code": "{event_code}", "text": "{eas_text}", "empty": {}}"#)
+                .expect("template");
+        let values = HashMap::from([
+            ("event_code", "TOR".to_string()),
+            ("eas_text", "Line \"one\"\nLine two".to_string()),
+        ]);
+        let rendered: Value = serde_json::from_str(&template.render(&values)).expect("json");
+        assert_eq!(
+            rendered,
+            json!({ "code": "TOR", "text": "Line \"one\"\nLine two", "empty": {} })
+        );
+    }
+
+    #[test]
+    fn parse_generic_webhooks_validates_templates_at_load() {
+        let parsed = parse_generic_webhooks(&json!({
+            "GENERIC_WEBHOOKS": [
+                {
+                    "name": "Incidents",
+                    "url": "https://incidents.example/api/alerts",
+                    "headers": { "Authorization": "Bearer token" },
+                    "body_template": { "summary": "{event_text} for {locations}" }
+                },
+                { "url": "https://other.example/hook" }
+            ]
+        }))
+        .expect("webhooks");
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].name, "Incidents");
+        assert_eq!(parsed[0].headers.len(), 1);
+        assert_eq!(parsed[1].name, "webhook #2");
+
+        let unknown = parse_generic_webhooks(&json!({
+            "GENERIC_WEBHOOKS": [
+                { "url": "https://incidents.example", "body_template": "{\"a\": \"{event_cod}\"}" }
+            ]
+        }))
+        .expect_err("unknown placeholder");
+        assert!(format!("{unknown:#}").contains("unknown placeholder '{event_cod}'"));
+
+        let invalid_json = parse_generic_webhooks(&json!({
+            "GENERIC_WEBHOOKS": [
+                { "url": "https://incidents.example", "body_template": "{\"a\": {event_code}}" }
+            ]
+        }))
+        .expect_err("invalid JSON");
+        assert!(invalid_json
+            .to_string()
+            .contains("GENERIC_WEBHOOKS[0].body_template does not render to valid JSON"));
+
+        assert!(parse_generic_webhooks(&json!({
+            "GENERIC_WEBHOOKS": [{ "url": "not a url" }]
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn signing_secret_signs_timestamp_and_body_and_stays_out_of_debug() {
+        let parsed = parse_generic_webhooks(&json!({
+            "GENERIC_WEBHOOKS": [
+                { "url": "https://incidents.example", "signing_secret": "whsec_test" },
+                { "url": "https://other.example", "signing_secret": "" }
+            ]
+        }))
+        .expect("webhooks");
+        let secret = parsed[0].signing_secret.as_ref().expect("secret");
+        assert!(parsed[1].signing_secret.is_none());
+        assert!(!format!("{:?}", parsed[0]).contains("whsec_test"));
+
+        assert_eq!(
+            secret.sign(1_700_000_000, br#"{"event_code":"TOR"}"#),
+            "fea33d40481190a8334adb1e4c05c30875c585f95c57bb598a73f118e367bdd8"
+        );
+    }
+}
@@ -1,11 +1,19 @@
-use std::f64::consts::PI;
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use std::f64::consts::{PI, TAU};
+use std::time::Duration;
 
-const MARK_FREQ: f64 = 2083.3;
-const SPACE_FREQ: f64 = 1562.5;
 const MIN_SAMPLE_RATE: u32 = 8000;
 const BIT_DURATION_SEC: f64 = 0.00192;
-const PREAMBLE_BYTE: u8 = 0xD5;
-const BURST_COUNT: usize = 3;
+const MARK_FREQ: f64 = 4.0 / BIT_DURATION_SEC;
+const SPACE_FREQ: f64 = 3.0 / BIT_DURATION_SEC;
+const PREAMBLE_BYTE: u8 = 0xAB;
+pub const PREAMBLE_BYTES: usize = 16;
+pub const BURST_COUNT: usize = 3;
+pub const BURST_GAP: Duration = Duration::from_secs(1);
+const ATTENTION_FREQS: [f64; 2] = [853.0, 960.0];
+pub const ATTENTION_TONE_SECS: f64 = 8.0;
+pub const STATION_ID_LEN: usize = 8;
+const FALLBACK_STATION_ID: &str = "EASLSTNR";
 
 #[derive(Debug)]
 pub enum HeaderError {
@@ -37,37 +45,65 @@ impl std::error::Error for HeaderError {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SameTiming {
+    pub preamble_bytes: usize,
+    pub bursts: usize,
+    pub burst_gap: Duration,
+}
+
+impl Default for SameTiming {
+    fn default() -> Self {
+        Self {
+            preamble_bytes: PREAMBLE_BYTES,
+            bursts: BURST_COUNT,
+            burst_gap: BURST_GAP,
+        }
+    }
+}
+
 pub fn generate_same_header_samples(
     header: &str,
     sr: u32,
     amp: f64,
+) -> Result<Vec<i16>, HeaderError> {
+    generate_same_header_samples_with_timing(header, sr, amp, &SameTiming::default())
+}
+
+pub fn generate_same_header_samples_with_timing(
+    header: &str,
+    sr: u32,
+    amp: f64,
+    timing: &SameTiming,
 ) -> Result<Vec<i16>, HeaderError> {
     validate_header(header)?;
     validate_amplitude(amp)?;
+    if timing.bursts == 0 {
+        return Err(HeaderError::InvalidConfig(
+            "SAME audio needs at least one burst",
+        ));
+    }
 
     let sr = sr.max(MIN_SAMPLE_RATE);
 
-    let bits = build_same_bits(header);
+    let bits = build_same_bits(header, timing.preamble_bytes);
 
-    let mut samples_per_bit = (sr as f64 * BIT_DURATION_SEC).floor() as usize;
-    if samples_per_bit < 1 {
-        samples_per_bit = 1;
-    }
+    let samples_per_bit = sr as f64 * BIT_DURATION_SEC;
+    let bit_edge = |bit: usize| (bit as f64 * samples_per_bit).round() as usize;
+    let burst_len = bit_edge(bits.len());
 
-    let mark = make_tone_cycle(MARK_FREQ, sr, samples_per_bit, amp);
-    let space = make_tone_cycle(SPACE_FREQ, sr, samples_per_bit, amp);
+    let silence = vec![0i16; (sr as f64 * timing.burst_gap.as_secs_f64()).round() as usize];
+    let mut out: Vec<i16> = Vec::with_capacity((burst_len + silence.len()) * timing.bursts);
 
-    let silence = vec![0i16; sr as usize];
-    let mut out: Vec<i16> = Vec::with_capacity(
-        (bits.len() * samples_per_bit * BURST_COUNT) + (silence.len() * BURST_COUNT),
-    );
-
-    for _ in 0..BURST_COUNT {
-        for &bit in &bits {
-            if bit == 1 {
-                out.extend_from_slice(&mark);
-            } else {
-                out.extend_from_slice(&space);
+    let step = |freq: f64| TAU * freq / sr as f64;
+    for _ in 0..timing.bursts {
+        let mut phase = 0.0f64;
+        for (index, &bit) in bits.iter().enumerate() {
+            let len = bit_edge(index + 1) - bit_edge(index);
+            let step = step(if bit == 1 { MARK_FREQ } else { SPACE_FREQ });
+            for _ in 0..len {
+                out.push(to_sample(phase.sin() * amp));
+                phase = (phase + step) % TAU;
             }
         }
         out.extend_from_slice(&silence);
@@ -76,6 +112,175 @@ pub fn generate_same_header_samples(
     Ok(out)
 }
 
+fn to_sample(s: f64) -> i16 {
+    (s * i16::MAX as f64).clamp(i16::MIN as f64, i16::MAX as f64) as i16
+}
+
+pub fn is_station_id_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '/'
+}
+
+pub fn pad_station_id(id: &str) -> String {
+    format!("{:/<STATION_ID_LEN$}", id.to_ascii_uppercase())
+}
+
+pub fn relay_station_id(relay_name: &str) -> String {
+    let id: String = relay_name.trim().chars().take(STATION_ID_LEN).collect();
+    if id.is_empty() {
+        FALLBACK_STATION_ID.to_string()
+    } else {
+        pad_station_id(&id)
+    }
+}
+
+fn split_station_id(header: &str) -> Option<(&str, &str)> {
+    let body = header.trim().strip_prefix("ZCZC-")?.strip_suffix('-')?;
+    let (prefix, station) = body.rsplit_once('-')?;
+    let (_, issued) = prefix.rsplit_once('-')?;
+    (issued.len() == 7 && issued.bytes().all(|b| b.is_ascii_digit())).then_some((prefix, station))
+}
+
+pub fn originator(header: &str) -> Option<&str> {
+    let body = header.trim().strip_prefix("ZCZC-")?;
+    let (originator, _) = body.split_once('-')?;
+    (originator.len() == 3).then_some(originator)
+}
+
+pub fn purge_duration(header: &str) -> Option<Duration> {
+    let (_, trailer) = header.trim().strip_prefix("ZCZC-")?.split_once('+')?;
+    let purge = trailer.get(..4)?;
+    if !purge.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let hours = purge[..2].parse::<u64>().ok()?;
+    let minutes = purge[2..].parse::<u64>().ok()?;
+    Some(Duration::from_secs((hours * 60 + minutes) * 60))
+}
+
+pub fn station_id(header: &str) -> Option<&str> {
+    split_station_id(header).map(|(_, station)| station)
+}
+
+const ORIGINATORS: [&str; 4] = ["PEP", "CIV", "WXR", "EAS"];
+const MAX_LOCATIONS: usize = 31;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedHeader {
+    pub originator: String,
+    pub event_code: String,
+    pub locations: Vec<String>,
+    pub purge: String,
+    pub issued: String,
+    pub station: String,
+}
+
+impl ParsedHeader {
+    pub fn to_header(&self) -> String {
+        format!(
+            "ZCZC-{}-{}-{}+{}-{}-{}-",
+            self.originator,
+            self.event_code,
+            self.locations.join("-"),
+            self.purge,
+            self.issued,
+            self.station
+        )
+    }
+}
+
+fn all_digits(field: &str, len: usize) -> bool {
+    field.len() == len && field.bytes().all(|b| b.is_ascii_digit())
+}
+
+pub fn validate_same_header(header: &str) -> Result<ParsedHeader, HeaderError> {
+    let invalid = HeaderError::InvalidConfig;
+    let body = header
+        .trim()
+        .strip_prefix("ZCZC-")
+        .ok_or(invalid("Header must start with 'ZCZC-'"))?
+        .strip_suffix('-')
+        .ok_or(invalid("Header must end with '-'"))?;
+    let (locations, trailer) = body
+        .split_once('+')
+        .ok_or(invalid("Header has no '+' before its purge time"))?;
+
+    let mut fields = locations.split('-');
+    let originator = fields
+        .next()
+        .filter(|field| ORIGINATORS.contains(field))
+        .ok_or(invalid("Header originator must be PEP, CIV, WXR or EAS"))?;
+    let event_code = fields
+        .next()
+        .filter(|field| field.len() == 3 && field.bytes().all(|b| b.is_ascii_uppercase()))
+        .ok_or(invalid("Header event code must be three capital letters"))?;
+    let locations: Vec<String> = fields.map(str::to_string).collect();
+    if locations.is_empty() || locations.len() > MAX_LOCATIONS {
+        return Err(invalid("Header must have between 1 and 31 location codes"));
+    }
+    if !locations.iter().all(|code| all_digits(code, 6)) {
+        return Err(invalid("Header location codes must be six digits (PSSCCC)"));
+    }
+
+    let mut trailer = trailer.splitn(3, '-');
+    let purge = trailer
+        .next()
+        .filter(|field| all_digits(field, 4) && field[2..] < *"60")
+        .ok_or(invalid("Header purge time must be +HHMM"))?;
+    let issued = trailer
+        .next()
+        .filter(|field| {
+            all_digits(field, 7)
+                && ("001"..="366").contains(&&field[..3])
+                && field[3..5] < *"24"
+                && field[5..] < *"60"
+        })
+        .ok_or(invalid("Header issue time must be JJJHHMM"))?;
+    let station = trailer
+        .next()
+        .filter(|field| {
+            field.len() == STATION_ID_LEN
+                && field.bytes().all(|b| b.is_ascii_graphic() || b == b' ')
+                && !field.contains(['-', '+'])
+        })
+        .ok_or(invalid(
+            "Header station id must be exactly eight characters",
+        ))?;
+
+    Ok(ParsedHeader {
+        originator: originator.to_string(),
+        event_code: event_code.to_string(),
+        locations,
+        purge: purge.to_string(),
+        issued: issued.to_string(),
+        station: station.to_string(),
+    })
+}
+
+pub fn reissue_header(
+    header: &str,
+    issued: DateTime<Utc>,
+    station: Option<&str>,
+) -> Option<String> {
+    let mut parsed = validate_same_header(header).ok()?;
+    parsed.issued = format!(
+        "{:03}{:02}{:02}",
+        issued.ordinal(),
+        issued.hour(),
+        issued.minute()
+    );
+    if let Some(station) = station {
+        parsed.station = station.to_string();
+    }
+    Some(parsed.to_header())
+}
+
+pub fn with_station_id(header: &str, station: &str) -> String {
+    match split_station_id(header) {
+        Some((prefix, _)) => format!("ZCZC-{prefix}-{station}-"),
+        None => header.to_string(),
+    }
+}
+
 fn validate_header(header: &str) -> Result<(), HeaderError> {
     if header.chars().count() == 4 && header == "NNNN" {
         return Ok(());
@@ -106,14 +311,6 @@ fn validate_amplitude(amp: f64) -> Result<(), HeaderError> {
     Ok(())
 }
 
-fn byte_to_bits_msb_first(b: u8) -> [u8; 8] {
-    let mut bits = [0u8; 8];
-    for j in (0..8).rev() {
-        bits[7 - j] = ((b >> j) & 1) as u8;
-    }
-    bits
-}
-
 fn byte_to_bits_lsb_first(b: u8) -> [u8; 8] {
     let mut bits = [0u8; 8];
     for i in 0..8 {
@@ -122,10 +319,10 @@ fn byte_to_bits_lsb_first(b: u8) -> [u8; 8] {
     bits
 }
 
-fn build_same_bits(header: &str) -> Vec<u8> {
-    let mut bits = Vec::with_capacity((16 + header.len()) * 8);
-    for _ in 0..16 {
-        bits.extend_from_slice(&byte_to_bits_msb_first(PREAMBLE_BYTE));
+fn build_same_bits(header: &str, preamble_bytes: usize) -> Vec<u8> {
+    let mut bits = Vec::with_capacity((preamble_bytes + header.len()) * 8);
+    for _ in 0..preamble_bytes {
+        bits.extend_from_slice(&byte_to_bits_lsb_first(PREAMBLE_BYTE));
     }
     for &b in header.as_bytes() {
         bits.extend_from_slice(&byte_to_bits_lsb_first(b));
@@ -133,37 +330,51 @@ fn build_same_bits(header: &str) -> Vec<u8> {
     bits
 }
 
-fn make_tone_cycle(freq: f64, sr: u32, samples_per_bit: usize, amp: f64) -> Vec<i16> {
-    let sr_f = sr as f64;
-    (0..samples_per_bit)
-        .map(|i| {
-            let t = i as f64 / sr_f;
-            let s = (2.0 * PI * freq * t).sin() * amp;
-            let v = (s * i16::MAX as f64).clamp(i16::MIN as f64, i16::MAX as f64);
-            v as i16
-        })
-        .collect()
-}
-
-pub fn generate_attention_tone(sr: u32, amp: f64) -> Result<Vec<i16>, HeaderError> {
+pub fn generate_attention_tone_samples(
+    duration_sec: f64,
+    sr: u32,
+    amp: f64,
+) -> Result<Vec<i16>, HeaderError> {
     validate_amplitude(amp)?;
+    if !duration_sec.is_finite() || duration_sec <= 0.0 {
+        return Err(HeaderError::InvalidConfig(
+            "Attention tone duration must be a positive number of seconds",
+        ));
+    }
 
     let sr = sr.max(MIN_SAMPLE_RATE);
-    let duration_sec = 8.0;
     let total_samples = (sr as f64 * duration_sec).floor() as usize;
 
     let mut samples = Vec::with_capacity(total_samples);
     for i in 0..total_samples {
         let t = i as f64 / sr as f64;
-        let s1 = (2.0 * PI * 853.0 * t).sin();
-        let s2 = (2.0 * PI * 960.0 * t).sin();
-        let s = (s1 + s2) * 0.5 * amp;
+        let s = ATTENTION_FREQS
+            .iter()
+            .map(|freq| (2.0 * PI * freq * t).sin())
+            .sum::<f64>()
+            * 0.5
+            * amp;
         let v = (s * i16::MAX as f64).clamp(i16::MIN as f64, i16::MAX as f64);
         samples.push(v as i16);
     }
     Ok(samples)
 }
 
+pub fn generate_attention_tone(sr: u32, amp: f64) -> Result<Vec<i16>, HeaderError> {
+    generate_attention_tone_samples(ATTENTION_TONE_SECS, sr, amp)
+}
+
+pub fn generate_same_header_with_attention_tone(
+    header: &str,
+    sr: u32,
+    amp: f64,
+    timing: &SameTiming,
+) -> Result<Vec<i16>, HeaderError> {
+    let mut samples = generate_same_header_samples_with_timing(header, sr, amp, timing)?;
+    samples.extend(generate_attention_tone(sr, amp)?);
+    Ok(samples)
+}
+
 pub fn generate_silence_for_duration(sr: u32, duration_sec: f64) -> Vec<i16> {
     let sr = sr.max(MIN_SAMPLE_RATE);
     let total_samples = (sr as f64 * duration_sec).floor() as usize;
@@ -178,11 +389,18 @@ mod tests {
     fn generate_same_header_samples_for_nnnn_is_sized_from_clamped_sample_rate() {
         let samples = generate_same_header_samples("NNNN", 2000, 0.5).expect("samples");
         let sr = MIN_SAMPLE_RATE as usize;
-        let samples_per_bit = (MIN_SAMPLE_RATE as f64 * BIT_DURATION_SEC).floor() as usize;
         let bits_len = (16 + 4) * 8;
-        let per_burst = (bits_len * samples_per_bit) + sr;
+        let burst = (bits_len as f64 * MIN_SAMPLE_RATE as f64 * BIT_DURATION_SEC).round() as usize;
+        let per_burst = burst + sr;
         let expected = per_burst * BURST_COUNT;
         assert_eq!(samples.len(), expected);
+
+        let samples = generate_same_header_samples("NNNN", 22_050, 0.5).expect("samples");
+        let burst = samples.len() / BURST_COUNT - 22_050;
+        assert_eq!(
+            burst,
+            (bits_len as f64 * 22_050.0 * BIT_DURATION_SEC).round() as usize
+        );
     }
 
     #[test]
@@ -213,6 +431,209 @@ mod tests {
         assert!(silence.iter().all(|sample| *sample == 0));
     }
 
+    #[test]
+    fn attention_tone_peaks_at_853_and_960_hz_and_follows_the_header() {
+        let short = generate_attention_tone_samples(2.5, 22_050, 0.4).expect("tone");
+        assert_eq!(short.len(), 55_125);
+        assert!(generate_attention_tone_samples(-1.0, 22_050, 0.4).is_err());
+        assert!(generate_attention_tone_samples(0.0, 22_050, 0.4).is_err());
+
+        let sr = 8_000usize;
+        let second = generate_attention_tone_samples(1.0, sr as u32, 0.4).expect("tone");
+        let mut bins: Vec<(f64, usize)> = (100..2_000)
+            .map(|freq| {
+                let (re, im) = second
+                    .iter()
+                    .enumerate()
+                    .fold((0.0, 0.0), |(re, im), (n, &x)| {
+                        let phase = 2.0 * PI * freq as f64 * n as f64 / sr as f64;
+                        (
+                            re + f64::from(x) * phase.cos(),
+                            im - f64::from(x) * phase.sin(),
+                        )
+                    });
+                ((re * re + im * im).sqrt(), freq)
+            })
+            .collect();
+        bins.sort_by(|a, b| b.0.total_cmp(&a.0));
+        let mut strongest = [bins[0].1, bins[1].1];
+        strongest.sort_unstable();
+        assert_eq!(strongest, [853, 960]);
+        assert!(bins[2].0 < bins[1].0 / 10.0, "{:?}", &bins[..3]);
+
+        let header = "ZCZC-WXR-RWT-031055+0015-1231645-KOAX/NWS-";
+        let with_tone =
+            generate_same_header_with_attention_tone(header, 8_000, 0.4, &SameTiming::default())
+                .expect("header");
+        let bursts = generate_same_header_samples(header, 8_000, 0.4).expect("bursts");
+        assert_eq!(with_tone.len(), bursts.len() + 8 * 8_000);
+        assert!(with_tone[bursts.len() - 8_000..bursts.len()]
+            .iter()
+            .all(|&sample| sample == 0));
+    }
+
+    #[test]
+    fn validate_same_header_checks_every_field() {
+        let parsed = validate_same_header("ZCZC-WXR-TOR-031055-031153+0030-0341402-KOAX/NWS-")
+            .expect("valid");
+        assert_eq!(
+            parsed,
+            ParsedHeader {
+                originator: "WXR".to_string(),
+                event_code: "TOR".to_string(),
+                locations: vec!["031055".to_string(), "031153".to_string()],
+                purge: "0030".to_string(),
+                issued: "0341402".to_string(),
+                station: "KOAX/NWS".to_string(),
+            }
+        );
+        assert_eq!(
+            parsed.to_header(),
+            "ZCZC-WXR-TOR-031055-031153+0030-0341402-KOAX/NWS-"
+        );
+
+        let many = vec!["019001"; 31].join("-");
+        for (header, problem) in [
+            ("ZCZC-EAS-RMT-000000+0100-3662359-WXYZ/FM -", None),
+            ("ZCZC-PEP-EAN-000000+0000-0010000-KWO35   -", None),
+            ("ZCZC-PEP-EAN-000000+0000-0010000-KWO35-", Some("station")),
+            (
+                &format!("ZCZC-CIV-CAE-{many}+0600-1001200-EASLISTE-")[..],
+                None,
+            ),
+            (
+                "ZCZC-WXR-??W-099999+0015-0341402-EASLISTE-",
+                Some("event code"),
+            ),
+            ("ZCZC-WXR-SVR-+0015-0341402-EASLISTE-", Some("location")),
+            (
+                &format!("ZCZC-CIV-CAE-{many}-019003+0600-1001200-EASLISTE-")[..],
+                Some("between 1 and 31"),
+            ),
+            ("ZCZC-WXR-SVR-31055+0015-0341402-EASLISTE-", Some("PSSCCC")),
+            (
+                "ZCZC-ABC-SVR-031055+0015-0341402-EASLISTE-",
+                Some("originator"),
+            ),
+            ("ZCZC-WXR-SVR-031055-0015-0341402-EASLISTE-", Some("'+'")),
+            ("ZCZC-WXR-SVR-031055+0075-0341402-EASLISTE-", Some("purge")),
+            (
+                "ZCZC-WXR-SVR-031055+0015-3671402-EASLISTE-",
+                Some("issue time"),
+            ),
+            (
+                "ZCZC-WXR-SVR-031055+0015-0342402-EASLISTE-",
+                Some("issue time"),
+            ),
+            (
+                "ZCZC-WXR-SVR-031055+0015-0341402-EASLISTENER-",
+                Some("station"),
+            ),
+            ("ZCZC-WXR-SVR-031055+0015-0341402--", Some("station")),
+            (
+                "ZCZC-WXR-SVR-031055+0015-0341402-EASLISTE",
+                Some("end with"),
+            ),
+            ("NNNN", Some("start with")),
+        ] {
+            match (validate_same_header(header), problem) {
+                (Ok(_), None) => {}
+                (Err(HeaderError::InvalidConfig(message)), Some(problem)) => {
+                    assert!(message.contains(problem), "{header}: {message}")
+                }
+                (result, _) => panic!("{header}: {result:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn reissued_headers_carry_the_new_time_and_keep_every_other_field() {
+        let issued = DateTime::parse_from_rfc3339("2025-02-03T14:08:00Z")
+            .expect("time")
+            .with_timezone(&Utc);
+        for (header, expected) in [
+            (
+                "ZCZC-WXR-TOR-031055-031153+0030-0341402-KOAX/NWS-",
+                "ZCZC-WXR-TOR-031055-031153+0030-0341408-KOAX/NWS-",
+            ),
+            (
+                "ZCZC-WXR-RWT-019001-019003-019005-019007-019009+0100-0341330-KDMX/NWS-",
+                "ZCZC-WXR-RWT-019001-019003-019005-019007-019009+0100-0341408-KDMX/NWS-",
+            ),
+            (
+                "ZCZC-CIV-CAE-000000+0600-3650001-WXYZ/FM -",
+                "ZCZC-CIV-CAE-000000+0600-0341408-WXYZ/FM -",
+            ),
+            (
+                "ZCZC-PEP-EAN-000000+0000-0011200-KWO35   -",
+                "ZCZC-PEP-EAN-000000+0000-0341408-KWO35   -",
+            ),
+        ] {
+            assert_eq!(
+                reissue_header(header, issued, None).as_deref(),
+                Some(expected)
+            );
+            let original = header.rsplit('-').nth(2).expect("issue time");
+            let field =
+                |range: std::ops::Range<usize>| original[range].parse::<u32>().expect("digits");
+            let then = chrono::NaiveDate::from_yo_opt(2025, field(0..3))
+                .and_then(|date| date.and_hms_opt(field(3..5), field(5..7), 0))
+                .expect("time")
+                .and_utc();
+            assert_eq!(
+                reissue_header(expected, then, None).as_deref(),
+                Some(header)
+            );
+        }
+        assert_eq!(
+            reissue_header(
+                "ZCZC-WXR-TOR-031055+0030-0341402-KOAX/NWS-",
+                issued,
+                Some("EASLISTE")
+            )
+            .as_deref(),
+            Some("ZCZC-WXR-TOR-031055+0030-0341408-EASLISTE-")
+        );
+        for malformed in [
+            "NNNN",
+            "ZCZC-WXR-TOR-031055+0030-0341402-",
+            "ZCZC-WXR-TOR-31055+0030-0341402-KOAX/NWS-",
+            "ZCZC-WXR-TOR+0030-0341402-KOAX/NWS-",
+            "ZCZC-WXR-TOR-031055+003-0341402-KOAX/NWS-",
+            "ZCZC-WXR-TOR-031055+0030-034140-KOAX/NWS-",
+            "ZCZC-WXR-TOR-031055+0030-0341402-KOAX/NWS",
+            "ZCZC-WXR-TOR-031055+0030-0341402-TOOLONGID-",
+        ] {
+            assert_eq!(reissue_header(malformed, issued, None), None, "{malformed}");
+        }
+    }
+
+    #[test]
+    fn station_id_is_derived_from_relay_name_and_replaced_in_headers() {
+        assert_eq!(relay_station_id("EASListener"), "EASLISTE");
+        assert_eq!(relay_station_id("wxyz/fm"), "WXYZ/FM/");
+        assert_eq!(relay_station_id(" KOAX "), "KOAX////");
+        assert_eq!(relay_station_id("OmahaWeatherRelay"), "OMAHAWEA");
+        assert_eq!(relay_station_id("  "), FALLBACK_STATION_ID);
+
+        let header = "ZCZC-WXR-RWT-031055-031153+0015-1231645-KWO35   -";
+        assert_eq!(station_id(header), Some("KWO35   "));
+        assert_eq!(
+            with_station_id(header, "EASLISTE"),
+            "ZCZC-WXR-RWT-031055-031153+0015-1231645-EASLISTE-"
+        );
+        assert_eq!(with_station_id("NNNN", "EASLISTE"), "NNNN");
+        assert_eq!(station_id("ZCZC-WXR-RWT-031055+0015-1231645-"), None);
+        assert_eq!(originator(header), Some("WXR"));
+        assert_eq!(originator("NNNN"), None);
+        assert_eq!(purge_duration(header), Some(Duration::from_secs(15 * 60)));
+        assert_eq!(
+            purge_duration("ZCZC-WXR-TOR-031055+0130-1231645-KWO35   -"),
+            Some(Duration::from_secs(90 * 60))
+        );
+        assert_eq!(purge_duration("ZCZC-WXR-RWT-031055+00"), None);
+    }
+
     #[test]
     fn generate_same_header_samples_for_standard_header_is_not_silent() {
         let header = "ZCZC-WXR-RWT-031055+0015-1231645-KWO35-";
@@ -220,4 +641,55 @@ mod tests {
         assert!(!samples.is_empty());
         assert!(samples.iter().any(|sample| *sample != 0));
     }
+
+    #[test]
+    fn generated_headers_decode_back_through_sameold() {
+        use sameold::{Message, SameReceiverBuilder};
+
+        let header = "ZCZC-WXR-TOR-031055-031153+0030-1231645-KOAX/NWS-";
+        for sr in [22_050, 44_100, 48_000] {
+            let mut samples = generate_same_header_samples(header, sr, 0.5).expect("header");
+            samples.extend(generate_silence_for_duration(sr, 2.0));
+            samples.extend(generate_same_header_samples("NNNN", sr, 0.5).expect("nnnn"));
+            samples.extend(generate_silence_for_duration(sr, 2.0));
+
+            let mut receiver = SameReceiverBuilder::new(sr).build();
+            let decoded: Vec<String> = receiver
+                .iter_messages(samples.iter().map(|&s| s as f32 / i16::MAX as f32))
+                .map(|message| match message {
+                    Message::StartOfMessage(header) => header.as_str().to_string(),
+                    Message::EndOfMessage => "NNNN".to_string(),
+                })
+                .collect();
+            assert_eq!(decoded, [header, "NNNN"], "at {sr} Hz");
+        }
+    }
+
+    #[test]
+    fn same_timing_sets_the_preamble_burst_count_and_gap() {
+        let timing = SameTiming {
+            preamble_bytes: 20,
+            bursts: 2,
+            burst_gap: Duration::from_millis(500),
+        };
+        let samples =
+            generate_same_header_samples_with_timing("NNNN", 8_000, 0.5, &timing).expect("nnnn");
+        let burst = ((20 + 4) * 8) as f64 * 8_000.0 * BIT_DURATION_SEC;
+        assert_eq!(samples.len(), 2 * (burst.round() as usize + 4_000));
+        assert!(samples[samples.len() - 4_000..].iter().all(|&s| s == 0));
+        assert!(samples[samples.len() / 2 - 4_000..samples.len() / 2]
+            .iter()
+            .all(|&s| s == 0));
+
+        let bits = build_same_bits("NNNN", 1);
+        assert_eq!(&bits[..8], &[1, 1, 0, 1, 0, 1, 0, 1]);
+        assert_eq!(MARK_FREQ * BIT_DURATION_SEC, 4.0);
+        assert_eq!(SPACE_FREQ * BIT_DURATION_SEC, 3.0);
+
+        let none = SameTiming {
+            bursts: 0,
+            ..SameTiming::default()
+        };
+        assert!(generate_same_header_samples_with_timing("NNNN", 8_000, 0.5, &none).is_err());
+    }
 }
@@ -1,6 +1,7 @@
 use crate::config::Config;
 use anyhow::{bail, Context, Result};
-use once_cell::sync::OnceCell;
+use once_cell::sync::{Lazy, OnceCell};
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::time::{Duration, Instant};
@@ -22,15 +23,28 @@ const COMFORT_NOISE_PEAK: i16 = 32;
 const NOISE_SEED: u64 = 0x9E37_79B9_7F4A_7C15;
 
 static ALERT_STREAM_TX: OnceCell<mpsc::UnboundedSender<PathBuf>> = OnceCell::new();
+static ALERT_STREAM_PENDING: Lazy<std::sync::Mutex<HashSet<PathBuf>>> = Lazy::new(Default::default);
+
+fn pending_alert_audio() -> std::sync::MutexGuard<'static, HashSet<PathBuf>> {
+    ALERT_STREAM_PENDING
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
 
 pub fn enqueue_alert_audio(path: PathBuf) {
     if let Some(tx) = ALERT_STREAM_TX.get() {
+        pending_alert_audio().insert(path.clone());
         if let Err(err) = tx.send(path) {
+            pending_alert_audio().remove(&err.0);
             warn!("Failed to enqueue alert audio for Icecast stream: {}", err);
         }
     }
 }
 
+pub fn alert_audio_pending(path: &Path) -> bool {
+    pending_alert_audio().contains(path)
+}
+
 async fn decode_to_pcm(path: &Path) -> Result<Vec<u8>> {
     let output = Command::new("ffmpeg")
         .arg("-nostdin")
@@ -171,6 +185,7 @@ pub async fn run_alert_stream(
     let mut gap_remaining = 0usize;
     let mut logged_disabled = false;
 
+    crate::reload::register("icecast_alert_stream");
     loop {
         loop {
             match reload_rx.try_recv() {
@@ -189,6 +204,7 @@ pub async fn run_alert_stream(
                         last_spawn_attempt = None;
                         spawn_failures = 0;
                     }
+                    crate::reload::acknowledge("icecast_alert_stream");
                 }
                 Err(broadcast::error::TryRecvError::Empty)
                 | Err(broadcast::error::TryRecvError::Closed) => break,
@@ -211,18 +227,27 @@ pub async fn run_alert_stream(
             tokio::select! {
                 reload = reload_rx.recv() => {
                     match reload {
-                        Ok(new_config) => config = new_config,
+                        Ok(new_config) => {
+                            config = new_config;
+                            crate::reload::acknowledge("icecast_alert_stream");
+                        }
                         Err(broadcast::error::RecvError::Lagged(_)) => {}
                         Err(broadcast::error::RecvError::Closed) => {
-                            if path_rx.recv().await.is_none() {
-                                return Ok(());
+                            match path_rx.recv().await {
+                                Some(path) => {
+                                    pending_alert_audio().remove(&path);
+                                }
+                                None => return Ok(()),
                             }
                         }
                     }
                 }
                 drained = path_rx.recv() => {
-                    if drained.is_none() {
-                        return Ok(());
+                    match drained {
+                        Some(path) => {
+                            pending_alert_audio().remove(&path);
+                        }
+                        None => return Ok(()),
                     }
                 }
             }
@@ -294,7 +319,9 @@ pub async fn run_alert_stream(
             if gap_remaining > 0 {
                 gap_remaining = gap_remaining.saturating_sub(CHUNK_BYTES);
             } else if let Ok(path) = path_rx.try_recv() {
-                match decode_to_pcm(&path).await {
+                let decoded = decode_to_pcm(&path).await;
+                pending_alert_audio().remove(&path);
+                match decoded {
                     Ok(pcm) if !pcm.is_empty() => {
                         info!("Streaming alert audio to Icecast mount: {}", path.display());
                         current = Some(pcm);
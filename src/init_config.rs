@@ -0,0 +1,1151 @@
+use crate::config::{Config, ConfigFormat};
+use crate::e2t_ng;
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use serde_json::ser::{PrettyFormatter, Serializer};
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+const HEADER_COMMENT: &str = "Generated by `eas_listener init-config`. Keys starting with _comment are notes for you and are ignored. Values marked REQUIRED are placeholders you must replace.";
+
+struct ExampleKey {
+    key: &'static str,
+    value: Value,
+    comment: &'static str,
+}
+
+fn key(key: &'static str, value: Value, comment: &'static str) -> ExampleKey {
+    ExampleKey {
+        key,
+        value,
+        comment,
+    }
+}
+
+fn example_keys() -> Vec<ExampleKey> {
+    vec![
+        key(
+            "ICECAST_STREAM_URL_ARRAY",
+            json!(["http://icecast.example.com:8000/stream.mp3"]),
+            "REQUIRED. Icecast/HTTP audio streams to monitor for SAME headers; each one is a numbered monitor in the order listed. An entry can also be an object with url plus any of name, watched_fips, filters (a list of rules or the name of a FILTER_SETS entry), nwr_tone_detection, inactivity_timeout_secs and recording_gain_db (dB applied to its audio in recordings), overriding the global settings for that stream.",
+        ),
+        key(
+            "WATCHED_FIPS",
+            json!("031055"),
+            "REQUIRED. Comma-separated six-digit SAME location codes (PSSCCC) that count as local alerts.",
+        ),
+        key(
+            "TZ",
+            json!("UTC"),
+            "IANA time zone used for timestamps, e.g. America/Chicago. An unknown name fails the config.",
+        ),
+        key(
+            "ALLOW_INVALID_TZ",
+            json!(false),
+            "Use UTC, with a warning, when TZ is not a known time zone instead of rejecting the config.",
+        ),
+        key(
+            "EAS_RELAY_NAME",
+            json!("EAS Listener"),
+            "Station name shown in notifications and on the dashboard.",
+        ),
+        key(
+            "SAME_STATION_ID",
+            json!("EASLISTE"),
+            "The eight-character station id (letters, digits and '/') put on SAME headers this relay generates, padded with '/' when shorter. Empty derives one from EAS_RELAY_NAME, which then may only use those characters.",
+        ),
+        key(
+            "SAME_PREAMBLE_BYTES",
+            json!(16),
+            "How many 0xAB preamble bytes come before each generated SAME header burst. The EAS rules call for 16.",
+        ),
+        key(
+            "SAME_BURST_COUNT",
+            json!(3),
+            "How many times each generated SAME header and NNNN is sent, from 1 to 3. The EAS rules call for 3.",
+        ),
+        key(
+            "SAME_BURST_GAP_MS",
+            json!(1000),
+            "Milliseconds of silence after each generated SAME burst, from 100 to 5000. The EAS rules call for one second.",
+        ),
+        key(
+            "DASHBOARD_USERNAME",
+            json!("admin"),
+            "REQUIRED. Username for the dashboard and monitoring API.",
+        ),
+        key(
+            "DASHBOARD_PASSWORD",
+            json!("change-me"),
+            "REQUIRED. Password for the dashboard and monitoring API. Like the other credentials, it can instead be read from the file DASHBOARD_PASSWORD_FILE names, in this file or the environment.",
+        ),
+        key(
+            "DASHBOARD_PASSWORD_HASH",
+            json!(""),
+            "Argon2 or bcrypt hash of the dashboard password, used instead of DASHBOARD_PASSWORD when set (e.g. from `htpasswd -nbBC 12 \"\" <password> | cut -d: -f2`).",
+        ),
+        key(
+            "SESSION_TTL_SECS",
+            json!(43200),
+            "Seconds a session token from POST /api/login stays valid (minimum 60).",
+        ),
+        key(
+            "API_TOKENS",
+            json!([]),
+            "Monitoring API tokens for other tools: objects with name, token (16+ characters), and scope \"read\" (default) or \"admin\".",
+        ),
+        key(
+            "AUTH_FAILURE_THRESHOLD",
+            json!(10),
+            "Failed API logins from one address, within the window, that lock it out.",
+        ),
+        key(
+            "AUTH_FAILURE_WINDOW_SECS",
+            json!(300),
+            "Window in seconds over which failed API logins are counted.",
+        ),
+        key(
+            "AUTH_LOCKOUT_SECS",
+            json!(900),
+            "Seconds an address stays locked out after too many failed logins.",
+        ),
+        key(
+            "WS_QUERY_AUTH_ENABLED",
+            json!(true),
+            "Deprecated: still accept the WebSocket token as ?auth=, where it ends up in proxy logs. Clients should send an Authorization header or the eas-auth subprotocol.",
+        ),
+        key(
+            "SHARED_STATE_DIR",
+            json!("/data"),
+            "Directory for logs, the alert database, recordings, and other state.",
+        ),
+        key(
+            "RECORDING_DIR",
+            json!("recordings"),
+            "Recording directory, relative to SHARED_STATE_DIR.",
+        ),
+        key(
+            "ALERT_LOG_FILE",
+            json!("alerts.log"),
+            "File name prefix for the daily application log inside SHARED_STATE_DIR.",
+        ),
+        key(
+            "DEDICATED_ALERT_LOG_FILE",
+            json!("dedicated-alerts.log"),
+            "Alert-only log inside SHARED_STATE_DIR; the hash-chained copy sits beside it as .jsonl.",
+        ),
+        key(
+            "ALERT_DATABASE_FILE",
+            json!("alerts.db"),
+            "SQLite alert database, relative to SHARED_STATE_DIR unless absolute.",
+        ),
+        key(
+            "ALERT_LOG_CHAIN_MAX_BYTES",
+            json!(10 * 1024 * 1024),
+            "Size at which the hash-chained alert log is sealed and rotated; 0 never rotates.",
+        ),
+        key(
+            "ALERT_LOG_ROTATE_MONTHLY",
+            json!(false),
+            "Move the dedicated alert log and the hash-chained log aside as <name>-YYYY-MM.log/.jsonl when the first alert of a new month is logged.",
+        ),
+        key(
+            "ALERT_LOG_ROTATE_MAX_BYTES",
+            json!(0),
+            "Also move the dedicated alert log and the hash-chained log aside before they grow past this many bytes; 0 never does.",
+        ),
+        key(
+            "ALERT_LOG_ROTATE_GZIP",
+            json!(false),
+            "Compress rotated dedicated alert logs and sealed hash-chain files to .gz.",
+        ),
+        key(
+            "ALERT_LOG_ROTATE_KEEP",
+            json!(12),
+            "Rotated dedicated alert logs, and sealed hash-chain files, to keep; older ones are deleted. 0 keeps them all.",
+        ),
+        key(
+            "SHOULD_LOG_ALL_ALERTS",
+            json!(false),
+            "Log alerts outside WATCHED_FIPS to the dedicated alert log as well.",
+        ),
+        key(
+            "RUST_LOG",
+            json!("INFO"),
+            "Log level: ERROR, WARN, INFO, DEBUG, or TRACE.",
+        ),
+        key(
+            "LOG_LEVEL_FILE",
+            json!(""),
+            "Log level for the rolling log file in SHARED_STATE_DIR instead of RUST_LOG; empty uses RUST_LOG.",
+        ),
+        key(
+            "LOG_LEVEL_STDOUT",
+            json!(""),
+            "Log level for standard output (docker logs) instead of RUST_LOG; empty uses RUST_LOG.",
+        ),
+        key(
+            "LOG_LEVEL_DASHBOARD",
+            json!(""),
+            "Log level for the dashboard's recent logs instead of RUST_LOG; empty uses RUST_LOG.",
+        ),
+        key(
+            "LOG_TARGET_LEVELS",
+            json!({}),
+            "Log levels for individual modules on top of RUST_LOG and the LOG_LEVEL_* keys, in every output, e.g. {\"eas_listener::audio\": \"debug\"}. Also written by PUT /api/logging with \"persist\": true.",
+        ),
+        key(
+            "RECORDING_FORMAT",
+            json!("wav"),
+            "Recording format: \"wav\", \"flac\", \"ogg\" (Opus) or \"mp3\". Everything but WAV is encoded by ffmpeg.",
+        ),
+        key(
+            "RECORDING_FILENAME_TEMPLATE",
+            json!("EAS_Recording_{date}_{time}_{event}_{stream}"),
+            "Recording names under RECORDING_DIR, without the extension. Placeholders: {event}, {originator}, {date}, {time}, {year}, {month}, {day}, {stream} and {seq}; '/' makes subdirectories, e.g. \"{year}/{month}/{day}/{event}_{time}_{stream}\". Without {seq}, _1, _2, ... is added when a name is taken.",
+        ),
+        key(
+            "RECORDING_SUBDIR_SCHEME",
+            json!("flat"),
+            "Directories recordings are sorted into ahead of RECORDING_FILENAME_TEMPLATE: \"flat\" (none), \"stream\", \"date\" (YYYY-MM-DD) or \"stream_date\" (stream, then date).",
+        ),
+        key(
+            "RECORDING_BITS_PER_SAMPLE",
+            json!(16),
+            "Sample depth of WAV and FLAC recordings: 16, 24 or \"32f\" (32-bit float WAV; FLAC stores it as 24-bit).",
+        ),
+        key(
+            "RECORDING_SAMPLE_RATE",
+            json!(48000),
+            "Sample rate of recordings, 8000 to 48000 Hz. Lower rates such as 22050 save space; audio is resampled from the 48 kHz pipeline.",
+        ),
+        key(
+            "RECORDING_NORMALIZE",
+            json!(false),
+            "false, or the peak in dBFS (e.g. -1; true means -1) received audio is scaled to before a recording is finished. The SAME header and NNNN keep their level. Clipped samples are logged either way.",
+        ),
+        key(
+            "RECORDING_INJECT_SAME",
+            json!(true),
+            "Put a regenerated SAME header before each recording and an NNNN after it. Turn off to keep exactly the received audio; relays add their own header either way.",
+        ),
+        key(
+            "RECORDING_ATTENTION_TONE",
+            json!(false),
+            "Follow the regenerated SAME header with the eight-second 853/960 Hz attention signal, as a broadcast ENDEC does, in recordings and in relays of them.",
+        ),
+        key(
+            "STORAGE_SAVER_MODE",
+            json!(false),
+            "Store recordings compressed instead of as WAV. Ignored when RECORDING_FORMAT is set.",
+        ),
+        key(
+            "STORAGE_SAVER_MODE_EXT",
+            json!("mp3"),
+            "Compressed recording format when STORAGE_SAVER_MODE is on: \"mp3\" or \"ogg\".",
+        ),
+        key(
+            "MIN_FREE_DISK_MB",
+            json!(100),
+            "Free space in MiB the recording directory needs before a recording starts; 0 disables the check.",
+        ),
+        key(
+            "LOW_DISK_ACTION",
+            json!("refuse"),
+            "When space is below MIN_FREE_DISK_MB: \"refuse\" to record, or \"delete_oldest\" recordings until there is room.",
+        ),
+        key(
+            "RECORDING_RETENTION_DAYS",
+            json!(0),
+            "Delete recordings older than this many days, checked hourly; 0 keeps them forever.",
+        ),
+        key(
+            "RECORDING_MAX_TOTAL_MB",
+            json!(0),
+            "Delete the oldest recordings, checked hourly, while all of them together take more than this many MiB; 0 sets no limit.",
+        ),
+        key(
+            "UPLOAD_DESTINATION",
+            json!(""),
+            "Copy each finished recording to \"s3\" (any S3-compatible bucket) or \"sftp\"; empty keeps recordings local only.",
+        ),
+        key(
+            "UPLOAD_S3_ENDPOINT",
+            json!(""),
+            "S3 endpoint, e.g. \"https://s3.us-east-1.amazonaws.com\"; the bucket is addressed path-style.",
+        ),
+        key("UPLOAD_S3_BUCKET", json!(""), "Bucket recordings are uploaded to."),
+        key("UPLOAD_S3_REGION", json!("us-east-1"), "Region the requests are signed for."),
+        key(
+            "UPLOAD_S3_ACCESS_KEY_ID",
+            json!(""),
+            "Access key ID; may be given as UPLOAD_S3_ACCESS_KEY_ID_FILE instead.",
+        ),
+        key(
+            "UPLOAD_S3_SECRET_ACCESS_KEY",
+            json!(""),
+            "Secret access key; may be given as UPLOAD_S3_SECRET_ACCESS_KEY_FILE instead.",
+        ),
+        key(
+            "UPLOAD_S3_PREFIX",
+            json!(""),
+            "Put before each recording's path in the bucket, e.g. \"eas\".",
+        ),
+        key("UPLOAD_SFTP_HOST", json!(""), "SFTP server recordings are uploaded to."),
+        key("UPLOAD_SFTP_PORT", json!(22), "SFTP server port."),
+        key("UPLOAD_SFTP_USER", json!(""), "SFTP user."),
+        key(
+            "UPLOAD_SFTP_IDENTITY_FILE",
+            json!(""),
+            "Private key the SFTP user logs in with.",
+        ),
+        key(
+            "UPLOAD_SFTP_DIR",
+            json!(""),
+            "Remote directory recordings are put in, created as needed; empty uses the login directory.",
+        ),
+        key(
+            "UPLOAD_PUBLIC_URL",
+            json!(""),
+            "Base URL uploads are served from, e.g. a CDN in front of the bucket; the recording's path is appended. Empty records the upload URL itself.",
+        ),
+        key(
+            "UPLOAD_THEN_DELETE",
+            json!(false),
+            "Delete the local recording once it has been uploaded.",
+        ),
+        key(
+            "UPLOAD_MAX_ATTEMPTS",
+            json!(5),
+            "Tries per recording, backing off from 5 seconds to 5 minutes, before it is left local only.",
+        ),
+        key(
+            "RELAY_TMP_DIR",
+            json!(""),
+            "Directory relays build their audio in, relative to SHARED_STATE_DIR, e.g. \"tmp\" to keep it on the recordings volume. Empty uses the system temp directory.",
+        ),
+        key(
+            "NWS_ENRICHMENT",
+            json!(false),
+            "Look each relevant SAME alert up in the NWS API (api.weather.gov) by event and county, and attach the headline, description and instructions found. Notifications never wait for it.",
+        ),
+        key(
+            "NWS_ENRICHMENT_TIMEOUT_SECS",
+            json!(5),
+            "How long the NWS API lookup may take, 1 to 60 seconds.",
+        ),
+        key("NWS_API_URL", json!("https://api.weather.gov"), "NWS API base URL."),
+        key(
+            "NWS_API_USER_AGENT",
+            json!(""),
+            "User-Agent sent to the NWS API, which asks for one with contact details, e.g. \"EAS_Listener (you@example.com)\". Empty sends EAS_Listener/<version>.",
+        ),
+        key(
+            "CAP_XML_DIR",
+            json!(""),
+            "Directory, relative to SHARED_STATE_DIR, to write a CAP 1.2 document to for each relevant alert, e.g. \"cap\". Empty writes none; /api/alerts/{id}/cap.xml serves them either way.",
+        ),
+        key(
+            "DEEPLINK_CACHE_MAX_AGE_DAYS",
+            json!(0),
+            "At startup, delete the cached dashboard host (deeplink_host*.txt) when it has not changed in this many days; 0 keeps it.",
+        ),
+        key(
+            "MONITORING_ENABLED",
+            json!(true),
+            "Run the monitoring API the dashboard talks to.",
+        ),
+        key(
+            "MONITORING_BIND_ADDR",
+            json!("0.0.0.0:8080"),
+            "Socket address the monitoring API listens on.",
+        ),
+        key(
+            "MONITORING_BIND_PORT",
+            json!(8080),
+            "Port the dashboard uses to reach the monitoring API.",
+        ),
+        key(
+            "MONITORING_MAX_LOGS",
+            json!(500),
+            "Log lines kept in memory for the dashboard.",
+        ),
+        key(
+            "MONITORING_EVENT_CHANNEL_CAPACITY",
+            json!(256),
+            "Monitoring events buffered for each WebSocket client and internal watcher before slow ones skip events (16-65536). Takes effect on restart.",
+        ),
+        key(
+            "STREAM_HISTORY_MAX_ENTRIES",
+            json!(200),
+            "Connection state changes kept per stream for /api/streams/{index}/history and the 24-hour availability figure; 0 keeps none. Takes effect on restart.",
+        ),
+        key(
+            "MONITORING_ACTIVITY_WINDOW_SECS",
+            json!(45),
+            "Seconds without audio before a stream is reported as idle.",
+        ),
+        key(
+            "API_TLS_CERT_PATH",
+            json!(""),
+            "PEM certificate chain for serving the monitoring API over HTTPS; set together with API_TLS_KEY_PATH.",
+        ),
+        key(
+            "API_TLS_KEY_PATH",
+            json!(""),
+            "PEM private key for API_TLS_CERT_PATH. Both files are re-read when the config is reloaded.",
+        ),
+        key(
+            "SERVE_DASHBOARD",
+            json!(true),
+            "Serve the dashboard from the monitoring API port at /; turn off if you only use the separate web server.",
+        ),
+        key(
+            "DASHBOARD_STATIC_DIR",
+            json!(""),
+            "Serve dashboard files from this directory instead of the copy built into the binary.",
+        ),
+        key(
+            "WEB_SERVER_PORT",
+            json!("3010"),
+            "Port the PHP dashboard is served on.",
+        ),
+        key(
+            "LOCAL_DEEPLINK_HOST",
+            json!("auto"),
+            "Host used in dashboard links in notifications; \"auto\" learns it from dashboard requests.",
+        ),
+        key(
+            "USE_REVERSE_PROXY",
+            json!(false),
+            "Set when the dashboard sits behind a reverse proxy.",
+        ),
+        key(
+            "ALLOWED_ORIGINS",
+            json!([]),
+            "Origins allowed to call the monitoring API from a browser, e.g. [\"http://192.168.1.20:3010\"], or [\"*\"] for any. Empty allows private-network and localhost origins, plus the reverse proxy.",
+        ),
+        key(
+            "REVERSE_PROXY_URL",
+            json!("localhost"),
+            "Public dashboard URL when USE_REVERSE_PROXY is on.",
+        ),
+        key(
+            "WS_REVERSE_PROXY_URL",
+            json!("localhost"),
+            "Public WebSocket URL for the monitoring API when USE_REVERSE_PROXY is on.",
+        ),
+        key(
+            "MAX_ACTIVE_ALERTS",
+            json!(100),
+            "Active alerts kept at once; the least severe are evicted first.",
+        ),
+        key(
+            "RECENT_ALERTS_LIMIT",
+            json!(100),
+            "Processed alerts kept in memory, expired or filtered out included, for the dashboard's recent history (up to 10000; 0 keeps none).",
+        ),
+        key(
+            "DEPENDENCY_CHECK",
+            json!(true),
+            "At startup, check that ffmpeg, ffprobe, E2T-NG and (without APPRISE_API_URL) the apprise CLI work, and that the configured files exist; problems are logged and shown in /api/status.",
+        ),
+        key(
+            "DEPENDENCY_CHECK_FATAL",
+            json!(false),
+            "Refuse to start when the dependency check finds a problem, instead of only warning.",
+        ),
+        key(
+            "SHUTDOWN_GRACE_SECS",
+            json!(8),
+            "Seconds a stopping instance waits for recordings in progress to be finished. Docker kills the container 10 seconds after docker stop unless stop_grace_period is raised.",
+        ),
+        key(
+            "SHARE_LINK_SECRET",
+            json!(""),
+            "Secret for signing recording share links; leave empty to generate one in SHARED_STATE_DIR.",
+        ),
+        key(
+            "SHARE_LINK_TTL_SECS",
+            json!(24 * 60 * 60),
+            "Default lifetime of a recording share link, in seconds.",
+        ),
+        key(
+            "ALERT_INJECTION_ENABLED",
+            json!(false),
+            "Allow POST /api/alerts/inject to push drill alerts through the pipeline, relays included. Turn it on only while running a drill.",
+        ),
+        key(
+            "RELOAD_SIGNAL_FILE_ENABLED",
+            json!(true),
+            "Also reload the configuration when the reload signal file (--reload-signal, /app/reload_signal by default) is touched, as the PHP dashboard's reload page does. Saving this file always reloads it. Applied at startup.",
+        ),
+        key(
+            "APPRISE_CONFIG_PATH",
+            json!("/app/apprise.yml"),
+            "AppRise YAML listing notification targets (discord:// URLs are sent natively).",
+        ),
+        key(
+            "APPRISE_ATTACHMENT_SUPPORT",
+            json!({}),
+            "AppRise URL prefixes mapped to whether they accept the recording, e.g. {\"sns://\": false}. Unlisted destinations are learned automatically.",
+        ),
+        key(
+            "DISCORD_MENTIONS",
+            json!({}),
+            "Event codes or severity classes (warning, watch, advisory, test) mapped to a mention added to the Discord message, e.g. {\"TOR\": \"<@&123456789>\", \"EAN\": \"@here\"}.",
+        ),
+        key(
+            "WEBHOOK_SHOW_HEARD_ON",
+            json!(false),
+            "List every monitor that heard an alert in notifications.",
+        ),
+        key(
+            "PARTIAL_RECEPTION_THRESHOLD",
+            json!(0.5),
+            "Fraction of healthy monitors below which an alert is flagged as partially received.",
+        ),
+        key(
+            "DISCORD_ATTACHMENT_MAX_BYTES",
+            json!(9 * 1024 * 1024),
+            "Largest recording attached to Discord; bigger ones are transcoded to Opus or linked.",
+        ),
+        key(
+            "DISCORD_MAX_RETRIES",
+            json!(3),
+            "Times a rate-limited Discord message is retried before it is dropped.",
+        ),
+        key(
+            "OPERATIONAL_APPRISE_CONFIG_PATH",
+            json!(""),
+            "AppRise YAML for operational notices such as monitors going down; empty uses APPRISE_CONFIG_PATH.",
+        ),
+        key(
+            "APPRISE_API_URL",
+            json!(""),
+            "Base URL of an AppRise API server (e.g. http://apprise:8000) to notify through instead of the apprise CLI; the CLI is still used if the server fails.",
+        ),
+        key(
+            "SMTP_HOST",
+            json!(""),
+            "SMTP server for alert emails with the recording attached; empty disables email.",
+        ),
+        key(
+            "SMTP_PORT",
+            json!(587),
+            "SMTP server port; 0 picks the default for SMTP_SECURITY (587, 465 or 25).",
+        ),
+        key(
+            "SMTP_SECURITY",
+            json!("starttls"),
+            "How the SMTP connection is secured: starttls, tls (implicit TLS) or none.",
+        ),
+        key("SMTP_USERNAME", json!(""), "SMTP login; leave empty for an unauthenticated relay."),
+        key("SMTP_PASSWORD", json!(""), "Password for SMTP_USERNAME."),
+        key(
+            "SMTP_FROM",
+            json!("EAS Listener <eas@example.com>"),
+            "Sender address for alert emails.",
+        ),
+        key(
+            "SMTP_TO",
+            json!([]),
+            "Recipients of alert emails, as an array or a comma-separated string.",
+        ),
+        key(
+            "SMTP_ATTACHMENT_MAX_BYTES",
+            json!(15 * 1024 * 1024),
+            "Largest recording attached to an email; bigger ones are transcoded to Opus or left off.",
+        ),
+        key(
+            "STREAM_HEALTH_NOTIFICATIONS",
+            json!(false),
+            "Notify when a monitor is disconnected or silent too long, and again when it recovers.",
+        ),
+        key(
+            "STREAM_HEALTH_THRESHOLD_SECS",
+            json!(300),
+            "Seconds a monitor must be down or silent before a notification is sent.",
+        ),
+        key(
+            "STREAM_HEALTH_COOLDOWN_SECS",
+            json!(900),
+            "Minimum seconds between a recovery and the next down notification for the same monitor.",
+        ),
+        key(
+            "STREAM_HEALTH_EXCLUDED_STREAMS",
+            json!([]),
+            "Stream URLs that never produce health notifications.",
+        ),
+        key(
+            "READY_MIN_HEALTHY_STREAMS",
+            json!(1),
+            "Monitors that must be connected and receiving audio before /api/ready reports ready (capped at the number of monitors).",
+        ),
+        key(
+            "FEEDBACK_LOOP_ALLOWED_STREAMS",
+            json!([]),
+            "Stream URLs that intentionally carry this relay's own output; alerts heard there are only deduplicated, not treated as a feedback loop.",
+        ),
+        key(
+            "RESOURCE_CPU_WARN_PERCENT",
+            json!(150.0),
+            "Log a warning when process CPU stays above this percentage (100 = one core); 0 disables it.",
+        ),
+        key(
+            "RESOURCE_CPU_WARN_SECS",
+            json!(60),
+            "Seconds CPU must stay above RESOURCE_CPU_WARN_PERCENT before the warning is logged.",
+        ),
+        key(
+            "RESOURCE_DISK_WARN_MB",
+            json!(1024),
+            "Free space in MiB below which the shared state or recording filesystem is warned about; 0 disables.",
+        ),
+        key(
+            "ALERT_STATS_RETENTION_DAYS",
+            json!(365),
+            "Days of per-stream, per-event-code alert counts kept for /api/stats/alerts.",
+        ),
+        key(
+            "GENERIC_WEBHOOKS",
+            json!([]),
+            "JSON webhooks: objects with url and optional name, headers, body_template, and signing_secret (adds X-EAS-Signature and X-EAS-Timestamp HMAC headers).",
+        ),
+        key(
+            "MQTT_URL",
+            json!(""),
+            "mqtt://host:port broker to publish alerts to; leave empty to disable MQTT.",
+        ),
+        key(
+            "MQTT_USERNAME",
+            json!(""),
+            "MQTT username, if the broker needs one.",
+        ),
+        key(
+            "MQTT_PASSWORD",
+            json!(""),
+            "MQTT password, if the broker needs one.",
+        ),
+        key(
+            "MQTT_TOPIC_PREFIX",
+            json!("eas_listener"),
+            "Topic prefix for published MQTT messages.",
+        ),
+        key(
+            "ENABLE_FILTERS",
+            json!(true),
+            "Apply FILTERS to decide what is relayed, logged, or ignored.",
+        ),
+        key(
+            "FILTERS",
+            json!([
+                {
+                    "name": "Default Filter",
+                    "event_codes": ["*"],
+                    "action": "relay"
+                }
+            ]),
+            "Event-code filters, higher priority first and then in order, first match wins. Codes may be TOR, TO*, *W, * or an exclusion like !RWT; optional originators and fips lists limit a rule to those originators and counties, and min_duration_minutes/max_duration_minutes to alerts whose purge time is within those bounds. Actions: relay (which also forwards), forward, log, or ignore.",
+        ),
+        key(
+            "FILTER_SETS",
+            json!({
+                "log_only": [
+                    {
+                        "name": "Log everything",
+                        "event_codes": ["*"],
+                        "action": "log"
+                    }
+                ]
+            }),
+            "Named filter lists, written like FILTERS, that a stream entry picks with \"filters\": \"<name>\" instead of FILTERS, such as one that never relays what is heard off another station.",
+        ),
+        key(
+            "DEFAULT_FILTER_ACTION",
+            json!("relay"),
+            "What happens to alerts no filter rule matches: relay, forward, log, or ignore.",
+        ),
+        key(
+            "PROCESS_CAP_ALERTS",
+            json!(false),
+            "Poll CAP_ENDPOINTS and turn CAP alerts into audio.",
+        ),
+        key(
+            "CAP_ENDPOINTS",
+            json!([
+                {
+                    "name": "ENDEC CAP Endpoint",
+                    "url": "https://apps.fema.gov/IPAWSOPEN_EAS_SERVICE/rest/feed"
+                }
+            ]),
+            "CAP feeds to poll; entries are URLs or objects with name and url.",
+        ),
+        key(
+            "TTS_ENGINE",
+            json!("piper"),
+            "Text-to-speech engine for CAP alerts: speechify, piper, or espeak-ng.",
+        ),
+        key(
+            "TTS_MODEL",
+            json!(""),
+            "Voice model for the TTS engine; leave empty for the engine default.",
+        ),
+        key(
+            "PREFERRED_SENDERID",
+            json!(""),
+            "Sender ID to use in generated headers; leave empty to keep the original.",
+        ),
+        key(
+            "SHOULD_RELAY",
+            json!(false),
+            "Relay received alerts to the destinations below.",
+        ),
+        key(
+            "SHOULD_RELAY_ICECAST",
+            json!(false),
+            "Relay alert audio to ICECAST_RELAY.",
+        ),
+        key(
+            "ICECAST_RELAY",
+            json!(""),
+            "Icecast source URL to relay to; REQUIRED when SHOULD_RELAY_ICECAST is on.",
+        ),
+        key(
+            "ICECAST_RELAY_METADATA",
+            json!(true),
+            "While an alert is relayed, show \"EAS: <event> — <locations>\" as the relay mount's now-playing title, then put the previous title back.",
+        ),
+        key(
+            "ICECAST_ADMIN_USER",
+            json!(""),
+            "User for Icecast's /admin/metadata endpoint; defaults to the one in ICECAST_RELAY.",
+        ),
+        key(
+            "ICECAST_ADMIN_PASSWORD",
+            json!(""),
+            "Password for Icecast's /admin/metadata endpoint; defaults to the one in ICECAST_RELAY.",
+        ),
+        key(
+            "USE_ICECAST_INTRO_OUTRO",
+            json!(false),
+            "Play ICECAST_INTRO and ICECAST_OUTRO around relayed audio.",
+        ),
+        key(
+            "USE_PRE_POST_ROLL_FOR_RECORDINGS",
+            json!(false),
+            "Add ICECAST_INTRO and ICECAST_OUTRO to saved recordings too.",
+        ),
+        key(
+            "ICECAST_INTRO",
+            json!(""),
+            "Audio file played before relayed alerts.",
+        ),
+        key(
+            "ICECAST_OUTRO",
+            json!(""),
+            "Audio file played after relayed alerts.",
+        ),
+        key(
+            "SHOULD_RELAY_DASDEC",
+            json!(false),
+            "Relay alerts to a DASDEC at DASDEC_URL.",
+        ),
+        key(
+            "DASDEC_URL",
+            json!(""),
+            "DASDEC endpoint to relay to.",
+        ),
+        key(
+            "SHOULD_RELAY_MYOD",
+            json!(false),
+            "Post relayed alerts to a MYOD ingest endpoint at MYOD_URL: a multipart form with the raw header, its decode as JSON and the recording.",
+        ),
+        key("MYOD_URL", json!(""), "MYOD ingest endpoint to post alerts to."),
+        key(
+            "MYOD_TOKEN",
+            json!(""),
+            "Bearer token for MYOD_URL; may be given as MYOD_TOKEN_FILE instead.",
+        ),
+        key(
+            "MYOD_FORWARD",
+            json!(false),
+            "Also post alerts a filter 'forward's to MYOD, not only relayed ones.",
+        ),
+        key(
+            "MYOD_MAX_ATTEMPTS",
+            json!(3),
+            "Tries per MYOD upload, 2 seconds apart and doubling, before it counts as failed.",
+        ),
+        key(
+            "ICECAST_ALERT_STREAM_ENABLED",
+            json!(false),
+            "Publish an alerts-only Icecast stream.",
+        ),
+        key(
+            "ICECAST_ALERT_HOST",
+            json!("127.0.0.1"),
+            "Icecast server host for the alert stream.",
+        ),
+        key(
+            "ICECAST_ALERT_PORT",
+            json!(8000),
+            "Icecast server port for the alert stream.",
+        ),
+        key(
+            "ICECAST_ALERT_MOUNT",
+            json!("/stream.ogg"),
+            "Mount point of the alert stream.",
+        ),
+        key(
+            "ICECAST_ALERT_SOURCE_USER",
+            json!("source"),
+            "Icecast source user for the alert stream.",
+        ),
+        key(
+            "ICECAST_ALERT_SOURCE_PASSWORD",
+            json!("change-me"),
+            "Icecast source password; REQUIRED when ICECAST_ALERT_STREAM_ENABLED is on.",
+        ),
+        key(
+            "ICECAST_ALERT_PUBLIC_URL",
+            json!(""),
+            "Public URL of the alert stream shown on the dashboard.",
+        ),
+    ]
+}
+
+fn pretty_value(value: &Value) -> String {
+    let mut buffer = Vec::new();
+    let mut serializer =
+        Serializer::with_formatter(&mut buffer, PrettyFormatter::with_indent(b"    "));
+    value
+        .serialize(&mut serializer)
+        .expect("serializing a JSON value cannot fail");
+    String::from_utf8(buffer).expect("serde_json writes UTF-8")
+}
+
+fn render_example_config(keys: &[ExampleKey]) -> String {
+    let mut rendered = String::from("{\n");
+    rendered.push_str(&format!(
+        "    \"_comment\": {},\n",
+        Value::from(HEADER_COMMENT)
+    ));
+    for (index, entry) in keys.iter().enumerate() {
+        rendered.push_str(&format!(
+            "    \"_comment_{}\": {},\n",
+            entry.key,
+            Value::from(entry.comment)
+        ));
+        rendered.push_str(&format!(
+            "    \"{}\": {}",
+            entry.key,
+            pretty_value(&entry.value).replace('\n', "\n    ")
+        ));
+        rendered.push_str(if index + 1 < keys.len() { ",\n" } else { "\n" });
+    }
+    rendered.push_str("}\n");
+    rendered
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct InitAnswers {
+    stream_urls: Vec<String>,
+    watched_fips: Vec<String>,
+    dashboard_username: String,
+    dashboard_password: String,
+}
+
+fn read_answer(input: &mut impl BufRead, output: &mut impl Write, prompt: &str) -> Result<String> {
+    write!(output, "{prompt}")?;
+    output.flush()?;
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Err(anyhow!("input ended before init-config finished"));
+    }
+    Ok(line.trim().to_string())
+}
+
+fn validate_stream_url(value: &str) -> Result<(), String> {
+    match reqwest::Url::parse(value) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => Ok(()),
+        Ok(url) => Err(format!(
+            "'{}' streams are not supported; use http or https",
+            url.scheme()
+        )),
+        Err(err) => Err(format!("not a valid URL: {err}")),
+    }
+}
+
+fn prompt_answers(input: &mut impl BufRead, output: &mut impl Write) -> Result<InitAnswers> {
+    writeln!(
+        output,
+        "Enter the stream URLs to monitor, one per line. Leave the line blank when done."
+    )?;
+    let mut stream_urls = Vec::new();
+    loop {
+        let answer = read_answer(
+            input,
+            output,
+            &format!("Stream URL #{}: ", stream_urls.len() + 1),
+        )?;
+        if answer.is_empty() {
+            if stream_urls.is_empty() {
+                writeln!(output, "  At least one stream URL is required.")?;
+                continue;
+            }
+            break;
+        }
+        match validate_stream_url(&answer) {
+            Ok(()) => stream_urls.push(answer),
+            Err(reason) => writeln!(output, "  {reason}")?,
+        }
+    }
+
+    let watched_fips = loop {
+        let answer = read_answer(
+            input,
+            output,
+            "Watched FIPS codes (six digits, comma-separated, e.g. 031055): ",
+        )?;
+        let codes: Vec<String> = answer
+            .split(',')
+            .map(str::trim)
+            .filter(|code| !code.is_empty())
+            .map(str::to_string)
+            .collect();
+        if codes.is_empty() {
+            writeln!(output, "  At least one FIPS code is required.")?;
+            continue;
+        }
+        let unknown: Vec<&str> = codes
+            .iter()
+            .filter(|code| e2t_ng::us_location_name(code).is_none())
+            .map(String::as_str)
+            .collect();
+        if !unknown.is_empty() {
+            writeln!(
+                output,
+                "  Not in the SAME location table: {}",
+                unknown.join(", ")
+            )?;
+            continue;
+        }
+        for code in &codes {
+            if let Some(name) = e2t_ng::us_location_name(code) {
+                writeln!(output, "  {code}: {name}")?;
+            }
+        }
+        break codes;
+    };
+
+    let dashboard_username = match read_answer(input, output, "Dashboard username [admin]: ")? {
+        answer if answer.is_empty() => "admin".to_string(),
+        answer => answer,
+    };
+    let dashboard_password = loop {
+        let answer = read_answer(input, output, "Dashboard password: ")?;
+        if !answer.is_empty() {
+            break answer;
+        }
+        writeln!(output, "  The dashboard password cannot be empty.")?;
+    };
+
+    Ok(InitAnswers {
+        stream_urls,
+        watched_fips,
+        dashboard_username,
+        dashboard_password,
+    })
+}
+
+fn apply_answers(keys: &mut [ExampleKey], answers: &InitAnswers) {
+    for entry in keys {
+        match entry.key {
+            "ICECAST_STREAM_URL_ARRAY" => entry.value = json!(answers.stream_urls),
+            "WATCHED_FIPS" => entry.value = json!(answers.watched_fips.join(",")),
+            "DASHBOARD_USERNAME" => entry.value = json!(answers.dashboard_username),
+            "DASHBOARD_PASSWORD" => entry.value = json!(answers.dashboard_password),
+            _ => {}
+        }
+    }
+}
+
+fn build_config(answers: Option<&InitAnswers>) -> Result<String> {
+    let mut keys = example_keys();
+    if let Some(answers) = answers {
+        apply_answers(&mut keys, answers);
+    }
+    let rendered = render_example_config(&keys);
+    let parsed: Value =
+        serde_json::from_str(&rendered).context("generated configuration is not valid JSON")?;
+    Config::from_config_value(&parsed).context("generated configuration failed validation")?;
+    Ok(rendered)
+}
+
+pub fn run(output_path: Option<PathBuf>, interactive: bool) -> Result<()> {
+    if let Some(path) = output_path.as_ref() {
+        if path.exists() {
+            return Err(anyhow!(
+                "{} already exists; remove it or choose another --output path",
+                path.display()
+            ));
+        }
+    }
+
+    let answers = if interactive {
+        let stdin = io::stdin();
+        Some(prompt_answers(&mut stdin.lock(), &mut io::stderr())?)
+    } else {
+        None
+    };
+    let rendered = build_config(answers.as_ref())?;
+
+    match output_path {
+        Some(path) => {
+            let format = ConfigFormat::from_path(&path);
+            let rendered = match format {
+                ConfigFormat::Json => rendered,
+                _ => format.render(&serde_json::from_str(&rendered)?)?,
+            };
+            std::fs::write(&path, rendered)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            eprintln!("Wrote example configuration to {}", path.display());
+        }
+        None => print!("{rendered}"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+    use std::io::Cursor;
+
+    fn keys_read_by(source: &str) -> BTreeSet<String> {
+        let source = source.split("#[cfg(test)]").next().unwrap_or(source);
+        let mut keys = BTreeSet::new();
+        for marker in ["config_json, \"", ".get(\""] {
+            for (index, _) in source.match_indices(marker) {
+                let rest = &source[index + marker.len()..];
+                let Some(end) = rest.find('"') else {
+                    continue;
+                };
+                let key = &rest[..end];
+                if !key.is_empty()
+                    && key
+                        .bytes()
+                        .all(|byte| byte.is_ascii_uppercase() || byte == b'_')
+                {
+                    keys.insert(key.to_string());
+                }
+            }
+        }
+        keys
+    }
+
+    #[test]
+    fn example_config_covers_every_parsed_key() {
+        let read_by_modules: BTreeSet<String> = [
+            include_str!("api_tokens.rs"),
+            include_str!("email.rs"),
+            include_str!("filter.rs"),
+            include_str!("generic_webhook.rs"),
+        ]
+        .into_iter()
+        .flat_map(keys_read_by)
+        .collect();
+        let declared: BTreeSet<String> = crate::config::MODULE_CONFIG_KEYS
+            .iter()
+            .map(|key| key.to_string())
+            .collect();
+        assert_eq!(
+            read_by_modules, declared,
+            "MODULE_CONFIG_KEYS is out of date"
+        );
+        let parsed: BTreeSet<String> = crate::config::raw_config_keys()
+            .iter()
+            .map(|key| key.to_string())
+            .chain(declared)
+            .collect();
+        assert!(parsed.contains("ICECAST_STREAM_URL_ARRAY"));
+        assert!(parsed.contains("TZ"));
+
+        let generated: BTreeSet<String> = example_keys()
+            .iter()
+            .map(|entry| entry.key.to_string())
+            .collect();
+        let missing: Vec<_> = parsed.difference(&generated).collect();
+        let unknown: Vec<_> = generated.difference(&parsed).collect();
+        assert!(
+            missing.is_empty(),
+            "init-config is missing keys: {missing:?}"
+        );
+        assert!(
+            unknown.is_empty(),
+            "init-config writes unknown keys: {unknown:?}"
+        );
+    }
+
+    #[test]
+    fn generated_config_round_trips_through_the_loader() {
+        let rendered = build_config(None).expect("default config");
+        let file = tempfile::NamedTempFile::new().expect("temp file");
+        std::fs::write(file.path(), &rendered).expect("write config");
+        let cfg = Config::from_file(file.path()).expect("load");
+        assert_eq!(
+            cfg.streams
+                .iter()
+                .map(|stream| stream.url.as_str())
+                .collect::<Vec<_>>(),
+            vec!["http://icecast.example.com:8000/stream.mp3"]
+        );
+        assert!(cfg.watched_fips.contains("031055"));
+        assert_eq!(cfg.monitoring_bind_addr.to_string(), "0.0.0.0:8080");
+        assert_eq!(cfg.tts_model, None);
+
+        let raw: Value = serde_json::from_str(&rendered).expect("json");
+        assert!(raw["_comment_WATCHED_FIPS"]
+            .as_str()
+            .is_some_and(|comment| comment.starts_with("REQUIRED")));
+    }
+
+    #[test]
+    fn interactive_answers_are_validated_and_applied() {
+        let mut input = Cursor::new(
+            "ftp://example.com/stream\nhttp://radio.example/stream1.mp3\n\n923456, 031055\n031055,131153\n\n\nhunter2\n",
+        );
+        let mut prompts = Vec::new();
+        let answers = prompt_answers(&mut input, &mut prompts).expect("answers");
+        let prompts = String::from_utf8(prompts).expect("utf8");
+        assert!(prompts.contains("'ftp' streams are not supported"));
+        assert!(prompts.contains("Not in the SAME location table: 923456"));
+        assert!(prompts.contains("031055: Douglas County, NE"));
+        assert_eq!(
+            answers,
+            InitAnswers {
+                stream_urls: vec!["http://radio.example/stream1.mp3".to_string()],
+                watched_fips: vec!["031055".to_string(), "131153".to_string()],
+                dashboard_username: "admin".to_string(),
+                dashboard_password: "hunter2".to_string(),
+            }
+        );
+
+        let rendered = build_config(Some(&answers)).expect("config");
+        let cfg = Config::from_config_value(&serde_json::from_str(&rendered).expect("json"))
+            .expect("load");
+        assert_eq!(
+            cfg.streams
+                .iter()
+                .map(|stream| stream.url.clone())
+                .collect::<Vec<_>>(),
+            answers.stream_urls
+        );
+        assert!(cfg.watched_fips.contains("131153"));
+        assert_eq!(cfg.dashboard_password, "hunter2");
+
+        assert!(prompt_answers(&mut Cursor::new(""), &mut Vec::new()).is_err());
+    }
+}
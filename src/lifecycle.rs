@@ -0,0 +1,282 @@
+use crate::monitoring::{read_state_file, write_state_file};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use once_cell::sync::{Lazy, OnceCell};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::backtrace::Backtrace;
+use std::io::Write;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::time::Duration;
+use tracing::{error, warn};
+
+const SHUTDOWN_STATE_FILE: &str = "shutdown_reason.json";
+const CRASH_LOG_FILE: &str = "crash.log";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShutdownKind {
+    Running,
+    Signal,
+    TaskExited,
+    Panic,
+    Unclean,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShutdownRecord {
+    pub kind: ShutdownKind,
+    pub reason: String,
+    pub started_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stopped_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub panic: Option<String>,
+    pub version: String,
+}
+
+impl ShutdownRecord {
+    fn new(kind: ShutdownKind, reason: String, started_at: DateTime<Utc>) -> Self {
+        Self {
+            kind,
+            reason,
+            started_at,
+            stopped_at: (kind != ShutdownKind::Running).then(Utc::now),
+            panic: None,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrashRecord {
+    pub at: DateTime<Utc>,
+    pub instance_started_at: DateTime<Utc>,
+    pub message: String,
+    pub backtrace: String,
+    pub version: String,
+}
+
+static PREVIOUS_SHUTDOWN: OnceCell<Option<ShutdownRecord>> = OnceCell::new();
+static PREVIOUS_CRASH: OnceCell<Option<CrashRecord>> = OnceCell::new();
+static LAST_PANIC: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+static API_LISTENING: AtomicBool = AtomicBool::new(false);
+
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+pub struct Heartbeat(AtomicI64);
+
+impl Heartbeat {
+    const fn new() -> Self {
+        Self(AtomicI64::new(0))
+    }
+
+    pub fn beat(&self) {
+        self.0
+            .store(Utc::now().timestamp_millis(), Ordering::Relaxed);
+    }
+
+    pub fn fresh_at(&self, now: DateTime<Utc>, max_age: Duration) -> bool {
+        let last = self.0.load(Ordering::Relaxed);
+        let max_age = i64::try_from(max_age.as_millis()).unwrap_or(i64::MAX);
+        last > 0 && now.timestamp_millis().saturating_sub(last) <= max_age
+    }
+}
+
+pub static AUDIO_PROCESSOR_HEARTBEAT: Heartbeat = Heartbeat::new();
+pub static ALERT_MANAGER_HEARTBEAT: Heartbeat = Heartbeat::new();
+
+pub fn note_api_listening() {
+    API_LISTENING.store(true, Ordering::Relaxed);
+}
+
+pub fn api_listening() -> bool {
+    API_LISTENING.load(Ordering::Relaxed)
+}
+
+pub fn begin_shutdown() {
+    SHUTTING_DOWN.store(true, Ordering::Relaxed);
+}
+
+pub fn shutting_down() -> bool {
+    SHUTTING_DOWN.load(Ordering::Relaxed)
+}
+
+pub fn begin(state_dir: &Path, started_at: DateTime<Utc>) -> Option<ShutdownRecord> {
+    let previous =
+        read_state_file::<ShutdownRecord>(state_dir, SHUTDOWN_STATE_FILE).map(|mut record| {
+            if record.kind == ShutdownKind::Running {
+                record.kind = ShutdownKind::Unclean;
+                record.reason =
+                    "Stopped without shutting down (killed, out of memory or a host restart)"
+                        .to_string();
+            }
+            record
+        });
+    let marker = ShutdownRecord::new(ShutdownKind::Running, "Running".to_string(), started_at);
+    if let Err(err) = write_state_file(state_dir, SHUTDOWN_STATE_FILE, &marker) {
+        warn!("Failed to write the shutdown marker: {:#}", err);
+    }
+    let crash = previous
+        .as_ref()
+        .and_then(|previous| crash_of_instance(state_dir, previous.started_at));
+    let _ = PREVIOUS_SHUTDOWN.set(previous.clone());
+    let _ = PREVIOUS_CRASH.set(crash);
+    previous
+}
+
+pub fn previous_crash() -> Option<CrashRecord> {
+    PREVIOUS_CRASH.get().cloned().flatten()
+}
+
+fn append_crash(state_dir: &Path, record: &CrashRecord) -> Result<()> {
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(state_dir.join(CRASH_LOG_FILE))?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+fn crash_of_instance(state_dir: &Path, started_at: DateTime<Utc>) -> Option<CrashRecord> {
+    let contents = std::fs::read_to_string(state_dir.join(CRASH_LOG_FILE)).ok()?;
+    contents
+        .lines()
+        .rev()
+        .find_map(|line| serde_json::from_str::<CrashRecord>(line).ok())
+        .filter(|crash| crash.instance_started_at == started_at)
+}
+
+pub fn previous_shutdown() -> Option<ShutdownRecord> {
+    PREVIOUS_SHUTDOWN.get().cloned().flatten()
+}
+
+pub fn record_shutdown(
+    state_dir: &Path,
+    started_at: DateTime<Utc>,
+    kind: ShutdownKind,
+    reason: &str,
+) -> Result<()> {
+    let mut record = ShutdownRecord::new(kind, reason.to_string(), started_at);
+    record.panic = LAST_PANIC.lock().clone();
+    write_state_file(state_dir, SHUTDOWN_STATE_FILE, &record)
+}
+
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    let payload = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Box<dyn Any>".to_string());
+    let thread = std::thread::current();
+    let location = info
+        .location()
+        .map(|location| format!(" at {location}"))
+        .unwrap_or_default();
+    format!(
+        "thread '{}' panicked{location}: {payload}",
+        thread.name().unwrap_or("<unnamed>")
+    )
+}
+
+pub fn install_panic_hook(state_dir: PathBuf, started_at: DateTime<Utc>) {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = panic_message(info);
+        let backtrace = Backtrace::force_capture().to_string();
+        let crash = CrashRecord {
+            at: Utc::now(),
+            instance_started_at: started_at,
+            message: message.clone(),
+            backtrace: backtrace.clone(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        };
+        if let Err(err) = append_crash(&state_dir, &crash) {
+            eprintln!("Failed to append the panic to {CRASH_LOG_FILE}: {err:#}");
+        }
+        *LAST_PANIC.lock() = Some(message.clone());
+        let record = ShutdownRecord::new(ShutdownKind::Panic, message.clone(), started_at);
+        if let Err(err) = write_state_file(&state_dir, SHUTDOWN_STATE_FILE, &record) {
+            eprintln!("Failed to record the panic as the shutdown reason: {err:#}");
+        }
+        error!("{}\n{}", message, backtrace);
+        default_hook(info);
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_missing_shutdown_reason_is_reported_as_an_unclean_exit() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let first_start = Utc::now();
+        assert_eq!(begin(dir.path(), first_start), None);
+
+        let previous = begin(dir.path(), Utc::now()).expect("marker");
+        assert_eq!(previous.kind, ShutdownKind::Unclean);
+        assert_eq!(previous.started_at, first_start);
+        assert_eq!(previous.stopped_at, None);
+
+        let started_at = Utc::now();
+        record_shutdown(
+            dir.path(),
+            started_at,
+            ShutdownKind::Signal,
+            "Received SIGTERM",
+        )
+        .expect("record");
+        let previous = begin(dir.path(), Utc::now()).expect("record");
+        assert_eq!(previous.kind, ShutdownKind::Signal);
+        assert_eq!(previous.reason, "Received SIGTERM");
+        assert_eq!(previous.started_at, started_at);
+        assert!(previous.stopped_at.is_some());
+    }
+
+    #[test]
+    fn only_the_previous_instances_crash_is_reported() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let previous_instance = Utc::now();
+        assert_eq!(crash_of_instance(dir.path(), previous_instance), None);
+
+        let crash = |instance_started_at, message: &str| CrashRecord {
+            at: Utc::now(),
+            instance_started_at,
+            message: message.to_string(),
+            backtrace: "disabled backtrace".to_string(),
+            version: "test".to_string(),
+        };
+        let older_instance = previous_instance - chrono::Duration::days(1);
+        append_crash(dir.path(), &crash(older_instance, "older")).expect("append");
+        assert_eq!(crash_of_instance(dir.path(), previous_instance), None);
+
+        append_crash(dir.path(), &crash(previous_instance, "first")).expect("append");
+        append_crash(dir.path(), &crash(previous_instance, "latest")).expect("append");
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(dir.path().join(CRASH_LOG_FILE))
+            .and_then(|mut file| file.write_all(b"{\"truncated"))
+            .expect("append partial line");
+        let latest = crash_of_instance(dir.path(), previous_instance).expect("crash");
+        assert_eq!(latest.message, "latest");
+    }
+
+    #[test]
+    fn heartbeats_go_stale_after_their_max_age() {
+        let heartbeat = Heartbeat::new();
+        let now = Utc::now();
+        assert!(!heartbeat.fresh_at(now, Duration::from_secs(30)));
+
+        heartbeat.beat();
+        let now = Utc::now();
+        assert!(heartbeat.fresh_at(now, Duration::from_secs(30)));
+        assert!(!heartbeat.fresh_at(now + chrono::Duration::seconds(31), Duration::from_secs(30)));
+    }
+}
@@ -0,0 +1,299 @@
+use crate::config::Config;
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+const FIXED_DIRECTIVES: [&str; 2] = ["symphonia=error", "sameold=warn"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogOutput {
+    File,
+    Stdout,
+    Dashboard,
+}
+
+impl LogOutput {
+    pub const ALL: [LogOutput; 3] = [LogOutput::File, LogOutput::Stdout, LogOutput::Dashboard];
+
+    pub fn config_key(self) -> &'static str {
+        match self {
+            LogOutput::File => "LOG_LEVEL_FILE",
+            LogOutput::Stdout => "LOG_LEVEL_STDOUT",
+            LogOutput::Dashboard => "LOG_LEVEL_DASHBOARD",
+        }
+    }
+}
+
+struct LogFilterState {
+    handles: BTreeMap<LogOutput, reload::Handle<EnvFilter, Registry>>,
+    level: String,
+    output_levels: BTreeMap<LogOutput, String>,
+    configured: BTreeMap<String, LevelFilter>,
+    runtime: BTreeMap<String, LevelFilter>,
+    output_runtime: BTreeMap<LogOutput, BTreeMap<String, LevelFilter>>,
+}
+
+static STATE: Lazy<Mutex<LogFilterState>> = Lazy::new(|| {
+    Mutex::new(LogFilterState {
+        handles: BTreeMap::new(),
+        level: "info".to_string(),
+        output_levels: BTreeMap::new(),
+        configured: BTreeMap::new(),
+        runtime: BTreeMap::new(),
+        output_runtime: BTreeMap::new(),
+    })
+});
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OutputLoggingStatus {
+    pub level: String,
+    pub runtime: BTreeMap<String, String>,
+    pub effective: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LoggingStatus {
+    pub level: String,
+    pub configured: BTreeMap<String, String>,
+    pub runtime: BTreeMap<String, String>,
+    pub outputs: BTreeMap<LogOutput, OutputLoggingStatus>,
+}
+
+pub fn parse_target_level(target: &str, level: &str) -> Result<(String, LevelFilter)> {
+    let target = target.trim();
+    if target.is_empty()
+        || !target
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-:.".contains(c))
+    {
+        return Err(anyhow!("{target:?} is not a valid log target"));
+    }
+    let level = level.trim().parse::<LevelFilter>().map_err(|_| {
+        anyhow!(
+            "{level:?} is not a log level for {target}; use off, error, warn, info, debug or trace"
+        )
+    })?;
+    Ok((target.to_string(), level))
+}
+
+pub fn parse_level_directives(key: &str, value: &str) -> Result<String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(anyhow!("{key} cannot be empty in your config.json file"));
+    }
+    EnvFilter::builder()
+        .parse(value)
+        .map_err(|err| anyhow!("{key} {value:?} is not a valid log filter: {err}"))?;
+    Ok(value.to_string())
+}
+
+fn directives<'a>(
+    level: &str,
+    overrides: impl IntoIterator<Item = &'a BTreeMap<String, LevelFilter>>,
+) -> String {
+    let level = level.trim();
+    let base = if level.is_empty() || EnvFilter::builder().parse(level).is_err() {
+        "info"
+    } else {
+        level
+    };
+    std::iter::once(base.to_string())
+        .chain(
+            FIXED_DIRECTIVES
+                .iter()
+                .map(|directive| directive.to_string()),
+        )
+        .chain(
+            overrides
+                .into_iter()
+                .flatten()
+                .map(|(target, level)| format!("{target}={level}")),
+        )
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn level_names(levels: &BTreeMap<String, LevelFilter>) -> BTreeMap<String, String> {
+    levels
+        .iter()
+        .map(|(target, level)| (target.clone(), level.to_string()))
+        .collect()
+}
+
+impl LogFilterState {
+    fn level_for(&self, output: LogOutput) -> &str {
+        self.output_levels.get(&output).unwrap_or(&self.level)
+    }
+
+    fn filter(&self, output: LogOutput) -> EnvFilter {
+        let empty = BTreeMap::new();
+        let output_runtime = self.output_runtime.get(&output).unwrap_or(&empty);
+        EnvFilter::builder().parse_lossy(directives(
+            self.level_for(output),
+            [&self.configured, &self.runtime, output_runtime],
+        ))
+    }
+
+    fn apply(&self) {
+        for (output, handle) in &self.handles {
+            if let Err(err) = handle.reload(self.filter(*output)) {
+                eprintln!("Failed to apply the {output:?} log filter: {err}");
+            }
+        }
+    }
+
+    fn status(&self) -> LoggingStatus {
+        LoggingStatus {
+            level: self.level.clone(),
+            configured: level_names(&self.configured),
+            runtime: level_names(&self.runtime),
+            outputs: LogOutput::ALL
+                .into_iter()
+                .map(|output| {
+                    let status = OutputLoggingStatus {
+                        level: self.level_for(output).to_string(),
+                        runtime: self
+                            .output_runtime
+                            .get(&output)
+                            .map(level_names)
+                            .unwrap_or_default(),
+                        effective: self.filter(output).to_string(),
+                    };
+                    (output, status)
+                })
+                .collect(),
+        }
+    }
+
+    fn take_config(&mut self, config: &Config) -> bool {
+        if self.level == config.log_level
+            && self.output_levels == config.log_output_levels
+            && self.configured == config.log_target_levels
+        {
+            return false;
+        }
+        self.level = config.log_level.clone();
+        self.output_levels = config.log_output_levels.clone();
+        self.configured = config.log_target_levels.clone();
+        true
+    }
+}
+
+pub struct OutputFilters {
+    pub file: reload::Layer<EnvFilter, Registry>,
+    pub stdout: reload::Layer<EnvFilter, Registry>,
+    pub dashboard: reload::Layer<EnvFilter, Registry>,
+}
+
+pub fn output_filters(config: &Config) -> OutputFilters {
+    let mut state = STATE.lock();
+    state.take_config(config);
+    let mut layer_for = |output: LogOutput| {
+        let (layer, handle) = reload::Layer::new(state.filter(output));
+        state.handles.insert(output, handle);
+        layer
+    };
+    OutputFilters {
+        file: layer_for(LogOutput::File),
+        stdout: layer_for(LogOutput::Stdout),
+        dashboard: layer_for(LogOutput::Dashboard),
+    }
+}
+
+pub fn apply_config(config: &Config) {
+    let mut state = STATE.lock();
+    if state.take_config(config) {
+        state.apply();
+    }
+}
+
+pub fn set_runtime_levels(
+    output: Option<LogOutput>,
+    changes: &BTreeMap<String, Option<LevelFilter>>,
+) -> LoggingStatus {
+    let mut state = STATE.lock();
+    let runtime = match output {
+        Some(output) => state.output_runtime.entry(output).or_default(),
+        None => &mut state.runtime,
+    };
+    for (target, level) in changes {
+        match level {
+            Some(level) => runtime.insert(target.clone(), *level),
+            None => runtime.remove(target),
+        };
+    }
+    state.apply();
+    state.status()
+}
+
+pub fn clear_runtime_levels<'a>(targets: impl IntoIterator<Item = &'a String>) -> LoggingStatus {
+    let mut state = STATE.lock();
+    for target in targets {
+        state.runtime.remove(target);
+    }
+    state.apply();
+    state.status()
+}
+
+pub fn status() -> LoggingStatus {
+    STATE.lock().status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runtime_levels_override_configured_ones_after_the_base_level() {
+        assert!(parse_target_level("eas_listener::audio", "DEBUG").is_ok());
+        assert!(parse_target_level("eas listener", "debug").is_err());
+        assert!(parse_target_level("eas_listener", "loud").is_err());
+
+        let configured = BTreeMap::from([("eas_listener::audio".to_string(), LevelFilter::WARN)]);
+        let runtime = BTreeMap::from([("eas_listener::audio".to_string(), LevelFilter::DEBUG)]);
+        let directives = directives("INFO", [&configured, &runtime]);
+        assert_eq!(
+            directives,
+            "INFO,symphonia=error,sameold=warn,eas_listener::audio=warn,eas_listener::audio=debug"
+        );
+        let filter = EnvFilter::builder().parse(&directives).expect("directives");
+        assert!(filter.to_string().contains("eas_listener::audio=debug"));
+        assert!(!filter.to_string().contains("eas_listener::audio=warn"));
+    }
+
+    #[test]
+    fn each_output_has_its_own_level_and_overrides() {
+        let mut state = LogFilterState {
+            handles: BTreeMap::new(),
+            level: "info".to_string(),
+            output_levels: BTreeMap::from([
+                (LogOutput::File, "debug".to_string()),
+                (LogOutput::Dashboard, "warn".to_string()),
+            ]),
+            configured: BTreeMap::new(),
+            runtime: BTreeMap::new(),
+            output_runtime: BTreeMap::new(),
+        };
+        state.output_runtime.insert(
+            LogOutput::Stdout,
+            BTreeMap::from([("eas_listener::relay".to_string(), LevelFilter::TRACE)]),
+        );
+
+        let status = state.status();
+        let effective = |output| status.outputs[&output].effective.clone();
+        assert!(effective(LogOutput::File).contains("debug"));
+        assert!(effective(LogOutput::Stdout).contains("eas_listener::relay=trace"));
+        assert!(!effective(LogOutput::File).contains("eas_listener::relay"));
+        assert!(effective(LogOutput::Dashboard).contains("warn"));
+        for output in LogOutput::ALL {
+            assert!(effective(output).contains("symphonia=error"), "{output:?}");
+        }
+        assert!(parse_level_directives("LOG_LEVEL_FILE", "info,eas_listener=debug").is_ok());
+        assert!(parse_level_directives("LOG_LEVEL_FILE", "eas_listener=loud").is_err());
+    }
+}
@@ -1,47 +1,143 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
 use monitoring::{MonitoringHub, MonitoringLayer};
+use once_cell::sync::Lazy;
 use recording::RecordingState;
 use std::collections::HashMap;
 use std::io::ErrorKind;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{broadcast, mpsc, Mutex};
-use tracing::level_filters::LevelFilter;
 use tracing::{info, warn};
-use tracing_subscriber::filter as other_filter;
 use tracing_subscriber::fmt::time::ChronoLocal;
 use tracing_subscriber::prelude::*;
-use tracing_subscriber::EnvFilter;
 
+mod alert_log;
 mod alerts;
+mod api_tls;
+mod api_tokens;
 mod audio;
+mod audit;
+mod auth_lockout;
 mod backend;
+mod build_info;
 mod cap;
+mod cap_xml;
+mod check_config;
 mod cleanup;
 mod config;
+mod credentials;
+mod dashboard;
 mod db;
+mod dependencies;
 mod e2t_ng;
+mod email;
+mod file_stream;
 mod filter;
+mod generic_webhook;
 mod header;
 mod icecast;
+mod init_config;
+mod lifecycle;
+mod logging;
 mod monitoring;
+mod mqtt;
 mod nws_bulletin;
+mod nws_enrichment;
 mod recording;
 mod relay;
+mod reload;
+mod resources;
+mod sessions;
+mod share;
 mod state;
+mod stream_health;
+mod supervisor;
+#[cfg(unix)]
+mod systemd;
+mod upload;
 mod webhook;
+mod webhook_capabilities;
 
 use config::Config;
+use filter::{FilterHandle, Filters};
 use state::AppState;
 
-const CONFIG_PATH: &str = "/app/config.json";
-const RELOAD_SIGNAL_PATH: &str = "/app/reload_signal";
+const DEFAULT_CONFIG_PATH: &str = "/app/config.json";
+const DEFAULT_RELOAD_SIGNAL_PATH: &str = "/app/reload_signal";
 const TEST_ALERT_SIGNAL_PATH: &str = "/app/test_alert_signal";
 const WEB_RUNTIME_CONFIG_PATH: &str = "/app/web_config.json";
 const WEB_RUNTIME_CONFIG_FALLBACK_PATH: &str = "web_server/web_config.json";
 const TEST_ALERT_STREAM_ID: &str = "Manual Test Alert";
-const TEST_ALERT_RECORDING_SECS: u64 = 8;
+const CONFIG_WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+pub(crate) const TEST_ALERT_RECORDING_SECS: u64 = 8;
+
+static LAST_APPLIED_CONFIG: Lazy<parking_lot::Mutex<Option<serde_json::Value>>> =
+    Lazy::new(|| parking_lot::Mutex::new(None));
+
+#[derive(Debug, Parser)]
+#[command(version)]
+struct Cli {
+    #[arg(long, value_name = "PATH", env = "EAS_CONFIG_PATH")]
+    config: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        default_value = DEFAULT_RELOAD_SIGNAL_PATH,
+        help = "The file whose touch reloads the configuration, with RELOAD_SIGNAL_FILE_ENABLED"
+    )]
+    reload_signal: PathBuf,
+    #[arg(
+        long,
+        value_name = "PATH",
+        num_args = 0..=1,
+        help = "Validate the config (PATH, or the one --config picks) and exit: 0 if valid, 1 if not"
+    )]
+    check_config: Option<Option<PathBuf>>,
+    #[arg(
+        long,
+        requires = "check_config",
+        help = "Print the --check-config report as JSON"
+    )]
+    json: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    InitConfig {
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+        #[arg(short, long)]
+        interactive: bool,
+    },
+}
+
+impl Cli {
+    fn config_path(&self) -> Result<PathBuf> {
+        let Some(path) = self.config.as_ref() else {
+            return Ok(PathBuf::from(DEFAULT_CONFIG_PATH));
+        };
+        match std::fs::metadata(path) {
+            Ok(metadata) if metadata.is_file() => Ok(path.clone()),
+            Ok(_) => Err(anyhow!("Config path {} is not a file", path.display())),
+            Err(err) if err.kind() == ErrorKind::NotFound => Err(anyhow!(
+                "Config file {} does not exist; create one with `eas_listener init-config --output {}`",
+                path.display(),
+                path.display()
+            )),
+            Err(err) => Err(anyhow!("Cannot read config file {}: {}", path.display(), err)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ConfigPaths {
+    pub config: PathBuf,
+    pub reload_signal: PathBuf,
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ConfigSource {
@@ -49,16 +145,17 @@ enum ConfigSource {
     BuiltInDefault,
 }
 
-fn load_config_with_fallback(config_path: &str) -> (Config, ConfigSource, Option<String>) {
+fn load_config_with_fallback(config_path: &Path) -> (Config, ConfigSource, Option<String>) {
+    let display = config_path.display();
     match std::fs::metadata(config_path) {
-        Ok(_) => match Config::from_config_json(config_path) {
+        Ok(_) => match Config::from_file(config_path) {
             Ok(config) => (config, ConfigSource::File, None),
             Err(err) => (
                 Config::safe_internal_defaults(),
                 ConfigSource::BuiltInDefault,
                 Some(format!(
                     "Configuration file '{}' is invalid: {:?}. Using built-in safe defaults.",
-                    config_path, err
+                    display, err
                 )),
             ),
         },
@@ -67,7 +164,7 @@ fn load_config_with_fallback(config_path: &str) -> (Config, ConfigSource, Option
             ConfigSource::BuiltInDefault,
             Some(format!(
                 "Configuration file '{}' was not found. Using built-in safe defaults.",
-                config_path
+                display
             )),
         ),
         Err(err) => (
@@ -75,15 +172,14 @@ fn load_config_with_fallback(config_path: &str) -> (Config, ConfigSource, Option
             ConfigSource::BuiltInDefault,
             Some(format!(
                 "Failed to access configuration file '{}': {}. Using built-in safe defaults.",
-                config_path, err
+                display, err
             )),
         ),
     }
 }
 
-fn load_raw_config_json(config_path: &str) -> Option<serde_json::Value> {
-    let payload = std::fs::read_to_string(config_path).ok()?;
-    serde_json::from_str::<serde_json::Value>(&payload).ok()
+fn load_raw_config_document(config_path: &Path) -> Option<serde_json::Value> {
+    config::read_config_document(config_path).ok()
 }
 
 fn boolish_value(value: &serde_json::Value) -> Option<bool> {
@@ -131,6 +227,10 @@ fn build_web_runtime_config_payload(
         "DASHBOARD_PASSWORD".to_string(),
         serde_json::Value::String(config.dashboard_password.clone()),
     );
+    map.insert(
+        "DASHBOARD_PASSWORD_HASH".to_string(),
+        serde_json::Value::String(config.dashboard_password_hash.clone().unwrap_or_default()),
+    );
     map.insert(
         "SHARED_STATE_DIR".to_string(),
         serde_json::Value::String(config.shared_state_dir.to_string_lossy().to_string()),
@@ -152,6 +252,10 @@ fn build_web_runtime_config_payload(
         "ALERT_DATABASE_FILE".to_string(),
         serde_json::Value::String(config.alert_database_file.to_string_lossy().to_string()),
     );
+    map.insert(
+        "MONITORING_ENABLED".to_string(),
+        serde_json::Value::Bool(config.monitoring_enabled),
+    );
     map.insert(
         "MONITORING_BIND_PORT".to_string(),
         serde_json::Value::Number(serde_json::Number::from(config.monitoring_bind_port as u64)),
@@ -174,10 +278,9 @@ fn build_web_runtime_config_payload(
         "ICECAST_STREAM_URL_ARRAY".to_string(),
         serde_json::Value::Array(
             config
-                .icecast_stream_urls
+                .streams
                 .iter()
-                .cloned()
-                .map(serde_json::Value::String)
+                .map(|stream| serde_json::Value::String(stream.url.clone()))
                 .collect(),
         ),
     );
@@ -202,12 +305,19 @@ fn build_web_runtime_config_payload(
         serde_json::Value::Bool(alert_sound_enabled),
     );
 
-    if !map.contains_key("ICECAST_STREAM_URL_MAPPING") {
-        map.insert(
-            "ICECAST_STREAM_URL_MAPPING".to_string(),
-            serde_json::Value::Object(serde_json::Map::new()),
-        );
+    let mut stream_names = match map.remove("ICECAST_STREAM_URL_MAPPING") {
+        Some(serde_json::Value::Object(mapping)) => mapping,
+        _ => serde_json::Map::new(),
+    };
+    for stream in &config.streams {
+        if let Some(name) = &stream.name {
+            stream_names.insert(stream.id.clone(), serde_json::Value::String(name.clone()));
+        }
     }
+    map.insert(
+        "ICECAST_STREAM_URL_MAPPING".to_string(),
+        serde_json::Value::Object(stream_names),
+    );
 
     map.insert(
         "ICECAST_ALERT_STREAM_ENABLED".to_string(),
@@ -246,8 +356,8 @@ fn write_atomic_text_file(path: &str, contents: &str) -> std::io::Result<()> {
     Ok(())
 }
 
-fn sync_web_runtime_config(config: &Config) {
-    let raw_config = load_raw_config_json(CONFIG_PATH);
+fn sync_web_runtime_config(config: &Config, config_path: &Path) {
+    let raw_config = load_raw_config_document(config_path);
     let payload = build_web_runtime_config_payload(config, raw_config.as_ref());
     let serialized = match serde_json::to_string_pretty(&payload) {
         Ok(serialized) => serialized,
@@ -276,7 +386,28 @@ fn sync_web_runtime_config(config: &Config) {
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let (config, config_source, config_warning) = load_config_with_fallback(CONFIG_PATH);
+    let cli = Cli::parse();
+    if let Some(Command::InitConfig {
+        output,
+        interactive,
+    }) = cli.command
+    {
+        return init_config::run(output, interactive);
+    }
+    if let Some(path) = &cli.check_config {
+        let path = path
+            .clone()
+            .or_else(|| cli.config.clone())
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH));
+        let valid = check_config::run(&path, cli.json);
+        std::process::exit(if valid { 0 } else { 1 });
+    }
+    let paths = ConfigPaths {
+        config: cli.config_path()?,
+        reload_signal: cli.reload_signal,
+    };
+
+    let (config, config_source, config_warning) = load_config_with_fallback(&paths.config);
 
     if let Err(err) = std::fs::create_dir_all(&config.shared_state_dir) {
         eprintln!(
@@ -294,60 +425,88 @@ async fn main() -> Result<()> {
     let monitoring = MonitoringHub::new(
         config.monitoring_max_log_entries,
         Duration::from_secs(config.monitoring_activity_window_secs),
-    );
+    )
+    .with_event_capacity(config.monitoring_event_channel_capacity)
+    .with_stream_history_limit(config.stream_history_max_entries);
 
     let timer = ChronoLocal::new("%Y-%m-%d %I:%M:%S.%3f %p ".to_string());
     let file_appender =
         tracing_appender::rolling::daily(&config.shared_state_dir, &config.alert_log_file);
     let (non_blocking_file, _guard) = tracing_appender::non_blocking(file_appender);
-    let env_filter = EnvFilter::from_default_env();
-    let log_level = config
-        .log_level
-        .parse::<LevelFilter>()
-        .unwrap_or(LevelFilter::INFO);
     let monitoring_layer = MonitoringLayer::new(monitoring.clone());
-    let filter = other_filter::Targets::new()
-        .with_default(log_level)
-        .with_target("symphonia", tracing::Level::ERROR)
-        .with_target("sameold", tracing::Level::WARN);
 
+    let filters = logging::output_filters(&config);
     tracing_subscriber::registry()
-        .with(env_filter)
-        .with(
+        .with(vec![
             tracing_subscriber::fmt::layer()
                 .with_writer(non_blocking_file)
                 .with_ansi(false)
-                .with_timer(timer.clone()),
-        )
-        .with(
+                .with_timer(timer.clone())
+                .with_filter(filters.file)
+                .boxed(),
             tracing_subscriber::fmt::layer()
                 .with_writer(std::io::stdout)
-                .with_timer(timer),
-        )
-        .with(monitoring_layer)
-        .with(filter)
+                .with_timer(timer)
+                .with_filter(filters.stdout)
+                .boxed(),
+            monitoring_layer.with_filter(filters.dashboard).boxed(),
+        ])
         .init();
+    lifecycle::install_panic_hook(config.shared_state_dir.clone(), monitoring.started_at());
 
     if config_source == ConfigSource::BuiltInDefault {
         if let Some(message) = config_warning.as_deref() {
             warn!("{}", message);
         }
     } else {
-        info!("Loaded configuration from {}", CONFIG_PATH);
+        info!("Loaded configuration from {}", paths.config.display());
+    }
+    for warning in &config.warnings {
+        warn!("{}", warning);
+    }
+    monitoring.load_persisted(&config.shared_state_dir);
+    if let Some(previous) = lifecycle::begin(&config.shared_state_dir, monitoring.started_at()) {
+        info!(
+            "Previous instance stopped ({:?}): {}",
+            previous.kind, previous.reason
+        );
+    }
+    if let Some(crash) = lifecycle::previous_crash() {
+        warn!(
+            "Previous instance panicked at {}: {}",
+            crash.at, crash.message
+        );
     }
 
     webhook::apply_runtime_config(&config);
-    sync_web_runtime_config(&config);
+    sync_web_runtime_config(&config, &paths.config);
+    remember_applied_config(&paths.config);
 
     let db = db::DbHandle::open(&config.alert_database_file)?;
     if let Err(err) = db.migrate_legacy_log(&config.dedicated_alert_log_file, &config.recording_dir)
     {
         warn!("Legacy alert log migration failed: {}", err);
     }
-
-    info!("Starting EAS Listener...");
-
-    let app_state = Arc::new(Mutex::new(AppState::new(config.filters.clone())));
+    recording::recover_partial_recordings(&config.recording_dir);
+    cleanup::sweep_orphaned_files(&config);
+
+    info!("Starting {}...", build_info::build_info());
+
+    log_filter_config(&config);
+    if config.dependency_check_enabled {
+        let report = dependencies::probe(&config).await;
+        let failed: Vec<&str> = report.failures().map(|check| check.name).collect();
+        if !failed.is_empty() && config.dependency_check_fatal {
+            return Err(anyhow!(
+                "Dependency check failed for {} (DEPENDENCY_CHECK_FATAL is set)",
+                failed.join(", ")
+            ));
+        }
+    }
+    let mut initial_state = AppState::new(FilterHandle::new(Filters::from_config(&config)));
+    initial_state.set_max_active_alerts(config.max_active_alerts);
+    initial_state.set_recent_alerts_limit(config.recent_alerts_limit);
+    let app_state = Arc::new(Mutex::new(initial_state));
     let recording_state = Arc::new(Mutex::new(HashMap::<String, RecordingState>::new()));
 
     let (tx, rx) = mpsc::channel::<(String, String, String, String, Duration, String)>(32);
@@ -356,147 +515,508 @@ async fn main() -> Result<()> {
 
     let test_alert_tx = tx.clone();
     let test_alert_nnnn_tx = nnnn_tx.clone();
+    let injected_alert_tx = tx.clone();
+
+    let mut supervisor = supervisor::Supervisor::new();
+    supervisor.essential(
+        "Audio processor",
+        tokio::spawn(audio::run_audio_processor(
+            config.clone(),
+            tx,
+            recording_state.clone(),
+            nnnn_tx.clone(),
+            monitoring.clone(),
+            app_state.clone(),
+            reload_tx.subscribe(),
+        )),
+    );
+    supervisor.essential(
+        "Alert manager",
+        tokio::spawn(alerts::run_alert_manager(
+            config.clone(),
+            app_state.clone(),
+            rx,
+            recording_state.clone(),
+            nnnn_tx.subscribe(),
+            monitoring.clone(),
+            reload_tx.subscribe(),
+            db.clone(),
+        )),
+    );
+    supervisor.restartable("State cleanup", &config, {
+        let (app_state, monitoring, reload_tx) =
+            (app_state.clone(), monitoring.clone(), reload_tx.clone());
+        move |config| {
+            tokio::spawn(alerts::run_state_cleanup(
+                config.clone(),
+                app_state.clone(),
+                monitoring.clone(),
+                reload_tx.subscribe(),
+            ))
+        }
+    });
+    supervisor.restartable("Log cleanup", &config, {
+        let reload_tx = reload_tx.clone();
+        move |config| {
+            tokio::spawn(cleanup::run_log_cleanup(
+                config.clone(),
+                reload_tx.subscribe(),
+            ))
+        }
+    });
+    supervisor.restartable("Recording cleanup", &config, {
+        let (recording_state, reload_tx) = (recording_state.clone(), reload_tx.clone());
+        move |config| {
+            tokio::spawn(cleanup::run_recording_cleanup(
+                config.clone(),
+                recording_state.clone(),
+                reload_tx.subscribe(),
+            ))
+        }
+    });
+    supervisor.restartable("Recording uploader", &config, {
+        let (app_state, db, reload_tx) = (app_state.clone(), db.clone(), reload_tx.clone());
+        move |config| {
+            tokio::spawn(upload::run_uploader(
+                config.clone(),
+                app_state.clone(),
+                db.clone(),
+                reload_tx.subscribe(),
+            ))
+        }
+    });
+    supervisor.restartable("Reload handler", &config, {
+        let (paths, app_state, reload_tx) = (paths.clone(), app_state.clone(), reload_tx.clone());
+        move |config| {
+            tokio::spawn(run_reload_handler(
+                paths.clone(),
+                config.reload_signal_file_enabled,
+                app_state.clone(),
+                reload_tx.clone(),
+            ))
+        }
+    });
+    supervisor.restartable("Test alert handler", &config, move |config| {
+        tokio::spawn(run_test_alert_handler(
+            test_alert_tx.clone(),
+            test_alert_nnnn_tx.clone(),
+            config.same_station_id.clone(),
+        ))
+    });
+    if config.monitoring_enabled {
+        let mut api_tls = Some(api_tls::load(&config)?);
+        let (app_state, monitoring, reload_tx, db) = (
+            app_state.clone(),
+            monitoring.clone(),
+            reload_tx.clone(),
+            db.clone(),
+        );
+        let config_file = backend::ConfigFile {
+            path: paths.config.clone(),
+            reload_tx: reload_tx.clone(),
+        };
+        let pipeline = backend::PipelineHandles {
+            alert_tx: injected_alert_tx,
+            nnnn_tx: nnnn_tx.clone(),
+            recording_state: recording_state.clone(),
+        };
+        supervisor.restartable("Monitoring API", &config, move |config| {
+            let tls = api_tls.take().map_or_else(|| api_tls::load(config), Ok);
+            let server = (
+                app_state.clone(),
+                monitoring.clone(),
+                config.clone(),
+                config_file.clone(),
+                db.clone(),
+                pipeline.clone(),
+            );
+            tokio::spawn(async move {
+                let (app_state, monitoring, config, config_file, db, pipeline) = server;
+                backend::run_server(
+                    tls?,
+                    app_state,
+                    monitoring,
+                    config,
+                    config_file,
+                    db,
+                    pipeline,
+                )
+                .await
+            })
+        });
+    } else {
+        warn!(
+            "Monitoring API disabled (MONITORING_ENABLED is false): no HTTP/WebSocket listener will be opened, the dashboard will show no live data, and deeplink host learning is inactive. Use the logs or MQTT for status."
+        );
+    }
+    supervisor.restartable("CAP supervisor", &config, {
+        let (app_state, monitoring, reload_tx, db) = (
+            app_state.clone(),
+            monitoring.clone(),
+            reload_tx.clone(),
+            db.clone(),
+        );
+        move |config| {
+            tokio::spawn(cap::run_cap_supervisor(
+                config.clone(),
+                app_state.clone(),
+                monitoring.clone(),
+                reload_tx.subscribe(),
+                db.clone(),
+            ))
+        }
+    });
+    supervisor.restartable("Icecast alert stream", &config, {
+        let reload_tx = reload_tx.clone();
+        move |config| {
+            tokio::spawn(icecast::run_alert_stream(
+                config.clone(),
+                reload_tx.subscribe(),
+            ))
+        }
+    });
+    supervisor.restartable("MQTT publisher", &config, {
+        let (monitoring, app_state, reload_tx) =
+            (monitoring.clone(), app_state.clone(), reload_tx.clone());
+        move |config| {
+            tokio::spawn(mqtt::run_mqtt_publisher(
+                config.clone(),
+                monitoring.clone(),
+                app_state.clone(),
+                reload_tx.subscribe(),
+            ))
+        }
+    });
+    supervisor.restartable("Stream health watcher", &config, {
+        let (monitoring, reload_tx) = (monitoring.clone(), reload_tx.clone());
+        move |config| {
+            tokio::spawn(stream_health::run_stream_health_watcher(
+                config.clone(),
+                monitoring.clone(),
+                reload_tx.subscribe(),
+            ))
+        }
+    });
+    supervisor.restartable("Resource monitor", &config, {
+        let (monitoring, reload_tx) = (monitoring.clone(), reload_tx.clone());
+        move |config| {
+            tokio::spawn(resources::run_resource_monitor(
+                config.clone(),
+                monitoring.clone(),
+                reload_tx.subscribe(),
+            ))
+        }
+    });
+    supervisor.restartable("Telemetry persistence", &config, {
+        let (monitoring, reload_tx) = (monitoring.clone(), reload_tx.clone());
+        move |config| {
+            tokio::spawn(monitoring::run_telemetry_persistence(
+                config.clone(),
+                monitoring.clone(),
+                reload_tx.subscribe(),
+            ))
+        }
+    });
 
-    let audio_processor_handle = tokio::spawn(audio::run_audio_processor(
-        config.clone(),
-        tx,
-        recording_state.clone(),
-        nnnn_tx.clone(),
-        monitoring.clone(),
-        app_state.clone(),
-        reload_tx.subscribe(),
-    ));
-    let alert_manager_handle = tokio::spawn(alerts::run_alert_manager(
-        config.clone(),
-        app_state.clone(),
-        rx,
-        recording_state,
-        nnnn_tx.subscribe(),
-        monitoring.clone(),
-        reload_tx.subscribe(),
-        db.clone(),
-    ));
-    let state_cleanup_handle = tokio::spawn(alerts::run_state_cleanup(
-        config.clone(),
-        app_state.clone(),
-        monitoring.clone(),
-    ));
-    let log_cleanup_handle = tokio::spawn(cleanup::run_log_cleanup(config.clone()));
-    let reload_handler_handle =
-        tokio::spawn(run_reload_handler(app_state.clone(), reload_tx.clone()));
-    let test_alert_handler_handle =
-        tokio::spawn(run_test_alert_handler(test_alert_tx, test_alert_nnnn_tx));
-    let api_handle = tokio::spawn(backend::run_server(
-        config.monitoring_bind_addr,
-        app_state.clone(),
-        monitoring.clone(),
-        config.clone(),
-    ));
-    let cap_supervisor_handle = tokio::spawn(cap::run_cap_supervisor(
-        config.clone(),
-        app_state.clone(),
-        monitoring.clone(),
-        reload_tx.subscribe(),
-        db.clone(),
-    ));
-    let icecast_stream_handle = tokio::spawn(icecast::run_alert_stream(
-        config.clone(),
-        reload_tx.subscribe(),
-    ));
+    #[cfg(unix)]
+    if systemd::enabled() {
+        let (monitoring, wait_for_api) = (monitoring.clone(), config.monitoring_enabled);
+        supervisor.restartable("systemd notifier", &config, move |_| {
+            tokio::spawn(systemd::run_notifier(monitoring.clone(), wait_for_api))
+        });
+    }
 
-    tokio::select! {
-        _ = audio_processor_handle => info!("Audio processor task exited."),
-        _ = alert_manager_handle => info!("Alert manager task exited."),
-        _ = state_cleanup_handle => info!("State cleanup task exited."),
-        _ = log_cleanup_handle => info!("Log cleanup task exited."),
-        _ = cap_supervisor_handle => info!("CAP supervisor task exited."),
-        _ = reload_handler_handle => info!("Reload handler task exited."),
-        _ = test_alert_handler_handle => info!("Test alert handler task exited."),
-        _ = icecast_stream_handle => info!("Icecast alert stream task exited."),
-        _ = api_handle => info!("Monitoring API task exited."),
-    };
+    let (kind, reason) = supervisor
+        .run(config.clone(), reload_tx.subscribe(), shutdown_signal())
+        .await;
+    info!("{}; stopping.", reason);
+    #[cfg(unix)]
+    systemd::stopping();
+
+    lifecycle::begin_shutdown();
+    let unfinished = recording::finish_recordings(
+        &recording_state,
+        Duration::from_secs(config.shutdown_grace_secs),
+    )
+    .await;
+    if unfinished > 0 {
+        warn!(
+            "{} recording(s) were still being written after {} seconds; they will be salvaged at the next start",
+            unfinished, config.shutdown_grace_secs
+        );
+    }
+
+    if let Err(err) = monitoring.save_persisted(&config.shared_state_dir) {
+        warn!("Failed to save stream telemetry: {:#}", err);
+    }
+    if let Err(err) = lifecycle::record_shutdown(
+        &config.shared_state_dir,
+        monitoring.started_at(),
+        kind,
+        &reason,
+    ) {
+        warn!("Failed to record the shutdown reason: {:#}", err);
+    }
 
     Ok(())
 }
 
-async fn run_reload_handler(
-    app_state: Arc<Mutex<AppState>>,
-    reload_tx: broadcast::Sender<Config>,
-) -> Result<()> {
-    let mut poller = tokio::time::interval(Duration::from_secs(1));
-    poller.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
-    let mut last_seen_modified: Option<std::time::SystemTime> = None;
-
-    loop {
-        poller.tick().await;
-
-        let metadata = match tokio::fs::metadata(RELOAD_SIGNAL_PATH).await {
-            Ok(metadata) => metadata,
-            Err(err) if err.kind() == ErrorKind::NotFound => continue,
+async fn shutdown_signal() -> &'static str {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for Ctrl-C: {}", err);
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
             Err(err) => {
-                warn!("Failed checking reload signal file: {}", err);
-                continue;
+                warn!("Failed to listen for SIGTERM: {}", err);
+                std::future::pending::<()>().await;
             }
-        };
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
 
-        let modified = metadata
-            .modified()
-            .unwrap_or(std::time::SystemTime::UNIX_EPOCH);
-        let should_reload = last_seen_modified
-            .map(|known_modified| modified > known_modified)
-            .unwrap_or(true);
-        if !should_reload {
+    tokio::select! {
+        _ = ctrl_c => "Ctrl-C",
+        _ = terminate => "SIGTERM",
+    }
+}
+
+fn log_filter_config(config: &Config) {
+    let default_action = config.default_filter_action;
+    filter::log_effective_filters("Global", &config.filters, default_action);
+    for stream in &config.streams {
+        let Some(rules) = &stream.filters else {
             continue;
+        };
+        let mut label = format!("Stream {}", stream.name.as_deref().unwrap_or(&stream.url));
+        if let Some(set) = &stream.filter_set {
+            label.push_str(&format!(" (filter set '{set}')"));
         }
+        filter::log_effective_filters(&label, rules, default_action);
+    }
+}
+
+pub(crate) async fn apply_config_reload(
+    new_config: Config,
+    config_path: &Path,
+    app_state: &Arc<Mutex<AppState>>,
+    reload_tx: &broadcast::Sender<Config>,
+) -> u64 {
+    let generation = reload::begin();
+    remember_applied_config(config_path);
+    for warning in &new_config.warnings {
+        warn!("{}", warning);
+    }
+    webhook::apply_runtime_config(&new_config);
+    reload::acknowledge("webhooks");
+    sync_web_runtime_config(&new_config, config_path);
+    reload::acknowledge("web_config");
+    logging::apply_config(&new_config);
+    reload::acknowledge("logging");
+
+    let filters = {
+        let mut guard = app_state.lock().await;
+        guard.set_max_active_alerts(new_config.max_active_alerts);
+        guard.set_recent_alerts_limit(new_config.recent_alerts_limit);
+        guard.filters()
+    };
+    filters.replace(Filters::from_config(&new_config));
+    log_filter_config(&new_config);
+    reload::acknowledge("alert_filters");
 
-        let (new_config, config_source, config_warning) = load_config_with_fallback(CONFIG_PATH);
+    if reload_tx.send(new_config).is_err() {
+        warn!("No active reload receivers were available for configuration update.");
+    }
+    generation
+}
 
-        if config_source == ConfigSource::BuiltInDefault {
-            if let Some(message) = config_warning.as_deref() {
-                warn!("{}", message);
+fn watch_config_file(
+    config_path: &Path,
+    events: mpsc::UnboundedSender<()>,
+) -> notify::Result<notify::RecommendedWatcher> {
+    use notify::{EventKind, RecursiveMode, Watcher};
+
+    let file_name = config_path.file_name().map(|name| name.to_os_string());
+    let directory = match config_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let mut watcher =
+        notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+            Ok(event) => {
+                if matches!(event.kind, EventKind::Access(_)) {
+                    return;
+                }
+                let touches_config = event
+                    .paths
+                    .iter()
+                    .any(|path| path.file_name() == file_name.as_deref());
+                if touches_config {
+                    let _ = events.send(());
+                }
             }
+            Err(err) => warn!("Config file watcher error: {}", err),
+        })?;
+    watcher.watch(&directory, RecursiveMode::NonRecursive)?;
+    Ok(watcher)
+}
+
+async fn settle_config_events(events: &mut mpsc::UnboundedReceiver<()>) -> bool {
+    loop {
+        match tokio::time::timeout(CONFIG_WATCH_DEBOUNCE, events.recv()).await {
+            Ok(Some(())) => continue,
+            Ok(None) => return false,
+            Err(_) => return true,
         }
+    }
+}
 
-        webhook::apply_runtime_config(&new_config);
-        sync_web_runtime_config(&new_config);
+fn remember_applied_config(config_path: &Path) {
+    *LAST_APPLIED_CONFIG.lock() = load_raw_config_document(config_path);
+}
 
-        {
-            let mut guard = app_state.lock().await;
-            guard.update_filters(new_config.filters.clone());
+async fn reload_changed_config_file(
+    config_path: &Path,
+    app_state: &Arc<Mutex<AppState>>,
+    reload_tx: &broadcast::Sender<Config>,
+) {
+    let Some(raw_config) = load_raw_config_document(config_path) else {
+        warn!(
+            "Configuration file '{}' changed but cannot be parsed; keeping the running configuration.",
+            config_path.display()
+        );
+        return;
+    };
+    if LAST_APPLIED_CONFIG.lock().as_ref() == Some(&raw_config) {
+        return;
+    }
+
+    let trigger = format!("after {} changed", config_path.display());
+    reload_config_file(config_path, app_state, reload_tx, &trigger).await;
+}
+
+async fn reload_config_file(
+    config_path: &Path,
+    app_state: &Arc<Mutex<AppState>>,
+    reload_tx: &broadcast::Sender<Config>,
+    trigger: &str,
+) {
+    match Config::from_file(config_path) {
+        Ok(new_config) => {
+            apply_config_reload(new_config, config_path, app_state, reload_tx).await;
+            info!("Applied configuration reload {}.", trigger);
         }
+        Err(err) => warn!(
+            "Rejected configuration reload {}, keeping the running configuration: {:#}",
+            trigger, err
+        ),
+    }
+}
 
-        if reload_tx.send(new_config).is_err() {
-            warn!("No active reload receivers were available for configuration update.");
+async fn check_reload_signal(
+    paths: &ConfigPaths,
+    last_seen_modified: &mut Option<std::time::SystemTime>,
+    app_state: &Arc<Mutex<AppState>>,
+    reload_tx: &broadcast::Sender<Config>,
+) {
+    let metadata = match tokio::fs::metadata(&paths.reload_signal).await {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == ErrorKind::NotFound => return,
+        Err(err) => {
+            warn!("Failed checking reload signal file: {}", err);
+            return;
         }
+    };
 
-        if config_source == ConfigSource::File {
-            info!("Applied configuration reload from reload signal.");
-        } else {
-            warn!("Applied built-in safe defaults for configuration reload.");
+    let modified = metadata
+        .modified()
+        .unwrap_or(std::time::SystemTime::UNIX_EPOCH);
+    let should_reload = last_seen_modified
+        .map(|known_modified| modified > known_modified)
+        .unwrap_or(true);
+    if !should_reload {
+        return;
+    }
+
+    reload_config_file(&paths.config, app_state, reload_tx, "from reload signal").await;
+
+    if let Err(err) = tokio::fs::remove_file(&paths.reload_signal).await {
+        if err.kind() != ErrorKind::NotFound {
+            warn!("Failed to remove reload signal file: {}", err);
         }
+    }
 
-        if let Err(err) = tokio::fs::remove_file(RELOAD_SIGNAL_PATH).await {
-            if err.kind() != ErrorKind::NotFound {
-                warn!("Failed to remove reload signal file: {}", err);
-            }
+    *last_seen_modified = Some(modified);
+}
+
+async fn run_reload_handler(
+    paths: ConfigPaths,
+    signal_file_enabled: bool,
+    app_state: Arc<Mutex<AppState>>,
+    reload_tx: broadcast::Sender<Config>,
+) -> Result<()> {
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+    let watcher = match watch_config_file(&paths.config, event_tx) {
+        Ok(watcher) => Some(watcher),
+        Err(err) => {
+            warn!(
+                "Failed to watch '{}' for changes: {}. Edits need the reload signal file.",
+                paths.config.display(),
+                err
+            );
+            None
         }
+    };
+    let mut watching = watcher.is_some();
 
-        last_seen_modified = Some(modified);
+    let mut poller = tokio::time::interval(Duration::from_secs(1));
+    poller.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut last_seen_modified = tokio::fs::metadata(&paths.reload_signal)
+        .await
+        .ok()
+        .and_then(|metadata| metadata.modified().ok());
+
+    loop {
+        tokio::select! {
+            event = event_rx.recv(), if watching => {
+                if event.is_some() && settle_config_events(&mut event_rx).await {
+                    reload_changed_config_file(&paths.config, &app_state, &reload_tx).await;
+                } else {
+                    warn!("Config file watcher stopped; edits need the reload signal file.");
+                    watching = false;
+                }
+            }
+            _ = poller.tick(), if signal_file_enabled => {
+                check_reload_signal(&paths, &mut last_seen_modified, &app_state, &reload_tx)
+                    .await;
+            }
+            else => std::future::pending::<()>().await,
+        }
     }
 }
 
-fn build_test_alert_header() -> String {
+fn build_test_alert_header(station_id: &str) -> String {
     use chrono::{Datelike, Timelike};
 
     let now = chrono::Utc::now();
     let issuance = format!("{:03}{:02}{:02}", now.ordinal(), now.hour(), now.minute());
 
-    format!("ZCZC-EAS-RWT-000000+0015-{issuance}-EASLSTNR-")
+    format!("ZCZC-EAS-RWT-000000+0015-{issuance}-{station_id}-")
 }
 
 async fn run_test_alert_handler(
     tx: mpsc::Sender<(String, String, String, String, Duration, String)>,
     nnnn_tx: broadcast::Sender<String>,
+    station_id: String,
 ) -> Result<()> {
     if let Err(err) = tokio::fs::remove_file(TEST_ALERT_SIGNAL_PATH).await {
         if err.kind() != ErrorKind::NotFound {
@@ -538,7 +1058,7 @@ async fn run_test_alert_handler(
             continue;
         }
 
-        let raw_header = build_test_alert_header();
+        let raw_header = build_test_alert_header(&station_id);
         info!("Manual test alert triggered from dashboard: {}", raw_header);
 
         let alert = (
@@ -571,9 +1091,9 @@ mod tests {
 
     #[test]
     fn test_alert_header_is_a_valid_decodable_rwt() {
-        let header = build_test_alert_header();
+        let header = build_test_alert_header("KOAX/NWS");
         assert!(header.starts_with("ZCZC-EAS-RWT-000000+0015-"));
-        assert!(header.ends_with("-EASLSTNR-"));
+        assert!(header.ends_with("-KOAX/NWS-"));
 
         let parsed_json =
             crate::e2t_ng::parse_header_json(&header).expect("test alert header should parse");
@@ -583,4 +1103,56 @@ mod tests {
         crate::header::generate_same_header_samples(&header, 44_100, 0.5)
             .expect("test alert header should generate SAME samples");
     }
+
+    #[test]
+    fn an_explicit_config_path_must_exist() {
+        let cli = Cli::parse_from(["eas_listener"]);
+        assert_eq!(
+            cli.config_path().expect("default"),
+            PathBuf::from(DEFAULT_CONFIG_PATH)
+        );
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        let missing = dir.path().join("config.json");
+        let cli = Cli::parse_from([
+            "eas_listener".as_ref(),
+            "--config".as_ref(),
+            missing.as_os_str(),
+        ]);
+        let err = cli.config_path().expect_err("missing config");
+        assert!(err.to_string().contains("does not exist"));
+
+        std::fs::write(&missing, "{}").expect("write");
+        assert_eq!(cli.config_path().expect("existing"), missing);
+
+        let cli = Cli::parse_from(["eas_listener", "init-config", "-o", "out.json", "-i"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::InitConfig {
+                output: Some(_),
+                interactive: true
+            })
+        ));
+    }
+    #[tokio::test]
+    async fn an_atomic_rename_over_the_config_file_is_one_change() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let config_path = dir.path().join("config.json");
+        std::fs::write(&config_path, "{}").expect("write");
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+        let _watcher = watch_config_file(&config_path, event_tx).expect("watch");
+
+        std::fs::write(dir.path().join("unrelated.json"), "{}").expect("write");
+        let staged = dir.path().join(".config.json.swp");
+        std::fs::write(&staged, r#"{"TZ": "America/Chicago"}"#).expect("write");
+        std::fs::rename(&staged, &config_path).expect("rename");
+        std::fs::write(&config_path, r#"{"TZ": "America/Denver"}"#).expect("write");
+
+        let first = tokio::time::timeout(Duration::from_secs(5), event_rx.recv())
+            .await
+            .expect("a change within five seconds");
+        assert!(first.is_some());
+        assert!(settle_config_events(&mut event_rx).await);
+        assert!(event_rx.try_recv().is_err());
+    }
 }
@@ -1,10 +1,16 @@
+use crate::config::Config;
+use crate::resources::ResourceSnapshot;
 use crate::state::ActiveAlert;
-use chrono::{DateTime, Utc};
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, NaiveDate, Utc};
 use parking_lot::RwLock;
-use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::fmt;
+use std::io::Write;
+use std::path::Path;
 use std::sync::{
     atomic::{AtomicU64, Ordering},
     Arc,
@@ -12,14 +18,19 @@ use std::sync::{
 use std::time::Duration;
 use tokio::sync::broadcast::{self, Receiver, Sender};
 use tracing::field::{Field, Visit};
-use tracing::{Event, Level, Subscriber};
+use tracing::{info, warn, Event, Level, Subscriber};
 use tracing_subscriber::layer::Context;
 use tracing_subscriber::registry::LookupSpan;
 use tracing_subscriber::Layer;
 
 const STREAM_ACTIVITY_EMIT_INTERVAL: Duration = Duration::from_secs(2);
+pub const DEFAULT_EVENT_CHANNEL_CAPACITY: usize = 256;
+pub const DEFAULT_STREAM_HISTORY_ENTRIES: usize = 200;
+pub const TELEMETRY_STATE_FILE: &str = "stream_telemetry.json";
+pub const LOG_HISTORY_FILE: &str = "monitoring_logs.json";
+const TELEMETRY_PERSIST_INTERVAL: Duration = Duration::from_secs(60);
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogEntry {
     pub id: u64,
     #[serde(with = "chrono::serde::ts_milliseconds")]
@@ -30,6 +41,38 @@ pub struct LogEntry {
     pub fields: Map<String, Value>,
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    pub min_level: Option<Level>,
+    pub target_prefix: Option<String>,
+    pub contains: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl LogFilter {
+    fn matches(&self, entry: &LogEntry) -> bool {
+        if let Some(min_level) = self.min_level {
+            if !entry
+                .level
+                .parse::<Level>()
+                .is_ok_and(|level| level <= min_level)
+            {
+                return false;
+            }
+        }
+        self.target_prefix
+            .as_deref()
+            .is_none_or(|prefix| entry.target.starts_with(prefix))
+            && self
+                .contains
+                .as_deref()
+                .is_none_or(|needle| entry.message.contains(needle))
+            && self.since.is_none_or(|since| entry.timestamp >= since)
+            && self.until.is_none_or(|until| entry.timestamp <= until)
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct StreamStatusPayload {
     pub stream_url: String,
@@ -50,6 +93,61 @@ pub struct StreamStatusPayload {
     pub last_alert_received: Option<String>,
     pub last_error: Option<String>,
     pub uptime_seconds: Option<i64>,
+    pub is_paused: bool,
+    #[serde(with = "chrono::serde::ts_seconds_option")]
+    pub paused_until: Option<DateTime<Utc>>,
+    pub availability_percent_24h: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionState {
+    Connected,
+    Disconnected,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamTransition {
+    pub state: ConnectionState,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamHistoryEntry {
+    #[serde(flatten)]
+    pub transition: StreamTransition,
+    pub duration_secs: i64,
+    pub is_current: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamHistory {
+    pub stream_url: String,
+    pub availability_percent_24h: Option<f64>,
+    pub transitions: Vec<StreamHistoryEntry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertsReason {
+    New,
+    Expired,
+    Evicted,
+    Updated,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertsUpdate {
+    pub alerts: Vec<ActiveAlert>,
+    pub reason: AlertsReason,
+    pub source_stream: Option<String>,
+    pub triggering_event_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub evicted: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -57,7 +155,8 @@ pub struct StreamStatusPayload {
 pub enum MonitoringEvent {
     Log(LogEntry),
     Stream(StreamStatusPayload),
-    Alerts(Vec<ActiveAlert>),
+    Alerts(AlertsUpdate),
+    Resources(Box<ResourceSnapshot>),
 }
 
 struct StreamTelemetry {
@@ -72,6 +171,9 @@ struct StreamTelemetry {
     alerts_received: u64,
     last_alert_received_ts: Option<DateTime<Utc>>,
     last_alert_received: Option<String>,
+    paused: bool,
+    paused_until: Option<DateTime<Utc>>,
+    history: VecDeque<StreamTransition>,
 }
 
 impl StreamTelemetry {
@@ -88,6 +190,112 @@ impl StreamTelemetry {
             alerts_received: 0,
             last_alert_received_ts: None,
             last_alert_received: None,
+            paused: false,
+            paused_until: None,
+            history: VecDeque::new(),
+        }
+    }
+
+    fn record_transition(
+        &mut self,
+        state: ConnectionState,
+        at: DateTime<Utc>,
+        error: Option<String>,
+        limit: usize,
+    ) {
+        if limit == 0 || self.history.back().is_some_and(|last| last.state == state) {
+            return;
+        }
+        self.history
+            .push_back(StreamTransition { state, at, error });
+        while self.history.len() > limit {
+            self.history.pop_front();
+        }
+    }
+
+    fn availability_percent(&self, since: DateTime<Utc>, now: DateTime<Utc>) -> Option<f64> {
+        let mut observed = 0i64;
+        let mut connected = 0i64;
+        for (index, transition) in self.history.iter().enumerate() {
+            let start = transition.at.max(since);
+            let end = self.history.get(index + 1).map_or(now, |next| next.at);
+            if end <= start {
+                continue;
+            }
+            let span = (end - start).num_milliseconds();
+            observed += span;
+            if transition.state == ConnectionState::Connected {
+                connected += span;
+            }
+        }
+        (observed > 0).then(|| (connected as f64 * 10_000.0 / observed as f64).round() / 100.0)
+    }
+
+    fn is_paused_at(&self, now: DateTime<Utc>) -> bool {
+        self.paused && self.paused_until.is_none_or(|until| until > now)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct PersistedCounters {
+    connection_attempts: u64,
+    alerts_received: u64,
+    last_alert_received_ts: Option<DateTime<Utc>>,
+    last_alert_received: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    history: Vec<StreamTransition>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DayAlertCounts {
+    pub total: BTreeMap<String, u64>,
+    pub streams: BTreeMap<String, BTreeMap<String, u64>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyAlertStats {
+    pub date: NaiveDate,
+    #[serde(flatten)]
+    pub counts: DayAlertCounts,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertStats {
+    pub since: NaiveDate,
+    pub until: NaiveDate,
+    pub totals: BTreeMap<String, u64>,
+    pub daily: Vec<DailyAlertStats>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PersistedTelemetry {
+    #[serde(default)]
+    saved_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    streams: BTreeMap<String, PersistedCounters>,
+    #[serde(default)]
+    alert_counts: BTreeMap<NaiveDate, DayAlertCounts>,
+}
+
+impl StreamTelemetry {
+    fn restored(stream_url: String, counters: PersistedCounters) -> Self {
+        Self {
+            attempts: counters.connection_attempts,
+            alerts_received: counters.alerts_received,
+            last_alert_received_ts: counters.last_alert_received_ts,
+            last_alert_received: counters.last_alert_received,
+            history: counters.history.into(),
+            ..Self::new(stream_url)
+        }
+    }
+
+    fn counters(&self) -> PersistedCounters {
+        PersistedCounters {
+            connection_attempts: self.attempts,
+            alerts_received: self.alerts_received,
+            last_alert_received_ts: self.last_alert_received_ts,
+            last_alert_received: self.last_alert_received.clone(),
+            history: self.history.iter().cloned().collect(),
         }
     }
 }
@@ -95,6 +303,8 @@ impl StreamTelemetry {
 struct MonitoringState {
     logs: VecDeque<LogEntry>,
     streams: HashMap<String, StreamTelemetry>,
+    restored: HashMap<String, PersistedCounters>,
+    alert_counts: BTreeMap<NaiveDate, DayAlertCounts>,
 }
 
 impl MonitoringState {
@@ -102,30 +312,224 @@ impl MonitoringState {
         Self {
             logs: VecDeque::new(),
             streams: HashMap::new(),
+            restored: HashMap::new(),
+            alert_counts: BTreeMap::new(),
         }
     }
+
+    fn count_alert(&mut self, stream: &str, event_code: &str, counts_globally: bool) {
+        let day = self
+            .alert_counts
+            .entry(Utc::now().date_naive())
+            .or_default();
+        if counts_globally {
+            *day.total.entry(event_code.to_string()).or_default() += 1;
+        }
+        *day.streams
+            .entry(stream.to_string())
+            .or_default()
+            .entry(event_code.to_string())
+            .or_default() += 1;
+    }
+
+    fn stream_mut(&mut self, stream: &str) -> &mut StreamTelemetry {
+        let restored = &mut self.restored;
+        self.streams
+            .entry(stream.to_string())
+            .or_insert_with(|| match restored.remove(stream) {
+                Some(counters) => StreamTelemetry::restored(stream.to_string(), counters),
+                None => StreamTelemetry::new(stream.to_string()),
+            })
+    }
+}
+
+#[derive(Default)]
+struct EventChannelCounters {
+    logs_sent: AtomicU64,
+    streams_sent: AtomicU64,
+    alerts_sent: AtomicU64,
+    resources_sent: AtomicU64,
+    lag_events: AtomicU64,
+    events_dropped: AtomicU64,
+    next_ws_client_id: AtomicU64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EventsSent {
+    pub log: u64,
+    pub stream: u64,
+    pub alerts: u64,
+    pub resources: u64,
+}
+
+#[derive(Default)]
+struct WsClientCounters {
+    connected_at: DateTime<Utc>,
+    messages_sent: AtomicU64,
+    lag_skips: AtomicU64,
+    events_dropped: AtomicU64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WsClientStats {
+    pub id: u64,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub connected_at: DateTime<Utc>,
+    pub messages_sent: u64,
+    pub lag_skips: u64,
+    pub events_dropped: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EventChannelStats {
+    pub capacity: usize,
+    pub subscribers: usize,
+    pub events_sent: EventsSent,
+    pub lag_events: u64,
+    pub events_dropped: u64,
+    pub websocket_clients: Vec<WsClientStats>,
+}
+
+pub struct WsClientHandle {
+    id: u64,
+    counters: Arc<WsClientCounters>,
+    hub: MonitoringHub,
+}
+
+impl WsClientHandle {
+    pub fn note_sent(&self) {
+        self.counters.messages_sent.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn note_lagged(&self, skipped: u64) -> bool {
+        self.hub.note_lagged(skipped);
+        self.counters
+            .events_dropped
+            .fetch_add(skipped, Ordering::Relaxed);
+        self.counters.lag_skips.fetch_add(1, Ordering::Relaxed) == 0
+    }
+}
+
+impl Drop for WsClientHandle {
+    fn drop(&mut self) {
+        self.hub.ws_clients.write().remove(&self.id);
+    }
 }
 
 #[derive(Clone)]
 pub struct MonitoringHub {
     inner: Arc<RwLock<MonitoringState>>,
     events_tx: Sender<MonitoringEvent>,
+    event_capacity: usize,
+    event_counters: Arc<EventChannelCounters>,
+    ws_clients: Arc<RwLock<BTreeMap<u64, Arc<WsClientCounters>>>>,
     next_log_id: Arc<AtomicU64>,
+    status_revision: Arc<AtomicU64>,
     max_logs: usize,
+    history_limit: usize,
     inactivity_timeout: Duration,
     stream_activity_emit_interval: Duration,
+    started_at: DateTime<Utc>,
 }
 
 impl MonitoringHub {
     pub fn new(max_logs: usize, inactivity_timeout: Duration) -> Self {
-        let (tx, _rx) = broadcast::channel(256);
+        let (tx, _rx) = broadcast::channel(DEFAULT_EVENT_CHANNEL_CAPACITY);
         Self {
             inner: Arc::new(RwLock::new(MonitoringState::new())),
             events_tx: tx,
+            event_capacity: DEFAULT_EVENT_CHANNEL_CAPACITY,
+            event_counters: Arc::new(EventChannelCounters::default()),
+            ws_clients: Arc::new(RwLock::new(BTreeMap::new())),
             next_log_id: Arc::new(AtomicU64::new(1)),
+            status_revision: Arc::new(AtomicU64::new(0)),
             max_logs,
+            history_limit: DEFAULT_STREAM_HISTORY_ENTRIES,
             inactivity_timeout,
             stream_activity_emit_interval: STREAM_ACTIVITY_EMIT_INTERVAL,
+            started_at: Utc::now(),
+        }
+    }
+
+    pub fn started_at(&self) -> DateTime<Utc> {
+        self.started_at
+    }
+
+    pub fn with_event_capacity(mut self, capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        self.events_tx = broadcast::channel(capacity).0;
+        self.event_capacity = capacity;
+        self
+    }
+
+    pub fn with_stream_history_limit(mut self, limit: usize) -> Self {
+        self.history_limit = limit;
+        self
+    }
+
+    fn send_event(&self, event: MonitoringEvent) {
+        let counter = match &event {
+            MonitoringEvent::Log(_) => &self.event_counters.logs_sent,
+            MonitoringEvent::Stream(_) => &self.event_counters.streams_sent,
+            MonitoringEvent::Alerts(_) => &self.event_counters.alerts_sent,
+            MonitoringEvent::Resources(_) => &self.event_counters.resources_sent,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        let _ = self.events_tx.send(event);
+    }
+
+    pub fn note_lagged(&self, skipped: u64) {
+        self.event_counters
+            .lag_events
+            .fetch_add(1, Ordering::Relaxed);
+        self.event_counters
+            .events_dropped
+            .fetch_add(skipped, Ordering::Relaxed);
+    }
+
+    pub fn register_ws_client(&self) -> WsClientHandle {
+        let id = self
+            .event_counters
+            .next_ws_client_id
+            .fetch_add(1, Ordering::Relaxed)
+            + 1;
+        let counters = Arc::new(WsClientCounters {
+            connected_at: Utc::now(),
+            ..WsClientCounters::default()
+        });
+        self.ws_clients.write().insert(id, counters.clone());
+        WsClientHandle {
+            id,
+            counters,
+            hub: self.clone(),
+        }
+    }
+
+    pub fn event_channel_stats(&self) -> EventChannelStats {
+        let counters = &self.event_counters;
+        EventChannelStats {
+            capacity: self.event_capacity,
+            subscribers: self.subscriber_count(),
+            events_sent: EventsSent {
+                log: counters.logs_sent.load(Ordering::Relaxed),
+                stream: counters.streams_sent.load(Ordering::Relaxed),
+                alerts: counters.alerts_sent.load(Ordering::Relaxed),
+                resources: counters.resources_sent.load(Ordering::Relaxed),
+            },
+            lag_events: counters.lag_events.load(Ordering::Relaxed),
+            events_dropped: counters.events_dropped.load(Ordering::Relaxed),
+            websocket_clients: self
+                .ws_clients
+                .read()
+                .iter()
+                .map(|(id, client)| WsClientStats {
+                    id: *id,
+                    connected_at: client.connected_at,
+                    messages_sent: client.messages_sent.load(Ordering::Relaxed),
+                    lag_skips: client.lag_skips.load(Ordering::Relaxed),
+                    events_dropped: client.events_dropped.load(Ordering::Relaxed),
+                })
+                .collect(),
         }
     }
 
@@ -133,26 +537,116 @@ impl MonitoringHub {
         self.events_tx.subscribe()
     }
 
+    pub fn subscriber_count(&self) -> usize {
+        self.events_tx.receiver_count()
+    }
+
     pub fn max_logs(&self) -> usize {
         self.max_logs
     }
 
+    pub fn latest_log_id(&self) -> u64 {
+        self.next_log_id.load(Ordering::Relaxed).saturating_sub(1)
+    }
+
+    pub fn status_revision(&self) -> u64 {
+        self.status_revision.load(Ordering::Relaxed)
+    }
+
+    fn bump_status_revision(&self) {
+        self.status_revision.fetch_add(1, Ordering::Relaxed);
+    }
+
     pub fn broadcast_alerts(
         &self,
         alerts: Vec<ActiveAlert>,
+        reason: AlertsReason,
         source_stream: Option<&str>,
         event_code: Option<&str>,
     ) {
-        if let Some(stream) = source_stream {
-            self.update_stream(stream, |state| {
-                state.alerts_received = state.alerts_received.saturating_add(1);
-                state.last_alert_received_ts = Some(Utc::now());
-                if let Some(event_code) = event_code {
-                    state.last_alert_received = Some(event_code.to_string());
+        if reason == AlertsReason::New {
+            if let (Some(stream), Some(event_code)) = (source_stream, event_code) {
+                self.inner.write().count_alert(stream, event_code, true);
+            }
+            if let Some(stream) = source_stream {
+                self.update_stream(stream, |state| {
+                    state.alerts_received = state.alerts_received.saturating_add(1);
+                    state.last_alert_received_ts = Some(Utc::now());
+                    if let Some(event_code) = event_code {
+                        state.last_alert_received = Some(event_code.to_string());
+                    }
+                });
+            }
+        }
+        self.bump_status_revision();
+        self.send_event(MonitoringEvent::Alerts(AlertsUpdate {
+            alerts,
+            reason,
+            source_stream: source_stream.map(str::to_string),
+            triggering_event_code: event_code.map(str::to_string),
+            evicted: Vec::new(),
+        }));
+    }
+
+    pub fn broadcast_evicted_alerts(&self, alerts: Vec<ActiveAlert>, evicted: &[ActiveAlert]) {
+        if evicted.is_empty() {
+            return;
+        }
+        self.bump_status_revision();
+        self.send_event(MonitoringEvent::Alerts(AlertsUpdate {
+            alerts,
+            reason: AlertsReason::Evicted,
+            source_stream: None,
+            triggering_event_code: None,
+            evicted: evicted
+                .iter()
+                .map(|alert| alert.raw_header.clone())
+                .collect(),
+        }));
+    }
+
+    pub fn note_duplicate_alert(&self, stream: &str, event_code: &str) {
+        self.inner.write().count_alert(stream, event_code, false);
+        self.bump_status_revision();
+    }
+
+    pub fn alert_stats(&self, days: u32, today: NaiveDate) -> AlertStats {
+        let since = today
+            .checked_sub_days(chrono::Days::new(u64::from(days.max(1)) - 1))
+            .unwrap_or(NaiveDate::MIN);
+        let guard = self.inner.read();
+        let mut totals = BTreeMap::new();
+        let daily = since
+            .iter_days()
+            .take_while(|date| *date <= today)
+            .map(|date| {
+                let counts = guard.alert_counts.get(&date).cloned().unwrap_or_default();
+                for (event_code, count) in &counts.total {
+                    *totals.entry(event_code.clone()).or_default() += count;
                 }
-            });
+                DailyAlertStats { date, counts }
+            })
+            .collect();
+        AlertStats {
+            since,
+            until: today,
+            totals,
+            daily,
         }
-        let _ = self.events_tx.send(MonitoringEvent::Alerts(alerts));
+    }
+
+    pub fn prune_alert_stats(&self, retention_days: u64, today: NaiveDate) {
+        let Some(cutoff) = today.checked_sub_days(chrono::Days::new(retention_days)) else {
+            return;
+        };
+        self.inner
+            .write()
+            .alert_counts
+            .retain(|date, _| *date > cutoff);
+    }
+
+    pub fn broadcast_resources(&self, snapshot: ResourceSnapshot) {
+        self.send_event(MonitoringEvent::Resources(Box::new(snapshot)));
     }
 
     pub fn record_log(
@@ -177,7 +671,7 @@ impl MonitoringHub {
                 guard.logs.pop_front();
             }
         }
-        let _ = self.events_tx.send(MonitoringEvent::Log(entry));
+        self.send_event(MonitoringEvent::Log(entry));
     }
 
     pub fn note_connecting(&self, stream: &str) {
@@ -193,7 +687,9 @@ impl MonitoringHub {
 
     pub fn note_connected(&self, stream: &str) {
         let now = Utc::now();
+        let limit = self.history_limit;
         self.update_stream(stream, |state| {
+            state.record_transition(ConnectionState::Connected, now, None, limit);
             state.is_connected = true;
             state.connected_since = Some(now);
             state.last_activity = Some(now);
@@ -209,10 +705,7 @@ impl MonitoringHub {
         let emit_interval = self.stream_activity_emit_interval;
         let payload = {
             let mut guard = self.inner.write();
-            let state = guard
-                .streams
-                .entry(stream.to_string())
-                .or_insert_with(|| StreamTelemetry::new(stream.to_string()));
+            let state = guard.stream_mut(stream);
             let was_receiving_audio = state
                 .last_activity
                 .map(|ts| {
@@ -242,23 +735,29 @@ impl MonitoringHub {
             }
         };
         if let Some(payload) = payload {
-            let _ = self.events_tx.send(MonitoringEvent::Stream(payload));
+            self.bump_status_revision();
+            self.send_event(MonitoringEvent::Stream(payload));
         }
     }
 
     pub fn note_error(&self, stream: &str, error: String) {
+        let now = Utc::now();
+        let limit = self.history_limit;
         self.update_stream(stream, move |state| {
+            state.record_transition(ConnectionState::Error, now, Some(error.clone()), limit);
             state.is_connected = false;
             state.connected_since = None;
             state.last_activity_broadcast_at = None;
-            state.last_disconnect = Some(Utc::now());
+            state.last_disconnect = Some(now);
             state.last_error = Some(error.clone());
         });
     }
 
     pub fn note_disconnected(&self, stream: &str) {
         let now = Utc::now();
+        let limit = self.history_limit;
         self.update_stream(stream, |state| {
+            state.record_transition(ConnectionState::Disconnected, now, None, limit);
             state.is_connected = false;
             state.connected_since = None;
             state.last_activity_broadcast_at = None;
@@ -269,6 +768,7 @@ impl MonitoringHub {
     pub fn remove_stream(&self, stream: &str) {
         let removed = {
             let mut guard = self.inner.write();
+            guard.restored.remove(stream);
             guard.streams.remove(stream).is_some()
         };
 
@@ -287,9 +787,54 @@ impl MonitoringHub {
                 last_alert_received: None,
                 last_error: None,
                 uptime_seconds: None,
+                is_paused: false,
+                paused_until: None,
+                availability_percent_24h: None,
             };
-            let _ = self.events_tx.send(MonitoringEvent::Stream(payload));
+            self.bump_status_revision();
+            self.send_event(MonitoringEvent::Stream(payload));
+        }
+    }
+
+    pub fn pause_stream(&self, stream: &str, until: Option<DateTime<Utc>>) {
+        self.update_stream(stream, |state| {
+            state.paused = true;
+            state.paused_until = until;
+        });
+    }
+
+    pub fn resume_stream(&self, stream: &str) -> bool {
+        let now = Utc::now();
+        let was_paused = self
+            .inner
+            .read()
+            .streams
+            .get(stream)
+            .is_some_and(|state| state.is_paused_at(now));
+        self.update_stream(stream, |state| {
+            state.paused = false;
+            state.paused_until = None;
+        });
+        was_paused
+    }
+
+    pub fn is_stream_paused(&self, stream: &str) -> bool {
+        let now = Utc::now();
+        let expired = {
+            let guard = self.inner.read();
+            match guard.streams.get(stream) {
+                Some(state) if state.paused => !state.is_paused_at(now),
+                _ => return false,
+            }
+        };
+        if expired {
+            self.update_stream(stream, |state| {
+                state.paused = false;
+                state.paused_until = None;
+            });
+            info!(stream = %stream, "Pause expired; resuming alert detection.");
         }
+        !expired
     }
 
     pub fn recent_logs(&self, count: usize) -> Vec<LogEntry> {
@@ -297,6 +842,18 @@ impl MonitoringHub {
         guard.logs.iter().rev().take(count).cloned().collect()
     }
 
+    pub fn filtered_logs(&self, count: usize, filter: &LogFilter) -> Vec<LogEntry> {
+        let guard = self.inner.read();
+        guard
+            .logs
+            .iter()
+            .rev()
+            .filter(|entry| filter.matches(entry))
+            .take(count)
+            .cloned()
+            .collect()
+    }
+
     pub fn stream_snapshots(&self) -> Vec<StreamStatusPayload> {
         let guard = self.inner.read();
         let mut snapshots: Vec<_> = guard
@@ -308,7 +865,6 @@ impl MonitoringHub {
         snapshots
     }
 
-    #[allow(dead_code)]
     pub fn stream_snapshot(&self, stream: &str) -> Option<StreamStatusPayload> {
         let guard = self.inner.read();
         guard
@@ -317,20 +873,163 @@ impl MonitoringHub {
             .map(|state| self.make_snapshot(state))
     }
 
+    pub fn stream_history(&self, stream: &str) -> Option<StreamHistory> {
+        let now = Utc::now();
+        let guard = self.inner.read();
+        let state = guard.streams.get(stream)?;
+        let transitions = state
+            .history
+            .iter()
+            .enumerate()
+            .map(|(index, transition)| {
+                let next = state.history.get(index + 1);
+                StreamHistoryEntry {
+                    transition: transition.clone(),
+                    duration_secs: (next.map_or(now, |next| next.at) - transition.at)
+                        .num_seconds()
+                        .max(0),
+                    is_current: next.is_none(),
+                }
+            })
+            .rev()
+            .collect();
+        Some(StreamHistory {
+            stream_url: state.stream_url.clone(),
+            availability_percent_24h: availability_24h(state, now),
+            transitions,
+        })
+    }
+
+    pub fn load_persisted(&self, state_dir: &Path) {
+        if let Some(persisted) =
+            read_state_file::<PersistedTelemetry>(state_dir, TELEMETRY_STATE_FILE)
+        {
+            self.restore_telemetry(persisted);
+        }
+        if let Some(logs) = read_state_file::<Vec<LogEntry>>(state_dir, LOG_HISTORY_FILE) {
+            self.restore_logs(logs);
+        }
+    }
+
+    fn restore_telemetry(&self, persisted: PersistedTelemetry) {
+        let stopped_at = persisted.saved_at.unwrap_or_else(Utc::now);
+        let mut guard = self.inner.write();
+        for (date, counts) in persisted.alert_counts {
+            let day = guard.alert_counts.entry(date).or_default();
+            for (event_code, count) in counts.total {
+                *day.total.entry(event_code).or_default() += count;
+            }
+            for (stream, by_code) in counts.streams {
+                let stream_counts = day.streams.entry(stream).or_default();
+                for (event_code, count) in by_code {
+                    *stream_counts.entry(event_code).or_default() += count;
+                }
+            }
+        }
+        for (stream, mut counters) in persisted.streams {
+            if counters
+                .history
+                .last()
+                .is_some_and(|last| last.state != ConnectionState::Disconnected)
+            {
+                counters.history.push(StreamTransition {
+                    state: ConnectionState::Disconnected,
+                    at: stopped_at,
+                    error: None,
+                });
+            }
+            let excess = counters.history.len().saturating_sub(self.history_limit);
+            counters.history.drain(..excess);
+            match guard.streams.get_mut(&stream) {
+                Some(state) => {
+                    state.attempts = state.attempts.saturating_add(counters.connection_attempts);
+                    state.alerts_received = state
+                        .alerts_received
+                        .saturating_add(counters.alerts_received);
+                    if state.last_alert_received_ts.is_none() {
+                        state.last_alert_received_ts = counters.last_alert_received_ts;
+                        state.last_alert_received = counters.last_alert_received;
+                    }
+                    let current = std::mem::take(&mut state.history);
+                    state.history = counters.history.into();
+                    state.history.extend(current);
+                    let excess = state.history.len().saturating_sub(self.history_limit);
+                    state.history.drain(..excess);
+                }
+                None => {
+                    guard.restored.insert(stream, counters);
+                }
+            }
+        }
+    }
+
+    fn restore_logs(&self, previous: Vec<LogEntry>) {
+        if previous.is_empty() {
+            return;
+        }
+        let mut guard = self.inner.write();
+        let since_startup = std::mem::take(&mut guard.logs);
+        let mut next_id = previous.iter().map(|entry| entry.id).max().unwrap_or(0) + 1;
+        guard.logs.extend(previous);
+
+        let restart_at = since_startup
+            .front()
+            .map(|entry| entry.timestamp)
+            .unwrap_or_else(Utc::now);
+        guard.logs.push_back(LogEntry {
+            id: next_id,
+            timestamp: restart_at,
+            level: Level::INFO.to_string(),
+            target: env!("CARGO_PKG_NAME").to_string(),
+            message: "Process restarted; earlier entries are from the previous run.".to_string(),
+            fields: Map::new(),
+        });
+        for mut entry in since_startup {
+            next_id += 1;
+            entry.id = next_id;
+            guard.logs.push_back(entry);
+        }
+        while guard.logs.len() > self.max_logs {
+            guard.logs.pop_front();
+        }
+        self.next_log_id.store(next_id + 1, Ordering::Relaxed);
+    }
+
+    pub fn save_persisted(&self, state_dir: &Path) -> Result<()> {
+        let (persisted, logs) = {
+            let guard = self.inner.read();
+            let mut streams: BTreeMap<String, PersistedCounters> =
+                guard.restored.clone().into_iter().collect();
+            streams.extend(
+                guard
+                    .streams
+                    .iter()
+                    .map(|(stream, state)| (stream.clone(), state.counters())),
+            );
+            let persisted = PersistedTelemetry {
+                saved_at: Some(Utc::now()),
+                streams,
+                alert_counts: guard.alert_counts.clone(),
+            };
+            (persisted, guard.logs.iter().cloned().collect::<Vec<_>>())
+        };
+
+        write_state_file(state_dir, TELEMETRY_STATE_FILE, &persisted)?;
+        write_state_file(state_dir, LOG_HISTORY_FILE, &logs)
+    }
+
     fn update_stream<F>(&self, stream: &str, mut update_fn: F)
     where
         F: FnMut(&mut StreamTelemetry),
     {
         let payload = {
             let mut guard = self.inner.write();
-            let state = guard
-                .streams
-                .entry(stream.to_string())
-                .or_insert_with(|| StreamTelemetry::new(stream.to_string()));
+            let state = guard.stream_mut(stream);
             update_fn(state);
             self.make_snapshot(state)
         };
-        let _ = self.events_tx.send(MonitoringEvent::Stream(payload));
+        self.bump_status_revision();
+        self.send_event(MonitoringEvent::Stream(payload));
     }
 
     fn make_snapshot(&self, state: &StreamTelemetry) -> StreamStatusPayload {
@@ -365,6 +1064,87 @@ impl MonitoringHub {
             last_alert_received: state.last_alert_received.clone(),
             last_error: state.last_error.clone(),
             uptime_seconds,
+            is_paused: state.is_paused_at(now),
+            paused_until: state.paused_until.filter(|_| state.is_paused_at(now)),
+            availability_percent_24h: availability_24h(state, now),
+        }
+    }
+}
+
+fn availability_24h(state: &StreamTelemetry, now: DateTime<Utc>) -> Option<f64> {
+    state.availability_percent(now - chrono::Duration::hours(24), now)
+}
+
+pub(crate) fn read_state_file<T: DeserializeOwned>(state_dir: &Path, name: &str) -> Option<T> {
+    let path = state_dir.join(name);
+    match std::fs::read(&path) {
+        Ok(bytes) => match serde_json::from_slice(&bytes) {
+            Ok(value) => Some(value),
+            Err(err) => {
+                warn!("Ignoring unreadable {}: {}", path.display(), err);
+                None
+            }
+        },
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+        Err(err) => {
+            warn!("Failed to read {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+pub(crate) fn write_state_file<T: Serialize>(
+    state_dir: &Path,
+    name: &str,
+    value: &T,
+) -> Result<()> {
+    let path = state_dir.join(name);
+    let mut temp = tempfile::NamedTempFile::new_in(state_dir)
+        .with_context(|| format!("failed to create a temp file in {}", state_dir.display()))?;
+    serde_json::to_writer(&mut temp, value)?;
+    temp.write_all(b"\n")?;
+    temp.persist(&path)
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(())
+}
+
+pub async fn run_telemetry_persistence(
+    mut config: Config,
+    monitoring: MonitoringHub,
+    mut reload_rx: broadcast::Receiver<Config>,
+) -> Result<()> {
+    let mut ticker = tokio::time::interval(TELEMETRY_PERSIST_INTERVAL);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut saved_revision = (monitoring.status_revision(), monitoring.latest_log_id());
+
+    crate::reload::register("telemetry");
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            reload = reload_rx.recv() => {
+                match reload {
+                    Ok(new_config) => {
+                        config = new_config;
+                        crate::reload::acknowledge("telemetry");
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                }
+                continue;
+            }
+        }
+
+        monitoring.prune_alert_stats(config.alert_stats_retention_days, Utc::now().date_naive());
+        let revision = (monitoring.status_revision(), monitoring.latest_log_id());
+        if revision == saved_revision {
+            continue;
+        }
+        let hub = monitoring.clone();
+        let dir = config.shared_state_dir.clone();
+        match tokio::task::spawn_blocking(move || hub.save_persisted(&dir)).await {
+            Ok(Ok(())) => saved_revision = revision,
+            Ok(Err(err)) => warn!("Failed to save stream telemetry: {:#}", err),
+            Err(err) => warn!("Stream telemetry save task failed: {}", err),
         }
     }
 }
@@ -449,3 +1229,318 @@ where
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_filters_combine_level_target_text_and_time() {
+        let hub = MonitoringHub::new(10, Duration::from_secs(30));
+        hub.record_log(
+            Level::INFO,
+            "eas_listener::relay",
+            "relay ok".into(),
+            Map::new(),
+        );
+        hub.record_log(
+            Level::WARN,
+            "eas_listener::relay",
+            "relay slow".into(),
+            Map::new(),
+        );
+        hub.record_log(
+            Level::ERROR,
+            "eas_listener::relay",
+            "relay failed".into(),
+            Map::new(),
+        );
+        hub.record_log(
+            Level::ERROR,
+            "eas_listener::audio",
+            "decode failed".into(),
+            Map::new(),
+        );
+
+        let filter = LogFilter {
+            min_level: Some(Level::WARN),
+            target_prefix: Some("eas_listener::relay".into()),
+            ..LogFilter::default()
+        };
+        let messages: Vec<_> = hub
+            .filtered_logs(10, &filter)
+            .into_iter()
+            .map(|entry| entry.message)
+            .collect();
+        assert_eq!(messages, ["relay failed", "relay slow"]);
+
+        let filter = LogFilter {
+            contains: Some("failed".into()),
+            ..LogFilter::default()
+        };
+        assert_eq!(hub.filtered_logs(1, &filter)[0].message, "decode failed");
+
+        let filter = LogFilter {
+            since: Some(Utc::now() + chrono::Duration::seconds(1)),
+            ..LogFilter::default()
+        };
+        assert!(hub.filtered_logs(10, &filter).is_empty());
+    }
+
+    #[test]
+    fn evicted_alerts_are_broadcast_with_their_headers() {
+        let hub = MonitoringHub::new(10, Duration::from_secs(30));
+        let mut events = hub.subscribe();
+        hub.broadcast_evicted_alerts(Vec::new(), &[]);
+        assert!(events.try_recv().is_err());
+
+        let alert = |raw_header: &str| {
+            ActiveAlert::new(
+                crate::state::EasAlertData {
+                    eas_text: String::new(),
+                    event_text: "Flood Advisory".to_string(),
+                    event_code: "FLS".to_string(),
+                    fips: vec!["031055".to_string()],
+                    locations: String::new(),
+                    originator: "WXR".to_string(),
+                    description: None,
+                    parsed_header: None,
+                },
+                raw_header.to_string(),
+                std::time::Duration::from_secs(1800),
+            )
+        };
+        let kept = alert("ZCZC-WXR-FLS-031055+0030-1231645-KWO35   -");
+        let evicted = alert("ZCZC-WXR-FLS-031153+0030-1231645-KWO35   -");
+        hub.broadcast_evicted_alerts(vec![kept], std::slice::from_ref(&evicted));
+        match events.try_recv().expect("event") {
+            MonitoringEvent::Alerts(update) => {
+                assert_eq!(update.reason, AlertsReason::Evicted);
+                assert_eq!(update.alerts.len(), 1);
+                assert_eq!(update.evicted, vec![evicted.raw_header]);
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+    }
+
+    #[test]
+    fn paused_streams_stay_paused_across_reconnects_until_resumed_or_expired() {
+        let hub = MonitoringHub::new(10, Duration::from_secs(30));
+        let stream = "http://radio/stream";
+        hub.note_connecting(stream);
+        assert!(!hub.is_stream_paused(stream));
+
+        hub.pause_stream(stream, None);
+        hub.note_disconnected(stream);
+        hub.note_connecting(stream);
+        assert!(hub.is_stream_paused(stream));
+        assert!(hub.stream_snapshot(stream).expect("snapshot").is_paused);
+        assert!(hub.resume_stream(stream));
+        assert!(!hub.resume_stream(stream));
+
+        hub.pause_stream(stream, Some(Utc::now() - chrono::Duration::seconds(1)));
+        assert!(!hub.stream_snapshot(stream).expect("snapshot").is_paused);
+        assert!(!hub.is_stream_paused(stream));
+
+        let until = Utc::now() + chrono::Duration::hours(1);
+        hub.pause_stream(stream, Some(until));
+        let snapshot = hub.stream_snapshot(stream).expect("snapshot");
+        assert!(snapshot.is_paused);
+        assert_eq!(snapshot.paused_until, Some(until));
+    }
+
+    #[test]
+    fn stream_counters_survive_a_restart_but_connection_state_does_not() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let stream = "http://radio/stream";
+        let hub = MonitoringHub::new(10, Duration::from_secs(30));
+        hub.load_persisted(dir.path());
+        hub.note_connecting(stream);
+        hub.note_connected(stream);
+        hub.broadcast_alerts(Vec::new(), AlertsReason::New, Some(stream), Some("RWT"));
+        hub.save_persisted(dir.path()).expect("save");
+
+        let restarted = MonitoringHub::new(10, Duration::from_secs(30));
+        restarted.load_persisted(dir.path());
+        assert!(restarted.stream_snapshots().is_empty());
+        restarted.note_connecting(stream);
+        let snapshot = restarted.stream_snapshot(stream).expect("snapshot");
+        assert_eq!(snapshot.connection_attempts, 2);
+        assert_eq!(snapshot.alerts_received, 1);
+        assert_eq!(snapshot.last_alert_received.as_deref(), Some("RWT"));
+        assert!(!snapshot.is_connected);
+
+        std::fs::write(dir.path().join(TELEMETRY_STATE_FILE), "{not json").expect("corrupt");
+        let fresh = MonitoringHub::new(10, Duration::from_secs(30));
+        fresh.load_persisted(dir.path());
+        fresh.note_connecting(stream);
+        assert_eq!(
+            fresh
+                .stream_snapshot(stream)
+                .expect("snapshot")
+                .connection_attempts,
+            1
+        );
+    }
+
+    #[test]
+    fn stream_history_tracks_transitions_and_availability_across_a_restart() {
+        let now = Utc::now();
+        let at = |minutes_ago: i64| now - chrono::Duration::minutes(minutes_ago);
+        let mut telemetry = StreamTelemetry::new("http://radio/stream".to_string());
+        telemetry.record_transition(ConnectionState::Connected, at(25 * 60), None, 3);
+        telemetry.record_transition(ConnectionState::Error, at(120), Some("reset".into()), 3);
+        telemetry.record_transition(ConnectionState::Disconnected, at(90), None, 3);
+        telemetry.record_transition(ConnectionState::Disconnected, at(80), None, 3);
+        telemetry.record_transition(ConnectionState::Connected, at(60), None, 3);
+        assert_eq!(telemetry.history.len(), 3);
+        assert_eq!(telemetry.history[0].state, ConnectionState::Error);
+        assert_eq!(telemetry.availability_percent(at(24 * 60), now), Some(50.0));
+        assert_eq!(
+            StreamTelemetry::new(String::new()).availability_percent(at(24 * 60), now),
+            None
+        );
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        let stream = "http://radio/stream";
+        let hub = MonitoringHub::new(10, Duration::from_secs(30));
+        hub.note_connected(stream);
+        hub.note_error(stream, "connection reset".to_string());
+        hub.note_connected(stream);
+        let history = hub.stream_history(stream).expect("history");
+        assert_eq!(history.transitions.len(), 3);
+        assert!(history.transitions[0].is_current);
+        assert_eq!(
+            history.transitions[1].transition.error.as_deref(),
+            Some("connection reset")
+        );
+        hub.save_persisted(dir.path()).expect("save");
+
+        let restarted = MonitoringHub::new(10, Duration::from_secs(30));
+        restarted.load_persisted(dir.path());
+        restarted.note_connected(stream);
+        let states: Vec<_> = restarted
+            .stream_history(stream)
+            .expect("history")
+            .transitions
+            .iter()
+            .map(|entry| entry.transition.state)
+            .collect();
+        assert_eq!(
+            states,
+            [
+                ConnectionState::Connected,
+                ConnectionState::Disconnected,
+                ConnectionState::Connected,
+                ConnectionState::Error,
+                ConnectionState::Connected,
+            ]
+        );
+        assert!(restarted
+            .stream_snapshot(stream)
+            .expect("snapshot")
+            .availability_percent_24h
+            .is_some());
+    }
+
+    #[test]
+    fn alert_stats_count_duplicates_per_stream_but_once_globally() {
+        let hub = MonitoringHub::new(10, Duration::from_secs(30));
+        let today = Utc::now().date_naive();
+        hub.broadcast_alerts(Vec::new(), AlertsReason::New, Some("stream-a"), Some("TOR"));
+        hub.note_duplicate_alert("stream-b", "TOR");
+        hub.broadcast_alerts(Vec::new(), AlertsReason::New, Some("stream-a"), Some("RWT"));
+        hub.broadcast_alerts(Vec::new(), AlertsReason::Updated, None, None);
+
+        let stats = hub.alert_stats(7, today);
+        assert_eq!(stats.daily.len(), 7);
+        assert_eq!(stats.since, today - chrono::Duration::days(6));
+        assert_eq!(stats.totals.get("TOR"), Some(&1));
+        assert_eq!(stats.totals.get("RWT"), Some(&1));
+        let day = &stats.daily.last().expect("today").counts;
+        assert_eq!(day.streams["stream-a"].get("TOR"), Some(&1));
+        assert_eq!(day.streams["stream-b"].get("TOR"), Some(&1));
+        assert!(stats.daily[0].counts.total.is_empty());
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        hub.save_persisted(dir.path()).expect("save");
+        let restarted = MonitoringHub::new(10, Duration::from_secs(30));
+        restarted.load_persisted(dir.path());
+        assert_eq!(restarted.alert_stats(1, today).totals, stats.totals);
+
+        restarted.prune_alert_stats(30, today + chrono::Duration::days(30));
+        assert!(restarted
+            .alert_stats(31, today + chrono::Duration::days(30))
+            .totals
+            .is_empty());
+    }
+
+    #[test]
+    fn log_history_is_restored_behind_a_restart_marker() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let log = |hub: &MonitoringHub, message: &str| {
+            hub.record_log(Level::INFO, "eas_listener", message.into(), Map::new())
+        };
+        let hub = MonitoringHub::new(4, Duration::from_secs(30));
+        for message in ["one", "two", "three"] {
+            log(&hub, message);
+        }
+        hub.save_persisted(dir.path()).expect("save");
+
+        let restarted = MonitoringHub::new(4, Duration::from_secs(30));
+        log(&restarted, "started");
+        restarted.load_persisted(dir.path());
+        log(&restarted, "after load");
+
+        let messages: Vec<_> = restarted
+            .recent_logs(10)
+            .into_iter()
+            .rev()
+            .map(|entry| (entry.id, entry.message))
+            .collect();
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[0], (3, "three".to_string()));
+        assert!(messages[1].1.starts_with("Process restarted"));
+        assert_eq!(messages[1].0, 4);
+        assert_eq!(messages[2], (5, "started".to_string()));
+        assert_eq!(messages[3], (6, "after load".to_string()));
+        assert_eq!(restarted.latest_log_id(), 6);
+
+        std::fs::write(dir.path().join(LOG_HISTORY_FILE), "[{").expect("corrupt");
+        let fresh = MonitoringHub::new(4, Duration::from_secs(30));
+        fresh.load_persisted(dir.path());
+        assert!(fresh.recent_logs(10).is_empty());
+    }
+
+    #[tokio::test]
+    async fn lagging_receivers_are_counted_and_clients_warn_once() {
+        let hub = MonitoringHub::new(10, Duration::from_secs(30)).with_event_capacity(2);
+        let mut events = hub.subscribe();
+        let client = hub.register_ws_client();
+        for message in ["one", "two", "three", "four"] {
+            hub.record_log(Level::INFO, "eas_listener", message.into(), Map::new());
+        }
+        hub.broadcast_alerts(Vec::new(), AlertsReason::Updated, None, None);
+
+        let skipped = match events.recv().await {
+            Err(broadcast::error::RecvError::Lagged(skipped)) => skipped,
+            other => panic!("expected a lag, got {other:?}"),
+        };
+        assert!(client.note_lagged(skipped));
+        assert!(!client.note_lagged(1));
+        client.note_sent();
+
+        let stats = hub.event_channel_stats();
+        assert_eq!(stats.capacity, 2);
+        assert_eq!(stats.events_sent.log, 4);
+        assert_eq!(stats.events_sent.alerts, 1);
+        assert_eq!(stats.lag_events, 2);
+        assert_eq!(stats.events_dropped, skipped + 1);
+        assert_eq!(stats.websocket_clients.len(), 1);
+        assert_eq!(stats.websocket_clients[0].lag_skips, 2);
+        assert_eq!(stats.websocket_clients[0].messages_sent, 1);
+
+        drop(client);
+        assert!(hub.event_channel_stats().websocket_clients.is_empty());
+    }
+}
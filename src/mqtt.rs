@@ -0,0 +1,309 @@
+use crate::config::Config;
+use crate::monitoring::{MonitoringEvent, MonitoringHub, StreamStatusPayload};
+use crate::state::{ActiveAlert, AppState};
+use anyhow::{anyhow, Context, Result};
+use rumqttc::{AsyncClient, Event, EventLoop, LastWill, MqttOptions, Packet, QoS};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, Receiver as BroadcastReceiver};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+const MQTT_DEFAULT_PORT: u16 = 1883;
+const MQTT_KEEP_ALIVE: Duration = Duration::from_secs(30);
+const MQTT_REQUEST_CAPACITY: usize = 64;
+const RECONNECT_BACKOFF_MIN: Duration = Duration::from_secs(1);
+const RECONNECT_BACKOFF_MAX: Duration = Duration::from_secs(60);
+const STATUS_ONLINE: &str = "online";
+const STATUS_OFFLINE: &str = "offline";
+
+pub fn parse_broker_url(url: &str) -> Result<(String, u16)> {
+    let trimmed = url.trim();
+    let rest = match trimmed.split_once("://") {
+        Some((scheme, rest)) => match scheme.to_ascii_lowercase().as_str() {
+            "mqtt" | "tcp" => rest,
+            other => return Err(anyhow!("unsupported MQTT scheme '{other}' (use mqtt://)")),
+        },
+        None => trimmed,
+    };
+    let authority = rest.split('/').next().unwrap_or_default();
+    if authority.is_empty() {
+        return Err(anyhow!("MQTT broker host is missing"));
+    }
+
+    match authority.rsplit_once(':') {
+        Some((host, port)) if !host.is_empty() => {
+            let port = port
+                .parse::<u16>()
+                .with_context(|| format!("invalid MQTT broker port '{port}'"))?;
+            Ok((host.to_string(), port))
+        }
+        Some(_) => Err(anyhow!("MQTT broker host is missing")),
+        None => Ok((authority.to_string(), MQTT_DEFAULT_PORT)),
+    }
+}
+
+fn mqtt_settings_changed(old: &Config, new: &Config) -> bool {
+    old.mqtt_url != new.mqtt_url
+        || old.mqtt_username != new.mqtt_username
+        || old.mqtt_password != new.mqtt_password
+        || old.mqtt_topic_prefix != new.mqtt_topic_prefix
+}
+
+fn status_topic(prefix: &str) -> String {
+    format!("{prefix}/status")
+}
+
+fn alert_topic(prefix: &str, event_code: &str) -> String {
+    format!("{prefix}/alerts/{event_code}")
+}
+
+fn stream_topic(prefix: &str, index: usize) -> String {
+    format!("{prefix}/streams/{index}")
+}
+
+fn connect(config: &Config, url: &str) -> Result<(AsyncClient, EventLoop)> {
+    let (host, port) = parse_broker_url(url)?;
+    let client_id = format!("eas-listener-{}", std::process::id());
+    let mut options = MqttOptions::new(client_id, host, port);
+    options.set_keep_alive(MQTT_KEEP_ALIVE);
+    options.set_last_will(LastWill::new(
+        status_topic(&config.mqtt_topic_prefix),
+        STATUS_OFFLINE,
+        QoS::AtLeastOnce,
+        true,
+    ));
+    if let Some(username) = config.mqtt_username.as_deref() {
+        options.set_credentials(username, config.mqtt_password.clone().unwrap_or_default());
+    }
+    Ok(AsyncClient::new(options, MQTT_REQUEST_CAPACITY))
+}
+
+fn publish(client: &AsyncClient, topic: String, retain: bool, payload: Vec<u8>) {
+    if let Err(err) = client.try_publish(&topic, QoS::AtLeastOnce, retain, payload) {
+        warn!("Failed to queue MQTT publish to {}: {}", topic, err);
+    }
+}
+
+fn alert_payloads_by_code(alerts: &[ActiveAlert]) -> HashMap<String, Vec<u8>> {
+    let mut latest: HashMap<String, &ActiveAlert> = HashMap::new();
+    for alert in alerts {
+        let code = alert.data.event_code.trim().to_ascii_uppercase();
+        if code.is_empty() {
+            continue;
+        }
+        let newer = latest
+            .get(&code)
+            .map(|existing| alert.received_at >= existing.received_at)
+            .unwrap_or(true);
+        if newer {
+            latest.insert(code, alert);
+        }
+    }
+
+    latest
+        .into_iter()
+        .filter_map(|(code, alert)| match serde_json::to_vec(alert) {
+            Ok(payload) => Some((code, payload)),
+            Err(err) => {
+                warn!("Failed to serialize alert {} for MQTT: {}", code, err);
+                None
+            }
+        })
+        .collect()
+}
+
+fn publish_alerts(
+    client: &AsyncClient,
+    prefix: &str,
+    published: &mut HashMap<String, Vec<u8>>,
+    alerts: &[ActiveAlert],
+) {
+    let current = alert_payloads_by_code(alerts);
+
+    for code in published.keys() {
+        if !current.contains_key(code) {
+            publish(client, alert_topic(prefix, code), true, Vec::new());
+        }
+    }
+    for (code, payload) in &current {
+        if published.get(code) != Some(payload) {
+            publish(client, alert_topic(prefix, code), true, payload.clone());
+        }
+    }
+
+    *published = current;
+}
+
+fn publish_stream_status(client: &AsyncClient, config: &Config, status: &StreamStatusPayload) {
+    let Some(index) = config
+        .streams
+        .iter()
+        .position(|stream| stream.id == status.stream_url)
+    else {
+        return;
+    };
+
+    let topic = stream_topic(&config.mqtt_topic_prefix, index);
+    if status.is_removed {
+        publish(client, topic, true, Vec::new());
+        return;
+    }
+    match serde_json::to_vec(status) {
+        Ok(payload) => publish(client, topic, true, payload),
+        Err(err) => warn!("Failed to serialize stream status for MQTT: {}", err),
+    }
+}
+
+enum SessionEnd {
+    Reload(Box<Config>),
+    Closed,
+}
+
+async fn run_session(
+    config: &mut Config,
+    url: &str,
+    monitoring: &MonitoringHub,
+    app_state: &Arc<Mutex<AppState>>,
+    reload_rx: &mut BroadcastReceiver<Config>,
+) -> Result<SessionEnd> {
+    let (client, mut eventloop) = connect(config, url)?;
+    let prefix = config.mqtt_topic_prefix.clone();
+    let mut events_rx = monitoring.subscribe();
+    let mut published_alerts: HashMap<String, Vec<u8>> = HashMap::new();
+    let mut backoff = RECONNECT_BACKOFF_MIN;
+    let mut connected = false;
+
+    info!("MQTT publisher connecting to {}", url);
+
+    let end = loop {
+        tokio::select! {
+            polled = eventloop.poll() => match polled {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    info!("MQTT publisher connected to {}", url);
+                    connected = true;
+                    backoff = RECONNECT_BACKOFF_MIN;
+                    publish(&client, status_topic(&prefix), true, STATUS_ONLINE.into());
+                    let alerts = app_state.lock().await.active_alerts.clone();
+                    published_alerts.clear();
+                    publish_alerts(&client, &prefix, &mut published_alerts, &alerts);
+                    for status in monitoring.stream_snapshots() {
+                        publish_stream_status(&client, config, &status);
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    if connected {
+                        warn!("MQTT connection lost: {}. Reconnecting.", err);
+                    } else {
+                        warn!(
+                            "MQTT connection to {} failed: {}. Retrying in {}s.",
+                            url,
+                            err,
+                            backoff.as_secs()
+                        );
+                    }
+                    connected = false;
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(RECONNECT_BACKOFF_MAX);
+                }
+            },
+            event = events_rx.recv() => match event {
+                Ok(MonitoringEvent::Alerts(update)) => {
+                    publish_alerts(&client, &prefix, &mut published_alerts, &update.alerts);
+                }
+                Ok(MonitoringEvent::Stream(status)) => {
+                    publish_stream_status(&client, config, &status);
+                }
+                Ok(MonitoringEvent::Log(_)) | Ok(MonitoringEvent::Resources(_)) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    monitoring.note_lagged(skipped);
+                    warn!("MQTT publisher lagged behind by {} monitoring events.", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break SessionEnd::Closed,
+            },
+            reload = reload_rx.recv() => match reload {
+                Ok(new_config) => {
+                    if mqtt_settings_changed(config, &new_config) {
+                        break SessionEnd::Reload(Box::new(new_config));
+                    }
+                    *config = new_config;
+                    crate::reload::acknowledge("mqtt");
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => break SessionEnd::Closed,
+            },
+        }
+    };
+
+    if connected {
+        publish(&client, status_topic(&prefix), true, STATUS_OFFLINE.into());
+        let _ = client.try_disconnect();
+        let _ = tokio::time::timeout(Duration::from_secs(2), async {
+            while eventloop.poll().await.is_ok() {}
+        })
+        .await;
+    }
+
+    Ok(end)
+}
+
+pub async fn run_mqtt_publisher(
+    mut config: Config,
+    monitoring: MonitoringHub,
+    app_state: Arc<Mutex<AppState>>,
+    mut reload_rx: BroadcastReceiver<Config>,
+) -> Result<()> {
+    crate::reload::register("mqtt");
+    loop {
+        let Some(url) = config.mqtt_url.clone() else {
+            match reload_rx.recv().await {
+                Ok(new_config) => {
+                    config = new_config;
+                    crate::reload::acknowledge("mqtt");
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            }
+            continue;
+        };
+
+        match run_session(&mut config, &url, &monitoring, &app_state, &mut reload_rx).await {
+            Ok(SessionEnd::Reload(new_config)) => {
+                info!("MQTT settings changed; restarting publisher.");
+                config = *new_config;
+                crate::reload::acknowledge("mqtt");
+            }
+            Ok(SessionEnd::Closed) => return Ok(()),
+            Err(err) => {
+                warn!("MQTT publisher disabled: {}", err);
+                config.mqtt_url = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_broker_url_accepts_schemes_and_default_port() {
+        assert_eq!(
+            parse_broker_url("mqtt://broker.local:1884").unwrap(),
+            ("broker.local".to_string(), 1884)
+        );
+        assert_eq!(
+            parse_broker_url("tcp://10.0.0.5").unwrap(),
+            ("10.0.0.5".to_string(), MQTT_DEFAULT_PORT)
+        );
+        assert_eq!(
+            parse_broker_url("homeassistant:1883/").unwrap(),
+            ("homeassistant".to_string(), 1883)
+        );
+        assert!(parse_broker_url("mqtts://broker.local").is_err());
+        assert!(parse_broker_url("mqtt://:1883").is_err());
+        assert!(parse_broker_url("mqtt://broker.local:notaport").is_err());
+    }
+}
@@ -0,0 +1,433 @@
+use crate::alerts::update_alert_files;
+use crate::config::Config;
+use crate::db::DbHandle;
+use crate::monitoring::{AlertsReason, MonitoringHub};
+use crate::state::{ActiveAlert, AppState};
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{debug, error, info, warn};
+
+const STATE_ABBREVIATIONS: &[(&str, &str)] = &[
+    ("01", "AL"),
+    ("02", "AK"),
+    ("04", "AZ"),
+    ("05", "AR"),
+    ("06", "CA"),
+    ("08", "CO"),
+    ("09", "CT"),
+    ("10", "DE"),
+    ("11", "DC"),
+    ("12", "FL"),
+    ("13", "GA"),
+    ("15", "HI"),
+    ("16", "ID"),
+    ("17", "IL"),
+    ("18", "IN"),
+    ("19", "IA"),
+    ("20", "KS"),
+    ("21", "KY"),
+    ("22", "LA"),
+    ("23", "ME"),
+    ("24", "MD"),
+    ("25", "MA"),
+    ("26", "MI"),
+    ("27", "MN"),
+    ("28", "MS"),
+    ("29", "MO"),
+    ("30", "MT"),
+    ("31", "NE"),
+    ("32", "NV"),
+    ("33", "NH"),
+    ("34", "NJ"),
+    ("35", "NM"),
+    ("36", "NY"),
+    ("37", "NC"),
+    ("38", "ND"),
+    ("39", "OH"),
+    ("40", "OK"),
+    ("41", "OR"),
+    ("42", "PA"),
+    ("44", "RI"),
+    ("45", "SC"),
+    ("46", "SD"),
+    ("47", "TN"),
+    ("48", "TX"),
+    ("49", "UT"),
+    ("50", "VT"),
+    ("51", "VA"),
+    ("53", "WA"),
+    ("54", "WV"),
+    ("55", "WI"),
+    ("56", "WY"),
+    ("60", "AS"),
+    ("66", "GU"),
+    ("69", "MP"),
+    ("72", "PR"),
+    ("78", "VI"),
+];
+
+fn state_abbreviation(state_fips: &str) -> Option<&'static str> {
+    STATE_ABBREVIATIONS
+        .iter()
+        .find(|(fips, _)| *fips == state_fips)
+        .map(|(_, abbreviation)| *abbreviation)
+}
+
+fn area_query(fips: &[String]) -> Option<(&'static str, String)> {
+    let mut zones = BTreeSet::new();
+    let mut states = BTreeSet::new();
+    let mut whole_state = false;
+    for code in fips {
+        let code = code.trim();
+        if code.len() != 6 || !code.bytes().all(|byte| byte.is_ascii_digit()) {
+            continue;
+        }
+        let Some(state) = state_abbreviation(&code[1..3]) else {
+            continue;
+        };
+        states.insert(state);
+        if &code[3..] == "000" {
+            whole_state = true;
+        } else {
+            zones.insert(format!("{state}C{}", &code[3..]));
+        }
+    }
+    if states.is_empty() {
+        None
+    } else if whole_state {
+        Some(("area", states.into_iter().collect::<Vec<_>>().join(",")))
+    } else {
+        Some(("zone", zones.into_iter().collect::<Vec<_>>().join(",")))
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct AlertCollection {
+    #[serde(default)]
+    features: Vec<AlertFeature>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AlertFeature {
+    properties: AlertProperties,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct SameCodes {
+    #[serde(rename = "SAME")]
+    same: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AlertProperties {
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    sent: String,
+    #[serde(default)]
+    geocode: SameCodes,
+    #[serde(default)]
+    event_code: SameCodes,
+    headline: Option<String>,
+    description: Option<String>,
+    instruction: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NwsEnrichment {
+    pub id: String,
+    pub headline: Option<String>,
+    pub description: Option<String>,
+    pub instruction: Option<String>,
+}
+
+fn unwrap_paragraphs(text: &str) -> String {
+    text.split("\n\n")
+        .map(|paragraph| {
+            paragraph
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+impl NwsEnrichment {
+    pub fn text(&self) -> Option<String> {
+        let parts: Vec<String> = [&self.headline, &self.description]
+            .into_iter()
+            .flatten()
+            .map(|part| unwrap_paragraphs(part))
+            .filter(|part| !part.is_empty())
+            .collect();
+        (!parts.is_empty()).then(|| parts.join("\n\n"))
+    }
+
+    pub fn instruction_text(&self) -> Option<String> {
+        self.instruction
+            .as_deref()
+            .map(unwrap_paragraphs)
+            .filter(|text| !text.is_empty())
+    }
+}
+
+fn same_county(a: &str, b: &str) -> bool {
+    a.len() == 6 && b.len() == 6 && a[1..] == b[1..]
+}
+
+fn matching_alert(
+    collection: AlertCollection,
+    event_code: &str,
+    fips: &[String],
+) -> Option<NwsEnrichment> {
+    collection
+        .features
+        .into_iter()
+        .map(|feature| feature.properties)
+        .filter(|properties| {
+            properties.event_code.same.is_empty()
+                || properties
+                    .event_code
+                    .same
+                    .iter()
+                    .any(|code| code.eq_ignore_ascii_case(event_code))
+        })
+        .filter(|properties| {
+            properties.geocode.same.iter().any(|theirs| {
+                fips.iter().any(|ours| {
+                    same_county(theirs, ours)
+                        || (ours.ends_with("000") && theirs.get(1..3) == ours.get(1..3))
+                })
+            })
+        })
+        .max_by(|a, b| a.sent.cmp(&b.sent))
+        .map(|properties| NwsEnrichment {
+            id: properties.id,
+            headline: properties.headline,
+            description: properties.description,
+            instruction: properties.instruction,
+        })
+}
+
+async fn lookup(
+    client: &reqwest::Client,
+    config: &Config,
+    event_code: &str,
+    fips: &[String],
+) -> Result<Option<NwsEnrichment>> {
+    let Some((area_key, area)) = area_query(fips) else {
+        return Ok(None);
+    };
+    let response = client
+        .get(format!("{}/alerts/active", config.nws_api_url))
+        .query(&[("code", event_code), (area_key, area.as_str())])
+        .header(reqwest::header::USER_AGENT, &config.nws_api_user_agent)
+        .header(reqwest::header::ACCEPT, "application/geo+json")
+        .send()
+        .await
+        .context("NWS API request failed")?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!("NWS API answered {}", status);
+    }
+    let collection: AlertCollection = response
+        .json()
+        .await
+        .context("NWS API returned unexpected JSON")?;
+    Ok(matching_alert(collection, event_code, fips))
+}
+
+pub async fn enrich(
+    config: Config,
+    state: Arc<Mutex<AppState>>,
+    monitoring: MonitoringHub,
+    db: DbHandle,
+    alert: ActiveAlert,
+) {
+    let event_code = alert.data.event_code.trim().to_ascii_uppercase();
+    let timeout = Duration::from_secs(config.nws_enrichment_timeout_secs);
+    let client = match reqwest::Client::builder().timeout(timeout).build() {
+        Ok(client) => client,
+        Err(err) => {
+            warn!("Failed to build NWS API client: {}", err);
+            return;
+        }
+    };
+    let found = match lookup(&client, &config, &event_code, &alert.data.fips).await {
+        Ok(Some(found)) => found,
+        Ok(None) => {
+            debug!(
+                "NWS API has no active {} alert for {:?}",
+                event_code, alert.data.fips
+            );
+            return;
+        }
+        Err(err) => {
+            warn!("NWS enrichment for {} failed: {:#}", event_code, err);
+            return;
+        }
+    };
+    let Some(description) = found.text() else {
+        return;
+    };
+    info!("Enriched {} with NWS alert {}", event_code, found.id);
+
+    let active_snapshot = {
+        let mut guard = state.lock().await;
+        if !guard.update_alert_description(&alert.raw_header, &description) {
+            return;
+        }
+        if let Err(err) = update_alert_files(&config.shared_state_dir, &guard).await {
+            error!("Failed to update alert files with NWS text: {}", err);
+        }
+        guard.active_alerts.clone()
+    };
+    monitoring.broadcast_alerts(
+        active_snapshot,
+        AlertsReason::Updated,
+        None,
+        Some(event_code.as_str()),
+    );
+    db.update_description(
+        &alert.raw_header,
+        &description,
+        found.instruction_text().as_deref(),
+    )
+    .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESPONSE: &str = r#"{
+        "type": "FeatureCollection",
+        "features": [
+            {
+                "properties": {
+                    "id": "urn:oid:2.49.0.1.840.0.older",
+                    "sent": "2024-12-04T11:30:00-06:00",
+                    "geocode": { "SAME": ["031055"], "UGC": ["NEC055"] },
+                    "eventCode": { "SAME": ["SVR"], "NationalWeatherService": ["SVW"] },
+                    "headline": "Older warning",
+                    "description": "Old text."
+                }
+            },
+            {
+                "properties": {
+                    "id": "urn:oid:2.49.0.1.840.0.newer",
+                    "sent": "2024-12-04T11:58:00-06:00",
+                    "geocode": { "SAME": ["031153", "031055"] },
+                    "eventCode": { "SAME": ["SVR"] },
+                    "headline": "Severe Thunderstorm Warning issued December 4 at 11:58AM CST",
+                    "description": "* WHAT...Severe thunderstorm winds up to 60 mph.\n\n* WHERE...Douglas and\nSarpy Counties.",
+                    "instruction": "For your protection move to an interior room\non the lowest floor."
+                }
+            },
+            {
+                "properties": {
+                    "id": "urn:oid:2.49.0.1.840.0.elsewhere",
+                    "sent": "2024-12-04T12:10:00-06:00",
+                    "geocode": { "SAME": ["019155"] },
+                    "eventCode": { "SAME": ["SVR"] }
+                }
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn fips_codes_become_nws_zones_or_states() {
+        let fips = |codes: &[&str]| {
+            codes
+                .iter()
+                .map(|code| code.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            area_query(&fips(&["031153", "131055", "031055"])),
+            Some(("zone", "NEC055,NEC153".to_string()))
+        );
+        assert_eq!(
+            area_query(&fips(&["031055", "019000"])),
+            Some(("area", "IA,NE".to_string()))
+        );
+        assert_eq!(area_query(&fips(&["073530", "bogus"])), None);
+    }
+
+    #[test]
+    fn the_newest_alert_for_the_event_and_county_is_chosen() {
+        let collection = || serde_json::from_str::<AlertCollection>(RESPONSE).expect("parse");
+        let found = matching_alert(collection(), "SVR", &["031055".to_string()]).expect("match");
+        assert_eq!(found.id, "urn:oid:2.49.0.1.840.0.newer");
+        assert_eq!(
+            found.text().as_deref(),
+            Some("Severe Thunderstorm Warning issued December 4 at 11:58AM CST\n\n* WHAT...Severe thunderstorm winds up to 60 mph.\n\n* WHERE...Douglas and Sarpy Counties.")
+        );
+        assert_eq!(
+            found.instruction_text().as_deref(),
+            Some("For your protection move to an interior room on the lowest floor.")
+        );
+
+        assert!(matching_alert(collection(), "TOR", &["031055".to_string()]).is_none());
+        assert!(matching_alert(collection(), "SVR", &["031177".to_string()]).is_none());
+        let statewide = matching_alert(collection(), "SVR", &["019000".to_string()]);
+        assert_eq!(
+            statewide.map(|found| found.id).as_deref(),
+            Some("urn:oid:2.49.0.1.840.0.elsewhere")
+        );
+    }
+
+    #[tokio::test]
+    async fn lookups_query_by_event_and_zone() {
+        use axum::extract::RawQuery;
+        use axum::http::HeaderMap;
+        use axum::routing::get;
+
+        let app = axum::Router::new().route(
+            "/alerts/active",
+            get(|RawQuery(query): RawQuery, headers: HeaderMap| async move {
+                let agent = headers
+                    .get("user-agent")
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or_default();
+                if query.as_deref() == Some("code=SVR&zone=NEC055") && agent == "test-agent" {
+                    RESPONSE.to_string()
+                } else {
+                    r#"{"features": []}"#.to_string()
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let mut config = Config::safe_internal_defaults();
+        config.nws_api_url = format!("http://{}", listener.local_addr().expect("addr"));
+        config.nws_api_user_agent = "test-agent".to_string();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("serve");
+        });
+
+        let client = reqwest::Client::new();
+        let found = lookup(&client, &config, "SVR", &["031055".to_string()])
+            .await
+            .expect("lookup");
+        assert_eq!(
+            found.map(|found| found.id).as_deref(),
+            Some("urn:oid:2.49.0.1.840.0.newer")
+        );
+        let missing = lookup(&client, &config, "TOR", &["031055".to_string()])
+            .await
+            .expect("lookup");
+        assert!(missing.is_none());
+    }
+}
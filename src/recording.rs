@@ -1,16 +1,24 @@
-use crate::config::Config;
+use crate::config::{Config, LowDiskAction, RecordingFormat, RecordingSampleFormat};
 use crate::header;
+use crate::state::EasAlertData;
+use crate::webhook;
 use anyhow::{anyhow, Context, Result};
-use chrono::Local;
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
 use hound::{WavSpec, WavWriter};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
 use rubato::{
     Resampler, SincFixedIn, SincInterpolationParameters, SincInterpolationType, WindowFunction,
 };
-use serde::Deserialize;
-use std::collections::VecDeque;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::f32::consts::PI;
+use std::ffi::OsString;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 use symphonia::core::audio::SampleBuffer;
 use symphonia::core::codecs::DecoderOptions;
 use symphonia::core::formats::FormatOptions;
@@ -25,9 +33,29 @@ const HEADER_AMPLITUDE: f64 = 0.42;
 const SAME_MARK_FREQ_HZ: f32 = 2083.3;
 const SAME_SPACE_FREQ_HZ: f32 = 1562.5;
 const SAME_BIT_DURATION_SEC: f64 = 0.00192;
+const PARTIAL_SUFFIX: &str = ".part";
 const SAME_PREAMBLE_BYTE: u8 = 0xD5;
 const SAME_PREAMBLE_BYTES: usize = 16;
 const NNNN_TAIL_BUFFER_SECONDS: usize = 10;
+const NORMALIZE_MAX_HELD_SECONDS: usize = 600;
+const NORMALIZE_MAX_GAIN_DB: f32 = 20.0;
+const RECORDING_FILE_PREFIX: &str = "EAS_Recording_";
+const RECORDING_TIMESTAMP_FORMAT: &str = "%Y-%m-%d_%H-%M-%S";
+pub const DEFAULT_RECORDING_FILENAME_TEMPLATE: &str =
+    "EAS_Recording_{date}_{time}_{event}_{stream}";
+const RECORDING_TEMPLATE_FIELDS: [&str; 9] = [
+    "event",
+    "originator",
+    "date",
+    "time",
+    "year",
+    "month",
+    "day",
+    "stream",
+    "seq",
+];
+const RECORDING_EXTENSIONS: [&str; 4] = ["wav", "flac", "mp3", "ogg"];
+static RUNNING_ENCODERS: AtomicUsize = AtomicUsize::new(0);
 const NNNN_DETECT_SCAN_SECONDS: usize = 8;
 const NNNN_OFFSET_STEP: usize = 2;
 const NNNN_MIN_MATCH_BITS: usize = 128;
@@ -42,6 +70,33 @@ const TRAILING_NEAR_SILENCE_HOP_MS: usize = 5;
 const TRAILING_NEAR_SILENCE_FLOOR: i16 = 16;
 const TRAILING_NEAR_SILENCE_PEAK_THRESHOLD: i16 = 1200;
 const TRAILING_NEAR_SILENCE_RMS_THRESHOLD: f32 = 80.0;
+const LOW_DISK_COLOR: u32 = 0xFFA500;
+const LOW_DISK_NOTICE_COOLDOWN: Duration = Duration::from_secs(15 * 60);
+
+pub const RECORDING_INDEX_FILE: &str = "index.jsonl";
+static RECORDING_INDEX_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+static LAST_LOW_DISK_NOTICE: Lazy<Mutex<Option<Instant>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingMetadata {
+    pub raw_header: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alert: Option<EasAlertData>,
+    pub source_stream: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    pub sample_rate: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_samples: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_start_sample: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_samples: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clipped_samples: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_url: Option<String>,
+}
 
 #[derive(Debug, Clone)]
 pub struct RecordingState {
@@ -50,45 +105,238 @@ pub struct RecordingState {
     pub source_stream: String,
 }
 
+#[derive(Debug, PartialEq, Eq)]
+enum SpaceCheck {
+    Enough,
+    Freed {
+        deleted: Vec<String>,
+        available: u64,
+    },
+    Short {
+        deleted: Vec<String>,
+        available: u64,
+    },
+}
+
+fn make_room(
+    recording_dir: &Path,
+    min_free_bytes: u64,
+    action: LowDiskAction,
+    active: &HashSet<OsString>,
+    free_bytes: impl Fn(&Path) -> Option<u64>,
+) -> SpaceCheck {
+    let Some(mut available) = free_bytes(recording_dir) else {
+        return SpaceCheck::Enough;
+    };
+    if available >= min_free_bytes {
+        return SpaceCheck::Enough;
+    }
+
+    let mut deleted = Vec::new();
+    if action == LowDiskAction::DeleteOldest {
+        let recordings = scan_recordings(recording_dir).unwrap_or_else(|err| {
+            warn!("Failed to list recordings in {:?}: {}", recording_dir, err);
+            Vec::new()
+        });
+        for recording in recordings.into_iter().rev() {
+            if available >= min_free_bytes {
+                break;
+            }
+            if Path::new(&recording.file)
+                .file_stem()
+                .is_some_and(|stem| active.contains(stem))
+            {
+                continue;
+            }
+            let path = recording_dir.join(&recording.file);
+            if let Err(err) = remove_recording_files(&path) {
+                warn!("Failed to delete recording {}: {}", recording.file, err);
+                continue;
+            }
+            info!(
+                "Recording {} deleted ({} bytes) to free space for a new recording",
+                recording.file, recording.size_bytes
+            );
+            available = free_bytes(recording_dir)
+                .unwrap_or_else(|| available.saturating_add(recording.size_bytes));
+            deleted.push(recording.file);
+        }
+    }
+
+    if available >= min_free_bytes {
+        SpaceCheck::Freed { deleted, available }
+    } else {
+        SpaceCheck::Short { deleted, available }
+    }
+}
+
+fn notify_low_disk(title: &str, body: String) {
+    {
+        let mut last = LAST_LOW_DISK_NOTICE.lock();
+        if last.is_some_and(|sent| sent.elapsed() < LOW_DISK_NOTICE_COOLDOWN) {
+            return;
+        }
+        *last = Some(Instant::now());
+    }
+    let title = title.to_string();
+    tokio::spawn(async move {
+        webhook::send_operational_notification(&title, &body, LOW_DISK_COLOR).await;
+    });
+}
+
+fn ensure_recording_space(
+    config: &Config,
+    active_recordings: &HashMap<String, RecordingState>,
+) -> Result<()> {
+    if config.min_free_disk_mb == 0 {
+        return Ok(());
+    }
+    let active: HashSet<OsString> = active_recordings
+        .values()
+        .filter_map(|recording| recording.output_path.file_stem())
+        .map(ToOwned::to_owned)
+        .collect();
+    let min_free_bytes = config.min_free_disk_mb.saturating_mul(1024 * 1024);
+    let check = make_room(
+        &config.recording_dir,
+        min_free_bytes,
+        config.low_disk_action,
+        &active,
+        |path| crate::resources::filesystem_space(path).map(|(available, _)| available),
+    );
+
+    let mib = |bytes: u64| bytes / (1024 * 1024);
+    match check {
+        SpaceCheck::Enough => Ok(()),
+        SpaceCheck::Freed { deleted, available } => {
+            let body = format!(
+                "Free space in {} dropped below {} MiB; deleted the {} oldest recording(s) ({}), leaving {} MiB free.",
+                config.recording_dir.display(),
+                config.min_free_disk_mb,
+                deleted.len(),
+                deleted.join(", "),
+                mib(available)
+            );
+            warn!("{}", body);
+            notify_low_disk("Recordings deleted to free disk space", body);
+            Ok(())
+        }
+        SpaceCheck::Short { deleted, available } => {
+            let mut body = format!(
+                "Only {} MiB free in {}, below MIN_FREE_DISK_MB ({} MiB); new alerts will not be recorded.",
+                mib(available),
+                config.recording_dir.display(),
+                config.min_free_disk_mb
+            );
+            if !deleted.is_empty() {
+                body.push_str(&format!(
+                    " {} recording(s) were deleted without freeing enough space.",
+                    deleted.len()
+                ));
+            }
+            notify_low_disk("Low disk space for recordings", body);
+            Err(anyhow!(
+                "only {} MiB free in {:?}, below MIN_FREE_DISK_MB ({} MiB)",
+                mib(available),
+                config.recording_dir,
+                config.min_free_disk_mb
+            ))
+        }
+    }
+}
+
 pub fn start_encoding_task(
     config: &Config,
     header_text: &str,
+    alert: Option<&EasAlertData>,
     source_stream: &str,
+    active_recordings: &HashMap<String, RecordingState>,
 ) -> Result<(tokio::task::JoinHandle<Result<()>>, RecordingState)> {
-    start_encoding_task_with_timestamp(config, header_text, source_stream, None)
+    start_encoding_task_with_timestamp(
+        config,
+        header_text,
+        alert,
+        source_stream,
+        None,
+        active_recordings,
+    )
 }
 
 pub fn start_encoding_task_with_timestamp(
     config: &Config,
     header_text: &str,
+    alert: Option<&EasAlertData>,
     source_stream: &str,
     filename_timestamp: Option<&str>,
+    active_recordings: &HashMap<String, RecordingState>,
 ) -> Result<(tokio::task::JoinHandle<Result<()>>, RecordingState)> {
+    if crate::lifecycle::shutting_down() {
+        return Err(anyhow!("Shutting down; not starting a recording"));
+    }
     std::fs::create_dir_all(&config.recording_dir)?;
-    let timestamp = filename_timestamp
-        .map(ToOwned::to_owned)
-        .unwrap_or_else(|| Local::now().format("%Y-%m-%d_%H-%M-%S").to_string());
-    let event_code = event_code_from_header(header_text);
-    let stream_label = stream_label_from_source(source_stream);
-    let storage_saver = config.storage_saver_mode;
-    let saver_format = config.storage_saver_ext;
-    let codec_args = saver_format.ffmpeg_codec_args();
-    let final_extension = if storage_saver {
-        saver_format.extension()
-    } else {
-        "wav"
-    };
-    let output_path = next_available_recording_path(
-        &config.recording_dir,
-        event_code.as_str(),
-        &timestamp,
-        stream_label.as_str(),
-        final_extension,
+    ensure_recording_space(config, active_recordings)?;
+    let recorded_at = filename_timestamp
+        .and_then(|timestamp| {
+            NaiveDateTime::parse_from_str(timestamp, RECORDING_TIMESTAMP_FORMAT).ok()
+        })
+        .unwrap_or_else(|| Local::now().naive_local());
+    let fields = RecordingNameFields::from_header(
+        header_text,
+        &stream_label_from_source(source_stream),
+        recorded_at,
     );
-    let wav_path = if storage_saver {
-        output_path.with_extension("wav")
-    } else {
-        output_path.clone()
+    let recording_path = |format: RecordingFormat| -> Result<PathBuf> {
+        let path = next_available_recording_path(
+            &config.recording_dir,
+            &config
+                .recording_filename_template
+                .with_subdirs(config.recording_subdir_scheme),
+            &fields,
+            format.extension(),
+        );
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Ok(path)
+    };
+    let started_at = Utc::now();
+    let tags = recording_tags(header_text, alert, source_stream, started_at);
+    let mut output_path = recording_path(config.recording_format)?;
+    let create_sink = |path: &Path, format: RecordingFormat| {
+        RecordingSink::create(
+            path,
+            format,
+            config.recording_sample_format,
+            config.recording_sample_rate,
+            &tags,
+        )
+    };
+    let sink = match create_sink(&output_path, config.recording_format) {
+        Ok(sink) => sink,
+        Err(err) if config.recording_format != RecordingFormat::Wav => {
+            warn!(
+                "Failed to start the {} encoder ({:#}); recording as WAV instead",
+                config.recording_format.extension(),
+                err
+            );
+            output_path = recording_path(RecordingFormat::Wav)?;
+            create_sink(&output_path, RecordingFormat::Wav)?
+        }
+        Err(err) => return Err(err),
+    };
+    let recording_dir = config.recording_dir.clone();
+    let mut metadata = RecordingMetadata {
+        raw_header: header_text.to_string(),
+        alert: alert.cloned(),
+        source_stream: source_stream.to_string(),
+        started_at,
+        ended_at: started_at,
+        sample_rate: config.recording_sample_rate,
+        total_samples: None,
+        audio_start_sample: None,
+        audio_samples: None,
+        clipped_samples: None,
+        remote_url: None,
     };
     let output_path_clone = output_path.clone();
 
@@ -140,151 +388,161 @@ pub fn start_encoding_task_with_timestamp(
         None
     };
 
-    let header_samples =
-        header::generate_same_header_samples(header_text, TARGET_SAMPLE_RATE, HEADER_AMPLITUDE)?;
-    let header_sample_count = header_samples.len();
-
-    let nnnn_samples =
-        header::generate_same_header_samples("NNNN", TARGET_SAMPLE_RATE, HEADER_AMPLITUDE)?;
-    let nnnn_sample_count = nnnn_samples.len();
-    let nnnn_burst_cycle_samples = nnnn_sample_count / 3;
+    let inject_same = config.recording_inject_same;
+    let sample_rate = config.recording_sample_rate;
+    let (header_samples, nnnn_samples) = same_tones(config, header_text, sample_rate)?;
+    let nnnn_burst_cycle_samples =
+        header::generate_same_header_samples("NNNN", TARGET_SAMPLE_RATE, HEADER_AMPLITUDE)?.len()
+            / 3;
     let nnnn_tail_buffer_samples = TARGET_SAMPLE_RATE as usize * NNNN_TAIL_BUFFER_SECONDS;
+    let gain_db = config
+        .stream(source_stream)
+        .map_or(0.0, |stream| stream.recording_gain_db);
+    let mut writer =
+        RecordingWriter::new(sink, sample_rate, gain_db, config.recording_normalize_dbfs)?;
 
     let (audio_tx, audio_rx) = mpsc::channel::<Vec<f32>>(32);
 
+    let running = crate::resources::track(&RUNNING_ENCODERS);
     let handle = tokio::spawn(async move {
-        let spec = WavSpec {
-            channels: 1,
-            sample_rate: TARGET_SAMPLE_RATE,
-            bits_per_sample: 16,
-            sample_format: hound::SampleFormat::Int,
-        };
-
-        let writer = WavWriter::create(&wav_path, spec)?;
-
-        let samples_written = tokio::task::spawn_blocking(move || {
-            let mut blocking_writer = writer;
-            let mut audio_rx = audio_rx;
-            let mut samples_written = 0usize;
-
-            if let Some(ref intro) = intro_samples {
-                for &sample in intro {
-                    blocking_writer.write_sample(sample)?;
+        let _running = running;
+        let Some((samples_written, audio_start, audio_written, clipped_samples, peak)) =
+            tokio::task::spawn_blocking(move || {
+                let _active = crate::resources::track(&crate::resources::RECORDING_WRITERS);
+                let mut audio_rx = audio_rx;
+                let mut audio_received = 0usize;
+
+                if let Some(ref intro) = intro_samples {
+                    writer.write_pipeline_pcm16(intro)?;
                 }
-                samples_written += intro.len();
-            }
-
-            for &sample in &header_samples {
-                blocking_writer.write_sample(sample)?;
-            }
-            samples_written += header_sample_count;
-            let amplitude = i16::MAX as f32;
-            let mut trailing_buffer: VecDeque<i16> =
-                VecDeque::with_capacity(nnnn_tail_buffer_samples + 8192);
-            while let Some(samples) = audio_rx.blocking_recv() {
-                for sample in samples {
-                    trailing_buffer.push_back((sample * amplitude) as i16);
+                if inject_same {
+                    writer.write_tones(&header_samples)?;
+                } else {
+                    writer.flush()?;
                 }
-
-                let overflow = trailing_buffer
-                    .len()
-                    .saturating_sub(nnnn_tail_buffer_samples);
-                for _ in 0..overflow {
-                    if let Some(sample) = trailing_buffer.pop_front() {
-                        blocking_writer.write_sample(sample)?;
-                        samples_written += 1;
+                let audio_start = writer.frames_written;
+                let mut trailing_buffer: VecDeque<f32> =
+                    VecDeque::with_capacity(nnnn_tail_buffer_samples + 8192);
+                while let Some(samples) = audio_rx.blocking_recv() {
+                    trailing_buffer.extend(samples);
+                    let overflow = trailing_buffer
+                        .len()
+                        .saturating_sub(nnnn_tail_buffer_samples);
+                    if overflow > 0 {
+                        let overflow: Vec<f32> = trailing_buffer.drain(..overflow).collect();
+                        writer.write_received(&overflow)?;
+                        audio_received += overflow.len();
                     }
                 }
-            }
-
-            let mut trailing_samples: Vec<i16> = trailing_buffer.into_iter().collect();
-            if let Some(trim_from) =
-                detect_trailing_nnnn_start(&trailing_samples, nnnn_burst_cycle_samples)
-            {
-                let guard_samples = (TARGET_SAMPLE_RATE as usize * NNNN_TRIM_GUARD_MS) / 1000;
-                let zero_cross_lookback =
-                    (TARGET_SAMPLE_RATE as usize * NNNN_ZERO_CROSS_LOOKBACK_MS) / 1000;
-                let trim_from = trim_from.saturating_sub(guard_samples);
-                let trim_from =
-                    snap_trim_to_zero_crossing(&trailing_samples, trim_from, zero_cross_lookback);
-                trailing_samples.truncate(trim_from);
-            }
-            let min_silence_trim_samples =
-                (TARGET_SAMPLE_RATE as usize * TRAILING_SILENCE_MIN_TRIM_MS) / 1000;
-            let near_silence_window_samples =
-                (TARGET_SAMPLE_RATE as usize * TRAILING_NEAR_SILENCE_WINDOW_MS) / 1000;
-            let near_silence_hop_samples =
-                (TARGET_SAMPLE_RATE as usize * TRAILING_NEAR_SILENCE_HOP_MS) / 1000;
-            trim_trailing_near_silence(
-                &mut trailing_samples,
-                TRAILING_NEAR_SILENCE_FLOOR,
-                TRAILING_NEAR_SILENCE_PEAK_THRESHOLD,
-                TRAILING_NEAR_SILENCE_RMS_THRESHOLD,
-                near_silence_window_samples,
-                near_silence_hop_samples,
-                min_silence_trim_samples,
-            );
-            let fade_out_samples = (TARGET_SAMPLE_RATE as usize * TAIL_FADE_OUT_MS) / 1000;
-            apply_fade_out(&mut trailing_samples, fade_out_samples);
-            let trailing_len = trailing_samples.len();
-            for sample in trailing_samples {
-                blocking_writer.write_sample(sample)?;
-            }
-            samples_written += trailing_len;
 
-            let silence_samples_before_nnnn = TARGET_SAMPLE_RATE as usize;
-            for _ in 0..silence_samples_before_nnnn {
-                blocking_writer.write_sample(0i16)?;
-            }
-            samples_written += silence_samples_before_nnnn;
-
-            for &sample in &nnnn_samples {
-                blocking_writer.write_sample(sample)?;
-            }
-
-            samples_written += nnnn_sample_count;
-
-            if let Some(ref outro) = outro_samples {
-                let silence_before_outro = TARGET_SAMPLE_RATE as usize;
-                for _ in 0..silence_before_outro {
-                    blocking_writer.write_sample(0i16)?;
+                let mut trailing_samples: Vec<f32> = trailing_buffer.into_iter().collect();
+                let mut trailing_pcm: Vec<i16> = trailing_samples
+                    .iter()
+                    .map(|&sample| (sample * i16::MAX as f32) as i16)
+                    .collect();
+                let nnnn_start =
+                    detect_trailing_nnnn_start(&trailing_pcm, nnnn_burst_cycle_samples);
+                if !inject_same {
+                    writer.write_received(&trailing_samples)?;
+                    audio_received += nnnn_start.unwrap_or(trailing_samples.len());
+                } else {
+                    if let Some(trim_from) = nnnn_start {
+                        let guard_samples =
+                            (TARGET_SAMPLE_RATE as usize * NNNN_TRIM_GUARD_MS) / 1000;
+                        let zero_cross_lookback =
+                            (TARGET_SAMPLE_RATE as usize * NNNN_ZERO_CROSS_LOOKBACK_MS) / 1000;
+                        let trim_from = trim_from.saturating_sub(guard_samples);
+                        let trim_from = snap_trim_to_zero_crossing(
+                            &trailing_pcm,
+                            trim_from,
+                            zero_cross_lookback,
+                        );
+                        trailing_pcm.truncate(trim_from);
+                    }
+                    let min_silence_trim_samples =
+                        (TARGET_SAMPLE_RATE as usize * TRAILING_SILENCE_MIN_TRIM_MS) / 1000;
+                    let near_silence_window_samples =
+                        (TARGET_SAMPLE_RATE as usize * TRAILING_NEAR_SILENCE_WINDOW_MS) / 1000;
+                    let near_silence_hop_samples =
+                        (TARGET_SAMPLE_RATE as usize * TRAILING_NEAR_SILENCE_HOP_MS) / 1000;
+                    trim_trailing_near_silence(
+                        &mut trailing_pcm,
+                        TRAILING_NEAR_SILENCE_FLOOR,
+                        TRAILING_NEAR_SILENCE_PEAK_THRESHOLD,
+                        TRAILING_NEAR_SILENCE_RMS_THRESHOLD,
+                        near_silence_window_samples,
+                        near_silence_hop_samples,
+                        min_silence_trim_samples,
+                    );
+                    trailing_samples.truncate(trailing_pcm.len());
+                    let fade_out_samples = (TARGET_SAMPLE_RATE as usize * TAIL_FADE_OUT_MS) / 1000;
+                    apply_fade_out(&mut trailing_samples, fade_out_samples);
+                    writer.write_received(&trailing_samples)?;
+                    audio_received += trailing_samples.len();
+
+                    if !nnnn_samples.is_empty() {
+                        writer.write_silence(sample_rate as usize)?;
+                        writer.write_tones(&nnnn_samples)?;
+                    }
                 }
-                samples_written += silence_before_outro;
 
-                for &sample in outro {
-                    blocking_writer.write_sample(sample)?;
+                if let Some(ref outro) = outro_samples {
+                    writer.write_silence(sample_rate as usize)?;
+                    writer.write_pipeline_pcm16(outro)?;
                 }
-                samples_written += outro.len();
-            }
-
-            blocking_writer.finalize()?;
-            Ok::<_, anyhow::Error>(samples_written)
-        })
-        .await??;
+                writer.flush()?;
 
-        if samples_written == 0 {
-            let _ = tokio::fs::remove_file(&wav_path).await;
-            info!("Deleted empty recording file: {:?}", wav_path);
+                let samples_written = writer.frames_written;
+                let audio_written = writer.frames_for(audio_received);
+                if samples_written == 0 || (!inject_same && audio_written == 0) {
+                    writer.sink.discard();
+                    return Ok(None);
+                }
+                let (clipped_samples, peak) = (writer.clipped_samples, writer.peak);
+                writer.sink.finalize()?;
+                Ok::<_, anyhow::Error>(Some((
+                    samples_written,
+                    audio_start,
+                    audio_written,
+                    clipped_samples,
+                    peak,
+                )))
+            })
+            .await??
+        else {
+            info!("Discarded empty recording: {:?}", output_path);
             return Ok(());
-        }
+        };
 
-        if storage_saver {
-            match transcode_wav(&wav_path, &output_path, codec_args).await {
-                Ok(()) => {
-                    let _ = tokio::fs::remove_file(&wav_path).await;
-                    info!("Finished writing recording to: {:?}", output_path);
-                }
-                Err(err) => {
-                    warn!(
-                        "Failed to transcode recording to MP3 ({}); keeping WAV at {:?}",
-                        err, wav_path
-                    );
-                }
-            }
-        } else {
-            info!("Finished writing recording to: {:?}", output_path);
+        metadata.ended_at = Utc::now();
+        metadata.total_samples = Some(samples_written);
+        metadata.audio_start_sample = Some(audio_start);
+        metadata.audio_samples = Some(audio_written);
+        metadata.clipped_samples = Some(clipped_samples);
+        if clipped_samples > 0 {
+            warn!(
+                "Recording {:?} clipped {} sample(s); the stream peaked at {:.1} dBFS. Lower its recording_gain_db or set RECORDING_NORMALIZE",
+                output_path,
+                clipped_samples,
+                20.0 * peak.log10()
+            );
+        }
+        if let Err(err) = write_sidecar(&output_path, &metadata) {
+            warn!(
+                "Failed to write the metadata sidecar for {:?}: {:#}",
+                output_path, err
+            );
+        }
+        let index_path = output_path.clone();
+        let indexed = tokio::task::spawn_blocking(move || {
+            index_recording(&recording_dir, &index_path, Some(&metadata))
+        })
+        .await?;
+        if let Err(err) = indexed {
+            warn!("Failed to index recording {:?}: {:#}", output_path, err);
         }
 
+        info!("Finished writing recording to: {:?}", output_path);
         Ok(())
     });
 
@@ -296,6 +554,67 @@ pub fn start_encoding_task_with_timestamp(
     Ok((handle, state))
 }
 
+pub async fn finish_recordings(
+    recording_state: &tokio::sync::Mutex<HashMap<String, RecordingState>>,
+    grace: Duration,
+) -> usize {
+    let stopped = {
+        let mut recorder = recording_state.lock().await;
+        let stopped = recorder.len();
+        recorder.clear();
+        stopped
+    };
+    if stopped > 0 {
+        info!("Finishing {} recording(s) before shutting down", stopped);
+    }
+    let deadline = tokio::time::Instant::now() + grace;
+    loop {
+        let running = RUNNING_ENCODERS.load(Ordering::Relaxed);
+        if running == 0 || tokio::time::Instant::now() >= deadline {
+            return running;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+pub fn same_tones(
+    config: &Config,
+    header_text: &str,
+    sample_rate: u32,
+) -> Result<(Vec<i16>, Vec<i16>)> {
+    if let Err(err) = header::validate_same_header(header_text) {
+        warn!(
+            header = %header_text,
+            "Not synthesizing SAME tones for an invalid header: {}",
+            err
+        );
+        return Ok((Vec::new(), Vec::new()));
+    }
+    let relayed_header = header::with_station_id(header_text, &config.same_station_id);
+    let header_samples = if config.recording_attention_tone {
+        header::generate_same_header_with_attention_tone(
+            &relayed_header,
+            sample_rate,
+            HEADER_AMPLITUDE,
+            &config.same_timing,
+        )?
+    } else {
+        header::generate_same_header_samples_with_timing(
+            &relayed_header,
+            sample_rate,
+            HEADER_AMPLITUDE,
+            &config.same_timing,
+        )?
+    };
+    let nnnn_samples = header::generate_same_header_samples_with_timing(
+        "NNNN",
+        sample_rate,
+        HEADER_AMPLITUDE,
+        &config.same_timing,
+    )?;
+    Ok((header_samples, nnnn_samples))
+}
+
 fn detect_trailing_nnnn_start(samples: &[i16], nnnn_burst_cycle_samples: usize) -> Option<usize> {
     let samples_per_bit =
         ((TARGET_SAMPLE_RATE as f64 * SAME_BIT_DURATION_SEC).floor() as usize).max(1);
@@ -435,7 +754,7 @@ fn snap_trim_to_zero_crossing(samples: &[i16], trim_from: usize, max_lookback: u
     trim_from
 }
 
-fn apply_fade_out(samples: &mut [i16], fade_len: usize) {
+fn apply_fade_out(samples: &mut [f32], fade_len: usize) {
     let len = samples.len();
     let fade_len = fade_len.min(len);
     if fade_len == 0 {
@@ -445,7 +764,7 @@ fn apply_fade_out(samples: &mut [i16], fade_len: usize) {
     let fade_start = len - fade_len;
     for (i, sample) in samples[fade_start..].iter_mut().enumerate() {
         let gain = (fade_len - i) as f32 / fade_len as f32;
-        *sample = (*sample as f32 * gain) as i16;
+        *sample *= gain;
     }
 }
 
@@ -556,80 +875,1161 @@ fn goertzel_power_window(samples: &[i16], start: usize, window_len: usize, coeff
     (s_prev2 * s_prev2) + (s_prev * s_prev) - (coeff * s_prev * s_prev2)
 }
 
-fn next_available_recording_path(
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordingNameTemplate(String);
+
+impl Default for RecordingNameTemplate {
+    fn default() -> Self {
+        Self(DEFAULT_RECORDING_FILENAME_TEMPLATE.to_string())
+    }
+}
+
+impl RecordingNameTemplate {
+    pub fn parse(template: &str) -> Result<Self> {
+        let template = template.trim();
+        if template.is_empty() {
+            return Err(anyhow!("RECORDING_FILENAME_TEMPLATE cannot be empty"));
+        }
+        for segment in template.split('/') {
+            if segment.is_empty() || segment.starts_with('.') {
+                return Err(anyhow!(
+                    "RECORDING_FILENAME_TEMPLATE {template:?} has an empty or hidden path segment"
+                ));
+            }
+            let mut rest = segment;
+            while !rest.is_empty() {
+                if let Some(after) = rest.strip_prefix('{') {
+                    let (field, remainder) = after.split_once('}').ok_or_else(|| {
+                        anyhow!("RECORDING_FILENAME_TEMPLATE {template:?} has an unclosed {{")
+                    })?;
+                    if !RECORDING_TEMPLATE_FIELDS.contains(&field) {
+                        return Err(anyhow!(
+                            "RECORDING_FILENAME_TEMPLATE {template:?} uses the unknown placeholder {{{field}}}; use {}",
+                            RECORDING_TEMPLATE_FIELDS
+                                .map(|field| format!("{{{field}}}"))
+                                .join(", ")
+                        ));
+                    }
+                    rest = remainder;
+                    continue;
+                }
+                let literal_end = rest.find('{').unwrap_or(rest.len());
+                if let Some(bad) = rest[..literal_end]
+                    .chars()
+                    .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+                {
+                    return Err(anyhow!(
+                        "RECORDING_FILENAME_TEMPLATE {template:?} contains {bad:?}; literal text may only use letters, digits, '-', '_' and '.'"
+                    ));
+                }
+                rest = &rest[literal_end..];
+            }
+        }
+        Ok(Self(template.to_string()))
+    }
+
+    fn has_seq(&self) -> bool {
+        self.0.contains("{seq}")
+    }
+
+    fn expand(&self, fields: &RecordingNameFields, seq: usize) -> String {
+        let date = fields.recorded_at.date();
+        [
+            ("{event}", fields.event.clone()),
+            ("{originator}", fields.originator.clone()),
+            ("{date}", date.format("%Y-%m-%d").to_string()),
+            ("{time}", fields.recorded_at.format("%H-%M-%S").to_string()),
+            ("{year}", date.format("%Y").to_string()),
+            ("{month}", date.format("%m").to_string()),
+            ("{day}", date.format("%d").to_string()),
+            ("{stream}", fields.stream.clone()),
+            ("{seq}", seq.to_string()),
+        ]
+        .iter()
+        .fold(self.0.clone(), |name, (placeholder, value)| {
+            name.replace(placeholder, value)
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RecordingSubdirScheme {
+    #[default]
+    Flat,
+    Stream,
+    Date,
+    StreamDate,
+}
+
+impl RecordingSubdirScheme {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "flat" => Some(Self::Flat),
+            "stream" => Some(Self::Stream),
+            "date" => Some(Self::Date),
+            "stream_date" => Some(Self::StreamDate),
+            _ => None,
+        }
+    }
+
+    fn prefix(self) -> &'static str {
+        match self {
+            Self::Flat => "",
+            Self::Stream => "{stream}/",
+            Self::Date => "{date}/",
+            Self::StreamDate => "{stream}/{date}/",
+        }
+    }
+}
+
+impl RecordingNameTemplate {
+    pub fn with_subdirs(&self, scheme: RecordingSubdirScheme) -> Self {
+        Self(format!("{}{}", scheme.prefix(), self.0))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordingNameFields {
+    pub event: String,
+    pub originator: String,
+    pub stream: String,
+    pub recorded_at: NaiveDateTime,
+}
+
+impl RecordingNameFields {
+    pub fn from_header(header_text: &str, stream: &str, recorded_at: NaiveDateTime) -> Self {
+        #[derive(Deserialize)]
+        struct ParsedHeaderCodes {
+            event_code: String,
+            originator: String,
+        }
+
+        let parsed = crate::e2t_ng::parse_header_json(header_text.trim())
+            .ok()
+            .and_then(|json| serde_json::from_str::<ParsedHeaderCodes>(&json).ok());
+        Self {
+            event: parsed.as_ref().map_or_else(
+                || "UNK".to_string(),
+                |parsed| sanitize_filename_label(&parsed.event_code),
+            ),
+            originator: parsed.as_ref().map_or_else(
+                || "UNK".to_string(),
+                |parsed| sanitize_filename_label(&parsed.originator),
+            ),
+            stream: sanitize_filename_label(stream),
+            recorded_at,
+        }
+    }
+}
+
+pub fn next_available_recording_path(
     recording_dir: &Path,
-    event_code: &str,
-    timestamp: &str,
-    stream_label: &str,
+    template: &RecordingNameTemplate,
+    fields: &RecordingNameFields,
     extension: &str,
 ) -> PathBuf {
-    let base = format!("EAS_Recording_{timestamp}_{event_code}_{stream_label}");
+    let mut listings: HashMap<PathBuf, HashSet<OsString>> = HashMap::new();
+    let mut is_taken = |candidate: &Path| {
+        let (Some(parent), Some(name)) = (candidate.parent(), candidate.file_name()) else {
+            return candidate.exists();
+        };
+        let names = listings.entry(parent.to_path_buf()).or_insert_with(|| {
+            std::fs::read_dir(parent)
+                .map(|entries| entries.flatten().map(|entry| entry.file_name()).collect())
+                .unwrap_or_default()
+        });
+        let mut partial = name.to_owned();
+        partial.push(PARTIAL_SUFFIX);
+        names.contains(name) || names.contains(&partial)
+    };
     let mut index = 0usize;
     loop {
-        let filename = if index == 0 {
-            format!("{base}.{extension}")
+        let name = if template.has_seq() {
+            template.expand(fields, index + 1)
+        } else if index == 0 {
+            template.expand(fields, 0)
         } else {
-            format!("{base}_{index}.{extension}")
+            format!("{}_{index}", template.expand(fields, 0))
         };
-        let candidate = recording_dir.join(filename);
-        if !candidate.exists() {
+        let candidate = recording_dir.join(format!("{name}.{extension}"));
+        if !is_taken(&candidate) {
             return candidate;
         }
         index += 1;
     }
 }
 
-async fn transcode_wav(wav_path: &Path, out_path: &Path, codec_args: &[&str]) -> Result<()> {
-    let mut partial = out_path.as_os_str().to_owned();
-    partial.push(".partial");
-    let partial_path = PathBuf::from(partial);
-
-    let mut command = tokio::process::Command::new("ffmpeg");
-    command
-        .arg("-nostdin")
-        .arg("-hide_banner")
-        .arg("-loglevel")
-        .arg("warning")
-        .arg("-y")
-        .arg("-i")
-        .arg(wav_path)
-        .arg("-vn")
-        .args(codec_args)
-        .arg(&partial_path);
-
-    let status = command
-        .status()
-        .await
-        .context("Failed to invoke ffmpeg for storage-saver transcode")?;
-
-    if !status.success() {
-        let _ = tokio::fs::remove_file(&partial_path).await;
-        return Err(anyhow!(
-            "ffmpeg exited with status {:?} while transcoding {:?}",
-            status.code(),
-            wav_path
+pub fn recording_file_name(recording_dir: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(recording_dir).unwrap_or(path);
+    let parts = relative
+        .components()
+        .map(|component| match component {
+            std::path::Component::Normal(part) => part.to_str(),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordingName {
+    pub recorded_at: NaiveDateTime,
+    pub event_code: String,
+    pub stream_label: String,
+    pub collision_index: usize,
+    pub extension: String,
+}
+
+pub fn parse_recording_file_name(name: &str) -> Option<RecordingName> {
+    let (stem, extension) = name.strip_prefix(RECORDING_FILE_PREFIX)?.rsplit_once('.')?;
+    if !RECORDING_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str()) {
+        return None;
+    }
+    let recorded_at =
+        NaiveDateTime::parse_from_str(stem.get(..19)?, RECORDING_TIMESTAMP_FORMAT).ok()?;
+    let (event_code, label) = stem.get(19..)?.strip_prefix('_')?.split_once('_')?;
+    if event_code.is_empty() || label.is_empty() {
+        return None;
+    }
+    let (stream_label, collision_index) = match label.rsplit_once('_') {
+        Some((stem_label, index))
+            if !stem_label.is_empty()
+                && !index.is_empty()
+                && index.bytes().all(|byte| byte.is_ascii_digit()) =>
+        {
+            (stem_label, index.parse().ok()?)
+        }
+        _ => (label, 0),
+    };
+
+    Some(RecordingName {
+        recorded_at,
+        event_code: event_code.to_string(),
+        stream_label: stream_label.to_string(),
+        collision_index,
+        extension: extension.to_string(),
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingFile {
+    pub file: String,
+    pub size_bytes: u64,
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(flatten)]
+    pub name: RecordingName,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingIndexEntry {
+    #[serde(flatten)]
+    pub recording: RecordingFile,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<RecordingMetadata>,
+}
+
+fn name_from_metadata(metadata: &RecordingMetadata, extension: &str) -> Option<RecordingName> {
+    let event_code = match &metadata.alert {
+        Some(alert) => sanitize_filename_label(&alert.event_code),
+        None => {
+            RecordingNameFields::from_header(&metadata.raw_header, "", NaiveDateTime::default())
+                .event
+        }
+    };
+    Some(RecordingName {
+        recorded_at: metadata.started_at.with_timezone(&Local).naive_local(),
+        event_code,
+        stream_label: stream_label_from_source(&metadata.source_stream),
+        collision_index: 0,
+        extension: extension.to_string(),
+    })
+}
+
+fn index_entry(
+    recording_dir: &Path,
+    path: &Path,
+    metadata: Option<RecordingMetadata>,
+) -> Option<RecordingIndexEntry> {
+    let file = recording_file_name(recording_dir, path)?;
+    let file_name = path.file_name()?.to_str()?;
+    let (_, extension) = file_name.rsplit_once('.')?;
+    if !RECORDING_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str()) {
+        return None;
+    }
+    let name = parse_recording_file_name(file_name)
+        .or_else(|| name_from_metadata(metadata.as_ref()?, extension))?;
+    let stat = std::fs::metadata(path).ok().filter(|stat| stat.is_file())?;
+    Some(RecordingIndexEntry {
+        recording: RecordingFile {
+            file,
+            size_bytes: stat.len(),
+            created_at: stat
+                .created()
+                .or_else(|_| stat.modified())
+                .ok()
+                .map(DateTime::<Utc>::from),
+            name,
+        },
+        metadata,
+    })
+}
+
+fn unindexed_entry(recording_dir: &Path, path: &Path) -> Option<RecordingIndexEntry> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    if !RECORDING_EXTENSIONS.contains(&extension.as_str()) {
+        return None;
+    }
+    index_entry(recording_dir, path, read_sidecar(path))
+}
+
+fn walk_recordings(recording_dir: &Path) -> std::io::Result<Vec<RecordingIndexEntry>> {
+    let mut recordings = Vec::new();
+    let mut pending = vec![std::fs::read_dir(recording_dir)?];
+    while let Some(entries) = pending.pop() {
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let path = entry.path();
+            match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() => match std::fs::read_dir(&path) {
+                    Ok(children) => pending.push(children),
+                    Err(err) => warn!("Failed to list recordings in {:?}: {}", path, err),
+                },
+                Ok(_) => recordings.extend(unindexed_entry(recording_dir, &path)),
+                Err(_) => {}
+            }
+        }
+    }
+    Ok(recordings)
+}
+
+fn read_index(recording_dir: &Path) -> std::io::Result<Option<Vec<RecordingIndexEntry>>> {
+    let body = match std::fs::read_to_string(recording_dir.join(RECORDING_INDEX_FILE)) {
+        Ok(body) => body,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    Ok(Some(
+        body.lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect(),
+    ))
+}
+
+fn write_index(recording_dir: &Path, entries: &[RecordingIndexEntry]) -> Result<()> {
+    let mut body = Vec::new();
+    for entry in entries {
+        serde_json::to_writer(&mut body, entry)?;
+        body.push(b'\n');
+    }
+    let temp_path = recording_dir.join(format!(".{RECORDING_INDEX_FILE}.tmp"));
+    std::fs::write(&temp_path, body)?;
+    std::fs::rename(&temp_path, recording_dir.join(RECORDING_INDEX_FILE))?;
+    Ok(())
+}
+
+fn rebuild_index(recording_dir: &Path) -> std::io::Result<Vec<RecordingIndexEntry>> {
+    let recordings = walk_recordings(recording_dir)?;
+    match write_index(recording_dir, &recordings) {
+        Ok(()) => info!(
+            "Indexed {} existing recording(s) in {:?}",
+            recordings.len(),
+            recording_dir
+        ),
+        Err(err) => warn!(
+            "Failed to write the recordings index in {:?}: {:#}",
+            recording_dir, err
+        ),
+    }
+    Ok(recordings)
+}
+
+fn current_entries(
+    recording_dir: &Path,
+    entries: Vec<RecordingIndexEntry>,
+) -> Vec<RecordingIndexEntry> {
+    let mut seen = HashSet::new();
+    let mut current: Vec<_> = entries
+        .into_iter()
+        .rev()
+        .filter(|entry| seen.insert(entry.recording.file.clone()))
+        .filter(|entry| recording_dir.join(&entry.recording.file).is_file())
+        .collect();
+    current.reverse();
+    current
+}
+
+pub fn index_recording(
+    recording_dir: &Path,
+    path: &Path,
+    metadata: Option<&RecordingMetadata>,
+) -> Result<()> {
+    let entry = index_entry(recording_dir, path, metadata.cloned())
+        .ok_or_else(|| anyhow!("{path:?} is not a recording"))?;
+    let _guard = RECORDING_INDEX_LOCK.lock();
+    let index_path = recording_dir.join(RECORDING_INDEX_FILE);
+    if !index_path.exists() {
+        rebuild_index(recording_dir)?;
+        return Ok(());
+    }
+    let mut line = serde_json::to_vec(&entry)?;
+    line.push(b'\n');
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(&index_path)?
+        .write_all(&line)?;
+    Ok(())
+}
+
+pub fn compact_index(recording_dir: &Path) -> Result<usize> {
+    let _guard = RECORDING_INDEX_LOCK.lock();
+    let Some(entries) = read_index(recording_dir)? else {
+        return Ok(0);
+    };
+    let before = entries.len();
+    let current = current_entries(recording_dir, entries);
+    if current.len() == before {
+        return Ok(0);
+    }
+    write_index(recording_dir, &current)?;
+    Ok(before - current.len())
+}
+
+pub fn list_recordings(recording_dir: &Path) -> std::io::Result<Vec<RecordingIndexEntry>> {
+    if !recording_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut recordings = {
+        let _guard = RECORDING_INDEX_LOCK.lock();
+        match read_index(recording_dir)? {
+            Some(entries) => current_entries(recording_dir, entries),
+            None => rebuild_index(recording_dir)?,
+        }
+    };
+
+    let indexed: HashSet<String> = recordings
+        .iter()
+        .map(|entry| entry.recording.file.clone())
+        .collect();
+    for entry in std::fs::read_dir(recording_dir)?.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') || indexed.contains(&name) {
+            continue;
+        }
+        recordings.extend(unindexed_entry(recording_dir, &entry.path()));
+    }
+    recordings.sort_by(|a, b| {
+        let (a, b) = (&a.recording, &b.recording);
+        (b.name.recorded_at, b.name.collision_index, &b.file).cmp(&(
+            a.name.recorded_at,
+            a.name.collision_index,
+            &a.file,
+        ))
+    });
+    Ok(recordings)
+}
+
+pub fn scan_recordings(recording_dir: &Path) -> std::io::Result<Vec<RecordingFile>> {
+    Ok(list_recordings(recording_dir)?
+        .into_iter()
+        .map(|entry| entry.recording)
+        .collect())
+}
+
+fn partial_path(path: &Path) -> PathBuf {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(PARTIAL_SUFFIX);
+    PathBuf::from(partial)
+}
+
+struct PartialFile(Option<PathBuf>);
+
+impl PartialFile {
+    fn path(&self) -> &Path {
+        self.0.as_deref().unwrap_or(Path::new(""))
+    }
+
+    fn persist(mut self, output_path: &Path) -> std::io::Result<()> {
+        let Some(path) = self.0.take() else {
+            return Ok(());
+        };
+        std::fs::rename(&path, output_path).inspect_err(|_| {
+            let _ = std::fs::remove_file(&path);
+        })
+    }
+}
+
+impl Drop for PartialFile {
+    fn drop(&mut self) {
+        if let Some(path) = self.0.take() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+fn pcm16_to_f32(sample: i16) -> f32 {
+    f32::from(sample) / 32768.0
+}
+
+struct Dither(u64);
+
+impl Default for Dither {
+    fn default() -> Self {
+        Self(0x2545_F491_4F6C_DD1D)
+    }
+}
+
+impl Dither {
+    fn next_unit(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    fn quantize(&mut self, sample: f32, bits: u32) -> i32 {
+        let full_scale = (1i64 << (bits - 1)) as f64;
+        let noise = f64::from(self.next_unit() - self.next_unit());
+        (f64::from(sample) * full_scale + noise)
+            .round()
+            .clamp(-full_scale, full_scale - 1.0) as i32
+    }
+}
+
+struct Downsampler {
+    resampler: SincFixedIn<f32>,
+    ratio: f64,
+    pending: Vec<f32>,
+    delay: usize,
+    consumed: usize,
+    produced: usize,
+}
+
+impl Downsampler {
+    const CHUNK_SIZE: usize = 1024;
+
+    fn new(sample_rate: u32) -> Result<Self> {
+        let ratio = f64::from(sample_rate) / f64::from(TARGET_SAMPLE_RATE);
+        let resampler = SincFixedIn::<f32>::new(
+            ratio,
+            1.0,
+            SincInterpolationParameters {
+                sinc_len: 128,
+                f_cutoff: 0.95,
+                interpolation: SincInterpolationType::Linear,
+                oversampling_factor: 128,
+                window: WindowFunction::BlackmanHarris2,
+            },
+            Self::CHUNK_SIZE,
+            1,
+        )
+        .context("Failed to create the recording resampler")?;
+        Ok(Self {
+            delay: resampler.output_delay(),
+            resampler,
+            ratio,
+            pending: Vec::with_capacity(Self::CHUNK_SIZE),
+            consumed: 0,
+            produced: 0,
+        })
+    }
+
+    fn push(&mut self, samples: &[f32], output: &mut Vec<f32>) -> Result<()> {
+        self.pending.extend_from_slice(samples);
+        while self.pending.len() >= Self::CHUNK_SIZE {
+            let chunk: Vec<f32> = self.pending.drain(..Self::CHUNK_SIZE).collect();
+            let resampled = self.resampler.process(&[chunk], None)?;
+            self.consumed += Self::CHUNK_SIZE;
+            self.emit(&resampled[0], usize::MAX, output);
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self, output: &mut Vec<f32>) -> Result<()> {
+        let expected = ((self.consumed + self.pending.len()) as f64 * self.ratio).round() as usize;
+        let mut pending = std::mem::take(&mut self.pending);
+        while self.produced < expected {
+            let resampled = if pending.is_empty() {
+                self.resampler.process_partial::<Vec<f32>>(None, None)?
+            } else {
+                self.resampler
+                    .process_partial(Some(&[std::mem::take(&mut pending)]), None)?
+            };
+            self.emit(&resampled[0], expected, output);
+        }
+        self.resampler.reset();
+        self.delay = self.resampler.output_delay();
+        self.consumed = 0;
+        self.produced = 0;
+        Ok(())
+    }
+
+    fn emit(&mut self, frames: &[f32], limit: usize, output: &mut Vec<f32>) {
+        let skip = self.delay.min(frames.len());
+        self.delay -= skip;
+        let take = (frames.len() - skip).min(limit.saturating_sub(self.produced));
+        output.extend_from_slice(&frames[skip..skip + take]);
+        self.produced += take;
+    }
+}
+
+struct RecordingWriter {
+    sink: RecordingSink,
+    downsampler: Option<Downsampler>,
+    buffer: Vec<f32>,
+    frames_written: usize,
+    gain: f32,
+    normalizer: Option<Normalizer>,
+    peak: f32,
+    clipped_samples: u64,
+}
+
+struct Normalizer {
+    target: f32,
+    held: Vec<f32>,
+    scale: Option<f32>,
+}
+
+fn normalize_scale(target: f32, peak: f32) -> f32 {
+    if peak <= 0.0 {
+        return 1.0;
+    }
+    (target / peak).min(db_to_gain(NORMALIZE_MAX_GAIN_DB))
+}
+
+fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+impl RecordingWriter {
+    fn new(
+        sink: RecordingSink,
+        sample_rate: u32,
+        gain_db: f32,
+        normalize_dbfs: Option<f32>,
+    ) -> Result<Self> {
+        let downsampler = if sample_rate == TARGET_SAMPLE_RATE {
+            None
+        } else {
+            Some(Downsampler::new(sample_rate)?)
+        };
+        Ok(Self {
+            sink,
+            downsampler,
+            buffer: Vec::new(),
+            frames_written: 0,
+            gain: db_to_gain(gain_db),
+            normalizer: normalize_dbfs.map(|dbfs| Normalizer {
+                target: db_to_gain(dbfs),
+                held: Vec::new(),
+                scale: None,
+            }),
+            peak: 0.0,
+            clipped_samples: 0,
+        })
+    }
+
+    fn write_received(&mut self, samples: &[f32]) -> Result<()> {
+        let gain = self.gain;
+        let mut samples: Vec<f32> = samples.iter().map(|&sample| sample * gain).collect();
+        self.peak = samples
+            .iter()
+            .fold(self.peak, |peak, sample| peak.max(sample.abs()));
+        if let Some(normalizer) = self.normalizer.as_mut() {
+            match normalizer.scale {
+                Some(scale) => samples.iter_mut().for_each(|sample| *sample *= scale),
+                None => {
+                    normalizer.held.extend(samples);
+                    if normalizer.held.len()
+                        > TARGET_SAMPLE_RATE as usize * NORMALIZE_MAX_HELD_SECONDS
+                    {
+                        self.release_held()?;
+                    }
+                    return Ok(());
+                }
+            }
+        }
+        self.push_pipeline(&samples)
+    }
+
+    fn release_held(&mut self) -> Result<()> {
+        let Some(normalizer) = self.normalizer.as_mut() else {
+            return Ok(());
+        };
+        if normalizer.held.is_empty() {
+            return Ok(());
+        }
+        let scale = normalize_scale(normalizer.target, self.peak);
+        normalizer.scale = Some(scale);
+        let mut held = std::mem::take(&mut normalizer.held);
+        held.iter_mut().for_each(|sample| *sample *= scale);
+        self.push_pipeline(&held)
+    }
+
+    fn write_pipeline(&mut self, samples: &[f32]) -> Result<()> {
+        self.release_held()?;
+        self.push_pipeline(samples)
+    }
+
+    fn push_pipeline(&mut self, samples: &[f32]) -> Result<()> {
+        let Some(downsampler) = self.downsampler.as_mut() else {
+            for &sample in samples {
+                self.write_sample(sample)?;
+            }
+            return Ok(());
+        };
+        downsampler.push(samples, &mut self.buffer)?;
+        self.drain_buffer()
+    }
+
+    fn write_pipeline_pcm16(&mut self, samples: &[i16]) -> Result<()> {
+        let samples: Vec<f32> = samples.iter().copied().map(pcm16_to_f32).collect();
+        self.write_pipeline(&samples)
+    }
+
+    fn write_tones(&mut self, samples: &[i16]) -> Result<()> {
+        self.flush()?;
+        for &sample in samples {
+            self.sink.write_pcm16(sample)?;
+        }
+        self.frames_written += samples.len();
+        Ok(())
+    }
+
+    fn write_silence(&mut self, frames: usize) -> Result<()> {
+        self.write_tones(&vec![0; frames])
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.release_held()?;
+        if let Some(downsampler) = self.downsampler.as_mut() {
+            downsampler.flush(&mut self.buffer)?;
+            self.drain_buffer()?;
+        }
+        Ok(())
+    }
+
+    fn drain_buffer(&mut self) -> Result<()> {
+        for sample in std::mem::take(&mut self.buffer) {
+            self.write_sample(sample)?;
+        }
+        Ok(())
+    }
+
+    fn write_sample(&mut self, sample: f32) -> Result<()> {
+        if sample.abs() > 1.0 {
+            self.clipped_samples += 1;
+        }
+        self.sink.write_f32(sample)?;
+        self.frames_written += 1;
+        Ok(())
+    }
+
+    fn frames_for(&self, pipeline_samples: usize) -> usize {
+        match &self.downsampler {
+            Some(downsampler) => (pipeline_samples as f64 * downsampler.ratio).round() as usize,
+            None => pipeline_samples,
+        }
+    }
+}
+
+enum RecordingSink {
+    Wav {
+        writer: WavWriter<BufWriter<std::fs::File>>,
+        sample_format: RecordingSampleFormat,
+        dither: Dither,
+        partial: PartialFile,
+        output_path: PathBuf,
+        tags: Vec<(&'static str, String)>,
+    },
+    Encoder(EncoderSink),
+}
+
+struct EncoderSink {
+    child: std::process::Child,
+    stdin: Option<BufWriter<std::process::ChildStdin>>,
+    partial_path: PathBuf,
+    output_path: PathBuf,
+}
+
+impl RecordingSink {
+    fn create(
+        output_path: &Path,
+        format: RecordingFormat,
+        sample_format: RecordingSampleFormat,
+        sample_rate: u32,
+        tags: &[(&'static str, String)],
+    ) -> Result<Self> {
+        let partial_path = partial_path(output_path);
+        if format == RecordingFormat::Wav {
+            let (bits_per_sample, hound_format) = match sample_format {
+                RecordingSampleFormat::Int16 => (16, hound::SampleFormat::Int),
+                RecordingSampleFormat::Int24 => (24, hound::SampleFormat::Int),
+                RecordingSampleFormat::Float32 => (32, hound::SampleFormat::Float),
+            };
+            let spec = WavSpec {
+                channels: 1,
+                sample_rate,
+                bits_per_sample,
+                sample_format: hound_format,
+            };
+            return Ok(Self::Wav {
+                writer: WavWriter::create(&partial_path, spec)?,
+                sample_format,
+                dither: Dither::default(),
+                partial: PartialFile(Some(partial_path)),
+                output_path: output_path.to_path_buf(),
+                tags: tags.to_vec(),
+            });
+        }
+
+        let mut command = std::process::Command::new("ffmpeg");
+        command
+            .arg("-nostdin")
+            .arg("-hide_banner")
+            .arg("-loglevel")
+            .arg("warning")
+            .arg("-y")
+            .arg("-f")
+            .arg("f32le")
+            .arg("-ar")
+            .arg(sample_rate.to_string())
+            .arg("-ac")
+            .arg("1")
+            .arg("-i")
+            .arg("pipe:0")
+            .arg("-vn");
+        for (key, value) in tags {
+            command.arg("-metadata").arg(format!("{key}={value}"));
+        }
+        let mut child = command
+            .args(format.ffmpeg_codec_args(sample_format))
+            .arg(&partial_path)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::null())
+            .spawn()
+            .context("Failed to invoke ffmpeg to encode the recording")?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow!("ffmpeg started without a stdin pipe"))?;
+        Ok(Self::Encoder(EncoderSink {
+            child,
+            stdin: Some(BufWriter::new(stdin)),
+            partial_path,
+            output_path: output_path.to_path_buf(),
+        }))
+    }
+
+    fn write_pcm16(&mut self, sample: i16) -> Result<()> {
+        match self {
+            Self::Wav {
+                writer,
+                sample_format,
+                ..
+            } => match sample_format {
+                RecordingSampleFormat::Int16 => writer.write_sample(sample)?,
+                RecordingSampleFormat::Int24 => writer.write_sample(i32::from(sample) << 8)?,
+                RecordingSampleFormat::Float32 => writer.write_sample(pcm16_to_f32(sample))?,
+            },
+            Self::Encoder(encoder) => encoder.write(pcm16_to_f32(sample))?,
+        }
+        Ok(())
+    }
+
+    fn write_f32(&mut self, sample: f32) -> Result<()> {
+        match self {
+            Self::Wav {
+                writer,
+                sample_format,
+                dither,
+                ..
+            } => match sample_format {
+                RecordingSampleFormat::Int16 => {
+                    writer.write_sample(dither.quantize(sample, 16) as i16)?
+                }
+                RecordingSampleFormat::Int24 => writer.write_sample(dither.quantize(sample, 24))?,
+                RecordingSampleFormat::Float32 => writer.write_sample(sample)?,
+            },
+            Self::Encoder(encoder) => encoder.write(sample)?,
+        }
+        Ok(())
+    }
+
+    fn finalize(self) -> Result<()> {
+        match self {
+            Self::Wav {
+                writer,
+                partial,
+                output_path,
+                tags,
+                ..
+            } => {
+                writer.finalize()?;
+                append_wav_info(partial.path(), &tags)
+                    .with_context(|| format!("Failed to tag recording {:?}", output_path))?;
+                partial.persist(&output_path).with_context(|| {
+                    format!("Failed to finalize recording at {:?}", output_path)
+                })?;
+            }
+            Self::Encoder(mut encoder) => {
+                if let Some(mut stdin) = encoder.stdin.take() {
+                    stdin
+                        .flush()
+                        .context("Failed to flush audio to the encoder")?;
+                }
+                let status = encoder
+                    .child
+                    .wait()
+                    .context("Failed to wait for the recording encoder")?;
+                if !status.success() {
+                    return Err(anyhow!(
+                        "ffmpeg exited with status {:?} while encoding {:?}",
+                        status.code(),
+                        encoder.output_path
+                    ));
+                }
+                std::fs::rename(&encoder.partial_path, &encoder.output_path).with_context(
+                    || format!("Failed to finalize recording at {:?}", encoder.output_path),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn discard(self) {
+        drop(self);
+    }
+}
+
+fn recording_tags(
+    header_text: &str,
+    alert: Option<&EasAlertData>,
+    source_stream: &str,
+    started_at: DateTime<Utc>,
+) -> Vec<(&'static str, String)> {
+    let mut tags = Vec::new();
+    if let Some(alert) = alert {
+        tags.push((
+            "title",
+            format!("{} ({})", alert.event_text, alert.event_code),
         ));
+        tags.push(("artist", alert.originator.clone()));
     }
+    tags.push(("album", source_stream.to_string()));
+    tags.push(("date", started_at.to_rfc3339()));
+    tags.push(("comment", header_text.trim().to_string()));
+    tags
+}
 
-    tokio::fs::rename(&partial_path, out_path)
-        .await
-        .with_context(|| format!("Failed to finalize recording at {:?}", out_path))?;
+fn append_wav_info(path: &Path, tags: &[(&str, String)]) -> Result<()> {
+    let mut info = b"INFO".to_vec();
+    for (key, value) in tags {
+        let id: &[u8; 4] = match *key {
+            "title" => b"INAM",
+            "artist" => b"IART",
+            "album" => b"IPRD",
+            "date" => b"ICRD",
+            "comment" => b"ICMT",
+            _ => continue,
+        };
+        let mut text = value.as_bytes().to_vec();
+        text.push(0);
+        info.extend_from_slice(id);
+        info.extend_from_slice(&u32::try_from(text.len())?.to_le_bytes());
+        let padded = text.len() % 2 == 1;
+        info.extend_from_slice(&text);
+        if padded {
+            info.push(0);
+        }
+    }
+    if info.len() == 4 {
+        return Ok(());
+    }
 
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)?;
+    let mut end = file.seek(SeekFrom::End(0))?;
+    if end % 2 == 1 {
+        file.write_all(&[0])?;
+        end += 1;
+    }
+    file.write_all(b"LIST")?;
+    file.write_all(&u32::try_from(info.len())?.to_le_bytes())?;
+    file.write_all(&info)?;
+    let riff_size = end + info.len() as u64;
+    file.seek(SeekFrom::Start(4))?;
+    file.write_all(&u32::try_from(riff_size)?.to_le_bytes())?;
     Ok(())
 }
 
-fn event_code_from_header(header_text: &str) -> String {
-    let trimmed = header_text.trim();
-    #[derive(Deserialize)]
-    struct ParsedHeaderEventCode {
-        event_code: String,
+fn repair_wav_sizes(path: &Path) -> Result<u64> {
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)?;
+    let len = file.metadata()?.len();
+    let mut riff = [0u8; 12];
+    file.read_exact(&mut riff)
+        .context("Too short for a WAV header")?;
+    if &riff[..4] != b"RIFF" || &riff[8..] != b"WAVE" {
+        return Err(anyhow!("Not a WAV file"));
     }
 
-    crate::e2t_ng::parse_header_json(trimmed)
-        .ok()
-        .and_then(|json| serde_json::from_str::<ParsedHeaderEventCode>(&json).ok())
-        .map(|parsed| sanitize_filename_label(parsed.event_code.as_str()))
-        .unwrap_or_else(|| "UNK".to_string())
+    let mut offset = 12u64;
+    let mut block_align = None;
+    while offset + 8 <= len {
+        let mut chunk = [0u8; 8];
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(&mut chunk)?;
+        let size = u64::from(u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]));
+        match &chunk[..4] {
+            b"fmt " => {
+                let mut fmt = [0u8; 16];
+                file.read_exact(&mut fmt)?;
+                block_align = Some(u64::from(u16::from_le_bytes([fmt[12], fmt[13]])).max(1));
+            }
+            b"data" => {
+                let block_align =
+                    block_align.ok_or_else(|| anyhow!("No fmt chunk before the data"))?;
+                let data_len = (len - offset - 8) / block_align * block_align;
+                file.set_len(offset + 8 + data_len)?;
+                file.seek(SeekFrom::Start(offset + 4))?;
+                file.write_all(&u32::try_from(data_len)?.to_le_bytes())?;
+                file.seek(SeekFrom::Start(4))?;
+                file.write_all(&u32::try_from(offset + data_len)?.to_le_bytes())?;
+                return Ok(data_len);
+            }
+            _ => {}
+        }
+        offset += 8 + size + size % 2;
+    }
+    Err(anyhow!("No data chunk"))
+}
+
+pub fn recover_partial_recordings(recording_dir: &Path) -> (usize, usize) {
+    let mut partials = Vec::new();
+    let mut pending = vec![recording_dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() => pending.push(path),
+                Ok(_)
+                    if path
+                        .extension()
+                        .is_some_and(|ext| matches!(ext.to_str(), Some("part" | "partial"))) =>
+                {
+                    partials.push(path)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let (mut salvaged, mut deleted) = (0, 0);
+    for partial in partials {
+        let output_path = partial.with_extension("");
+        let is_wav = output_path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"));
+        let repaired = is_wav
+            && !output_path.exists()
+            && match repair_wav_sizes(&partial) {
+                Ok(data_len) => data_len > 0,
+                Err(err) => {
+                    warn!("Cannot salvage {:?}: {:#}", partial, err);
+                    false
+                }
+            };
+        if repaired {
+            match std::fs::rename(&partial, &output_path) {
+                Ok(()) => {
+                    info!("Salvaged interrupted recording {:?}", output_path);
+                    salvaged += 1;
+                    if let Err(err) = index_recording(recording_dir, &output_path, None) {
+                        warn!("Failed to index recording {:?}: {:#}", output_path, err);
+                    }
+                }
+                Err(err) => warn!("Failed to move {:?} into place: {}", partial, err),
+            }
+            continue;
+        }
+        match std::fs::remove_file(&partial) {
+            Ok(()) => {
+                info!("Deleted interrupted recording {:?}", partial);
+                deleted += 1;
+            }
+            Err(err) => warn!("Failed to delete {:?}: {}", partial, err),
+        }
+    }
+    (salvaged, deleted)
+}
+
+pub fn sidecar_path(recording: &Path) -> PathBuf {
+    recording.with_extension("json")
+}
+
+pub fn write_sidecar(recording: &Path, metadata: &RecordingMetadata) -> Result<()> {
+    let body = serde_json::to_vec_pretty(metadata)?;
+    std::fs::write(sidecar_path(recording), body)?;
+    Ok(())
+}
+
+pub fn read_sidecar(recording: &Path) -> Option<RecordingMetadata> {
+    let body = std::fs::read(sidecar_path(recording)).ok()?;
+    serde_json::from_slice(&body).ok()
+}
+
+pub fn remove_sidecar(recording: &Path) {
+    let path = sidecar_path(recording);
+    if let Err(err) = std::fs::remove_file(&path) {
+        if err.kind() != std::io::ErrorKind::NotFound {
+            warn!("Failed to delete recording metadata {:?}: {}", path, err);
+        }
+    }
+}
+
+pub fn remove_recording_files(recording: &Path) -> std::io::Result<()> {
+    std::fs::remove_file(recording)?;
+    remove_sidecar(recording);
+    if let Some(cache) = webhook::discord_attachment_cache_path(recording) {
+        if let Err(err) = std::fs::remove_file(&cache) {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                warn!("Failed to delete transcoded copy {:?}: {}", cache, err);
+            }
+        }
+    }
+    Ok(())
+}
+
+impl EncoderSink {
+    fn write(&mut self, sample: f32) -> Result<()> {
+        let stdin = self
+            .stdin
+            .as_mut()
+            .ok_or_else(|| anyhow!("The recording encoder is already closed"))?;
+        stdin
+            .write_all(&sample.to_le_bytes())
+            .context("The recording encoder stopped accepting audio")
+    }
+}
+
+impl Drop for EncoderSink {
+    fn drop(&mut self) {
+        drop(self.stdin.take());
+        if matches!(self.child.try_wait(), Ok(None)) {
+            let _ = self.child.kill();
+        }
+        let _ = self.child.wait();
+        let _ = std::fs::remove_file(&self.partial_path);
+    }
 }
 
 fn stream_label_from_source(source_stream: &str) -> String {
@@ -789,3 +2189,661 @@ fn sanitize_filename_label(label: &str) -> String {
         trimmed.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tor_fields() -> RecordingNameFields {
+        RecordingNameFields {
+            event: "TOR".to_string(),
+            originator: "WXR".to_string(),
+            stream: "STREAM_A".to_string(),
+            recorded_at: NaiveDateTime::parse_from_str(
+                "2024-12-04_11-58-45",
+                RECORDING_TIMESTAMP_FORMAT,
+            )
+            .expect("timestamp"),
+        }
+    }
+
+    #[test]
+    fn removing_a_recording_takes_its_sidecar_and_transcoded_copy() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir
+            .path()
+            .join("EAS_Recording_2024-12-04_11-58-45_TOR_STREAM_A.wav");
+        let cache = webhook::discord_attachment_cache_path(&path).expect("cache path");
+        std::fs::write(&path, b"RIFF").expect("wav");
+        std::fs::write(sidecar_path(&path), b"{}").expect("sidecar");
+        std::fs::write(&cache, b"OggS").expect("cache");
+
+        remove_recording_files(&path).expect("remove");
+        assert!(!path.exists());
+        assert!(!sidecar_path(&path).exists());
+        assert!(!cache.exists());
+        assert!(remove_recording_files(&path).is_err());
+    }
+
+    #[test]
+    fn failed_or_abandoned_wav_recordings_remove_their_part_files() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let tags = recording_tags(
+            "ZCZC-WXR-TOR-031055+0030-3381200-KOAX/NWS-",
+            None,
+            "wxr",
+            Utc::now(),
+        );
+        let open = |path: &Path| {
+            let mut sink = RecordingSink::create(
+                path,
+                RecordingFormat::Wav,
+                RecordingSampleFormat::Int16,
+                TARGET_SAMPLE_RATE,
+                &tags,
+            )
+            .expect("sink");
+            sink.write_pcm16(1200).expect("write");
+            sink
+        };
+
+        let blocked = dir.path().join("blocked.wav");
+        std::fs::create_dir(&blocked).expect("mkdir");
+        std::fs::write(blocked.join("occupant"), b"x").expect("write");
+        let sink = open(&blocked);
+        assert!(dir.path().join("blocked.wav.part").exists());
+        assert!(sink.finalize().is_err());
+        assert!(!dir.path().join("blocked.wav.part").exists());
+
+        let abandoned = dir.path().join("abandoned.wav");
+        drop(open(&abandoned));
+        assert!(!dir.path().join("abandoned.wav.part").exists());
+        assert!(!abandoned.exists());
+    }
+
+    #[test]
+    fn recordings_are_tagged_with_sidecars_and_skip_partial_names() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir
+            .path()
+            .join("EAS_Recording_2024-12-04_11-58-45_TOR_STREAM_A.wav");
+        let tags = recording_tags(
+            "ZCZC-WXR-TOR-031055+0030-3381200-KOAX/NWS-",
+            None,
+            "wxr",
+            Utc::now(),
+        );
+        let mut sink = RecordingSink::create(
+            &path,
+            RecordingFormat::Wav,
+            RecordingSampleFormat::Int16,
+            TARGET_SAMPLE_RATE,
+            &tags,
+        )
+        .expect("sink");
+        for sample in [0i16, 1200, -1200, 0] {
+            sink.write_pcm16(sample).expect("write");
+        }
+        sink.finalize().expect("finalize");
+        let reader = hound::WavReader::open(&path).expect("wav");
+        assert_eq!(reader.spec().sample_rate, TARGET_SAMPLE_RATE);
+        assert_eq!(reader.duration(), 4);
+        drop(reader);
+        let bytes = std::fs::read(&path).expect("read");
+        let riff_size = u32::from_le_bytes(bytes[4..8].try_into().expect("size"));
+        assert_eq!(riff_size as usize, bytes.len() - 8);
+        assert!(bytes.windows(4).any(|id| id == b"ICMT"));
+        assert!(String::from_utf8_lossy(&bytes).contains("ZCZC-WXR-TOR"));
+
+        let metadata = RecordingMetadata {
+            raw_header: "ZCZC-WXR-TOR-031055+0030-3381200-KOAX/NWS-".to_string(),
+            alert: None,
+            source_stream: "wxr".to_string(),
+            started_at: Utc::now(),
+            ended_at: Utc::now(),
+            sample_rate: TARGET_SAMPLE_RATE,
+            total_samples: Some(4),
+            audio_start_sample: Some(0),
+            audio_samples: Some(0),
+            clipped_samples: None,
+            remote_url: None,
+        };
+        write_sidecar(&path, &metadata).expect("sidecar");
+        assert!(
+            sidecar_path(&path).ends_with("EAS_Recording_2024-12-04_11-58-45_TOR_STREAM_A.json")
+        );
+        let read = read_sidecar(&path).expect("sidecar reads back");
+        assert_eq!(read.raw_header, metadata.raw_header);
+        assert_eq!(read.total_samples, Some(4));
+        assert!(scan_recordings(dir.path())
+            .expect("scan")
+            .iter()
+            .all(|recording| recording.name.extension != "json"));
+        remove_sidecar(&path);
+        assert!(read_sidecar(&path).is_none());
+
+        let encoding = dir
+            .path()
+            .join("EAS_Recording_2024-12-04_11-58-45_TOR_STREAM_A.flac.part");
+        std::fs::write(&encoding, b"").expect("partial");
+        let next = next_available_recording_path(
+            dir.path(),
+            &RecordingNameTemplate::default(),
+            &tor_fields(),
+            RecordingFormat::Flac.extension(),
+        );
+        assert!(next.ends_with("EAS_Recording_2024-12-04_11-58-45_TOR_STREAM_A_1.flac"));
+        let parsed = parse_recording_file_name(&next.file_name().expect("name").to_string_lossy())
+            .expect("flac recordings parse");
+        assert_eq!(parsed.extension, "flac");
+        assert_eq!(parsed.collision_index, 1);
+    }
+
+    #[test]
+    fn recording_names_parse_with_and_without_collision_suffix() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let first = next_available_recording_path(
+            dir.path(),
+            &RecordingNameTemplate::default(),
+            &tor_fields(),
+            "wav",
+        );
+        std::fs::write(&first, b"RIFF").expect("write first");
+        let second = next_available_recording_path(
+            dir.path(),
+            &RecordingNameTemplate::default(),
+            &tor_fields(),
+            "wav",
+        );
+        std::fs::write(&second, b"RIFF....").expect("write second");
+        std::fs::write(dir.path().join(".EAS_Recording_x.discord.ogg"), b"").expect("cache");
+        std::fs::write(
+            dir.path()
+                .join("EAS_Recording_2024-12-04_12-00-00_SVR_STREAM1.mp3.part"),
+            b"",
+        )
+        .expect("partial");
+
+        let parsed =
+            parse_recording_file_name(&second.file_name().expect("name").to_string_lossy())
+                .expect("collision name parses");
+        assert_eq!(
+            parsed,
+            RecordingName {
+                recorded_at: NaiveDateTime::parse_from_str(
+                    "2024-12-04_11-58-45",
+                    RECORDING_TIMESTAMP_FORMAT
+                )
+                .expect("timestamp"),
+                event_code: "TOR".to_string(),
+                stream_label: "STREAM_A".to_string(),
+                collision_index: 1,
+                extension: "wav".to_string(),
+            }
+        );
+        let plain = parse_recording_file_name("EAS_Recording_2024-12-04_11-58-45_TOR_STREAM_A.wav")
+            .expect("plain name parses");
+        assert_eq!(plain.stream_label, "STREAM_A");
+        assert_eq!(plain.collision_index, 0);
+        assert!(parse_recording_file_name("EAS_Recording_1.wav").is_none());
+        assert!(parse_recording_file_name("EAS_Recording_2024-12-04_11-58-45_TOR.wav").is_none());
+
+        let listed = scan_recordings(dir.path()).expect("scan");
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].name.collision_index, 1);
+        assert_eq!(listed[0].size_bytes, 8);
+        assert!(scan_recordings(&dir.path().join("missing"))
+            .expect("missing dir")
+            .is_empty());
+    }
+
+    #[test]
+    fn filename_templates_expand_into_subdirectories_and_reject_escapes() {
+        for bad in [
+            "",
+            "../{event}",
+            "{year}/.hidden/{event}",
+            "{year}//{event}",
+            "{bogus}",
+            "{event",
+            "alert {event}",
+            "/abs/{event}",
+        ] {
+            assert!(RecordingNameTemplate::parse(bad).is_err(), "{bad:?}");
+        }
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        let default = next_available_recording_path(
+            dir.path(),
+            &RecordingNameTemplate::default(),
+            &tor_fields(),
+            "wav",
+        );
+        assert_eq!(
+            default,
+            dir.path()
+                .join("EAS_Recording_2024-12-04_11-58-45_TOR_STREAM_A.wav")
+        );
+
+        let nested = RecordingNameTemplate::parse(
+            "{year}/{month}/{day}/EAS_Recording_{date}_{time}_{event}_{stream}",
+        )
+        .expect("nested template");
+        let mut fields = tor_fields();
+        fields.stream =
+            RecordingNameFields::from_header("", "../../etc", fields.recorded_at).stream;
+        let path = next_available_recording_path(dir.path(), &nested, &fields, "mp3");
+        assert_eq!(
+            recording_file_name(dir.path(), &path).as_deref(),
+            Some("2024/12/04/EAS_Recording_2024-12-04_11-58-45_TOR_ETC.mp3")
+        );
+        std::fs::create_dir_all(path.parent().expect("parent")).expect("mkdir");
+        std::fs::write(&path, b"ID3").expect("write");
+
+        let seq = RecordingNameTemplate::parse("{originator}-{event}-{seq}").expect("seq template");
+        let first = next_available_recording_path(dir.path(), &seq, &tor_fields(), "wav");
+        assert!(first.ends_with("WXR-TOR-1.wav"));
+        std::fs::write(&first, b"RIFF").expect("write");
+        let second = next_available_recording_path(dir.path(), &seq, &tor_fields(), "wav");
+        assert!(second.ends_with("WXR-TOR-2.wav"));
+
+        let listed = scan_recordings(dir.path()).expect("scan");
+        assert_eq!(listed.len(), 1, "{listed:?}");
+        assert_eq!(
+            listed[0].file,
+            "2024/12/04/EAS_Recording_2024-12-04_11-58-45_TOR_ETC.mp3"
+        );
+    }
+
+    #[test]
+    fn subdir_schemes_and_the_index_list_recordings_without_a_walk() {
+        assert_eq!(
+            RecordingSubdirScheme::parse(" Stream_Date "),
+            Some(RecordingSubdirScheme::StreamDate)
+        );
+        assert_eq!(RecordingSubdirScheme::parse("year"), None);
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        let legacy = dir
+            .path()
+            .join("EAS_Recording_2024-01-02_03-04-05_RWT_STREAM_A.wav");
+        std::fs::write(&legacy, b"RIFF").expect("write");
+
+        let template =
+            RecordingNameTemplate::default().with_subdirs(RecordingSubdirScheme::StreamDate);
+        let first = next_available_recording_path(dir.path(), &template, &tor_fields(), "wav");
+        assert_eq!(
+            recording_file_name(dir.path(), &first).as_deref(),
+            Some("STREAM_A/2024-12-04/EAS_Recording_2024-12-04_11-58-45_TOR_STREAM_A.wav")
+        );
+        std::fs::create_dir_all(first.parent().expect("parent")).expect("mkdir");
+        std::fs::write(&first, b"RIFF").expect("write");
+        index_recording(dir.path(), &first, None).expect("index");
+        let second = next_available_recording_path(dir.path(), &template, &tor_fields(), "wav");
+        assert!(second.ends_with("EAS_Recording_2024-12-04_11-58-45_TOR_STREAM_A_1.wav"));
+        std::fs::write(&second, b"RIFF....").expect("write");
+        index_recording(dir.path(), &second, None).expect("index");
+        let index_lines = || {
+            std::fs::read_to_string(dir.path().join(RECORDING_INDEX_FILE))
+                .expect("index")
+                .lines()
+                .count()
+        };
+        assert_eq!(index_lines(), 3);
+
+        let copied = dir
+            .path()
+            .join("EAS_Recording_2023-06-07_08-09-10_RMT_STREAM_B.mp3");
+        std::fs::write(&copied, b"ID3").expect("write");
+        let listed = list_recordings(dir.path()).expect("list");
+        let files: Vec<&str> = listed
+            .iter()
+            .map(|entry| entry.recording.file.as_str())
+            .collect();
+        assert_eq!(
+            files,
+            [
+                "STREAM_A/2024-12-04/EAS_Recording_2024-12-04_11-58-45_TOR_STREAM_A_1.wav",
+                "STREAM_A/2024-12-04/EAS_Recording_2024-12-04_11-58-45_TOR_STREAM_A.wav",
+                "EAS_Recording_2024-01-02_03-04-05_RWT_STREAM_A.wav",
+                "EAS_Recording_2023-06-07_08-09-10_RMT_STREAM_B.mp3",
+            ]
+        );
+        assert_eq!(listed[0].recording.size_bytes, 8);
+
+        std::fs::remove_file(&first).expect("remove");
+        assert_eq!(scan_recordings(dir.path()).expect("scan").len(), 3);
+        assert_eq!(compact_index(dir.path()).expect("compact"), 1);
+        assert_eq!(index_lines(), 2);
+    }
+
+    #[tokio::test]
+    async fn finishing_recordings_for_shutdown_leaves_a_complete_wav() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let mut config = Config::safe_internal_defaults();
+        config.recording_dir = dir.path().to_path_buf();
+        config.min_free_disk_mb = 0;
+        let header = "ZCZC-WXR-TOR-031055+0030-3381200-KOAX/NWS-";
+        let recording_state = tokio::sync::Mutex::new(HashMap::new());
+        let (handle, state) =
+            start_encoding_task(&config, header, None, "wxr", &HashMap::new()).expect("start");
+        let path = state.output_path.clone();
+        state
+            .audio_tx
+            .send(vec![0.25; TARGET_SAMPLE_RATE as usize])
+            .await
+            .expect("send");
+        recording_state
+            .lock()
+            .await
+            .insert("wxr".to_string(), state);
+
+        finish_recordings(&recording_state, Duration::from_secs(10)).await;
+        assert!(recording_state.lock().await.is_empty());
+        handle.await.expect("join").expect("recording");
+
+        let reader = hound::WavReader::open(&path).expect("wav");
+        assert!(reader.len() as usize > TARGET_SAMPLE_RATE as usize);
+        assert!(!partial_path(&path).exists());
+        assert!(read_sidecar(&path).is_some());
+    }
+
+    #[tokio::test]
+    async fn injected_same_tones_are_optional_and_located_by_the_sidecar() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let mut config = Config::safe_internal_defaults();
+        config.recording_dir = dir.path().to_path_buf();
+        config.min_free_disk_mb = 0;
+        let header = "ZCZC-WXR-TOR-031055+0030-3381200-KOAX/NWS-";
+        let (header_samples, nnnn_samples) =
+            same_tones(&config, header, TARGET_SAMPLE_RATE).expect("tones");
+        let received: Vec<f32> = (0..TARGET_SAMPLE_RATE as usize)
+            .map(|n| 0.5 * (std::f32::consts::TAU * 440.0 * n as f32 / 48_000.0).sin())
+            .collect();
+
+        for inject in [true, false] {
+            config.recording_inject_same = inject;
+            let (handle, state) =
+                start_encoding_task(&config, header, None, "wxr", &HashMap::new()).expect("start");
+            state.audio_tx.send(received.clone()).await.expect("send");
+            let path = state.output_path.clone();
+            drop(state);
+            handle.await.expect("join").expect("recording");
+
+            let expected = if inject {
+                header_samples.len()
+                    + received.len()
+                    + TARGET_SAMPLE_RATE as usize
+                    + nnnn_samples.len()
+            } else {
+                received.len()
+            };
+            let reader = hound::WavReader::open(&path).expect("wav");
+            assert_eq!(reader.len() as usize, expected, "inject {inject}");
+            let metadata = read_sidecar(&path).expect("sidecar");
+            let audio_start = if inject { header_samples.len() } else { 0 };
+            assert_eq!(metadata.audio_start_sample, Some(audio_start));
+            assert_eq!(metadata.audio_samples, Some(received.len()));
+        }
+
+        config.recording_inject_same = true;
+        config.recording_attention_tone = true;
+        let (handle, state) =
+            start_encoding_task(&config, header, None, "wxr", &HashMap::new()).expect("start");
+        state.audio_tx.send(received.clone()).await.expect("send");
+        let path = state.output_path.clone();
+        drop(state);
+        handle.await.expect("join").expect("recording");
+        let metadata = read_sidecar(&path).expect("sidecar");
+        assert_eq!(
+            metadata.audio_start_sample,
+            Some(header_samples.len() + 8 * TARGET_SAMPLE_RATE as usize)
+        );
+        config.recording_attention_tone = false;
+        config.recording_inject_same = false;
+
+        let (handle, state) =
+            start_encoding_task(&config, header, None, "wxr", &HashMap::new()).expect("start");
+        let path = state.output_path.clone();
+        drop(state);
+        handle.await.expect("join").expect("recording");
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn recordings_use_the_configured_sample_depth_and_rate() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let mut config = Config::safe_internal_defaults();
+        config.recording_dir = dir.path().to_path_buf();
+        config.min_free_disk_mb = 0;
+        let header = "ZCZC-WXR-TOR-031055+0030-3381200-KOAX/NWS-";
+        let received: Vec<f32> = (0..TARGET_SAMPLE_RATE as usize)
+            .map(|n| 0.5 * (std::f32::consts::TAU * 440.0 * n as f32 / 48_000.0).sin())
+            .collect();
+
+        for (sample_format, sample_rate) in [
+            (RecordingSampleFormat::Int24, 48_000),
+            (RecordingSampleFormat::Float32, 22_050),
+            (RecordingSampleFormat::Int16, 22_050),
+        ] {
+            config.recording_sample_format = sample_format;
+            config.recording_sample_rate = sample_rate;
+            let (handle, state) =
+                start_encoding_task(&config, header, None, "wxr", &HashMap::new()).expect("start");
+            state.audio_tx.send(received.clone()).await.expect("send");
+            let path = state.output_path.clone();
+            drop(state);
+            handle.await.expect("join").expect("recording");
+
+            let rate = sample_rate as usize;
+            let (header_samples, nnnn_samples) =
+                same_tones(&config, header, sample_rate).expect("tones");
+            let mut reader = hound::WavReader::open(&path).expect("wav");
+            let spec = reader.spec();
+            assert_eq!(spec.sample_rate, sample_rate);
+            assert_eq!(
+                reader.len() as usize,
+                header_samples.len() + rate + rate + nnnn_samples.len(),
+                "{sample_format:?} at {sample_rate}"
+            );
+            let audio = header_samples.len()..header_samples.len() + rate;
+            match sample_format {
+                RecordingSampleFormat::Int24 => {
+                    assert_eq!(spec.bits_per_sample, 24);
+                    let peak = reader
+                        .samples::<i32>()
+                        .map(|sample| sample.expect("sample").abs())
+                        .max()
+                        .expect("samples");
+                    assert!((4_000_000..=4_200_000).contains(&peak), "peak {peak}");
+                }
+                RecordingSampleFormat::Float32 => {
+                    assert_eq!(spec.sample_format, hound::SampleFormat::Float);
+                    let peak = reader
+                        .samples::<f32>()
+                        .skip(audio.start)
+                        .take(audio.len())
+                        .map(|sample| sample.expect("sample").abs())
+                        .fold(0.0f32, f32::max);
+                    assert!((0.45..=0.55).contains(&peak), "peak {peak}");
+                }
+                RecordingSampleFormat::Int16 => assert_eq!(spec.bits_per_sample, 16),
+            }
+            let metadata = read_sidecar(&path).expect("sidecar");
+            assert_eq!(metadata.sample_rate, sample_rate);
+            assert_eq!(metadata.audio_start_sample, Some(audio.start));
+            assert_eq!(metadata.audio_samples, Some(rate));
+        }
+    }
+
+    #[tokio::test]
+    async fn hot_audio_is_counted_scaled_and_normalized_without_touching_the_tones() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let mut config = Config::safe_internal_defaults();
+        config.recording_dir = dir.path().to_path_buf();
+        config.min_free_disk_mb = 0;
+        let header = "ZCZC-WXR-TOR-031055+0030-3381200-KOAX/NWS-";
+        let received: Vec<f32> = (0..TARGET_SAMPLE_RATE as usize)
+            .map(|n| 2.0 * (std::f32::consts::TAU * 440.0 * n as f32 / 48_000.0).sin())
+            .collect();
+        let (header_samples, _) = same_tones(&config, header, TARGET_SAMPLE_RATE).expect("tones");
+
+        let mut stream = crate::config::StreamConfig::from_url("wxr");
+        for (gain_db, normalize, expected_peak, clips) in [
+            (0.0, None, 1.0, true),
+            (0.0, Some(-1.0), 0.891, false),
+            (-12.0, None, 0.502, false),
+        ] {
+            stream.recording_gain_db = gain_db;
+            config.streams = vec![stream.clone()];
+            config.recording_normalize_dbfs = normalize;
+            let (handle, state) =
+                start_encoding_task(&config, header, None, "wxr", &HashMap::new()).expect("start");
+            state.audio_tx.send(received.clone()).await.expect("send");
+            let path = state.output_path.clone();
+            drop(state);
+            handle.await.expect("join").expect("recording");
+
+            let samples: Vec<i16> = hound::WavReader::open(&path)
+                .expect("wav")
+                .samples::<i16>()
+                .map(|sample| sample.expect("sample"))
+                .collect();
+            assert_eq!(&samples[..header_samples.len()], &header_samples[..]);
+            let audio = &samples[header_samples.len()..header_samples.len() + 48_000];
+            let peak = audio
+                .iter()
+                .map(|&sample| pcm16_to_f32(sample).abs())
+                .fold(0.0f32, f32::max);
+            assert!(
+                (peak - expected_peak).abs() < 0.01,
+                "{gain_db} dB, {normalize:?}: peak {peak}"
+            );
+            let metadata = read_sidecar(&path).expect("sidecar");
+            assert_eq!(
+                metadata.clipped_samples.expect("counted") > 0,
+                clips,
+                "{gain_db} dB, {normalize:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn interrupted_wav_recordings_are_salvaged_and_other_partials_deleted() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let nested = dir.path().join("2024/12/04");
+        std::fs::create_dir_all(&nested).expect("mkdir");
+        let output = nested.join("EAS_Recording_2024-12-04_11-58-45_TOR_STREAM_A.wav");
+        let spec = WavSpec {
+            channels: 1,
+            sample_rate: TARGET_SAMPLE_RATE,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = WavWriter::create(partial_path(&output), spec).expect("writer");
+        for sample in 0..1000i16 {
+            writer.write_sample(sample).expect("write");
+        }
+        writer.finalize().expect("finalize");
+        let mut bytes = std::fs::read(partial_path(&output)).expect("read");
+        let data = bytes
+            .windows(4)
+            .position(|window| window == b"data")
+            .expect("data");
+        bytes[4..8].fill(0);
+        bytes[data + 4..data + 8].fill(0);
+        bytes.push(0x7f);
+        std::fs::write(partial_path(&output), &bytes).expect("write");
+        assert!(hound::WavReader::open(partial_path(&output))
+            .map(|reader| reader.len() != 1000)
+            .unwrap_or(true));
+
+        let header_only = dir
+            .path()
+            .join("EAS_Recording_2024-12-04_12-00-00_SVR_B.wav");
+        std::fs::write(partial_path(&header_only), &bytes[..data + 8]).expect("write");
+        let encoded = dir
+            .path()
+            .join("EAS_Recording_2024-12-04_12-00-00_SVR_B.mp3");
+        std::fs::write(partial_path(&encoded), b"ID3").expect("write");
+
+        assert_eq!(recover_partial_recordings(dir.path()), (1, 2));
+        let reader = hound::WavReader::open(&output).expect("salvaged wav");
+        assert_eq!(reader.len(), 1000);
+        assert!(!partial_path(&output).exists());
+        assert!(!partial_path(&header_only).exists() && !header_only.exists());
+        assert!(!partial_path(&encoded).exists());
+    }
+
+    #[test]
+    fn low_disk_space_refuses_or_deletes_the_oldest_recordings() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let names = [
+            "EAS_Recording_2024-12-04_11-00-00_RWT_STREAM1.wav",
+            "EAS_Recording_2024-12-04_12-00-00_SVR_STREAM1.mp3",
+            "EAS_Recording_2024-12-04_13-00-00_TOR_STREAM1.wav",
+            "EAS_Recording_2024-12-04_14-00-00_TOR_STREAM2.wav",
+        ];
+        for name in names {
+            std::fs::write(dir.path().join(name), [0u8; 400]).expect("write");
+        }
+        let free_bytes = |path: &Path| {
+            let used: u64 = scan_recordings(path)
+                .ok()?
+                .iter()
+                .map(|recording| recording.size_bytes)
+                .sum();
+            Some(2000 - used)
+        };
+        let active: HashSet<OsString> = [OsString::from(
+            "EAS_Recording_2024-12-04_11-00-00_RWT_STREAM1",
+        )]
+        .into();
+
+        assert_eq!(
+            make_room(dir.path(), 400, LowDiskAction::Refuse, &active, free_bytes),
+            SpaceCheck::Enough
+        );
+        assert_eq!(
+            make_room(dir.path(), 1000, LowDiskAction::Refuse, &active, free_bytes),
+            SpaceCheck::Short {
+                deleted: Vec::new(),
+                available: 400
+            }
+        );
+        assert_eq!(
+            make_room(dir.path(), 1000, LowDiskAction::Refuse, &active, |_| None),
+            SpaceCheck::Enough
+        );
+
+        assert_eq!(
+            make_room(
+                dir.path(),
+                1000,
+                LowDiskAction::DeleteOldest,
+                &active,
+                free_bytes
+            ),
+            SpaceCheck::Freed {
+                deleted: vec![names[1].to_string(), names[2].to_string()],
+                available: 1200
+            }
+        );
+        assert!(dir.path().join(names[0]).exists());
+        assert!(dir.path().join(names[3]).exists());
+
+        assert_eq!(
+            make_room(
+                dir.path(),
+                1900,
+                LowDiskAction::DeleteOldest,
+                &active,
+                free_bytes
+            ),
+            SpaceCheck::Short {
+                deleted: vec![names[3].to_string()],
+                available: 1600
+            }
+        );
+    }
+}
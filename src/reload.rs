@@ -0,0 +1,89 @@
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+struct ReloadTracker {
+    generation: AtomicU64,
+    acknowledged: Mutex<BTreeMap<&'static str, u64>>,
+    changed: Notify,
+}
+
+static TRACKER: Lazy<ReloadTracker> = Lazy::new(|| ReloadTracker {
+    generation: AtomicU64::new(0),
+    acknowledged: Mutex::new(BTreeMap::new()),
+    changed: Notify::new(),
+});
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ReloadAcks {
+    pub acknowledged: Vec<&'static str>,
+    pub pending: Vec<&'static str>,
+}
+
+pub fn register(subsystem: &'static str) {
+    TRACKER.acknowledged.lock().entry(subsystem).or_insert(0);
+}
+
+pub fn begin() -> u64 {
+    TRACKER.generation.fetch_add(1, Ordering::SeqCst) + 1
+}
+
+pub fn acknowledge(subsystem: &'static str) {
+    let generation = TRACKER.generation.load(Ordering::SeqCst);
+    TRACKER.acknowledged.lock().insert(subsystem, generation);
+    TRACKER.changed.notify_waiters();
+}
+
+fn acks_for(generation: u64) -> ReloadAcks {
+    let acknowledged = TRACKER.acknowledged.lock();
+    let (done, pending): (Vec<_>, Vec<_>) = acknowledged
+        .iter()
+        .partition(|(_, seen)| **seen >= generation);
+    ReloadAcks {
+        acknowledged: done.into_iter().map(|(name, _)| *name).collect(),
+        pending: pending.into_iter().map(|(name, _)| *name).collect(),
+    }
+}
+
+pub async fn wait_for_acks(generation: u64, timeout: Duration) -> ReloadAcks {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        let changed = TRACKER.changed.notified();
+        let acks = acks_for(generation);
+        if acks.pending.is_empty() {
+            return acks;
+        }
+        if tokio::time::timeout_at(deadline, changed).await.is_err() {
+            return acks_for(generation);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reload_waits_for_registered_subsystems() {
+        register("test-fast");
+        register("test-slow");
+        let generation = begin();
+
+        let waiter = tokio::spawn(wait_for_acks(generation, Duration::from_secs(5)));
+        acknowledge("test-fast");
+        acknowledge("test-slow");
+        let acks = waiter.await.expect("join");
+        assert!(acks.acknowledged.contains(&"test-fast"));
+        assert!(acks.acknowledged.contains(&"test-slow"));
+
+        let generation = begin();
+        acknowledge("test-fast");
+        let acks = wait_for_acks(generation, Duration::from_millis(50)).await;
+        assert!(acks.acknowledged.contains(&"test-fast"));
+        assert!(acks.pending.contains(&"test-slow"));
+    }
+}
@@ -0,0 +1,752 @@
+use crate::config::Config;
+use crate::monitoring::MonitoringHub;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::{self, Receiver as BroadcastReceiver};
+use tracing::{info, warn};
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+const PUSH_INTERVAL: Duration = Duration::from_secs(30);
+const FALLBACK_CLOCK_TICKS: f64 = 100.0;
+const AT_CLKTCK: u64 = 17;
+
+pub static DECODE_TASKS: AtomicUsize = AtomicUsize::new(0);
+pub static RECORDING_WRITERS: AtomicUsize = AtomicUsize::new(0);
+
+static LATEST: Lazy<RwLock<Option<ResourceSnapshot>>> = Lazy::new(|| RwLock::new(None));
+
+static CLOCK_TICKS: Lazy<f64> = Lazy::new(|| {
+    std::fs::read("/proc/self/auxv")
+        .ok()
+        .and_then(|auxv| auxv_value(&auxv, AT_CLKTCK))
+        .map(|ticks| ticks as f64)
+        .unwrap_or(FALLBACK_CLOCK_TICKS)
+});
+
+pub struct ActiveTask(&'static AtomicUsize);
+
+pub fn track(counter: &'static AtomicUsize) -> ActiveTask {
+    counter.fetch_add(1, Ordering::Relaxed);
+    ActiveTask(counter)
+}
+
+impl Drop for ActiveTask {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RuntimeStats {
+    pub workers: usize,
+    pub alive_tasks: usize,
+    pub global_queue_depth: usize,
+    pub busy_percent: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BlockingStats {
+    pub decode_tasks: usize,
+    pub recording_writers: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChildProcessStats {
+    pub ffmpeg: usize,
+    pub ffprobe: usize,
+    pub apprise: usize,
+    pub other: usize,
+}
+
+impl ChildProcessStats {
+    fn total(&self) -> usize {
+        self.ffmpeg + self.ffprobe + self.apprise + self.other
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LoadAverage {
+    pub one: f64,
+    pub five: f64,
+    pub fifteen: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskSpace {
+    pub name: &'static str,
+    pub path: String,
+    pub available_bytes: Option<u64>,
+    pub total_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_free_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResourceSnapshot {
+    pub sampled_at: DateTime<Utc>,
+    pub cpu_percent: f64,
+    pub children_cpu_percent: f64,
+    pub rss_bytes: u64,
+    pub threads: u64,
+    pub runtime: RuntimeStats,
+    pub blocking: BlockingStats,
+    pub child_processes: ChildProcessStats,
+    pub event_subscribers: usize,
+    pub top_contributor: String,
+    pub load_average: Option<LoadAverage>,
+    pub disks: Vec<DiskSpace>,
+}
+
+pub fn latest() -> Option<ResourceSnapshot> {
+    LATEST
+        .read()
+        .expect("resource snapshot lock poisoned")
+        .clone()
+}
+
+fn auxv_value(auxv: &[u8], key: u64) -> Option<u64> {
+    const WORD: usize = std::mem::size_of::<usize>();
+    auxv.chunks_exact(WORD * 2).find_map(|pair| {
+        let read = |bytes: &[u8]| {
+            let mut word = [0u8; WORD];
+            word.copy_from_slice(bytes);
+            usize::from_ne_bytes(word) as u64
+        };
+        (read(&pair[..WORD]) == key).then(|| read(&pair[WORD..]))
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ProcStat {
+    comm: String,
+    ppid: u32,
+    cpu_ticks: u64,
+    threads: u64,
+}
+
+fn parse_proc_stat(contents: &str) -> Option<ProcStat> {
+    let open = contents.find('(')?;
+    let close = contents.rfind(')')?;
+    let comm = contents.get(open + 1..close)?.to_string();
+    let fields: Vec<&str> = contents.get(close + 1..)?.split_whitespace().collect();
+    let field = |index: usize| fields.get(index)?.parse::<u64>().ok();
+    Some(ProcStat {
+        comm,
+        ppid: field(1)? as u32,
+        cpu_ticks: field(11)? + field(12)?,
+        threads: field(17)?,
+    })
+}
+
+fn parse_rss_bytes(status: &str) -> Option<u64> {
+    status.lines().find_map(|line| {
+        let kib = line.strip_prefix("VmRSS:")?.trim().strip_suffix("kB")?;
+        kib.trim().parse::<u64>().ok().map(|kib| kib * 1024)
+    })
+}
+
+fn parse_loadavg(contents: &str) -> Option<LoadAverage> {
+    let mut fields = contents.split_whitespace().map(str::parse::<f64>);
+    Some(LoadAverage {
+        one: fields.next()?.ok()?,
+        five: fields.next()?.ok()?,
+        fifteen: fields.next()?.ok()?,
+    })
+}
+
+#[cfg(unix)]
+pub(crate) fn filesystem_space(path: &std::path::Path) -> Option<(u64, u64)> {
+    let stat = rustix::fs::statvfs(path).ok()?;
+    let block = stat.f_frsize.max(1);
+    Some((
+        stat.f_bavail.saturating_mul(block),
+        stat.f_blocks.saturating_mul(block),
+    ))
+}
+
+#[cfg(not(unix))]
+pub(crate) fn filesystem_space(_path: &std::path::Path) -> Option<(u64, u64)> {
+    None
+}
+
+fn disk_space(config: &Config) -> Vec<DiskSpace> {
+    [
+        ("shared_state", &config.shared_state_dir),
+        ("recordings", &config.recording_dir),
+    ]
+    .into_iter()
+    .map(|(name, path)| {
+        let space = filesystem_space(path);
+        let min_free_mb = if name == "recordings" {
+            config.min_free_disk_mb
+        } else {
+            0
+        };
+        DiskSpace {
+            name,
+            path: path.display().to_string(),
+            available_bytes: space.map(|(available, _)| available),
+            total_bytes: space.map(|(_, total)| total),
+            min_free_bytes: (min_free_mb > 0).then(|| min_free_mb.saturating_mul(1024 * 1024)),
+        }
+    })
+    .collect()
+}
+
+fn child_processes(own_pid: u32) -> HashMap<u32, ProcStat> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return HashMap::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<u32>().ok())
+        .filter_map(|pid| {
+            let stat =
+                parse_proc_stat(&std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?)?;
+            (stat.ppid == own_pid).then_some((pid, stat))
+        })
+        .collect()
+}
+
+fn classify_children<'a>(children: impl Iterator<Item = &'a ProcStat>) -> ChildProcessStats {
+    let mut stats = ChildProcessStats::default();
+    for child in children {
+        match child.comm.as_str() {
+            "ffmpeg" => stats.ffmpeg += 1,
+            "ffprobe" => stats.ffprobe += 1,
+            "apprise" => stats.apprise += 1,
+            _ => stats.other += 1,
+        }
+    }
+    stats
+}
+
+fn top_contributor(snapshot: &ResourceSnapshot) -> String {
+    let runtime = snapshot.runtime.busy_percent;
+    let blocking = (snapshot.cpu_percent - runtime).max(0.0);
+    let children = snapshot.children_cpu_percent;
+
+    if children >= runtime && children >= blocking && children > 0.0 {
+        let processes = &snapshot.child_processes;
+        format!(
+            "child processes at {children:.0}% CPU: {} ffmpeg, {} ffprobe, {} apprise, {} other",
+            processes.ffmpeg, processes.ffprobe, processes.apprise, processes.other
+        )
+    } else if runtime >= blocking {
+        format!(
+            "async runtime busy at {runtime:.0}% CPU: {} tasks alive, global queue depth {}, {} event subscribers (WebSocket fanout)",
+            snapshot.runtime.alive_tasks,
+            snapshot.runtime.global_queue_depth,
+            snapshot.event_subscribers
+        )
+    } else {
+        format!(
+            "blocking pool at {blocking:.0}% CPU: {} stream decode task(s) (decoder/resampler) and {} recording writer(s) active",
+            snapshot.blocking.decode_tasks, snapshot.blocking.recording_writers
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AlarmEvent {
+    Warn,
+    Cleared,
+}
+
+#[derive(Debug, Default)]
+struct CpuAlarm {
+    above_since: Option<Instant>,
+    warned: bool,
+}
+
+impl CpuAlarm {
+    fn observe(
+        &mut self,
+        cpu_percent: f64,
+        threshold: f64,
+        sustain: Duration,
+        now: Instant,
+    ) -> Option<AlarmEvent> {
+        if threshold <= 0.0 || cpu_percent < threshold {
+            self.above_since = None;
+            return std::mem::take(&mut self.warned).then_some(AlarmEvent::Cleared);
+        }
+        let since = *self.above_since.get_or_insert(now);
+        if !self.warned && now.duration_since(since) >= sustain {
+            self.warned = true;
+            return Some(AlarmEvent::Warn);
+        }
+        None
+    }
+}
+
+#[derive(Debug, Default)]
+struct DiskAlarm {
+    low: HashSet<&'static str>,
+}
+
+impl DiskAlarm {
+    fn observe(&mut self, disk: &DiskSpace, threshold_bytes: u64) -> Option<AlarmEvent> {
+        let available = disk.available_bytes?;
+        if threshold_bytes > 0 && available < threshold_bytes {
+            self.low.insert(disk.name).then_some(AlarmEvent::Warn)
+        } else {
+            self.low.remove(disk.name).then_some(AlarmEvent::Cleared)
+        }
+    }
+}
+
+struct PreviousSample {
+    at: Instant,
+    cpu_ticks: u64,
+    children_ticks: HashMap<u32, u64>,
+    workers_busy: Duration,
+}
+
+struct Sampler {
+    own_pid: u32,
+    previous: Option<PreviousSample>,
+}
+
+impl Sampler {
+    fn new() -> Self {
+        Self {
+            own_pid: std::process::id(),
+            previous: None,
+        }
+    }
+
+    fn sample(&mut self, monitoring: &MonitoringHub, config: &Config) -> Result<ResourceSnapshot> {
+        let now = Instant::now();
+        let own = std::fs::read_to_string("/proc/self/stat")
+            .context("failed to read /proc/self/stat")
+            .and_then(|contents| {
+                parse_proc_stat(&contents)
+                    .ok_or_else(|| anyhow!("unexpected /proc/self/stat format"))
+            })?;
+        let rss_bytes = std::fs::read_to_string("/proc/self/status")
+            .ok()
+            .and_then(|status| parse_rss_bytes(&status))
+            .unwrap_or(0);
+        let children = child_processes(self.own_pid);
+
+        let metrics = tokio::runtime::Handle::current().metrics();
+        let workers = metrics.num_workers();
+        let workers_busy: Duration = (0..workers)
+            .map(|worker| metrics.worker_total_busy_duration(worker))
+            .sum();
+
+        let ticks = *CLOCK_TICKS;
+        let (cpu_percent, children_cpu_percent, busy_percent) = match &self.previous {
+            Some(previous) => {
+                let elapsed = now
+                    .duration_since(previous.at)
+                    .as_secs_f64()
+                    .max(f64::EPSILON);
+                let percent_of = |ticks_used: u64| ticks_used as f64 / ticks / elapsed * 100.0;
+                let children_ticks: u64 = children
+                    .iter()
+                    .map(|(pid, child)| {
+                        child
+                            .cpu_ticks
+                            .saturating_sub(previous.children_ticks.get(pid).copied().unwrap_or(0))
+                    })
+                    .sum();
+                (
+                    percent_of(own.cpu_ticks.saturating_sub(previous.cpu_ticks)),
+                    percent_of(children_ticks),
+                    workers_busy
+                        .saturating_sub(previous.workers_busy)
+                        .as_secs_f64()
+                        / elapsed
+                        * 100.0,
+                )
+            }
+            None => (0.0, 0.0, 0.0),
+        };
+
+        let mut snapshot = ResourceSnapshot {
+            sampled_at: Utc::now(),
+            cpu_percent,
+            children_cpu_percent,
+            rss_bytes,
+            threads: own.threads,
+            runtime: RuntimeStats {
+                workers,
+                alive_tasks: metrics.num_alive_tasks(),
+                global_queue_depth: metrics.global_queue_depth(),
+                busy_percent,
+            },
+            blocking: BlockingStats {
+                decode_tasks: DECODE_TASKS.load(Ordering::Relaxed),
+                recording_writers: RECORDING_WRITERS.load(Ordering::Relaxed),
+            },
+            child_processes: classify_children(children.values()),
+            event_subscribers: monitoring.subscriber_count(),
+            top_contributor: String::new(),
+            load_average: std::fs::read_to_string("/proc/loadavg")
+                .ok()
+                .and_then(|contents| parse_loadavg(&contents)),
+            disks: disk_space(config),
+        };
+        snapshot.top_contributor = top_contributor(&snapshot);
+
+        self.previous = Some(PreviousSample {
+            at: now,
+            cpu_ticks: own.cpu_ticks,
+            children_ticks: children
+                .iter()
+                .map(|(pid, child)| (*pid, child.cpu_ticks))
+                .collect(),
+            workers_busy,
+        });
+        Ok(snapshot)
+    }
+}
+
+pub fn prometheus_text(snapshot: &ResourceSnapshot) -> String {
+    let gauges: [(&str, &str, f64); 13] = [
+        (
+            "eas_listener_cpu_percent",
+            "Process CPU use as a percentage of one core.",
+            snapshot.cpu_percent,
+        ),
+        (
+            "eas_listener_children_cpu_percent",
+            "CPU use of running child processes as a percentage of one core.",
+            snapshot.children_cpu_percent,
+        ),
+        (
+            "eas_listener_resident_memory_bytes",
+            "Resident set size of the process.",
+            snapshot.rss_bytes as f64,
+        ),
+        (
+            "eas_listener_threads",
+            "Operating system threads in the process.",
+            snapshot.threads as f64,
+        ),
+        (
+            "eas_listener_runtime_workers",
+            "Async runtime worker threads.",
+            snapshot.runtime.workers as f64,
+        ),
+        (
+            "eas_listener_runtime_alive_tasks",
+            "Async tasks currently alive.",
+            snapshot.runtime.alive_tasks as f64,
+        ),
+        (
+            "eas_listener_runtime_global_queue_depth",
+            "Tasks waiting in the runtime's global queue.",
+            snapshot.runtime.global_queue_depth as f64,
+        ),
+        (
+            "eas_listener_runtime_busy_percent",
+            "Time the async workers spent busy as a percentage of one core.",
+            snapshot.runtime.busy_percent,
+        ),
+        (
+            "eas_listener_decode_tasks",
+            "Stream decoders running on the blocking pool.",
+            snapshot.blocking.decode_tasks as f64,
+        ),
+        (
+            "eas_listener_recording_writers",
+            "Recording writers running on the blocking pool.",
+            snapshot.blocking.recording_writers as f64,
+        ),
+        (
+            "eas_listener_event_subscribers",
+            "Subscribers to the monitoring event stream, including WebSocket clients.",
+            snapshot.event_subscribers as f64,
+        ),
+        (
+            "eas_listener_child_processes_total",
+            "Running child processes.",
+            snapshot.child_processes.total() as f64,
+        ),
+        (
+            "eas_listener_resource_sample_timestamp_seconds",
+            "When these values were sampled.",
+            snapshot.sampled_at.timestamp() as f64,
+        ),
+    ];
+
+    let mut out = String::new();
+    for (name, help, value) in gauges {
+        out.push_str(&format!(
+            "# HELP {name} {help}\n# TYPE {name} gauge\n{name} {value}\n"
+        ));
+    }
+    out.push_str(
+        "# HELP eas_listener_child_processes Running child processes by program.\n# TYPE eas_listener_child_processes gauge\n",
+    );
+    let processes = &snapshot.child_processes;
+    for (program, count) in [
+        ("ffmpeg", processes.ffmpeg),
+        ("ffprobe", processes.ffprobe),
+        ("apprise", processes.apprise),
+        ("other", processes.other),
+    ] {
+        out.push_str(&format!(
+            "eas_listener_child_processes{{program=\"{program}\"}} {count}\n"
+        ));
+    }
+    if let Some(load) = &snapshot.load_average {
+        out.push_str(
+            "# HELP eas_listener_load_average Host load average.\n# TYPE eas_listener_load_average gauge\n",
+        );
+        for (window, value) in [("1m", load.one), ("5m", load.five), ("15m", load.fifteen)] {
+            out.push_str(&format!(
+                "eas_listener_load_average{{window=\"{window}\"}} {value}\n"
+            ));
+        }
+    }
+    out.push_str(
+        "# HELP eas_listener_disk_available_bytes Free space on the filesystem holding each directory.\n# TYPE eas_listener_disk_available_bytes gauge\n",
+    );
+    for disk in &snapshot.disks {
+        if let Some(available) = disk.available_bytes {
+            out.push_str(&format!(
+                "eas_listener_disk_available_bytes{{directory=\"{}\"}} {available}\n",
+                disk.name
+            ));
+        }
+    }
+    out
+}
+
+pub async fn run_resource_monitor(
+    mut config: Config,
+    monitoring: MonitoringHub,
+    mut reload_rx: BroadcastReceiver<Config>,
+) -> Result<()> {
+    let mut ticker = tokio::time::interval(SAMPLE_INTERVAL);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut sampler = Sampler::new();
+    let mut alarm = CpuAlarm::default();
+    let mut disk_alarm = DiskAlarm::default();
+    let mut last_push: Option<Instant> = None;
+    let mut unavailable_logged = false;
+
+    crate::reload::register("resources");
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            reload = reload_rx.recv() => {
+                match reload {
+                    Ok(new_config) => {
+                        config = new_config;
+                        crate::reload::acknowledge("resources");
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                }
+                continue;
+            }
+        }
+
+        let snapshot = match sampler.sample(&monitoring, &config) {
+            Ok(snapshot) => snapshot,
+            Err(err) => {
+                if !unavailable_logged {
+                    info!(
+                        "Resource telemetry is unavailable on this system: {:#}",
+                        err
+                    );
+                    unavailable_logged = true;
+                }
+                continue;
+            }
+        };
+
+        match alarm.observe(
+            snapshot.cpu_percent,
+            config.resource_cpu_warn_percent,
+            Duration::from_secs(config.resource_cpu_warn_secs),
+            Instant::now(),
+        ) {
+            Some(AlarmEvent::Warn) => warn!(
+                "CPU has stayed above {:.0}% for {}s (now {:.0}%, RSS {} MiB); likely cause: {}",
+                config.resource_cpu_warn_percent,
+                config.resource_cpu_warn_secs,
+                snapshot.cpu_percent,
+                snapshot.rss_bytes / (1024 * 1024),
+                snapshot.top_contributor
+            ),
+            Some(AlarmEvent::Cleared) => info!(
+                "CPU is back below {:.0}% (now {:.0}%)",
+                config.resource_cpu_warn_percent, snapshot.cpu_percent
+            ),
+            None => {}
+        }
+
+        let threshold_bytes = config.resource_disk_warn_mb.saturating_mul(1024 * 1024);
+        for disk in &snapshot.disks {
+            let available_mb = disk.available_bytes.unwrap_or(0) / (1024 * 1024);
+            match disk_alarm.observe(disk, threshold_bytes) {
+                Some(AlarmEvent::Warn) => warn!(
+                    "Only {} MiB free on the filesystem holding the {} directory ({}); recordings and state may fail to write",
+                    available_mb, disk.name, disk.path
+                ),
+                Some(AlarmEvent::Cleared) => info!(
+                    "Free space for the {} directory is back above {} MiB (now {} MiB)",
+                    disk.name, config.resource_disk_warn_mb, available_mb
+                ),
+                None => {}
+            }
+        }
+
+        let now = Instant::now();
+        if last_push.is_none_or(|at| now.duration_since(at) >= PUSH_INTERVAL) {
+            last_push = Some(now);
+            monitoring.broadcast_resources(snapshot.clone());
+        }
+        *LATEST.write().expect("resource snapshot lock poisoned") = Some(snapshot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proc_stat_and_status_are_parsed() {
+        let stat = "4242 (tokio (worker) x) S 1 4242 4242 0 -1 4194560 1234 0 0 0 250 50 0 0 20 0 17 0 100 123456789 5000 18446744073709551615";
+        assert_eq!(
+            parse_proc_stat(stat),
+            Some(ProcStat {
+                comm: "tokio (worker) x".to_string(),
+                ppid: 1,
+                cpu_ticks: 300,
+                threads: 17,
+            })
+        );
+        assert_eq!(parse_proc_stat("garbage"), None);
+        assert_eq!(
+            parse_rss_bytes("Name:\teas_listener\nVmRSS:\t  20480 kB\nThreads:\t17\n"),
+            Some(20480 * 1024)
+        );
+
+        let mut auxv = Vec::new();
+        for word in [6u64, 4096, AT_CLKTCK, 100, 0, 0] {
+            auxv.extend_from_slice(&(word as usize).to_ne_bytes());
+        }
+        assert_eq!(auxv_value(&auxv, AT_CLKTCK), Some(100));
+    }
+
+    #[test]
+    fn cpu_alarm_warns_once_per_sustained_episode() {
+        let mut alarm = CpuAlarm::default();
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let sustain = Duration::from_secs(60);
+
+        assert_eq!(alarm.observe(180.0, 150.0, sustain, at(0)), None);
+        assert_eq!(alarm.observe(180.0, 150.0, sustain, at(30)), None);
+        assert_eq!(
+            alarm.observe(180.0, 150.0, sustain, at(60)),
+            Some(AlarmEvent::Warn)
+        );
+        assert_eq!(alarm.observe(190.0, 150.0, sustain, at(65)), None);
+        assert_eq!(
+            alarm.observe(40.0, 150.0, sustain, at(70)),
+            Some(AlarmEvent::Cleared)
+        );
+        assert_eq!(alarm.observe(180.0, 0.0, sustain, at(200)), None);
+    }
+
+    #[test]
+    fn top_contributor_names_the_busiest_subsystem() {
+        let mut snapshot = ResourceSnapshot {
+            sampled_at: Utc::now(),
+            cpu_percent: 190.0,
+            children_cpu_percent: 20.0,
+            rss_bytes: 0,
+            threads: 12,
+            runtime: RuntimeStats {
+                workers: 4,
+                alive_tasks: 40,
+                global_queue_depth: 0,
+                busy_percent: 15.0,
+            },
+            blocking: BlockingStats {
+                decode_tasks: 6,
+                recording_writers: 1,
+            },
+            child_processes: ChildProcessStats {
+                ffmpeg: 2,
+                ..ChildProcessStats::default()
+            },
+            event_subscribers: 3,
+            top_contributor: String::new(),
+            load_average: None,
+            disks: vec![DiskSpace {
+                name: "recordings",
+                path: "/recordings".to_string(),
+                available_bytes: Some(2048),
+                total_bytes: None,
+                min_free_bytes: None,
+            }],
+        };
+        assert!(
+            top_contributor(&snapshot).starts_with("blocking pool at 175% CPU: 6 stream decode")
+        );
+
+        snapshot.children_cpu_percent = 400.0;
+        assert!(top_contributor(&snapshot).contains("2 ffmpeg"));
+
+        snapshot.children_cpu_percent = 0.0;
+        snapshot.runtime.busy_percent = 180.0;
+        assert!(top_contributor(&snapshot).starts_with("async runtime busy at 180%"));
+
+        let text = prometheus_text(&snapshot);
+        assert!(text.contains("eas_listener_decode_tasks 6\n"));
+        assert!(text.contains("eas_listener_child_processes{program=\"ffmpeg\"} 2\n"));
+        assert!(text.contains("eas_listener_disk_available_bytes{directory=\"recordings\"} 2048\n"));
+        assert!(!text.contains("eas_listener_load_average{"));
+    }
+
+    #[test]
+    fn load_average_and_low_disk_space_are_reported() {
+        assert_eq!(
+            parse_loadavg("0.52 0.58 0.59 1/467 12345\n"),
+            Some(LoadAverage {
+                one: 0.52,
+                five: 0.58,
+                fifteen: 0.59,
+            })
+        );
+        assert_eq!(parse_loadavg(""), None);
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        let (available, total) = filesystem_space(dir.path()).expect("statvfs");
+        assert!(total >= available && total > 0);
+
+        let disk = |available_bytes| DiskSpace {
+            name: "recordings",
+            path: "/recordings".to_string(),
+            available_bytes,
+            total_bytes: None,
+            min_free_bytes: None,
+        };
+        let mut alarm = DiskAlarm::default();
+        assert_eq!(
+            alarm.observe(&disk(Some(500)), 1000),
+            Some(AlarmEvent::Warn)
+        );
+        assert_eq!(alarm.observe(&disk(Some(400)), 1000), None);
+        assert_eq!(alarm.observe(&disk(None), 1000), None);
+        assert_eq!(
+            alarm.observe(&disk(Some(5000)), 1000),
+            Some(AlarmEvent::Cleared)
+        );
+        assert_eq!(alarm.observe(&disk(Some(500)), 0), None);
+    }
+}
@@ -0,0 +1,139 @@
+use crate::share::random_bytes;
+use anyhow::Result;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+pub const SESSION_TOKEN_PREFIX: &str = "sess_";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionCheck {
+    Valid(String),
+    Expired,
+    NotASession,
+}
+
+struct Session {
+    username: String,
+    expires_at: Instant,
+}
+
+pub struct SessionStore {
+    ttl: Duration,
+    sessions: Mutex<HashMap<[u8; 32], Session>>,
+}
+
+fn digest(token: &str) -> [u8; 32] {
+    Sha256::digest(token.as_bytes()).into()
+}
+
+impl SessionStore {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn issue(&self, username: &str, now: Instant) -> Result<(String, DateTime<Utc>)> {
+        let token = format!(
+            "{SESSION_TOKEN_PREFIX}{}",
+            URL_SAFE_NO_PAD.encode(random_bytes(32)?)
+        );
+        self.sessions
+            .lock()
+            .expect("session store lock poisoned")
+            .insert(
+                digest(&token),
+                Session {
+                    username: username.to_string(),
+                    expires_at: now + self.ttl,
+                },
+            );
+        let expires_at = Utc::now() + chrono::Duration::from_std(self.ttl).unwrap_or_default();
+        info!(
+            "Issued a dashboard session for {} (expires {})",
+            username,
+            expires_at.to_rfc3339()
+        );
+        Ok((token, expires_at))
+    }
+
+    pub fn check(&self, token: &str, now: Instant) -> SessionCheck {
+        if !token.starts_with(SESSION_TOKEN_PREFIX) {
+            return SessionCheck::NotASession;
+        }
+        let sessions = self.sessions.lock().expect("session store lock poisoned");
+        match sessions.get(&digest(token)) {
+            Some(session) if session.expires_at > now => {
+                SessionCheck::Valid(session.username.clone())
+            }
+            _ => SessionCheck::Expired,
+        }
+    }
+
+    pub fn revoke(&self, token: &str) -> bool {
+        let removed = self
+            .sessions
+            .lock()
+            .expect("session store lock poisoned")
+            .remove(&digest(token));
+        match removed {
+            Some(session) => {
+                info!("Dashboard session for {} ended by logout", session.username);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn sweep(&self, now: Instant) -> usize {
+        let mut sessions = self.sessions.lock().expect("session store lock poisoned");
+        let before = sessions.len();
+        sessions.retain(|_, session| {
+            let live = session.expires_at > now;
+            if !live {
+                info!("Dashboard session for {} expired", session.username);
+            }
+            live
+        });
+        before - sessions.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sessions_expire_and_can_be_revoked() {
+        let store = SessionStore::new(Duration::from_secs(60));
+        let start = Instant::now();
+        let (token, _) = store.issue("operator", start).expect("issue");
+        assert!(token.starts_with(SESSION_TOKEN_PREFIX));
+
+        assert_eq!(
+            store.check(&token, start),
+            SessionCheck::Valid("operator".to_string())
+        );
+        assert_eq!(
+            store.check("YWxpY2U6czNjcmV0", start),
+            SessionCheck::NotASession
+        );
+        assert_eq!(store.check("sess_made-up", start), SessionCheck::Expired);
+
+        let later = start + Duration::from_secs(61);
+        assert_eq!(store.check(&token, later), SessionCheck::Expired);
+        assert_eq!(store.sweep(later), 1);
+
+        let (token, _) = store.issue("operator", start).expect("issue");
+        assert!(store.revoke(&token));
+        assert!(!store.revoke(&token));
+        assert_eq!(store.check(&token, start), SessionCheck::Expired);
+    }
+}
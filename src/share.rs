@@ -0,0 +1,338 @@
+use crate::config::Config;
+use anyhow::{anyhow, Context, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+const SHARE_SECRET_FILE: &str = "share_link_secret";
+const SHARE_STORE_FILE: &str = "shares.json";
+const EXPIRED_SHARE_RETENTION_DAYS: i64 = 7;
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShareRecord {
+    pub id: String,
+    pub file: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    #[serde(default)]
+    pub revoked: bool,
+    #[serde(default)]
+    pub access_count: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShareError {
+    Malformed,
+    BadSignature,
+    Expired,
+    Revoked,
+}
+
+pub struct ShareStore {
+    secret: Vec<u8>,
+    store_path: PathBuf,
+    shares: Mutex<Vec<ShareRecord>>,
+}
+
+pub(crate) fn random_bytes(len: usize) -> Result<Vec<u8>> {
+    let mut bytes = vec![0u8; len];
+    std::fs::File::open("/dev/urandom")
+        .and_then(|mut source| source.read_exact(&mut bytes))
+        .context("failed to read /dev/urandom")?;
+    Ok(bytes)
+}
+
+fn load_or_create_secret(config: &Config) -> Result<Vec<u8>> {
+    if let Some(secret) = config.share_link_secret.as_deref() {
+        return Ok(secret.as_bytes().to_vec());
+    }
+
+    let path = config.shared_state_dir.join(SHARE_SECRET_FILE);
+    match std::fs::read_to_string(&path) {
+        Ok(contents) if !contents.trim().is_empty() => {
+            return Ok(contents.trim().as_bytes().to_vec())
+        }
+        Ok(_) => {}
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        Err(err) => return Err(anyhow!("failed to read {}: {}", path.display(), err)),
+    }
+
+    let secret = URL_SAFE_NO_PAD.encode(random_bytes(32)?);
+    std::fs::write(&path, &secret)
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    info!("Generated a new share link secret at {}", path.display());
+    Ok(secret.into_bytes())
+}
+
+pub fn recording_path(config: &Config, file: &str) -> Option<PathBuf> {
+    let trimmed = file.trim();
+    let valid = !trimmed.is_empty()
+        && !trimmed.contains('\\')
+        && trimmed.split('/').all(|part| {
+            !part.is_empty()
+                && !part.starts_with('.')
+                && Path::new(part).file_name().and_then(|name| name.to_str()) == Some(part)
+        });
+    valid.then(|| config.recording_dir.join(trimmed))
+}
+
+pub async fn resolve_recording_path(
+    config: &Config,
+    file: &str,
+) -> std::io::Result<Option<PathBuf>> {
+    let Some(path) = recording_path(config, file) else {
+        return Ok(None);
+    };
+    let root = tokio::fs::canonicalize(&config.recording_dir).await?;
+    let resolved = tokio::fs::canonicalize(&path).await?;
+    Ok(resolved.starts_with(&root).then_some(resolved))
+}
+
+impl ShareStore {
+    pub fn load(config: &Config) -> Result<Self> {
+        let secret = load_or_create_secret(config)?;
+        let store_path = config.shared_state_dir.join(SHARE_STORE_FILE);
+        let shares = match std::fs::read_to_string(&store_path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|err| {
+                warn!(
+                    "Ignoring unreadable share store {}: {}",
+                    store_path.display(),
+                    err
+                );
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+
+        Ok(Self {
+            secret,
+            store_path,
+            shares: Mutex::new(shares),
+        })
+    }
+
+    fn signature(&self, id: &str, file: &str, expires: i64) -> Vec<u8> {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(format!("{id}\n{file}\n{expires}").as_bytes());
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    fn token_for(&self, record: &ShareRecord) -> String {
+        let expires = record.expires_at.timestamp();
+        format!(
+            "{}.{}.{}.{}",
+            record.id,
+            expires,
+            URL_SAFE_NO_PAD.encode(record.file.as_bytes()),
+            URL_SAFE_NO_PAD.encode(self.signature(&record.id, &record.file, expires))
+        )
+    }
+
+    async fn persist(&self, shares: &[ShareRecord]) {
+        let result = async {
+            let payload = serde_json::to_vec_pretty(shares)?;
+            let tmp_path = self.store_path.with_extension("json.tmp");
+            tokio::fs::write(&tmp_path, payload).await?;
+            tokio::fs::rename(&tmp_path, &self.store_path).await?;
+            Ok::<(), anyhow::Error>(())
+        }
+        .await;
+        if let Err(err) = result {
+            warn!(
+                "Failed to persist share store {}: {}",
+                self.store_path.display(),
+                err
+            );
+        }
+    }
+
+    fn prune(shares: &mut Vec<ShareRecord>, now: DateTime<Utc>) {
+        let cutoff = now - Duration::days(EXPIRED_SHARE_RETENTION_DAYS);
+        shares.retain(|share| share.expires_at > cutoff);
+    }
+
+    pub async fn mint(&self, file: &str, ttl: Duration) -> Result<(ShareRecord, String)> {
+        let now = Utc::now();
+        let record = ShareRecord {
+            id: random_bytes(8)?
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect(),
+            file: file.to_string(),
+            created_at: now,
+            expires_at: now + ttl,
+            revoked: false,
+            access_count: 0,
+        };
+        let token = self.token_for(&record);
+
+        let mut shares = self.shares.lock().await;
+        Self::prune(&mut shares, now);
+        shares.push(record.clone());
+        self.persist(&shares).await;
+        Ok((record, token))
+    }
+
+    pub async fn redeem(&self, token: &str) -> Result<ShareRecord, ShareError> {
+        let mut parts = token.split('.');
+        let (Some(id), Some(expires), Some(file), Some(signature), None) = (
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+        ) else {
+            return Err(ShareError::Malformed);
+        };
+        let expires = expires.parse::<i64>().map_err(|_| ShareError::Malformed)?;
+        let file = URL_SAFE_NO_PAD
+            .decode(file)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or(ShareError::Malformed)?;
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| ShareError::Malformed)?;
+
+        let mut mac =
+            HmacSha256::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(format!("{id}\n{file}\n{expires}").as_bytes());
+        mac.verify_slice(&signature)
+            .map_err(|_| ShareError::BadSignature)?;
+
+        if Utc::now().timestamp() >= expires {
+            return Err(ShareError::Expired);
+        }
+
+        let mut shares = self.shares.lock().await;
+        let Some(record) = shares.iter_mut().find(|share| share.id == id) else {
+            return Err(ShareError::Revoked);
+        };
+        if record.revoked {
+            return Err(ShareError::Revoked);
+        }
+        record.access_count = record.access_count.saturating_add(1);
+        let record = record.clone();
+        self.persist(&shares).await;
+        Ok(record)
+    }
+
+    pub async fn list(&self) -> Vec<ShareRecord> {
+        let mut shares = self.shares.lock().await;
+        Self::prune(&mut shares, Utc::now());
+        shares.clone()
+    }
+
+    pub async fn revoke(&self, id: &str) -> Option<ShareRecord> {
+        let mut shares = self.shares.lock().await;
+        let record = shares.iter_mut().find(|share| share.id == id)?;
+        record.revoked = true;
+        let record = record.clone();
+        self.persist(&shares).await;
+        Some(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_store() -> (tempfile::TempDir, ShareStore) {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let mut config = Config::safe_internal_defaults();
+        config.shared_state_dir = dir.path().to_path_buf();
+        config.share_link_secret = Some("test-secret".to_string());
+        let store = ShareStore::load(&config).expect("store");
+        (dir, store)
+    }
+
+    #[tokio::test]
+    async fn share_tokens_validate_and_reject_tampering_expiry_and_revocation() {
+        let (_dir, store) = test_store();
+        let (record, token) = store
+            .mint("EAS_Recording_1.wav", Duration::hours(1))
+            .await
+            .expect("mint");
+
+        let redeemed = store.redeem(&token).await.expect("redeem");
+        assert_eq!(redeemed.file, "EAS_Recording_1.wav");
+        assert_eq!(redeemed.access_count, 1);
+
+        let forged_file = URL_SAFE_NO_PAD.encode("EAS_Recording_2.wav");
+        let mut parts: Vec<&str> = token.split('.').collect();
+        parts[2] = &forged_file;
+        assert_eq!(
+            store.redeem(&parts.join(".")).await,
+            Err(ShareError::BadSignature)
+        );
+        assert_eq!(store.redeem("garbage").await, Err(ShareError::Malformed));
+
+        let (_, expired) = store
+            .mint("EAS_Recording_1.wav", Duration::seconds(-1))
+            .await
+            .expect("mint");
+        assert_eq!(store.redeem(&expired).await, Err(ShareError::Expired));
+
+        store.revoke(&record.id).await.expect("revoke");
+        assert_eq!(store.redeem(&token).await, Err(ShareError::Revoked));
+    }
+
+    #[test]
+    fn recording_path_rejects_traversal() {
+        let config = Config::safe_internal_defaults();
+        assert!(recording_path(&config, "EAS_Recording_1.wav").is_some());
+        assert!(recording_path(&config, "../config.json").is_none());
+        assert!(recording_path(&config, "2024/06/12/EAS_Recording_1.wav").is_some());
+        assert!(recording_path(&config, "sub/../../config.json").is_none());
+        assert!(recording_path(&config, "/etc/passwd").is_none());
+        assert!(recording_path(&config, "sub//EAS_Recording_1.wav").is_none());
+        assert!(recording_path(&config, "sub/.hidden/EAS_Recording_1.wav").is_none());
+        assert!(recording_path(&config, ".recording_manifest.json").is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn resolved_recording_paths_stay_inside_the_recording_dir() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let mut config = Config::safe_internal_defaults();
+        config.recording_dir = dir.path().join("recordings");
+        std::fs::create_dir_all(&config.recording_dir).expect("recording dir");
+        std::fs::write(config.recording_dir.join("EAS_Recording_1.wav"), b"RIFF").expect("wav");
+        std::fs::write(dir.path().join("config.json"), b"{}").expect("config");
+        std::os::unix::fs::symlink(
+            dir.path().join("config.json"),
+            config.recording_dir.join("EAS_Recording_2.wav"),
+        )
+        .expect("symlink");
+
+        let resolved = resolve_recording_path(&config, "EAS_Recording_1.wav")
+            .await
+            .expect("resolve");
+        assert!(resolved.is_some_and(|path| path.ends_with("EAS_Recording_1.wav")));
+        assert_eq!(
+            resolve_recording_path(&config, "EAS_Recording_2.wav")
+                .await
+                .expect("resolve"),
+            None
+        );
+        assert_eq!(
+            resolve_recording_path(&config, "../config.json")
+                .await
+                .expect("resolve"),
+            None
+        );
+        assert!(resolve_recording_path(&config, "EAS_Recording_3.wav")
+            .await
+            .is_err());
+    }
+}
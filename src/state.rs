@@ -1,9 +1,14 @@
 use crate::config::CapEndpoint;
 use crate::e2t_ng::ParsedEasSerialized;
-use crate::filter::{self, FilterRule};
+use crate::filter::FilterHandle;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::time::Duration;
+use tracing::warn;
+
+pub const DEFAULT_MAX_ACTIVE_ALERTS: usize = 100;
+pub const DEFAULT_RECENT_ALERTS_LIMIT: usize = 100;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct EasAlertData {
@@ -19,6 +24,16 @@ pub struct EasAlertData {
     pub parsed_header: Option<ParsedEasSerialized>,
 }
 
+impl EasAlertData {
+    pub fn originator_code(&self) -> &str {
+        self.parsed_header
+            .as_ref()
+            .map_or(self.originator.as_str(), |header| {
+                header.originator.as_str()
+            })
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum AlertRecordingState {
@@ -34,6 +49,13 @@ impl Default for AlertRecordingState {
     }
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AlsoHeard {
+    pub stream_url: String,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub heard_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[allow(dead_code)]
 pub struct ActiveAlert {
@@ -50,6 +72,25 @@ pub struct ActiveAlert {
     pub recording_file_name: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub source_stream_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub also_heard_on: Vec<AlsoHeard>,
+    #[serde(default)]
+    pub partially_received: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recording_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub relay_status: Vec<RelayOutcome>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RelayOutcome {
+    pub target: String,
+    pub ok: bool,
+    pub attempts: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub at: DateTime<Utc>,
 }
 
 impl ActiveAlert {
@@ -65,6 +106,10 @@ impl ActiveAlert {
             recording_state: AlertRecordingState::Pending,
             recording_file_name: None,
             source_stream_url: None,
+            also_heard_on: Vec::new(),
+            partially_received: false,
+            recording_url: None,
+            relay_status: Vec::new(),
         }
     }
 
@@ -86,6 +131,37 @@ impl ActiveAlert {
         }
         changed
     }
+
+    pub fn severity_rank(&self) -> u8 {
+        let text = self.data.event_text.to_ascii_lowercase();
+        if text.contains("warning") || text.contains("emergency") {
+            return 3;
+        }
+        if text.contains("watch") {
+            return 2;
+        }
+        if text.contains("test") {
+            return 0;
+        }
+        match self.data.event_code.trim().chars().last() {
+            Some('W' | 'E') => 3,
+            Some('A') => 2,
+            Some('T') => 0,
+            _ => 1,
+        }
+    }
+
+    pub fn heard_on_streams(&self) -> Vec<&str> {
+        self.source_stream_url
+            .iter()
+            .map(String::as_str)
+            .chain(
+                self.also_heard_on
+                    .iter()
+                    .map(|heard| heard.stream_url.as_str()),
+            )
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, Default, Serialize)]
@@ -107,29 +183,125 @@ pub struct CapRuntimeStatus {
     pub alerts_processed: u64,
 }
 
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertDisposition {
+    Relayed,
+    Logged,
+    Forwarded,
+    Ignored,
+    NotWatched,
+    CapDuplicate,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RecentAlert {
+    #[serde(default)]
+    pub id: String,
+    #[serde(flatten)]
+    pub alert: ActiveAlert,
+    pub disposition: AlertDisposition,
+}
+
 pub struct AppState {
     pub active_alerts: Vec<ActiveAlert>,
+    pub recent_alerts: VecDeque<RecentAlert>,
     pub cap_status: CapRuntimeStatus,
-    filters: Vec<FilterRule>,
+    pub alerts_evicted: u64,
+    filters: FilterHandle,
+    max_active_alerts: usize,
+    recent_alerts_limit: usize,
 }
 
 impl AppState {
-    pub fn new(filters: Vec<FilterRule>) -> Self {
-        filter::install_filters(filters.clone());
+    pub fn new(filters: FilterHandle) -> Self {
         Self {
             active_alerts: Vec::new(),
+            recent_alerts: VecDeque::new(),
             cap_status: CapRuntimeStatus::default(),
+            alerts_evicted: 0,
             filters,
+            max_active_alerts: DEFAULT_MAX_ACTIVE_ALERTS,
+            recent_alerts_limit: DEFAULT_RECENT_ALERTS_LIMIT,
         }
     }
 
-    pub fn cloned_filters(&self) -> Vec<FilterRule> {
-        self.filters.clone()
+    pub fn max_active_alerts(&self) -> usize {
+        self.max_active_alerts
+    }
+
+    pub fn set_max_active_alerts(&mut self, max_active_alerts: usize) {
+        self.max_active_alerts = max_active_alerts.max(1);
+    }
+
+    pub fn set_recent_alerts_limit(&mut self, limit: usize) {
+        self.recent_alerts_limit = limit;
+        self.trim_recent_alerts();
     }
 
-    pub fn update_filters(&mut self, filters: Vec<FilterRule>) {
-        filter::install_filters(filters.clone());
-        self.filters = filters;
+    fn trim_recent_alerts(&mut self) {
+        let excess = self
+            .recent_alerts
+            .len()
+            .saturating_sub(self.recent_alerts_limit);
+        self.recent_alerts.drain(..excess);
+    }
+
+    pub fn record_recent_alert(&mut self, alert: ActiveAlert, disposition: AlertDisposition) {
+        self.recent_alerts.push_back(RecentAlert {
+            id: crate::cap_xml::identifier(&alert.raw_header),
+            alert,
+            disposition,
+        });
+        self.trim_recent_alerts();
+    }
+
+    pub fn upsert_active_alert(&mut self, alert: ActiveAlert) -> Vec<ActiveAlert> {
+        let now = Utc::now();
+        let raw_header = alert.raw_header.clone();
+        self.active_alerts
+            .retain(|existing| existing.expires_at > now && existing.raw_header != raw_header);
+        self.active_alerts.push(alert);
+        self.enforce_active_alert_limit(Some(&raw_header))
+    }
+
+    pub fn enforce_active_alert_limit(&mut self, protected: Option<&str>) -> Vec<ActiveAlert> {
+        let limit = self.max_active_alerts;
+        if self.active_alerts.len() <= limit {
+            return Vec::new();
+        }
+
+        let mut candidates: Vec<usize> = (0..self.active_alerts.len())
+            .filter(|&idx| Some(self.active_alerts[idx].raw_header.as_str()) != protected)
+            .collect();
+        candidates.sort_by_key(|&idx| {
+            let alert = &self.active_alerts[idx];
+            (alert.severity_rank(), alert.expires_at, alert.received_at)
+        });
+        candidates.truncate(self.active_alerts.len() - limit);
+        candidates.sort_unstable_by(|a, b| b.cmp(a));
+
+        let evicted: Vec<ActiveAlert> = candidates
+            .into_iter()
+            .map(|idx| self.active_alerts.remove(idx))
+            .collect();
+        self.alerts_evicted = self.alerts_evicted.saturating_add(evicted.len() as u64);
+
+        warn!(
+            "Active alert limit of {} exceeded; evicted {} alert(s): {}",
+            limit,
+            evicted.len(),
+            evicted
+                .iter()
+                .map(|alert| alert.raw_header.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        evicted
+    }
+
+    pub fn filters(&self) -> FilterHandle {
+        self.filters.clone()
     }
 
     pub fn update_alert_recording_metadata(
@@ -138,6 +310,16 @@ impl AppState {
         recording_state: AlertRecordingState,
         recording_file_name: Option<String>,
     ) -> bool {
+        if let Some(recent) = self
+            .recent_alerts
+            .iter_mut()
+            .rev()
+            .find(|recent| recent.alert.raw_header == raw_header)
+        {
+            recent
+                .alert
+                .update_recording_metadata(recording_state.clone(), recording_file_name.clone());
+        }
         let Some(alert) = self
             .active_alerts
             .iter_mut()
@@ -147,11 +329,96 @@ impl AppState {
         };
         alert.update_recording_metadata(recording_state, recording_file_name)
     }
+
+    pub fn update_alert_recording_url(&mut self, raw_header: &str, url: &str) -> bool {
+        let mut changed = false;
+        let active = self
+            .active_alerts
+            .iter_mut()
+            .filter(|alert| alert.raw_header == raw_header);
+        let recent = self
+            .recent_alerts
+            .iter_mut()
+            .rev()
+            .find(|recent| recent.alert.raw_header == raw_header)
+            .map(|recent| &mut recent.alert);
+        for alert in active.chain(recent) {
+            if alert.recording_url.as_deref() != Some(url) {
+                alert.recording_url = Some(url.to_string());
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    pub fn record_relay_outcome(&mut self, raw_header: &str, outcome: &RelayOutcome) -> bool {
+        let active = self
+            .active_alerts
+            .iter_mut()
+            .filter(|alert| alert.raw_header == raw_header);
+        let recent = self
+            .recent_alerts
+            .iter_mut()
+            .rev()
+            .find(|recent| recent.alert.raw_header == raw_header)
+            .map(|recent| &mut recent.alert);
+        let mut changed = false;
+        for alert in active.chain(recent) {
+            alert
+                .relay_status
+                .retain(|existing| existing.target != outcome.target);
+            alert.relay_status.push(outcome.clone());
+            changed = true;
+        }
+        changed
+    }
+
+    pub fn update_alert_description(&mut self, raw_header: &str, description: &str) -> bool {
+        let mut changed = false;
+        let active = self
+            .active_alerts
+            .iter_mut()
+            .filter(|alert| alert.raw_header == raw_header);
+        let recent = self
+            .recent_alerts
+            .iter_mut()
+            .rev()
+            .find(|recent| recent.alert.raw_header == raw_header)
+            .map(|recent| &mut recent.alert);
+        for alert in active.chain(recent) {
+            if alert.data.description.is_none() {
+                alert.data.description = Some(description.to_string());
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    pub fn update_alert_reception(
+        &mut self,
+        raw_header: &str,
+        also_heard_on: Vec<AlsoHeard>,
+        partially_received: bool,
+    ) -> bool {
+        let Some(alert) = self
+            .active_alerts
+            .iter_mut()
+            .find(|alert| alert.raw_header == raw_header)
+        else {
+            return false;
+        };
+        let changed =
+            alert.also_heard_on != also_heard_on || alert.partially_received != partially_received;
+        alert.also_heard_on = also_heard_on;
+        alert.partially_received = partially_received;
+        changed
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::filter::{self, Filters};
     use serde_json::json;
 
     fn sample_data() -> EasAlertData {
@@ -178,31 +445,39 @@ mod tests {
     }
 
     #[test]
-    fn app_state_update_filters_refreshes_global_filters() {
-        let initial_filters = filter::parse_filters(&json!({
+    fn app_state_hands_out_the_filter_handle_reloads_replace() {
+        let filters = |config: serde_json::Value| Filters {
+            global: filter::parse_filters(&config),
+            ..Filters::default()
+        };
+        let handle = FilterHandle::new(filters(json!({
             "FILTERS": [
                 { "name": "Initial", "event_codes": ["*"], "action": "relay" }
             ]
-        }));
-        let mut state = AppState::new(initial_filters);
-        assert_eq!(filter::determine_filter_name("TOR"), "Initial");
+        })));
+        let state = AppState::new(handle.clone());
+        assert_eq!(
+            state
+                .filters()
+                .snapshot()
+                .rule_name("", "TOR", "WXR", &[], None),
+            "Initial"
+        );
 
-        let updated = filter::parse_filters(&json!({
+        handle.replace(filters(json!({
             "FILTERS": [
                 { "name": "Block TOR", "event_codes": ["TOR"], "action": "ignore" },
                 { "name": "Fallback", "event_codes": ["*"], "action": "relay" }
             ]
-        }));
-        state.update_filters(updated.clone());
-
-        let cloned = state.cloned_filters();
-        assert_eq!(cloned.len(), updated.len());
-        assert_eq!(filter::determine_filter_name("TOR"), "Block TOR");
+        })));
+        let snapshot = state.filters().snapshot();
+        assert_eq!(snapshot.global.len(), 2);
+        assert_eq!(snapshot.rule_name("", "TOR", "WXR", &[], None), "Block TOR");
     }
 
     #[test]
     fn app_state_updates_alert_recording_metadata() {
-        let mut state = AppState::new(Vec::new());
+        let mut state = AppState::new(FilterHandle::new(Filters::default()));
         let alert = ActiveAlert::new(
             sample_data(),
             "ZCZC-WXR-TOR-031055+0030-1231645-KWO35-".to_string(),
@@ -225,4 +500,103 @@ mod tests {
             Some("EAS_Recording_foo.wav")
         );
     }
+
+    #[test]
+    fn active_alert_limit_evicts_lowest_severity_first() {
+        let mut state = AppState::new(FilterHandle::new(Filters::default()));
+        state.set_max_active_alerts(2);
+
+        let mut warning = sample_data();
+        warning.event_text = "Tornado Warning".to_string();
+        let mut advisory = sample_data();
+        advisory.event_code = "FLS".to_string();
+        advisory.event_text = "Flood Statement".to_string();
+        let mut watch = sample_data();
+        watch.event_code = "TOA".to_string();
+        watch.event_text = "Tornado Watch".to_string();
+
+        let warning_header = "ZCZC-WXR-TOR-031055+0030-1231645-KWO35-";
+        let advisory_header = "ZCZC-WXR-FLS-031055+0600-1231645-KWO35-";
+        let watch_header = "ZCZC-WXR-TOA-031055+0400-1231645-KWO35-";
+
+        assert!(state
+            .upsert_active_alert(ActiveAlert::new(
+                warning.clone(),
+                warning_header.to_string(),
+                Duration::from_secs(60),
+            ))
+            .is_empty());
+        assert!(state
+            .upsert_active_alert(ActiveAlert::new(
+                advisory,
+                advisory_header.to_string(),
+                Duration::from_secs(3600),
+            ))
+            .is_empty());
+        assert!(state
+            .upsert_active_alert(ActiveAlert::new(
+                warning,
+                warning_header.to_string(),
+                Duration::from_secs(60),
+            ))
+            .is_empty());
+
+        let evicted = state.upsert_active_alert(ActiveAlert::new(
+            watch,
+            watch_header.to_string(),
+            Duration::from_secs(120),
+        ));
+        assert_eq!(evicted.len(), 1);
+        assert_eq!(evicted[0].raw_header, advisory_header);
+        assert_eq!(state.alerts_evicted, 1);
+        let remaining: Vec<&str> = state
+            .active_alerts
+            .iter()
+            .map(|alert| alert.raw_header.as_str())
+            .collect();
+        assert_eq!(remaining, vec![warning_header, watch_header]);
+    }
+
+    #[test]
+    fn recent_alerts_are_bounded_and_outlive_expiry() {
+        let mut state = AppState::new(FilterHandle::new(Filters::default()));
+        state.set_recent_alerts_limit(2);
+        let header = |n: u32| format!("ZCZC-WXR-TOR-031055+0030-123164{n}-KWO35-");
+        let expired = ActiveAlert::new(sample_data(), header(0), Duration::ZERO);
+        state.upsert_active_alert(expired.clone());
+        state.record_recent_alert(expired, AlertDisposition::Relayed);
+        state.record_recent_alert(
+            ActiveAlert::new(sample_data(), header(1), Duration::from_secs(60)),
+            AlertDisposition::Ignored,
+        );
+        state.record_recent_alert(
+            ActiveAlert::new(sample_data(), header(2), Duration::from_secs(60)),
+            AlertDisposition::NotWatched,
+        );
+
+        state.upsert_active_alert(ActiveAlert::new(
+            sample_data(),
+            header(3),
+            Duration::from_secs(60),
+        ));
+        assert_eq!(state.active_alerts.len(), 1);
+        let headers: Vec<&str> = state
+            .recent_alerts
+            .iter()
+            .map(|recent| recent.alert.raw_header.as_str())
+            .collect();
+        assert_eq!(headers, vec![header(1), header(2)]);
+
+        let value = serde_json::to_value(&state.recent_alerts[1]).expect("serialize");
+        assert_eq!(value["disposition"], "not_watched");
+        assert_eq!(value["id"], crate::cap_xml::identifier(&header(2)));
+        assert_eq!(value["raw_header"], header(2));
+        assert_eq!(value["data"]["event_code"], "TOR");
+        let round_trip: RecentAlert = serde_json::from_value(value).expect("deserialize");
+        assert_eq!(round_trip.disposition, AlertDisposition::NotWatched);
+        assert_eq!(round_trip.alert.raw_header, header(2));
+
+        state.set_recent_alerts_limit(0);
+        assert!(state.recent_alerts.is_empty());
+    }
 }
@@ -0,0 +1,314 @@
+use crate::config::Config;
+use crate::monitoring::{MonitoringEvent, MonitoringHub, StreamStatusPayload};
+use crate::webhook;
+use anyhow::Result;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, Receiver as BroadcastReceiver};
+use tracing::{info, warn};
+
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+const DOWN_COLOR: u32 = 0xFF0000;
+const RECOVERED_COLOR: u32 = 0x2ECC71;
+
+#[derive(Debug, Clone, PartialEq)]
+enum HealthNotice {
+    Down {
+        stream_url: String,
+        outage: ChronoDuration,
+        connected: bool,
+        last_error: Option<String>,
+    },
+    Recovered {
+        stream_url: String,
+        outage: ChronoDuration,
+    },
+}
+
+#[derive(Debug, Default)]
+struct StreamHealth {
+    outage_started: Option<DateTime<Utc>>,
+    down_notified: bool,
+    last_notified: Option<DateTime<Utc>>,
+}
+
+struct HealthSettings<'a> {
+    threshold: ChronoDuration,
+    cooldown: ChronoDuration,
+    excluded: &'a [String],
+}
+
+impl<'a> HealthSettings<'a> {
+    fn from_config(config: &'a Config) -> Self {
+        Self {
+            threshold: ChronoDuration::seconds(config.stream_health_threshold_secs as i64),
+            cooldown: ChronoDuration::seconds(config.stream_health_cooldown_secs as i64),
+            excluded: &config.stream_health_excluded_streams,
+        }
+    }
+}
+
+#[derive(Default)]
+struct HealthTracker {
+    streams: HashMap<String, StreamHealth>,
+}
+
+impl HealthTracker {
+    fn evaluate(
+        &mut self,
+        snapshots: &[StreamStatusPayload],
+        settings: &HealthSettings,
+        now: DateTime<Utc>,
+    ) -> Vec<HealthNotice> {
+        self.streams.retain(|url, _| {
+            !settings.excluded.contains(url)
+                && snapshots
+                    .iter()
+                    .any(|snapshot| &snapshot.stream_url == url && !snapshot.is_removed)
+        });
+
+        let mut notices = Vec::new();
+        for snapshot in snapshots {
+            if snapshot.is_removed || settings.excluded.contains(&snapshot.stream_url) {
+                continue;
+            }
+            let health = self.streams.entry(snapshot.stream_url.clone()).or_default();
+
+            if snapshot.is_connected && snapshot.is_receiving_audio {
+                if let Some(started) = health.outage_started.take() {
+                    if health.down_notified {
+                        notices.push(HealthNotice::Recovered {
+                            stream_url: snapshot.stream_url.clone(),
+                            outage: now - started,
+                        });
+                        health.last_notified = Some(now);
+                    }
+                }
+                health.down_notified = false;
+                continue;
+            }
+
+            let started = *health.outage_started.get_or_insert_with(|| {
+                let since = if snapshot.is_connected {
+                    snapshot.last_activity
+                } else {
+                    snapshot.last_disconnect
+                };
+                since.unwrap_or(now).min(now)
+            });
+            let cooled_down = health
+                .last_notified
+                .is_none_or(|last| now - last >= settings.cooldown);
+            if !health.down_notified && now - started >= settings.threshold && cooled_down {
+                notices.push(HealthNotice::Down {
+                    stream_url: snapshot.stream_url.clone(),
+                    outage: now - started,
+                    connected: snapshot.is_connected,
+                    last_error: snapshot.last_error.clone(),
+                });
+                health.down_notified = true;
+                health.last_notified = Some(now);
+            }
+        }
+        notices
+    }
+}
+
+fn format_outage(outage: ChronoDuration) -> String {
+    let total = outage.num_seconds().max(0);
+    let (hours, minutes, seconds) = (total / 3600, (total % 3600) / 60, total % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+fn monitor_label(config: &Config, stream_url: &str) -> String {
+    match config
+        .streams
+        .iter()
+        .position(|stream| stream.id == stream_url)
+    {
+        Some(index) => format!("Monitor #{}", index + 1),
+        None => "Monitor".to_string(),
+    }
+}
+
+fn render_notice(config: &Config, notice: &HealthNotice) -> (String, String, u32) {
+    match notice {
+        HealthNotice::Down {
+            stream_url,
+            outage,
+            connected,
+            last_error,
+        } => {
+            let label = monitor_label(config, stream_url);
+            let state = if *connected {
+                "connected but silent"
+            } else {
+                "disconnected"
+            };
+            let mut body = format!(
+                "{label} ({stream_url}) has been {state} for {}.",
+                format_outage(*outage)
+            );
+            if let Some(error) = last_error.as_deref().filter(|error| !error.is_empty()) {
+                body.push_str(&format!("\nLast error: {error}"));
+            }
+            (format!("{label} is down"), body, DOWN_COLOR)
+        }
+        HealthNotice::Recovered { stream_url, outage } => {
+            let label = monitor_label(config, stream_url);
+            (
+                format!("{label} has recovered"),
+                format!(
+                    "{label} ({stream_url}) is receiving audio again after an outage of {}.",
+                    format_outage(*outage)
+                ),
+                RECOVERED_COLOR,
+            )
+        }
+    }
+}
+
+pub async fn run_stream_health_watcher(
+    mut config: Config,
+    monitoring: MonitoringHub,
+    mut reload_rx: BroadcastReceiver<Config>,
+) -> Result<()> {
+    let mut events = monitoring.subscribe();
+    let mut ticker = tokio::time::interval(HEALTH_CHECK_INTERVAL);
+    let mut tracker = HealthTracker::default();
+
+    crate::reload::register("stream_health");
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            event = events.recv() => match event {
+                Ok(MonitoringEvent::Stream(_)) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => monitoring.note_lagged(skipped),
+                Ok(_) => continue,
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+            reload = reload_rx.recv() => match reload {
+                Ok(new_config) => {
+                    config = new_config;
+                    crate::reload::acknowledge("stream_health");
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+        }
+
+        if !config.stream_health_notifications {
+            tracker.streams.clear();
+            continue;
+        }
+
+        let settings = HealthSettings::from_config(&config);
+        for notice in tracker.evaluate(&monitoring.stream_snapshots(), &settings, Utc::now()) {
+            let (title, body, color) = render_notice(&config, &notice);
+            match notice {
+                HealthNotice::Down { .. } => warn!("{}: {}", title, body),
+                HealthNotice::Recovered { .. } => info!("{}: {}", title, body),
+            }
+            tokio::spawn(async move {
+                webhook::send_operational_notification(&title, &body, color).await;
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StreamConfig;
+
+    fn snapshot(url: &str, connected: bool, receiving: bool) -> StreamStatusPayload {
+        StreamStatusPayload {
+            stream_url: url.to_string(),
+            is_removed: false,
+            is_connected: connected,
+            is_receiving_audio: receiving,
+            connection_attempts: 1,
+            alerts_received: 0,
+            connected_since: None,
+            last_activity: None,
+            last_disconnect: None,
+            last_alert_received_ts: None,
+            last_alert_received: None,
+            last_error: (!connected).then(|| "connection refused".to_string()),
+            uptime_seconds: None,
+            is_paused: false,
+            paused_until: None,
+            availability_percent_24h: None,
+        }
+    }
+
+    #[test]
+    fn tracker_notifies_down_and_recovered_with_cooldown() {
+        let excluded = vec!["http://excluded/stream".to_string()];
+        let settings = HealthSettings {
+            threshold: ChronoDuration::seconds(60),
+            cooldown: ChronoDuration::seconds(600),
+            excluded: &excluded,
+        };
+        let start = Utc::now();
+        let at = |secs: i64| start + ChronoDuration::seconds(secs);
+        let mut tracker = HealthTracker::default();
+        let down = [
+            snapshot("http://a/stream", false, false),
+            snapshot("http://excluded/stream", false, false),
+        ];
+        let up = [snapshot("http://a/stream", true, true)];
+
+        assert!(tracker.evaluate(&down, &settings, at(0)).is_empty());
+        assert!(tracker.evaluate(&down, &settings, at(30)).is_empty());
+        assert_eq!(
+            tracker.evaluate(&down, &settings, at(90)),
+            vec![HealthNotice::Down {
+                stream_url: "http://a/stream".to_string(),
+                outage: ChronoDuration::seconds(90),
+                connected: false,
+                last_error: Some("connection refused".to_string()),
+            }]
+        );
+        assert!(tracker.evaluate(&down, &settings, at(120)).is_empty());
+        assert_eq!(
+            tracker.evaluate(&up, &settings, at(150)),
+            vec![HealthNotice::Recovered {
+                stream_url: "http://a/stream".to_string(),
+                outage: ChronoDuration::seconds(150),
+            }]
+        );
+
+        assert!(tracker.evaluate(&down, &settings, at(160)).is_empty());
+        assert!(tracker.evaluate(&down, &settings, at(700)).is_empty());
+        assert_eq!(tracker.evaluate(&down, &settings, at(760)).len(), 1);
+    }
+
+    #[test]
+    fn notices_render_monitor_number_outage_and_last_error() {
+        let mut config = Config::safe_internal_defaults();
+        config.streams = vec![StreamConfig::from_url("http://a/stream")];
+        let (title, body, _) = render_notice(
+            &config,
+            &HealthNotice::Down {
+                stream_url: "http://a/stream".to_string(),
+                outage: ChronoDuration::seconds(312),
+                connected: false,
+                last_error: Some("connection refused".to_string()),
+            },
+        );
+        assert_eq!(title, "Monitor #1 is down");
+        assert_eq!(
+            body,
+            "Monitor #1 (http://a/stream) has been disconnected for 5m 12s.\nLast error: connection refused"
+        );
+        assert_eq!(format_outage(ChronoDuration::seconds(3725)), "1h 2m");
+    }
+}
@@ -0,0 +1,248 @@
+use crate::config::Config;
+use crate::lifecycle::ShutdownKind;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::task::{JoinError, JoinHandle, JoinSet};
+use tokio::time::Instant;
+use tracing::{error, info, warn};
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+const STABLE_RUN: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TaskStatus {
+    pub restartable: bool,
+    pub running: bool,
+    pub restarts: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_exit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_exit_at: Option<DateTime<Utc>>,
+}
+
+static TASKS: Lazy<Mutex<BTreeMap<&'static str, TaskStatus>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+pub fn task_statuses() -> BTreeMap<&'static str, TaskStatus> {
+    TASKS.lock().clone()
+}
+
+type Spawn = Box<dyn FnMut(&Config) -> JoinHandle<Result<()>> + Send>;
+
+struct Task {
+    name: &'static str,
+    spawn: Option<Spawn>,
+    started_at: Instant,
+    failures: u32,
+}
+
+enum Event {
+    Exited(usize, Result<Result<()>, JoinError>),
+    RestartDue(usize),
+}
+
+#[derive(Default)]
+pub struct Supervisor {
+    tasks: Vec<Task>,
+    events: JoinSet<Event>,
+}
+
+fn watch(events: &mut JoinSet<Event>, index: usize, handle: JoinHandle<Result<()>>) {
+    events.spawn(async move { Event::Exited(index, handle.await) });
+}
+
+fn describe_exit(outcome: &Result<Result<()>, JoinError>) -> String {
+    match outcome {
+        Ok(Ok(())) => "returned".to_string(),
+        Ok(Err(err)) => format!("failed: {err:#}"),
+        Err(err) if err.is_panic() => "panicked".to_string(),
+        Err(_) => "was cancelled".to_string(),
+    }
+}
+
+fn backoff(failures: u32) -> Duration {
+    INITIAL_BACKOFF
+        .saturating_mul(2u32.saturating_pow(failures.saturating_sub(1)))
+        .min(MAX_BACKOFF)
+}
+
+impl Supervisor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn add(&mut self, name: &'static str, spawn: Option<Spawn>, handle: JoinHandle<Result<()>>) {
+        let index = self.tasks.len();
+        TASKS.lock().insert(
+            name,
+            TaskStatus {
+                restartable: spawn.is_some(),
+                running: true,
+                restarts: 0,
+                last_exit: None,
+                last_exit_at: None,
+            },
+        );
+        self.tasks.push(Task {
+            name,
+            spawn,
+            started_at: Instant::now(),
+            failures: 0,
+        });
+        watch(&mut self.events, index, handle);
+    }
+
+    pub fn essential(&mut self, name: &'static str, handle: JoinHandle<Result<()>>) {
+        self.add(name, None, handle);
+    }
+
+    pub fn restartable<F>(&mut self, name: &'static str, config: &Config, mut spawn: F)
+    where
+        F: FnMut(&Config) -> JoinHandle<Result<()>> + Send + 'static,
+    {
+        let handle = spawn(config);
+        self.add(name, Some(Box::new(spawn)), handle);
+    }
+
+    pub async fn run(
+        mut self,
+        mut config: Config,
+        mut reload_rx: broadcast::Receiver<Config>,
+        shutdown: impl Future<Output = &'static str>,
+    ) -> (ShutdownKind, String) {
+        tokio::pin!(shutdown);
+        let mut reload_enabled = true;
+        loop {
+            let event = tokio::select! {
+                Some(Ok(event)) = self.events.join_next() => event,
+                reload = reload_rx.recv(), if reload_enabled => {
+                    match reload {
+                        Ok(new_config) => config = new_config,
+                        Err(broadcast::error::RecvError::Lagged(_)) => {}
+                        Err(broadcast::error::RecvError::Closed) => reload_enabled = false,
+                    }
+                    continue;
+                }
+                signal = &mut shutdown => {
+                    return (ShutdownKind::Signal, format!("Shutdown requested by {signal}"));
+                }
+            };
+
+            match event {
+                Event::Exited(index, outcome) => {
+                    let task = &mut self.tasks[index];
+                    let exit = describe_exit(&outcome);
+                    if let Some(status) = TASKS.lock().get_mut(task.name) {
+                        status.running = false;
+                        status.last_exit = Some(exit.clone());
+                        status.last_exit_at = Some(Utc::now());
+                    }
+                    if task.spawn.is_none() {
+                        error!("{} task {}; it cannot be restarted", task.name, exit);
+                        return (
+                            ShutdownKind::TaskExited,
+                            format!("{} task {}", task.name, exit),
+                        );
+                    }
+                    if task.started_at.elapsed() >= STABLE_RUN {
+                        task.failures = 0;
+                    }
+                    task.failures += 1;
+                    let delay = backoff(task.failures);
+                    warn!(
+                        "{} task {}; restarting it in {} second(s)",
+                        task.name,
+                        exit,
+                        delay.as_secs()
+                    );
+                    self.events.spawn(async move {
+                        tokio::time::sleep(delay).await;
+                        Event::RestartDue(index)
+                    });
+                }
+                Event::RestartDue(index) => {
+                    let task = &mut self.tasks[index];
+                    let Some(spawn) = task.spawn.as_mut() else {
+                        continue;
+                    };
+                    let handle = spawn(&config);
+                    task.started_at = Instant::now();
+                    if let Some(status) = TASKS.lock().get_mut(task.name) {
+                        status.running = true;
+                        status.restarts += 1;
+                    }
+                    info!("Restarted the {} task", task.name);
+                    watch(&mut self.events, index, handle);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn backoff_doubles_up_to_a_minute() {
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(2), Duration::from_secs(2));
+        assert_eq!(backoff(4), Duration::from_secs(8));
+        assert_eq!(backoff(30), MAX_BACKOFF);
+    }
+
+    #[tokio::test]
+    async fn panicking_tasks_are_restarted_and_essential_ones_end_the_run() {
+        let config = Config::safe_internal_defaults();
+        let (reload_tx, _) = broadcast::channel::<Config>(4);
+        let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+        let starts = Arc::new(AtomicU32::new(0));
+
+        let mut supervisor = Supervisor::new();
+        let counted = Arc::clone(&starts);
+        supervisor.restartable("test-flaky", &config, move |_| {
+            let first = counted.fetch_add(1, Ordering::SeqCst) == 0;
+            tokio::spawn(async move {
+                if first {
+                    panic!("flaky task panics on its first run");
+                }
+                std::future::pending::<Result<()>>().await
+            })
+        });
+        supervisor.essential(
+            "test-essential",
+            tokio::spawn(async move {
+                let _ = stop_rx.await;
+                Ok(())
+            }),
+        );
+        let run =
+            tokio::spawn(supervisor.run(config, reload_tx.subscribe(), std::future::pending()));
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while task_statuses()["test-flaky"].restarts == 0 {
+            assert!(Instant::now() < deadline, "flaky task was not restarted");
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let flaky = &task_statuses()["test-flaky"];
+        assert!(flaky.running && flaky.restartable);
+        assert_eq!(flaky.last_exit.as_deref(), Some("panicked"));
+        assert_eq!(starts.load(Ordering::SeqCst), 2);
+
+        stop_tx.send(()).expect("stop");
+        let (kind, reason) = run.await.expect("run");
+        assert_eq!(kind, ShutdownKind::TaskExited);
+        assert_eq!(reason, "test-essential task returned");
+        assert!(!task_statuses()["test-essential"].running);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::lifecycle::{
+    self, ALERT_MANAGER_HEARTBEAT, AUDIO_PROCESSOR_HEARTBEAT, HEARTBEAT_INTERVAL,
+};
+use crate::monitoring::{MonitoringHub, StreamStatusPayload};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sd_notify::NotifyState;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+const STATUS_INTERVAL: Duration = Duration::from_secs(30);
+const READY_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+pub fn enabled() -> bool {
+    std::env::var_os("NOTIFY_SOCKET").is_some()
+}
+
+fn notify(state: NotifyState<'_>) {
+    if let Err(err) = sd_notify::notify(false, &[state]) {
+        warn!("Failed to notify systemd: {}", err);
+    }
+}
+
+pub fn stopping() {
+    notify(NotifyState::Stopping);
+}
+
+fn status_line(streams: &[StreamStatusPayload]) -> String {
+    let streams: Vec<_> = streams.iter().filter(|stream| !stream.is_removed).collect();
+    let connected = streams.iter().filter(|stream| stream.is_connected).count();
+    let receiving = streams
+        .iter()
+        .filter(|stream| stream.is_receiving_audio)
+        .count();
+    let paused = streams.iter().filter(|stream| stream.is_paused).count();
+    let mut line = format!(
+        "{connected}/{} streams connected, {receiving} receiving audio",
+        streams.len()
+    );
+    if paused > 0 {
+        line.push_str(&format!(", {paused} paused"));
+    }
+    line
+}
+
+fn core_tasks_alive(now: DateTime<Utc>, watchdog_timeout: Duration) -> bool {
+    let max_age = watchdog_timeout.max(HEARTBEAT_INTERVAL * 3);
+    AUDIO_PROCESSOR_HEARTBEAT.fresh_at(now, max_age)
+        && ALERT_MANAGER_HEARTBEAT.fresh_at(now, max_age)
+}
+
+pub async fn run_notifier(monitoring: MonitoringHub, wait_for_api: bool) -> Result<()> {
+    while wait_for_api && !lifecycle::api_listening() {
+        tokio::time::sleep(READY_POLL_INTERVAL).await;
+    }
+    notify(NotifyState::Ready);
+    info!("Notified systemd that startup is complete.");
+
+    let mut watchdog_usec = 0;
+    let watchdog = sd_notify::watchdog_enabled(false, &mut watchdog_usec)
+        .then(|| Duration::from_micros(watchdog_usec));
+    let tick = watchdog.map_or(STATUS_INTERVAL, |timeout| {
+        (timeout / 2).min(STATUS_INTERVAL)
+    });
+    let mut interval = tokio::time::interval(tick);
+    let mut last_status: Option<Instant> = None;
+    let mut starved = false;
+    loop {
+        interval.tick().await;
+        if let Some(timeout) = watchdog {
+            if core_tasks_alive(Utc::now(), timeout) {
+                notify(NotifyState::Watchdog);
+                if starved {
+                    info!("Audio processor and alert manager are beating again; resuming systemd watchdog pings.");
+                    starved = false;
+                }
+            } else if !starved {
+                warn!("Audio processor or alert manager has stopped beating; withholding systemd watchdog pings.");
+                starved = true;
+            }
+        }
+        if last_status.is_none_or(|at| at.elapsed() >= STATUS_INTERVAL) {
+            notify(NotifyState::Status(&status_line(
+                &monitoring.stream_snapshots(),
+            )));
+            last_status = Some(Instant::now());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(connected: bool, receiving: bool, paused: bool) -> StreamStatusPayload {
+        StreamStatusPayload {
+            stream_url: "https://example.com/stream".to_string(),
+            is_removed: false,
+            is_connected: connected,
+            is_receiving_audio: receiving,
+            connection_attempts: 1,
+            alerts_received: 0,
+            connected_since: None,
+            last_activity: None,
+            last_disconnect: None,
+            last_alert_received_ts: None,
+            last_alert_received: None,
+            last_error: None,
+            uptime_seconds: None,
+            is_paused: paused,
+            paused_until: None,
+            availability_percent_24h: None,
+        }
+    }
+
+    #[test]
+    fn status_line_summarizes_stream_health() {
+        assert_eq!(status_line(&[]), "0/0 streams connected, 0 receiving audio");
+        let streams = [
+            stream(true, true, false),
+            stream(true, false, false),
+            stream(false, false, true),
+        ];
+        assert_eq!(
+            status_line(&streams),
+            "2/3 streams connected, 1 receiving audio, 1 paused"
+        );
+    }
+}
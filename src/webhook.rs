@@ -1,17 +1,27 @@
-use crate::filter;
-use crate::state::ActiveAlert;
+use crate::email::{self, SmtpConfig};
+use crate::file_stream;
+use crate::filter::FilterHandle;
+use crate::generic_webhook::{self, GenericWebhook};
+use crate::header;
+use crate::state::{ActiveAlert, EasAlertData};
+use crate::webhook_capabilities;
 use crate::Config;
 use chrono::Local;
+use futures_util::future::join_all;
 use lazy_static::lazy_static;
+use reqwest::header::HeaderMap;
 use reqwest::{multipart, Client};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::collections::HashMap;
 use std::fs;
 use std::io::Read;
 use std::path::{Path, PathBuf};
-use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
 use tokio::process::Command;
+use tokio::time::Instant;
 use tracing::{info, warn};
 
 #[derive(Debug, Deserialize)]
@@ -26,7 +36,22 @@ struct SameUsLookup {
 struct WebhookRuntimeConfig {
     apprise_config_path: String,
     station_name: String,
+    station_id: String,
     stream_index_map: HashMap<String, usize>,
+    show_heard_on: bool,
+    generic_webhooks: Vec<GenericWebhook>,
+    shared_state_dir: PathBuf,
+    local_deeplink_host: String,
+    web_server_port: String,
+    use_reverse_proxy: bool,
+    reverse_proxy_url: String,
+    discord_attachment_max_bytes: u64,
+    discord_max_retries: u32,
+    operational_apprise_config_path: String,
+    apprise_attachment_support: Vec<(String, bool)>,
+    discord_mentions: Vec<(String, String)>,
+    apprise_api_url: Option<String>,
+    smtp: Option<SmtpConfig>,
 }
 
 impl WebhookRuntimeConfig {
@@ -34,30 +59,103 @@ impl WebhookRuntimeConfig {
         Self {
             apprise_config_path: config.apprise_config_path.clone(),
             station_name: config.eas_relay_name.clone(),
+            station_id: config.same_station_id.clone(),
             stream_index_map: config
-                .icecast_stream_urls
+                .streams
                 .iter()
                 .enumerate()
-                .map(|(idx, url)| (url.clone(), idx + 1))
+                .map(|(idx, stream)| (stream.id.clone(), idx + 1))
                 .collect(),
+            show_heard_on: config.webhook_show_heard_on,
+            generic_webhooks: config.generic_webhooks.clone(),
+            shared_state_dir: config.shared_state_dir.clone(),
+            local_deeplink_host: config.local_deeplink_host.clone(),
+            web_server_port: config.web_server_port.clone(),
+            use_reverse_proxy: config.use_reverse_proxy,
+            reverse_proxy_url: config.reverse_proxy_url.clone(),
+            discord_attachment_max_bytes: config.discord_attachment_max_bytes,
+            discord_max_retries: config.discord_max_retries,
+            operational_apprise_config_path: config
+                .operational_apprise_config_path
+                .clone()
+                .unwrap_or_else(|| config.apprise_config_path.clone()),
+            apprise_attachment_support: config.apprise_attachment_support.clone(),
+            discord_mentions: config.discord_mentions.clone(),
+            apprise_api_url: config.apprise_api_url.clone(),
+            smtp: config.smtp.clone(),
         }
     }
 
-    fn from_disk_or_default() -> Self {
-        let config = Config::from_config_json("/app/config.json").unwrap_or_else(|err| {
-            eprintln!(
-                "Warning: failed to load /app/config.json for webhook config: {:?}. Using built-in safe defaults.",
-                err
-            );
-            Config::safe_internal_defaults()
-        });
-        Self::from_config(&config)
+    fn dashboard_base_url(&self) -> Option<String> {
+        if self.use_reverse_proxy {
+            let proxy = self.reverse_proxy_url.trim().trim_end_matches('/');
+            if proxy.is_empty() {
+                return None;
+            }
+            return Some(if proxy.contains("://") {
+                proxy.to_string()
+            } else {
+                format!("http://{proxy}")
+            });
+        }
+
+        let configured = self.local_deeplink_host.trim();
+        let host = if configured.is_empty() || configured.eq_ignore_ascii_case("auto") {
+            let learned = fs::read_to_string(
+                self.shared_state_dir
+                    .join(crate::backend::DEEPLINK_HOST_CACHE_FILE),
+            )
+            .ok()?;
+            let learned = learned.trim();
+            let host = match learned.rsplit_once(':') {
+                Some((host, port)) if !host.ends_with(']') && port.parse::<u16>().is_ok() => host,
+                _ => learned,
+            };
+            if host.is_empty() {
+                return None;
+            }
+            format!("{}:{}", host, self.web_server_port)
+        } else if configured.contains(':') {
+            configured.to_string()
+        } else {
+            format!("{}:{}", configured, self.web_server_port)
+        };
+
+        Some(format!("http://{host}"))
+    }
+
+    fn recording_deeplink(&self, recording_name: &str) -> Option<String> {
+        let base = self
+            .dashboard_base_url()
+            .filter(|base| is_reachable_base_url(base))?;
+        let mut url = reqwest::Url::parse(&format!("{base}/archive.php")).ok()?;
+        url.query_pairs_mut()
+            .append_pair("recording_name", recording_name);
+        Some(url.to_string())
+    }
+}
+
+fn is_reachable_base_url(base: &str) -> bool {
+    let Some(url) = reqwest::Url::parse(base).ok() else {
+        return false;
+    };
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    match host.parse::<std::net::IpAddr>() {
+        Ok(ip) => !ip.is_loopback() && !ip.is_unspecified(),
+        Err(_) => {
+            let host = host.to_ascii_lowercase();
+            host != "localhost" && !host.ends_with(".localhost")
+        }
     }
 }
 
 lazy_static! {
-    static ref WEBHOOK_RUNTIME_CONFIG: RwLock<WebhookRuntimeConfig> =
-        RwLock::new(WebhookRuntimeConfig::from_disk_or_default());
+    static ref WEBHOOK_RUNTIME_CONFIG: RwLock<WebhookRuntimeConfig> = RwLock::new(
+        WebhookRuntimeConfig::from_config(&Config::safe_internal_defaults())
+    );
     static ref github_url: String =
         "https://github.com/wagwan-piffting-blud/EAS_Listener".to_string();
     static ref same_us_lookup: SameUsLookup =
@@ -71,6 +169,74 @@ fn runtime_config_snapshot() -> WebhookRuntimeConfig {
         .clone()
 }
 
+fn heard_on_summary(runtime_config: &WebhookRuntimeConfig, alert: &ActiveAlert) -> Option<String> {
+    if !runtime_config.show_heard_on || alert.also_heard_on.is_empty() {
+        return None;
+    }
+
+    let mut monitors: Vec<usize> = alert
+        .heard_on_streams()
+        .into_iter()
+        .filter_map(|url| runtime_config.stream_index_map.get(url).copied())
+        .collect();
+    monitors.sort_unstable();
+    monitors.dedup();
+    if monitors.len() < 2 {
+        return None;
+    }
+
+    Some(
+        monitors
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", "),
+    )
+}
+
+fn generic_webhook_values(
+    runtime_config: &WebhookRuntimeConfig,
+    stream_id: &str,
+    alert: &ActiveAlert,
+    recording_path: Option<&Path>,
+    matched_filter: &str,
+) -> HashMap<&'static str, String> {
+    let data = &alert.data;
+    let recording_name = alert.recording_file_name.clone().or_else(|| {
+        recording_path
+            .and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().to_string())
+    });
+    let monitor = runtime_config
+        .stream_index_map
+        .get(stream_id)
+        .map(ToString::to_string)
+        .unwrap_or_default();
+
+    HashMap::from([
+        ("event_code", data.event_code.clone()),
+        ("event_text", data.event_text.clone()),
+        ("originator", determine_originator_name(&data.originator)),
+        ("originator_code", data.originator.clone()),
+        ("fips", data.fips.join(",")),
+        ("locations", data.locations.clone()),
+        ("eas_text", data.eas_text.trim_end().to_string()),
+        ("raw_header", alert.raw_header.clone()),
+        ("received_at", alert.received_at.to_rfc3339()),
+        ("expires_at", alert.expires_at.to_rfc3339()),
+        ("stream_name", stream_id.to_string()),
+        ("monitor", monitor),
+        (
+            "recording_deeplink",
+            recording_name
+                .and_then(|name| runtime_config.recording_deeplink(&name))
+                .unwrap_or_default(),
+        ),
+        ("matched_filter", matched_filter.to_string()),
+        ("station_name", runtime_config.station_name.clone()),
+    ])
+}
+
 pub fn apply_runtime_config(config: &Config) {
     let mut guard = WEBHOOK_RUNTIME_CONFIG
         .write()
@@ -117,16 +283,8 @@ pub fn a_or_an(word: &str) -> &str {
     }
 }
 
-pub async fn send_alert_webhook(
-    url: &str,
-    alert: &ActiveAlert,
-    _dsame_text: &str,
-    _raw_header: &str,
-    recording_path: Option<PathBuf>,
-) {
-    let runtime_config = runtime_config_snapshot();
-    let config_path = runtime_config.apprise_config_path;
-    let apprise_urls_from_config_array: Vec<String> = match fs::File::open(&config_path) {
+pub(crate) fn read_apprise_urls(config_path: &str) -> Option<Vec<String>> {
+    match fs::File::open(config_path) {
         Ok(mut file) => {
             let mut contents = String::new();
             if let Err(err) = file.read_to_string(&mut contents) {
@@ -134,28 +292,146 @@ pub async fn send_alert_webhook(
                     "Failed to read AppRise config file at '{}': {}",
                     config_path, err
                 );
-                return;
+                return None;
             }
-            contents
-                .lines()
-                .map(str::trim)
-                .filter(|line| !line.is_empty() && !line.starts_with('#'))
-                .map(|line| {
-                    line.strip_prefix('-')
-                        .map(str::trim_start)
-                        .unwrap_or(line)
-                        .to_owned()
-                })
-                .collect()
+            Some(
+                contents
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(|line| {
+                        line.strip_prefix('-')
+                            .map(str::trim_start)
+                            .unwrap_or(line)
+                            .to_owned()
+                    })
+                    .collect(),
+            )
         }
         Err(err) => {
             warn!(
                 "Failed to open AppRise config file at '{}': {}",
                 config_path, err
             );
-            return;
+            None
         }
+    }
+}
+
+pub async fn send_operational_notification(title: &str, body: &str, color: u32) {
+    let runtime_config = runtime_config_snapshot();
+    let Some(urls) = read_apprise_urls(&runtime_config.operational_apprise_config_path) else {
+        return;
     };
+
+    let discord_urls: Vec<&str> = urls
+        .iter()
+        .map(|url| url.trim())
+        .filter(|url| url.starts_with("discord://"))
+        .collect();
+    if !discord_urls.is_empty() {
+        let client = Client::new();
+        let payload_json = json!({
+            "embeds": [{
+                "title": truncate_discord_text(title, 256),
+                "description": truncate_discord_text(body, 4096),
+                "color": color,
+                "author": {
+                    "name": truncate_discord_text(
+                        &format!("{} - Software ENDEC Logs", runtime_config.station_name),
+                        256,
+                    ),
+                    "url": github_url.as_str()
+                },
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }]
+        })
+        .to_string();
+        for discord_url in discord_urls {
+            let url = format!(
+                "https://discord.com/api/webhooks/{}",
+                discord_url.trim_start_matches("discord://")
+            );
+            match send_discord_rate_limited(
+                &client,
+                &url,
+                discord_url,
+                runtime_config.discord_max_retries,
+                || multipart::Form::new().text("payload_json", payload_json.clone()),
+            )
+            .await
+            {
+                DiscordSendOutcome::Response(response) if response.status().is_success() => {}
+                DiscordSendOutcome::Response(response) => {
+                    log_discord_webhook_error_response(
+                        response,
+                        discord_url,
+                        "operational notification",
+                    )
+                    .await;
+                }
+                DiscordSendOutcome::RateLimited => {}
+                DiscordSendOutcome::Failed(err) => {
+                    warn!(
+                        "Failed to send operational notification to Discord webhook '{}': {}",
+                        discord_url, err
+                    );
+                }
+            }
+        }
+    }
+
+    let other_urls: Vec<&str> = urls
+        .iter()
+        .map(|url| url.trim())
+        .filter(|url| url.contains("://") && !url.starts_with("discord://"))
+        .collect();
+    if other_urls.is_empty() {
+        return;
+    }
+
+    let bodies = [("text", body.to_string())];
+    if !run_apprise(&runtime_config, title, &bodies, None, &other_urls).await {
+        warn!("Unable to deliver operational notification via AppRise");
+    }
+}
+
+pub async fn send_alert_webhook(
+    url: &str,
+    alert: &ActiveAlert,
+    _dsame_text: &str,
+    _raw_header: &str,
+    recording_path: Option<PathBuf>,
+    filters: &FilterHandle,
+) -> DeliverySummary {
+    let runtime_config = runtime_config_snapshot();
+    let matched_filter = filters.snapshot().rule_name(
+        url,
+        &alert.data.event_code,
+        alert.data.originator_code(),
+        &alert.data.fips,
+        header::purge_duration(&alert.raw_header),
+    );
+    if !runtime_config.generic_webhooks.is_empty() {
+        let values = generic_webhook_values(
+            &runtime_config,
+            url,
+            alert,
+            recording_path.as_deref(),
+            &matched_filter,
+        );
+        let webhooks = runtime_config.generic_webhooks.clone();
+        tokio::spawn(async move {
+            generic_webhook::send_generic_webhooks(&webhooks, &values).await;
+        });
+    }
+    let heard_on = heard_on_summary(&runtime_config, alert);
+    let apprise_urls_from_config_array =
+        match read_apprise_urls(&runtime_config.apprise_config_path) {
+            Some(urls) => urls,
+            None if runtime_config.smtp.is_some() => Vec::new(),
+            None => return DeliverySummary::default(),
+        };
     let data = &alert.data;
     let description = data
         .description
@@ -187,247 +463,1019 @@ pub async fn send_alert_webhook(
     } else {
         None
     };
-    let discord_embed_body = build_discord_embed_body(
-        &url,
-        &event_title,
-        event_code,
-        &originator,
-        &received_timestamp,
-        &data.eas_text,
-        &alert.raw_header,
+    let recording_link = attachment_path
+        .as_ref()
+        .and_then(|path| path.file_name())
+        .and_then(|name| runtime_config.recording_deeplink(&name.to_string_lossy()));
+    let content = AlertBodyContent {
+        title: &event_title,
+        originator: &originator,
+        received_timestamp: &received_timestamp,
+        eas_text: &data.eas_text,
+        raw_header: &alert.raw_header,
         description,
-    );
-    let markdown_body = build_markdown_body(
-        &event_title,
-        &originator,
-        &received_timestamp,
-        &data.eas_text,
-        &alert.raw_header,
-        description,
-    );
-    let html_body = build_html_body(
-        &event_title,
-        &originator,
-        &received_timestamp,
-        &data.eas_text,
-        &alert.raw_header,
-        description,
-    );
-    let text_body = build_plain_body(
-        &event_title,
-        &originator,
-        &received_timestamp,
-        &data.eas_text,
-        &alert.raw_header,
-        description,
-    );
+        heard_on: heard_on.as_deref(),
+        recording_link: recording_link.as_deref(),
+    };
+    let discord_embed_body = build_discord_embed_body(&url, event_code, &matched_filter, &content);
+    let markdown_body = build_markdown_body(&content);
+    let html_body = build_html_body(&content);
+    let text_body = build_plain_body(&content);
 
     let discord_urls: Vec<&str> = apprise_urls_from_config_array
         .iter()
         .map(|url| url.trim())
         .filter(|url| url.starts_with("discord://"))
         .collect();
+    let non_discord_urls: Vec<&str> = apprise_urls_from_config_array
+        .iter()
+        .map(|u| u.trim())
+        .filter(|u| u.contains("://") && !u.starts_with("discord://"))
+        .collect();
 
-    if !discord_urls.is_empty() {
-        let client = Client::new();
-        let attachment_bytes = if let Some(path) = attachment_path.as_ref() {
-            match tokio::fs::read(path).await {
-                Ok(bytes) => Some(bytes),
+    let runtime_config = Arc::new(runtime_config);
+    let bodies = Arc::new([
+        ("markdown", markdown_body),
+        ("html", html_body.clone()),
+        ("text", text_body.clone()),
+    ]);
+    let apprise_deliveries: Vec<_> = non_discord_urls
+        .iter()
+        .map(|url| {
+            let runtime_config = runtime_config.clone();
+            let title = apprise_title.clone();
+            let bodies = bodies.clone();
+            let attachment_path = attachment_path.clone();
+            let url = url.to_string();
+            tokio::spawn(async move {
+                EndpointResult {
+                    endpoint: webhook_capabilities::destination_id(&url),
+                    outcome: deliver_apprise_endpoint(
+                        &runtime_config,
+                        &title,
+                        bodies.as_slice(),
+                        attachment_path.as_deref(),
+                        &url,
+                    )
+                    .await,
+                }
+            })
+        })
+        .collect();
+
+    let client = Client::new();
+    let prepared_attachment = match attachment_path.as_ref() {
+        Some(path) if !discord_urls.is_empty() => {
+            prepare_recording_attachment(path, runtime_config.discord_attachment_max_bytes).await
+        }
+        _ => None,
+    };
+    let linked_embed_body = recording_link.as_deref().map(|link| {
+        let mut embed = discord_embed_body.clone();
+        add_recording_link_to_embed(&mut embed, link);
+        embed
+    });
+    let initial_embed_body = match (&prepared_attachment, &linked_embed_body) {
+        (None, Some(linked)) => linked,
+        _ => &discord_embed_body,
+    };
+    let mention = discord_mention(
+        &runtime_config.discord_mentions,
+        event_code,
+        alert.severity_rank(),
+    );
+    let discord_payload = discord_alert_payload(initial_embed_body, mention);
+    let linked_discord_payload = linked_embed_body
+        .as_ref()
+        .map(|linked| discord_alert_payload(linked, mention));
+    let prepared_attachment = Arc::new(prepared_attachment);
+    let discord_deliveries: Vec<_> = discord_urls
+        .iter()
+        .map(|discord_url| {
+            let client = client.clone();
+            let discord_url = discord_url.to_string();
+            let max_retries = runtime_config.discord_max_retries;
+            let payload = discord_payload.clone();
+            let linked_payload = linked_discord_payload.clone();
+            let attachment = prepared_attachment.clone();
+            tokio::spawn(async move {
+                EndpointResult {
+                    endpoint: webhook_capabilities::destination_id(&discord_url),
+                    outcome: deliver_discord(
+                        &client,
+                        &discord_url,
+                        max_retries,
+                        payload,
+                        linked_payload,
+                        attachment.as_ref().as_ref(),
+                    )
+                    .await,
+                }
+            })
+        })
+        .collect();
+
+    let email_delivery = match runtime_config.smtp.clone() {
+        Some(smtp) => {
+            let max_retries = runtime_config.discord_max_retries;
+            let attachment_path = attachment_path.clone();
+            let has_recording = attachment_path.is_some();
+            let subject = apprise_title.clone();
+            vec![tokio::spawn(async move {
+                let email_attachment = match attachment_path.as_ref() {
+                    Some(path) => {
+                        prepare_recording_attachment(path, smtp.attachment_max_bytes).await
+                    }
+                    None => None,
+                };
+                let delivered = email::send_alert_email(
+                    &smtp,
+                    max_retries,
+                    &subject,
+                    &text_body,
+                    &html_body,
+                    email_attachment.as_ref(),
+                )
+                .await;
+                EndpointResult {
+                    endpoint: smtp.endpoint_id(),
+                    outcome: match (delivered, &email_attachment) {
+                        (false, _) => DeliveryOutcome::Failed,
+                        (true, None) if has_recording => {
+                            DeliveryOutcome::DeliveredWithoutAttachment
+                        }
+                        (true, _) => DeliveryOutcome::Delivered,
+                    },
+                }
+            })]
+        }
+        None => Vec::new(),
+    };
+
+    let (discord_results, apprise_results, email_result) = tokio::join!(
+        join_deliveries(discord_deliveries),
+        join_deliveries(apprise_deliveries),
+        join_deliveries(email_delivery)
+    );
+    let summary = DeliverySummary {
+        results: discord_results
+            .into_iter()
+            .chain(apprise_results)
+            .chain(email_result)
+            .collect(),
+    };
+    if !summary.results.is_empty() {
+        info!("Notification delivery for {}: {}", event_code, summary);
+    }
+    summary
+}
+
+async fn join_deliveries(
+    deliveries: Vec<tokio::task::JoinHandle<EndpointResult>>,
+) -> Vec<EndpointResult> {
+    let mut results = Vec::with_capacity(deliveries.len());
+    for delivery in join_all(deliveries).await {
+        match delivery {
+            Ok(result) => results.push(result),
+            Err(err) => warn!("Notification delivery task failed: {}", err),
+        }
+    }
+    results
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryOutcome {
+    Delivered,
+    DeliveredWithoutAttachment,
+    RateLimited,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EndpointResult {
+    pub endpoint: String,
+    pub outcome: DeliveryOutcome,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DeliverySummary {
+    pub results: Vec<EndpointResult>,
+}
+
+impl DeliverySummary {
+    pub fn delivered(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|result| {
+                matches!(
+                    result.outcome,
+                    DeliveryOutcome::Delivered | DeliveryOutcome::DeliveredWithoutAttachment
+                )
+            })
+            .count()
+    }
+}
+
+impl std::fmt::Display for DeliverySummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}/{} endpoint(s) delivered",
+            self.delivered(),
+            self.results.len()
+        )?;
+        for result in &self.results {
+            let outcome = match result.outcome {
+                DeliveryOutcome::Delivered => "ok",
+                DeliveryOutcome::DeliveredWithoutAttachment => "ok without attachment",
+                DeliveryOutcome::RateLimited => "dropped after rate limiting",
+                DeliveryOutcome::Failed => "failed",
+            };
+            write!(f, "; {} {}", result.endpoint, outcome)?;
+        }
+        Ok(())
+    }
+}
+
+const TEST_NOTIFICATION_STREAM_ID: &str = "Notification Test";
+const TEST_NOTIFICATION_SAMPLE_RATE: u32 = 16000;
+
+fn test_notification_header(station_id: &str) -> String {
+    use chrono::{Datelike, Timelike};
+
+    let now = chrono::Utc::now();
+    format!(
+        "ZCZC-EAS-DMO-000000+0015-{:03}{:02}{:02}-{}-",
+        now.ordinal(),
+        now.hour(),
+        now.minute(),
+        station_id
+    )
+}
+
+fn write_test_recording(path: &Path, raw_header: &str) -> anyhow::Result<()> {
+    let samples =
+        header::generate_same_header_samples(raw_header, TEST_NOTIFICATION_SAMPLE_RATE, 0.42)?;
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: TEST_NOTIFICATION_SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(path, spec)?;
+    for sample in samples {
+        writer.write_sample(sample)?;
+    }
+    writer.finalize()?;
+    Ok(())
+}
+
+pub async fn send_test_notification(
+    attach_recording: bool,
+    filters: &FilterHandle,
+) -> DeliverySummary {
+    let runtime_config = runtime_config_snapshot();
+    let station_name = runtime_config.station_name;
+    let raw_header = test_notification_header(&runtime_config.station_id);
+    let alert = ActiveAlert::new(
+        EasAlertData {
+            eas_text: format!(
+                "This is a test of the notification chain from {station_name}. No action is required."
+            ),
+            event_text: "Practice/Demo Warning".to_string(),
+            event_code: "DMO".to_string(),
+            fips: vec!["000000".to_string()],
+            locations: "United States".to_string(),
+            originator: "EAS".to_string(),
+            description: None,
+            parsed_header: None,
+        },
+        raw_header.clone(),
+        Duration::from_secs(15 * 60),
+    )
+    .with_source_stream_url(TEST_NOTIFICATION_STREAM_ID);
+
+    let recording_dir = if attach_recording {
+        tempfile::tempdir()
+            .map_err(|err| {
+                warn!(
+                    "Failed to create a directory for the test recording: {}",
+                    err
+                )
+            })
+            .ok()
+    } else {
+        None
+    };
+    let recording_path = recording_dir.as_ref().and_then(|dir| {
+        let path = dir.path().join("EAS_Notification_Test.wav");
+        match write_test_recording(&path, &raw_header) {
+            Ok(()) => Some(path),
+            Err(err) => {
+                warn!("Failed to write the test notification recording: {:#}", err);
+                None
+            }
+        }
+    });
+
+    send_alert_webhook(
+        TEST_NOTIFICATION_STREAM_ID,
+        &alert,
+        "",
+        &raw_header,
+        recording_path,
+        filters,
+    )
+    .await
+}
+
+fn discord_mention<'a>(
+    mentions: &'a [(String, String)],
+    event_code: &str,
+    severity_rank: u8,
+) -> Option<&'a str> {
+    let code = event_code.trim().to_ascii_uppercase();
+    let class = match severity_rank {
+        3 => "warning",
+        2 => "watch",
+        1 => "advisory",
+        _ => "test",
+    };
+    mentions
+        .iter()
+        .find(|(key, _)| *key == code)
+        .or_else(|| mentions.iter().find(|(key, _)| key == class))
+        .map(|(_, mention)| mention.as_str())
+}
+
+fn discord_allowed_mentions(mention: &str) -> serde_json::Value {
+    let mut parse = Vec::new();
+    if mention.contains("@everyone") || mention.contains("@here") {
+        parse.push("everyone");
+    }
+    let mut roles = Vec::new();
+    let mut users = Vec::new();
+    for (index, _) in mention.match_indices("<@") {
+        let rest = &mention[index + 2..];
+        let Some(end) = rest.find('>') else {
+            continue;
+        };
+        let target = &rest[..end];
+        let (list, id) = match target.strip_prefix('&') {
+            Some(id) => (&mut roles, id),
+            None => (&mut users, target.trim_start_matches('!')),
+        };
+        if !id.is_empty() && id.bytes().all(|byte| byte.is_ascii_digit()) && !list.contains(&id) {
+            list.push(id);
+        }
+    }
+    json!({ "parse": parse, "roles": roles, "users": users })
+}
+
+fn discord_alert_payload(
+    embed_body: &serde_json::Value,
+    mention: Option<&str>,
+) -> serde_json::Value {
+    match mention {
+        Some(mention) => json!({
+            "content": truncate_discord_text(mention, 2000),
+            "embeds": [embed_body],
+            "allowed_mentions": discord_allowed_mentions(mention),
+        }),
+        None => json!({ "embeds": [embed_body] }),
+    }
+}
+
+async fn deliver_discord(
+    client: &Client,
+    discord_url: &str,
+    max_retries: u32,
+    payload_value: serde_json::Value,
+    linked_payload: Option<serde_json::Value>,
+    attachment: Option<&RecordingAttachment>,
+) -> DeliveryOutcome {
+    let validation_errors = validate_discord_payload(&payload_value);
+    if !validation_errors.is_empty() {
+        warn!(
+            "Discord payload preflight validation found {} issue(s) for '{}': {}",
+            validation_errors.len(),
+            discord_url,
+            validation_errors.join("; ")
+        );
+    }
+
+    let payload_json = payload_value.to_string();
+    let build_form = || {
+        let mut form = multipart::Form::new().text("payload_json", payload_json.clone());
+        if let Some(attachment) = attachment {
+            match multipart::Part::bytes(attachment.bytes.clone())
+                .file_name(attachment.file_name.clone())
+                .mime_str(attachment.mime)
+            {
+                Ok(part) => form = form.part("file", part),
                 Err(err) => {
                     warn!(
-                        "Failed to read recording attachment at '{}': {}",
-                        path.display(),
-                        err
+                        "Failed to prepare Discord attachment part '{}': {}",
+                        attachment.file_name, err
                     );
-                    None
                 }
             }
-        } else {
-            None
-        };
+        }
+        form
+    };
+
+    let url = format!(
+        "https://discord.com/api/webhooks/{}",
+        discord_url.trim_start_matches("discord://")
+    );
 
-        let prepared_attachment: Option<(Vec<u8>, String)> =
-            match (attachment_path.as_ref(), attachment_bytes) {
-                (Some(path), Some(bytes)) => Some(prepare_discord_attachment(path, bytes).await),
-                _ => None,
+    match send_discord_rate_limited(client, &url, discord_url, max_retries, build_form).await {
+        DiscordSendOutcome::Response(response) if response.status().is_success() => {
+            DeliveryOutcome::Delivered
+        }
+        DiscordSendOutcome::Response(response)
+            if response.status() == reqwest::StatusCode::PAYLOAD_TOO_LARGE
+                && attachment.is_some() =>
+        {
+            log_discord_webhook_error_response(
+                response,
+                discord_url,
+                "initial request with attachment",
+            )
+            .await;
+            let retry_payload_json = match &linked_payload {
+                Some(linked) => linked.to_string(),
+                None => payload_json.clone(),
             };
+            match send_discord_rate_limited(client, &url, discord_url, max_retries, || {
+                multipart::Form::new().text("payload_json", retry_payload_json.clone())
+            })
+            .await
+            {
+                DiscordSendOutcome::Response(retry_response)
+                    if retry_response.status().is_success() =>
+                {
+                    DeliveryOutcome::DeliveredWithoutAttachment
+                }
+                DiscordSendOutcome::Response(retry_response) => {
+                    log_discord_webhook_error_response(
+                        retry_response,
+                        discord_url,
+                        "retry without attachment",
+                    )
+                    .await;
+                    DeliveryOutcome::Failed
+                }
+                DiscordSendOutcome::RateLimited => DeliveryOutcome::RateLimited,
+                DiscordSendOutcome::Failed(err) => {
+                    warn!(
+                        "Failed to retry Discord webhook '{}' without attachment: {}",
+                        discord_url, err
+                    );
+                    DeliveryOutcome::Failed
+                }
+            }
+        }
+        DiscordSendOutcome::Response(response) => {
+            log_discord_webhook_error_response(response, discord_url, "initial request").await;
+            DeliveryOutcome::Failed
+        }
+        DiscordSendOutcome::RateLimited => DeliveryOutcome::RateLimited,
+        DiscordSendOutcome::Failed(e) => {
+            warn!("Failed to send Discord webhook '{}': {}", discord_url, e);
+            DeliveryOutcome::Failed
+        }
+    }
+}
 
-        for discord_url in discord_urls {
-            let payload_value = json!({ "embeds": [discord_embed_body.clone()] });
-            let validation_errors = validate_discord_payload(&payload_value);
-            if !validation_errors.is_empty() {
+async fn deliver_apprise_endpoint(
+    runtime_config: &WebhookRuntimeConfig,
+    title: &str,
+    bodies: &[(&str, String)],
+    attachment: Option<&Path>,
+    url: &str,
+) -> DeliveryOutcome {
+    let state_dir = &runtime_config.shared_state_dir;
+    let id = webhook_capabilities::destination_id(url);
+    let attachment = attachment.filter(|_| {
+        webhook_capabilities::supports_attachments(
+            state_dir,
+            &runtime_config.apprise_attachment_support,
+            url,
+        )
+    });
+
+    if let Some(path) = attachment {
+        if run_apprise(runtime_config, title, bodies, Some(path), &[url]).await {
+            webhook_capabilities::record_attachment_success(state_dir, url);
+            return DeliveryOutcome::Delivered;
+        }
+    }
+    if run_apprise(runtime_config, title, bodies, None, &[url]).await {
+        if attachment.is_none() {
+            return DeliveryOutcome::Delivered;
+        }
+        info!(
+            "AppRise destination {} only accepted the notification without its attachment",
+            id
+        );
+        webhook_capabilities::record_attachment_failure(state_dir, url);
+        return DeliveryOutcome::DeliveredWithoutAttachment;
+    }
+    warn!(
+        "Unable to deliver notification to AppRise destination {} after trying all formats",
+        id
+    );
+    DeliveryOutcome::Failed
+}
+
+async fn run_apprise(
+    runtime_config: &WebhookRuntimeConfig,
+    title: &str,
+    bodies: &[(&str, String)],
+    attachment: Option<&Path>,
+    targets: &[&str],
+) -> bool {
+    if let Some(api_url) = runtime_config.apprise_api_url.as_deref() {
+        if run_apprise_api(api_url, title, bodies, attachment, targets).await {
+            return true;
+        }
+        warn!("AppRise API delivery failed; falling back to the apprise CLI");
+    }
+    run_apprise_cli(title, bodies, attachment, targets).await
+}
+
+const APPRISE_API_TIMEOUT: Duration = Duration::from_secs(60);
+const APPRISE_API_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn apprise_api_notify_url(api_url: &str) -> String {
+    let base = api_url.trim().trim_end_matches('/');
+    if base.ends_with("/notify") {
+        format!("{base}/")
+    } else {
+        format!("{base}/notify/")
+    }
+}
+
+async fn run_apprise_api(
+    api_url: &str,
+    title: &str,
+    bodies: &[(&str, String)],
+    attachment: Option<&Path>,
+    targets: &[&str],
+) -> bool {
+    let client = match Client::builder()
+        .timeout(APPRISE_API_TIMEOUT)
+        .connect_timeout(APPRISE_API_CONNECT_TIMEOUT)
+        .build()
+    {
+        Ok(client) => client,
+        Err(err) => {
+            warn!("Failed to build AppRise API client: {}", err);
+            return false;
+        }
+    };
+    let notify_url = apprise_api_notify_url(api_url);
+    let urls = targets.join(",");
+    let attachment = match attachment {
+        Some(path) => match tokio::fs::read(path).await {
+            Ok(bytes) => {
+                let file_name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| "recording.bin".to_string());
+                Some((bytes, file_name))
+            }
+            Err(err) => {
                 warn!(
-                    "Discord payload preflight validation found {} issue(s) for '{}': {}",
-                    validation_errors.len(),
-                    discord_url,
-                    validation_errors.join("; ")
+                    "Failed to read attachment {} for the AppRise API: {}",
+                    path.display(),
+                    err
                 );
+                return false;
             }
+        },
+        None => None,
+    };
 
-            let payload_json = payload_value.to_string();
-            let mut form = multipart::Form::new().text("payload_json", payload_json.clone());
-            let mut attachment_included = false;
+    for (format, body) in bodies {
+        let request = client.post(&notify_url);
+        let request = match &attachment {
+            Some((bytes, file_name)) => request.multipart(
+                multipart::Form::new()
+                    .text("urls", urls.clone())
+                    .text("title", title.to_string())
+                    .text("body", body.clone())
+                    .text("format", format.to_string())
+                    .part(
+                        "attach",
+                        multipart::Part::bytes(bytes.clone()).file_name(file_name.clone()),
+                    ),
+            ),
+            None => request.json(&json!({
+                "urls": urls,
+                "title": title,
+                "body": body,
+                "format": format,
+            })),
+        };
 
-            if let Some((bytes, file_name)) = prepared_attachment.as_ref() {
-                match multipart::Part::bytes(bytes.clone())
-                    .file_name(file_name.clone())
-                    .mime_str("application/octet-stream")
-                {
-                    Ok(part) => {
-                        form = form.part("file", part);
-                        attachment_included = true;
-                    }
-                    Err(err) => {
-                        warn!(
-                            "Failed to prepare Discord attachment part '{}': {}",
-                            file_name, err
-                        );
+        match request.send().await {
+            Ok(response) if response.status().is_success() => {
+                info!(
+                    "Delivered notification via the AppRise API using '{}' format to {} target(s){}",
+                    format,
+                    targets.len(),
+                    if attachment.is_some() {
+                        " with attachment"
+                    } else {
+                        ""
                     }
-                }
+                );
+                return true;
+            }
+            Ok(response) => {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                warn!(
+                    "AppRise API '{}' format attempt failed ({}): {}",
+                    format,
+                    status,
+                    truncate_for_log(body.trim(), 800)
+                );
+            }
+            Err(err) => {
+                warn!(
+                    "AppRise API request to {} failed{}: {}",
+                    notify_url,
+                    if err.is_timeout() { " (timed out)" } else { "" },
+                    err
+                );
+                return false;
             }
+        }
+    }
 
-            let url = format!(
-                "https://discord.com/api/webhooks/{}",
-                discord_url.trim_start_matches("discord://")
-            );
+    false
+}
 
-            match client.post(&url).multipart(form).send().await {
-                Ok(response) if response.status().is_success() => {}
-                Ok(response) => {
-                    let status = response.status();
-                    if status == reqwest::StatusCode::PAYLOAD_TOO_LARGE && attachment_included {
-                        log_discord_webhook_error_response(
-                            response,
-                            discord_url,
-                            "initial request with attachment",
-                        )
-                        .await;
-                        let retry_form = multipart::Form::new().text("payload_json", payload_json);
-                        match client.post(&url).multipart(retry_form).send().await {
-                            Ok(retry_response) if retry_response.status().is_success() => {}
-                            Ok(retry_response) => {
-                                log_discord_webhook_error_response(
-                                    retry_response,
-                                    discord_url,
-                                    "retry without attachment",
-                                )
-                                .await;
-                            }
-                            Err(err) => {
-                                warn!(
-                                    "Failed to retry Discord webhook '{}' without attachment: {}",
-                                    discord_url, err
-                                );
-                            }
-                        }
+async fn run_apprise_cli(
+    title: &str,
+    bodies: &[(&str, String)],
+    attachment: Option<&Path>,
+    targets: &[&str],
+) -> bool {
+    for (format, body) in bodies {
+        let mut command = Command::new("apprise");
+        command.arg("--title").arg(title);
+        command.arg("--body").arg(body);
+        command.arg("--input-format").arg(format);
+
+        if let Some(path) = attachment {
+            command.arg("--attach").arg(path);
+        }
+
+        for target in targets {
+            command.arg(target);
+        }
+
+        match command.output().await {
+            Ok(output) if output.status.success() => {
+                info!(
+                    "Delivered notification via AppRise using '{}' format to {} target(s){}",
+                    format,
+                    targets.len(),
+                    if attachment.is_some() {
+                        " with attachment"
                     } else {
-                        log_discord_webhook_error_response(
-                            response,
-                            discord_url,
-                            "initial request",
-                        )
-                        .await;
+                        ""
                     }
-                }
-                Err(e) => {
-                    warn!("Failed to send Discord webhook '{}': {}", discord_url, e);
-                }
+                );
+                return true;
+            }
+            Ok(output) => {
+                warn!(
+                    "AppRise '{}' format attempt failed (exit {:?}): stderr={} stdout={}",
+                    format,
+                    output.status.code(),
+                    truncate_for_log(String::from_utf8_lossy(&output.stderr).trim(), 800),
+                    truncate_for_log(String::from_utf8_lossy(&output.stdout).trim(), 800)
+                );
+            }
+            Err(err) => {
+                warn!(
+                    "Failed to invoke 'apprise' for '{}' format (is it installed and on PATH?): {}",
+                    format, err
+                );
+                return false;
             }
         }
     }
 
-    let non_discord_urls: Vec<&str> = apprise_urls_from_config_array
-        .iter()
-        .map(|u| u.trim())
-        .filter(|u| u.contains("://") && !u.starts_with("discord://"))
-        .collect();
+    false
+}
 
-    if non_discord_urls.is_empty() {
-        return;
+pub fn discord_delivery_counters() -> (u64, u64) {
+    (
+        DISCORD_MESSAGES_DEFERRED.load(Ordering::Relaxed),
+        DISCORD_MESSAGES_DROPPED.load(Ordering::Relaxed),
+    )
+}
+
+const DISCORD_RATE_LIMIT_MAX_WAIT: Duration = Duration::from_secs(60);
+const DISCORD_RATE_LIMIT_FALLBACK_WAIT: Duration = Duration::from_secs(1);
+
+static DISCORD_MESSAGES_DEFERRED: AtomicU64 = AtomicU64::new(0);
+static DISCORD_MESSAGES_DROPPED: AtomicU64 = AtomicU64::new(0);
+
+type DiscordRoute = Arc<tokio::sync::Mutex<Option<Instant>>>;
+
+lazy_static! {
+    static ref DISCORD_ROUTES: Mutex<HashMap<String, DiscordRoute>> = Mutex::new(HashMap::new());
+    static ref DISCORD_GLOBAL_PAUSE_UNTIL: Mutex<Option<Instant>> = Mutex::new(None);
+}
+
+enum DiscordSendOutcome {
+    Response(reqwest::Response),
+    RateLimited,
+    Failed(reqwest::Error),
+}
+
+fn discord_route(url: &str) -> DiscordRoute {
+    DISCORD_ROUTES
+        .lock()
+        .expect("discord route lock poisoned")
+        .entry(url.to_string())
+        .or_default()
+        .clone()
+}
+
+fn header_seconds(headers: &HeaderMap, name: &str) -> Option<Duration> {
+    headers
+        .get(name)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+        .map(Duration::from_secs_f64)
+}
+
+fn discord_rate_limit_delay(headers: &HeaderMap, body: &str) -> (Duration, bool) {
+    let body: serde_json::Value = serde_json::from_str(body).unwrap_or_default();
+    let body_delay = body
+        .get("retry_after")
+        .and_then(serde_json::Value::as_f64)
+        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+        .map(Duration::from_secs_f64);
+    let delay = header_seconds(headers, "retry-after")
+        .into_iter()
+        .chain(body_delay)
+        .max()
+        .unwrap_or(DISCORD_RATE_LIMIT_FALLBACK_WAIT)
+        .min(DISCORD_RATE_LIMIT_MAX_WAIT);
+
+    let header_is = |name: &str, expected: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.trim().eq_ignore_ascii_case(expected))
+    };
+    let global = header_is("x-ratelimit-global", "true")
+        || header_is("x-ratelimit-scope", "global")
+        || body.get("global").and_then(serde_json::Value::as_bool) == Some(true);
+
+    (delay, global)
+}
+
+fn discord_bucket_delay(headers: &HeaderMap) -> Option<Duration> {
+    let remaining = headers.get("x-ratelimit-remaining")?.to_str().ok()?.trim();
+    if remaining != "0" {
+        return None;
     }
+    header_seconds(headers, "x-ratelimit-reset-after")
+        .map(|delay| delay.min(DISCORD_RATE_LIMIT_MAX_WAIT))
+}
 
-    let attempts = [
-        ("markdown", markdown_body),
-        ("html", html_body),
-        ("text", text_body),
-    ];
+async fn wait_for_discord_global_pause() {
+    let until = *DISCORD_GLOBAL_PAUSE_UNTIL
+        .lock()
+        .expect("discord global pause lock poisoned");
+    if let Some(until) = until {
+        tokio::time::sleep_until(until).await;
+    }
+}
 
-    for (format, body) in attempts.iter() {
-        let mut command = Command::new("apprise");
-        command.arg("--title").arg(&apprise_title);
-        command.arg("--body").arg(body);
-        command.arg("--input-format").arg(format);
+async fn send_discord_rate_limited(
+    client: &Client,
+    url: &str,
+    discord_url: &str,
+    max_retries: u32,
+    build_form: impl Fn() -> multipart::Form,
+) -> DiscordSendOutcome {
+    let route = discord_route(url);
+    let mut next_send_at = route.lock().await;
+    let mut retries = 0;
+
+    loop {
+        if let Some(at) = next_send_at.take() {
+            tokio::time::sleep_until(at).await;
+        }
+        wait_for_discord_global_pause().await;
+
+        let response = match client.post(url).multipart(build_form()).send().await {
+            Ok(response) => response,
+            Err(err) => return DiscordSendOutcome::Failed(err),
+        };
+        if let Some(delay) = discord_bucket_delay(response.headers()) {
+            *next_send_at = Some(Instant::now() + delay);
+        }
+        if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return DiscordSendOutcome::Response(response);
+        }
+
+        let headers = response.headers().clone();
+        let body = response.text().await.unwrap_or_default();
+        let (delay, global) = discord_rate_limit_delay(&headers, &body);
+        if retries >= max_retries {
+            let dropped = DISCORD_MESSAGES_DROPPED.fetch_add(1, Ordering::Relaxed) + 1;
+            warn!(
+                "Dropping Discord message for '{}' after {} rate-limited retr{} ({} dropped, {} deferred since startup)",
+                discord_url,
+                retries,
+                if retries == 1 { "y" } else { "ies" },
+                dropped,
+                DISCORD_MESSAGES_DEFERRED.load(Ordering::Relaxed)
+            );
+            return DiscordSendOutcome::RateLimited;
+        }
+
+        retries += 1;
+        let resume_at = Instant::now() + delay;
+        if global {
+            let mut pause = DISCORD_GLOBAL_PAUSE_UNTIL
+                .lock()
+                .expect("discord global pause lock poisoned");
+            *pause = Some(pause.map_or(resume_at, |until| until.max(resume_at)));
+        } else {
+            *next_send_at = Some(resume_at);
+        }
+        let deferred = DISCORD_MESSAGES_DEFERRED.fetch_add(1, Ordering::Relaxed) + 1;
+        warn!(
+            "Discord {}rate limited '{}'; retrying in {:.1}s (attempt {}/{}, {} deferred, {} dropped since startup)",
+            if global { "globally " } else { "" },
+            discord_url,
+            delay.as_secs_f64(),
+            retries,
+            max_retries,
+            deferred,
+            DISCORD_MESSAGES_DROPPED.load(Ordering::Relaxed)
+        );
+    }
+}
+
+const RECORDING_FIELD_NAME: &str = "Recording";
+
+const DISCORD_OPUS_MIN_KBPS: u64 = 6;
+const DISCORD_OPUS_MAX_KBPS: u64 = 128;
+const DISCORD_OPUS_SIZE_HEADROOM: f64 = 0.9;
 
-        if let Some(path) = attachment_path.as_ref() {
-            command.arg("--attach").arg(path);
-        }
+pub(crate) struct RecordingAttachment {
+    pub(crate) bytes: Vec<u8>,
+    pub(crate) file_name: String,
+    pub(crate) mime: &'static str,
+}
 
-        for target in &non_discord_urls {
-            command.arg(target);
-        }
+fn opus_bitrate_to_fit(max_bytes: u64, duration_secs: f64) -> Option<u64> {
+    if !duration_secs.is_finite() || duration_secs <= 0.0 {
+        return Some(DISCORD_OPUS_MAX_KBPS);
+    }
+    let budget_kbps =
+        (max_bytes as f64 * 8.0 * DISCORD_OPUS_SIZE_HEADROOM) / duration_secs / 1000.0;
+    let kbps = (budget_kbps.floor() as u64).min(DISCORD_OPUS_MAX_KBPS);
+    (kbps >= DISCORD_OPUS_MIN_KBPS).then_some(kbps)
+}
 
-        match command.output().await {
-            Ok(output) if output.status.success() => {
-                info!(
-                    "Delivered notification via AppRise using '{}' format to {} target(s)",
-                    format,
-                    non_discord_urls.len()
-                );
-                return;
-            }
-            Ok(output) => {
-                warn!(
-                    "AppRise '{}' format attempt failed (exit {:?}): stderr={} stdout={}",
-                    format,
-                    output.status.code(),
-                    truncate_for_log(String::from_utf8_lossy(&output.stderr).trim(), 800),
-                    truncate_for_log(String::from_utf8_lossy(&output.stdout).trim(), 800)
-                );
-            }
-            Err(err) => {
-                warn!(
-                    "Failed to invoke 'apprise' for '{}' format (is it installed and on PATH?): {}",
-                    format, err
-                );
+pub(crate) fn discord_attachment_cache_path(path: &Path) -> Option<PathBuf> {
+    let stem = path.file_stem()?.to_string_lossy();
+    Some(path.with_file_name(format!(".{stem}.discord.ogg")))
+}
+
+async fn recording_duration_secs(path: &Path) -> Option<f64> {
+    let is_wav = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"));
+    if is_wav {
+        if let Ok(reader) = hound::WavReader::open(path) {
+            let spec = reader.spec();
+            if spec.sample_rate > 0 {
+                return Some(reader.duration() as f64 / spec.sample_rate as f64);
             }
         }
     }
 
-    warn!("Unable to deliver notification via AppRise after trying all formats");
+    let output = Command::new("ffprobe")
+        .arg("-v")
+        .arg("error")
+        .arg("-show_entries")
+        .arg("format=duration")
+        .arg("-of")
+        .arg("default=noprint_wrappers=1:nokey=1")
+        .arg(path)
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
 }
 
-const DISCORD_ATTACHMENT_COMPRESS_THRESHOLD: usize = 9 * 1024 * 1024;
-
-async fn prepare_discord_attachment(path: &Path, original_bytes: Vec<u8>) -> (Vec<u8>, String) {
-    let original_name = path
-        .file_name()
-        .map(|name| name.to_string_lossy().into_owned())
-        .filter(|name| !name.is_empty())
-        .unwrap_or_else(|| "recording.bin".to_string());
-
-    if original_bytes.len() <= DISCORD_ATTACHMENT_COMPRESS_THRESHOLD {
-        return (original_bytes, original_name);
+async fn cached_recording_attachment(
+    cache_path: &Path,
+    original: &std::fs::Metadata,
+    max_bytes: u64,
+) -> Option<Vec<u8>> {
+    let cached = tokio::fs::metadata(cache_path).await.ok()?;
+    if cached.len() > max_bytes {
+        return None;
+    }
+    if let (Ok(cached_at), Ok(recorded_at)) = (cached.modified(), original.modified()) {
+        if cached_at < recorded_at {
+            return None;
+        }
     }
+    tokio::fs::read(cache_path).await.ok()
+}
 
-    let compressed_temp = match tempfile::Builder::new()
-        .prefix("discord_recording_")
-        .suffix(".mp3")
-        .tempfile()
-    {
-        Ok(file) => file,
+pub(crate) async fn prepare_recording_attachment(
+    path: &Path,
+    max_bytes: u64,
+) -> Option<RecordingAttachment> {
+    let metadata = match tokio::fs::metadata(path).await {
+        Ok(metadata) => metadata,
         Err(err) => {
             warn!(
-                "Failed to allocate temp file to compress '{}' for Discord; sending original: {}",
+                "Failed to read recording attachment at '{}': {}",
                 path.display(),
                 err
             );
-            return (original_bytes, original_name);
+            return None;
         }
     };
+    let original_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "recording.bin".to_string());
+
+    if metadata.len() <= max_bytes {
+        return match tokio::fs::read(path).await {
+            Ok(bytes) => Some(RecordingAttachment {
+                bytes,
+                mime: file_stream::content_type_for(path),
+                file_name: original_name,
+            }),
+            Err(err) => {
+                warn!(
+                    "Failed to read recording attachment at '{}': {}",
+                    path.display(),
+                    err
+                );
+                None
+            }
+        };
+    }
+
+    let cache_path = discord_attachment_cache_path(path)?;
+    let ogg_name = Path::new(&original_name)
+        .with_extension("ogg")
+        .to_string_lossy()
+        .into_owned();
+    if let Some(bytes) = cached_recording_attachment(&cache_path, &metadata, max_bytes).await {
+        return Some(RecordingAttachment {
+            bytes,
+            file_name: ogg_name,
+            mime: file_stream::content_type_for(&cache_path),
+        });
+    }
 
-    let compressed_path = compressed_temp.into_temp_path();
-    let compressed_path_buf = compressed_path.to_path_buf();
+    let duration_secs = recording_duration_secs(path).await.unwrap_or(0.0);
+    let Some(kbps) = opus_bitrate_to_fit(max_bytes, duration_secs) else {
+        warn!(
+            "Recording '{}' ({:.0}s) cannot fit the {} byte attachment limit even at {} kbps Opus; linking it instead",
+            path.display(),
+            duration_secs,
+            max_bytes,
+            DISCORD_OPUS_MIN_KBPS
+        );
+        return None;
+    };
 
+    let partial_path = cache_path.with_extension("ogg.partial");
     let mut ffmpeg = Command::new("ffmpeg");
     ffmpeg
         .arg("-nostdin")
@@ -439,53 +1487,95 @@ async fn prepare_discord_attachment(path: &Path, original_bytes: Vec<u8>) -> (Ve
         .arg(path)
         .arg("-vn")
         .arg("-c:a")
-        .arg("libmp3lame")
+        .arg("libopus")
         .arg("-b:a")
-        .arg("128k")
-        .arg(&compressed_path_buf);
+        .arg(format!("{kbps}k"));
+    if kbps < 32 {
+        ffmpeg.arg("-ac").arg("1");
+    }
+    ffmpeg.arg("-f").arg("ogg").arg(&partial_path);
 
     match ffmpeg.status().await {
-        Ok(status) if status.success() => match tokio::fs::read(&compressed_path_buf).await {
-            Ok(compressed_bytes) => {
-                let mp3_name = Path::new(&original_name)
-                    .with_extension("mp3")
-                    .to_string_lossy()
-                    .into_owned();
-                info!(
-                    "Recording '{}' is {} bytes (over the {} byte Discord limit); attaching {} byte 128 kbps MP3 '{}' instead",
-                    path.display(),
-                    original_bytes.len(),
-                    DISCORD_ATTACHMENT_COMPRESS_THRESHOLD,
-                    compressed_bytes.len(),
-                    mp3_name
-                );
-                (compressed_bytes, mp3_name)
-            }
-            Err(err) => {
-                warn!(
-                    "Failed to read compressed Discord attachment for '{}'; sending original: {}",
-                    path.display(),
-                    err
-                );
-                (original_bytes, original_name)
-            }
-        },
+        Ok(status) if status.success() => {}
         Ok(status) => {
             warn!(
-                "ffmpeg failed to compress '{}' for Discord (status {:?}); sending original",
+                "ffmpeg failed to transcode '{}' for attachment (status {:?}); linking it instead",
                 path.display(),
                 status.code()
             );
-            (original_bytes, original_name)
+            let _ = tokio::fs::remove_file(&partial_path).await;
+            return None;
+        }
+        Err(err) => {
+            warn!(
+                "Failed to invoke ffmpeg to transcode '{}' for attachment; linking it instead: {}",
+                path.display(),
+                err
+            );
+            return None;
         }
+    }
+
+    let bytes = match tokio::fs::read(&partial_path).await {
+        Ok(bytes) => bytes,
         Err(err) => {
             warn!(
-                "Failed to invoke ffmpeg to compress '{}' for Discord; sending original: {}",
+                "Failed to read transcoded attachment for '{}': {}",
                 path.display(),
                 err
             );
-            (original_bytes, original_name)
+            let _ = tokio::fs::remove_file(&partial_path).await;
+            return None;
         }
+    };
+    if bytes.len() as u64 > max_bytes {
+        warn!(
+            "Transcoded '{}' is still {} bytes at {} kbps (limit {}); linking it instead",
+            path.display(),
+            bytes.len(),
+            kbps,
+            max_bytes
+        );
+        let _ = tokio::fs::remove_file(&partial_path).await;
+        return None;
+    }
+    if let Err(err) = tokio::fs::rename(&partial_path, &cache_path).await {
+        warn!(
+            "Failed to cache transcoded attachment at '{}': {}",
+            cache_path.display(),
+            err
+        );
+        let _ = tokio::fs::remove_file(&partial_path).await;
+    }
+
+    info!(
+        "Recording '{}' is {} bytes (over the {} byte attachment limit); attaching {} byte {} kbps Opus '{}' instead",
+        path.display(),
+        metadata.len(),
+        max_bytes,
+        bytes.len(),
+        kbps,
+        ogg_name
+    );
+    Some(RecordingAttachment {
+        bytes,
+        file_name: ogg_name,
+        mime: file_stream::content_type_for(&cache_path),
+    })
+}
+
+fn add_recording_link_to_embed(embed: &mut serde_json::Value, recording_link: &str) {
+    embed["url"] = json!(recording_link);
+    if let Some(fields) = embed["fields"].as_array_mut() {
+        fields.retain(|field| field["name"] != RECORDING_FIELD_NAME);
+        fields.insert(
+            0,
+            json!({
+                "name": "Recording (too large to attach)",
+                "value": truncate_discord_text(&format!("[Listen to the recording]({recording_link})"), 1024),
+                "inline": false
+            }),
+        );
     }
 }
 
@@ -571,16 +1661,33 @@ fn truncate_for_log(input: &str, max_bytes: usize) -> String {
     format!("{}...(truncated)", &input[..end])
 }
 
+struct AlertBodyContent<'a> {
+    title: &'a str,
+    originator: &'a str,
+    received_timestamp: &'a str,
+    eas_text: &'a str,
+    raw_header: &'a str,
+    description: Option<&'a str>,
+    heard_on: Option<&'a str>,
+    recording_link: Option<&'a str>,
+}
+
 fn build_discord_embed_body(
     stream_id: &str,
-    title: &str,
     event_code: &str,
-    originator: &str,
-    received_timestamp: &str,
-    eas_text: &str,
-    raw_header: &str,
-    description: Option<&str>,
+    filter_name: &str,
+    content: &AlertBodyContent,
 ) -> serde_json::Value {
+    let AlertBodyContent {
+        title,
+        originator,
+        received_timestamp,
+        eas_text,
+        raw_header,
+        description,
+        heard_on,
+        recording_link,
+    } = *content;
     let runtime_config = runtime_config_snapshot();
     let monitor_number = runtime_config
         .stream_index_map
@@ -591,7 +1698,6 @@ fn build_discord_embed_body(
         .chars()
         .filter(|c| c.is_ascii_alphabetic())
         .collect::<String>();
-    let filter_name = filter::determine_filter_name(&normalized_event_code);
 
     let img_name = if !normalized_event_code.is_empty() {
         normalized_event_code.as_str()
@@ -643,7 +1749,7 @@ fn build_discord_embed_body(
         }),
         json!({
             "name": "Filter",
-            "value": truncate_discord_text(filter_name.as_str(), 1024),
+            "value": truncate_discord_text(filter_name, 1024),
             "inline": true
         }),
         json!({
@@ -658,6 +1764,14 @@ fn build_discord_embed_body(
         }),
     ];
 
+    if let Some(value) = heard_on {
+        fields.push(json!({
+            "name": "Heard On Monitors",
+            "value": truncate_discord_text(value, 1024),
+            "inline": false
+        }));
+    }
+
     if let Some(value) = description {
         fields.push(json!({
             "name": "CAP Description:",
@@ -666,6 +1780,14 @@ fn build_discord_embed_body(
         }));
     }
 
+    if let Some(link) = recording_link {
+        fields.push(json!({
+            "name": RECORDING_FIELD_NAME,
+            "value": truncate_discord_text(&format!("[Listen to the recording]({link})"), 1024),
+            "inline": false
+        }));
+    }
+
     let embed = json!({
         "title": event_title,
         "color": img_color_dec,
@@ -680,30 +1802,43 @@ fn build_discord_embed_body(
     return embed;
 }
 
-fn build_markdown_body(
-    title: &str,
-    originator: &str,
-    received_timestamp: &str,
-    eas_text: &str,
-    raw_header: &str,
-    description: Option<&str>,
-) -> String {
+fn build_markdown_body(content: &AlertBodyContent) -> String {
+    let AlertBodyContent {
+        title,
+        originator,
+        received_timestamp,
+        eas_text,
+        raw_header,
+        description,
+        heard_on,
+        recording_link,
+    } = *content;
     let runtime_config = runtime_config_snapshot();
     let description_section = match description {
         Some(value) => format!("\n\n**CAP Description:**\n```\n{}\n```", value),
         None => String::new(),
     };
+    let heard_on_section = match heard_on {
+        Some(value) => format!("\n\n**Heard On Monitors:** {}", value),
+        None => String::new(),
+    };
+    let recording_section = match recording_link {
+        Some(link) => format!("\n\n**Recording:** [Listen to the recording](<{}>)", link),
+        None => String::new(),
+    };
 
     format!(
-        "**{} - Software ENDEC Logs**\n\n**{} {}** has just been received from: {}\n\n**Received:** {}\n\n**EAS Text Data:**\n```\n{}\n```\n\n**EAS Protocol Data:**\n```\n{}\n```{}\n\nPowered by [Wags' Software ENDEC]({})",
+        "**{} - Software ENDEC Logs**\n\n**{} {}** has just been received from: {}\n\n**Received:** {}{}\n\n**EAS Text Data:**\n```\n{}\n```\n\n**EAS Protocol Data:**\n```\n{}\n```{}{}\n\nPowered by [Wags' Software ENDEC]({})",
         runtime_config.station_name,
         a_or_an(title),
         title,
         originator,
         received_timestamp,
+        heard_on_section,
         eas_text.trim_end(),
         raw_header.trim_end(),
         description_section,
+        recording_section,
         github_url.as_str()
     )
 }
@@ -828,14 +1963,17 @@ fn discord_codeblock(content: &str, max_total_chars: usize) -> String {
     format!("```\n{}\n```", clipped)
 }
 
-fn build_html_body(
-    title: &str,
-    originator: &str,
-    received_timestamp: &str,
-    eas_text: &str,
-    raw_header: &str,
-    description: Option<&str>,
-) -> String {
+fn build_html_body(content: &AlertBodyContent) -> String {
+    let AlertBodyContent {
+        title,
+        originator,
+        received_timestamp,
+        eas_text,
+        raw_header,
+        description,
+        heard_on,
+        recording_link,
+    } = *content;
     let runtime_config = runtime_config_snapshot();
     let description_section = match description {
         Some(value) => format!(
@@ -844,53 +1982,84 @@ fn build_html_body(
         ),
         None => String::new(),
     };
+    let heard_on_section = match heard_on {
+        Some(value) => format!(
+            "<p><strong>Heard On Monitors:</strong> {}</p>",
+            html_escape(value)
+        ),
+        None => String::new(),
+    };
+    let recording_section = match recording_link {
+        Some(link) => format!(
+            "<p><strong>Recording:</strong> <a href=\"{}\">Listen to the recording</a></p>",
+            html_escape(link)
+        ),
+        None => String::new(),
+    };
 
     format!(
         "<p><strong>{} - Software ENDEC Logs</strong></p>\
          <p><strong>{} {}</strong> has just been received from: {}</p>\
          <p><strong>Received:</strong> {}</p>\
+         {}\
          <p><strong>EAS Text Data:</strong></p>\
          <pre>{}</pre>\
          <p><strong>EAS Protocol Data:</strong></p>\
          <pre>{}</pre>\
          {}\
+         {}\
          <p>Powered by <a href=\"{}\">Wags' Software ENDEC</a></p>",
         html_escape(&runtime_config.station_name),
         html_escape(a_or_an(title)),
         html_escape(title),
         html_escape(originator),
         html_escape(received_timestamp),
+        heard_on_section,
         html_escape(eas_text.trim_end()),
         html_escape(raw_header.trim_end()),
         description_section,
+        recording_section,
         github_url.as_str()
     )
 }
 
-fn build_plain_body(
-    title: &str,
-    originator: &str,
-    received_timestamp: &str,
-    eas_text: &str,
-    raw_header: &str,
-    description: Option<&str>,
-) -> String {
+fn build_plain_body(content: &AlertBodyContent) -> String {
+    let AlertBodyContent {
+        title,
+        originator,
+        received_timestamp,
+        eas_text,
+        raw_header,
+        description,
+        heard_on,
+        recording_link,
+    } = *content;
     let runtime_config = runtime_config_snapshot();
     let description_section = match description {
         Some(value) => format!("\n\nCAP Description:\n{}", value),
         None => String::new(),
     };
+    let heard_on_section = match heard_on {
+        Some(value) => format!("\nHeard On Monitors: {}", value),
+        None => String::new(),
+    };
+    let recording_section = match recording_link {
+        Some(link) => format!("\n\nRecording: {}", link),
+        None => String::new(),
+    };
 
     format!(
-        "{} - Software ENDEC Logs\n\n{} {} has just been received from: {}\nReceived: {}\n\nEAS Text Data:\n{}\n\nEAS Protocol Data:\n{}{}\n\nPowered by Wags' Software ENDEC ({})",
+        "{} - Software ENDEC Logs\n\n{} {} has just been received from: {}\nReceived: {}{}\n\nEAS Text Data:\n{}\n\nEAS Protocol Data:\n{}{}{}\n\nPowered by Wags' Software ENDEC ({})",
         runtime_config.station_name,
         a_or_an(title),
         title,
         originator,
         received_timestamp,
+        heard_on_section,
         eas_text.trim_end(),
         raw_header.trim_end(),
         description_section,
+        recording_section,
         github_url.as_str()
     )
 }
@@ -948,39 +2117,451 @@ mod tests {
 
         let embed = build_discord_embed_body(
             "unknown-stream",
-            "Tornado Warning",
             "TOR",
-            "The National Weather Service",
-            "2026-03-06 10:00:00 PM",
-            "Sample EAS text",
-            "ZCZC-WXR-TOR-031055+0030-1231645-KWO35-",
-            Some("CAP Description"),
+            "Default Filter",
+            &AlertBodyContent {
+                eas_text: "Sample EAS text",
+                raw_header: "ZCZC-WXR-TOR-031055+0030-1231645-KWO35-",
+                description: Some("CAP Description"),
+                heard_on: Some("1, 2, 4"),
+                recording_link: Some("https://eas.example.com/archive.php?recording_name=a.wav"),
+                ..sample_body_content()
+            },
         );
         let valid = json!({ "embeds": [embed] });
         let issues = validate_discord_payload(&valid);
         assert!(issues.is_empty(), "expected no issues, got: {:?}", issues);
     }
 
+    fn sample_body_content() -> AlertBodyContent<'static> {
+        AlertBodyContent {
+            title: "Tornado Warning",
+            originator: "The National Weather Service",
+            received_timestamp: "2026-03-06 10:00:00 PM",
+            eas_text: "Text",
+            raw_header: "Header",
+            description: None,
+            heard_on: None,
+            recording_link: None,
+        }
+    }
+
     #[test]
     fn markdown_and_plain_body_include_cap_description_when_present() {
-        let markdown = build_markdown_body(
-            "Tornado Warning",
-            "The National Weather Service",
-            "2026-03-06 10:00:00 PM",
-            "Text",
-            "Header",
-            Some("CAP details"),
-        );
+        let content = AlertBodyContent {
+            description: Some("CAP details"),
+            ..sample_body_content()
+        };
+        let markdown = build_markdown_body(&content);
         assert!(markdown.contains("CAP Description"));
 
-        let plain = build_plain_body(
-            "Tornado Warning",
-            "The National Weather Service",
-            "2026-03-06 10:00:00 PM",
-            "Text",
-            "Header",
-            Some("CAP details"),
-        );
+        let plain = build_plain_body(&content);
         assert!(plain.contains("CAP Description"));
     }
+
+    #[test]
+    fn every_body_links_the_recording_when_there_is_one() {
+        let link = "https://eas.example.com/archive.php?recording_name=EAS_Recording_1.wav&x=\"<b>";
+        let content = AlertBodyContent {
+            recording_link: Some(link),
+            ..sample_body_content()
+        };
+
+        let html = build_html_body(&content);
+        assert!(html.contains(
+            "<a href=\"https://eas.example.com/archive.php?recording_name=EAS_Recording_1.wav&amp;x=&quot;&lt;b&gt;\">Listen to the recording</a>"
+        ));
+        assert!(!html.contains(link));
+        assert!(
+            build_markdown_body(&content).contains(&format!("[Listen to the recording](<{link}>)"))
+        );
+        assert!(build_plain_body(&content).contains(&format!("Recording: {link}")));
+        let embed = build_discord_embed_body("stream", "TOR", "Default Filter", &content);
+        let fields = embed["fields"].as_array().expect("fields");
+        assert_eq!(fields.last().expect("field")["name"], RECORDING_FIELD_NAME);
+
+        let mut linked = embed.clone();
+        add_recording_link_to_embed(&mut linked, link);
+        let names: Vec<_> = linked["fields"]
+            .as_array()
+            .expect("fields")
+            .iter()
+            .map(|field| field["name"].as_str().unwrap_or_default().to_string())
+            .collect();
+        assert_eq!(names[0], "Recording (too large to attach)");
+        assert!(!names.iter().any(|name| name == RECORDING_FIELD_NAME));
+
+        let unlinked = sample_body_content();
+        assert!(!build_html_body(&unlinked).contains("Listen to the recording"));
+        assert!(!build_plain_body(&unlinked).contains("Recording:"));
+    }
+
+    #[test]
+    fn heard_on_summary_lists_monitor_numbers_when_enabled() {
+        let mut runtime_config = WebhookRuntimeConfig {
+            apprise_config_path: String::new(),
+            station_name: "Test".to_string(),
+            stream_index_map: HashMap::from([
+                ("stream-a".to_string(), 1),
+                ("stream-b".to_string(), 2),
+                ("stream-d".to_string(), 4),
+            ]),
+            show_heard_on: true,
+            ..WebhookRuntimeConfig::from_config(&Config::safe_internal_defaults())
+        };
+        let mut alert = ActiveAlert::new(
+            crate::state::EasAlertData {
+                eas_text: "Text".to_string(),
+                event_text: "Tornado Warning".to_string(),
+                event_code: "TOR".to_string(),
+                fips: vec!["031055".to_string()],
+                locations: "Douglas, NE".to_string(),
+                originator: "WXR".to_string(),
+                description: None,
+                parsed_header: None,
+            },
+            "ZCZC-WXR-TOR-031055+0030-1231645-KWO35-".to_string(),
+            std::time::Duration::from_secs(60),
+        )
+        .with_source_stream_url("stream-d");
+        assert_eq!(heard_on_summary(&runtime_config, &alert), None);
+
+        for url in ["stream-a", "stream-b"] {
+            alert.also_heard_on.push(crate::state::AlsoHeard {
+                stream_url: url.to_string(),
+                heard_at: chrono::Utc::now(),
+            });
+        }
+        assert_eq!(
+            heard_on_summary(&runtime_config, &alert).as_deref(),
+            Some("1, 2, 4")
+        );
+
+        runtime_config.show_heard_on = false;
+        assert_eq!(heard_on_summary(&runtime_config, &alert), None);
+
+        let markdown = build_markdown_body(&AlertBodyContent {
+            heard_on: Some("1, 2, 4"),
+            ..sample_body_content()
+        });
+        assert!(markdown.contains("**Heard On Monitors:** 1, 2, 4"));
+    }
+
+    #[test]
+    fn recording_deeplink_uses_learned_or_configured_dashboard_host() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let mut runtime_config = WebhookRuntimeConfig {
+            shared_state_dir: dir.path().to_path_buf(),
+            local_deeplink_host: "auto".to_string(),
+            web_server_port: "3010".to_string(),
+            use_reverse_proxy: false,
+            ..WebhookRuntimeConfig::from_config(&Config::safe_internal_defaults())
+        };
+        assert_eq!(
+            runtime_config.recording_deeplink("EAS_Recording_1.wav"),
+            None
+        );
+
+        fs::write(
+            dir.path().join(crate::backend::DEEPLINK_HOST_CACHE_FILE),
+            "listener.lan:8080\n",
+        )
+        .expect("write host");
+        assert_eq!(
+            runtime_config
+                .recording_deeplink("EAS_Recording_1.wav")
+                .as_deref(),
+            Some("http://listener.lan:3010/archive.php?recording_name=EAS_Recording_1.wav")
+        );
+
+        for loopback in ["localhost", "127.0.0.1", "[::1]"] {
+            runtime_config.local_deeplink_host = loopback.to_string();
+            assert_eq!(
+                runtime_config.recording_deeplink("EAS_Recording_1.wav"),
+                None
+            );
+        }
+
+        runtime_config.use_reverse_proxy = true;
+        runtime_config.reverse_proxy_url = "https://eas.example.com/".to_string();
+        assert_eq!(
+            runtime_config
+                .recording_deeplink("EAS Recording.wav")
+                .as_deref(),
+            Some("https://eas.example.com/archive.php?recording_name=EAS+Recording.wav")
+        );
+    }
+
+    #[test]
+    fn opus_bitrate_fits_the_limit_or_gives_up() {
+        assert_eq!(opus_bitrate_to_fit(9 * 1024 * 1024, 300.0), Some(128));
+        assert_eq!(opus_bitrate_to_fit(9 * 1024 * 1024, 3600.0), Some(18));
+        assert_eq!(opus_bitrate_to_fit(1024 * 1024, 3600.0), None);
+        assert_eq!(opus_bitrate_to_fit(1024, 0.0), Some(128));
+    }
+
+    #[tokio::test]
+    async fn discord_attachment_uses_original_or_cached_transcode() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let recording = dir.path().join("EAS_Recording_1.wav");
+        fs::write(&recording, vec![0u8; 4096]).expect("write recording");
+
+        let original = prepare_recording_attachment(&recording, 8192)
+            .await
+            .expect("original fits");
+        assert_eq!(original.file_name, "EAS_Recording_1.wav");
+        assert_eq!(original.mime, "audio/wav");
+        assert_eq!(original.bytes.len(), 4096);
+
+        let cache_path = discord_attachment_cache_path(&recording).expect("cache path");
+        assert_eq!(cache_path, dir.path().join(".EAS_Recording_1.discord.ogg"));
+        fs::write(&cache_path, b"OggS cached").expect("write cache");
+        let cached = prepare_recording_attachment(&recording, 1024)
+            .await
+            .expect("cached transcode");
+        assert_eq!(cached.file_name, "EAS_Recording_1.ogg");
+        assert_eq!(cached.mime, "audio/ogg");
+        assert_eq!(cached.bytes, b"OggS cached");
+    }
+
+    #[test]
+    fn recording_link_is_added_to_the_top_of_the_embed() {
+        let mut embed =
+            json!({ "title": "Alert", "fields": [{ "name": "Monitor", "value": "#1" }] });
+        add_recording_link_to_embed(
+            &mut embed,
+            "http://listener.lan:3010/archive.php?recording_name=EAS_Recording_1.wav",
+        );
+        assert_eq!(
+            embed["url"],
+            "http://listener.lan:3010/archive.php?recording_name=EAS_Recording_1.wav"
+        );
+        assert_eq!(
+            embed["fields"][0]["name"],
+            "Recording (too large to attach)"
+        );
+        assert_eq!(embed["fields"][1]["name"], "Monitor");
+        assert!(validate_discord_payload(&json!({ "embeds": [embed] })).is_empty());
+    }
+
+    #[test]
+    fn discord_mentions_resolve_by_code_then_severity_and_allow_their_targets() {
+        let mentions = vec![
+            ("TOR".to_string(), "<@&1234> <@!5678>".to_string()),
+            ("EAN".to_string(), "@here".to_string()),
+            ("warning".to_string(), "<@&42>".to_string()),
+        ];
+        assert_eq!(
+            discord_mention(&mentions, "tor", 3),
+            Some("<@&1234> <@!5678>")
+        );
+        assert_eq!(discord_mention(&mentions, "SVR", 3), Some("<@&42>"));
+        assert_eq!(discord_mention(&mentions, "RWT", 0), None);
+
+        assert_eq!(
+            discord_allowed_mentions("<@&1234> <@!5678> <@&1234>"),
+            json!({ "parse": [], "roles": ["1234"], "users": ["5678"] })
+        );
+        assert_eq!(
+            discord_allowed_mentions("@here"),
+            json!({ "parse": ["everyone"], "roles": [], "users": [] })
+        );
+
+        let embed = json!({ "title": "Alert" });
+        let payload = discord_alert_payload(&embed, discord_mention(&mentions, "EAN", 3));
+        assert_eq!(payload["content"], "@here");
+        assert_eq!(payload["embeds"][0], embed);
+        let payload = discord_alert_payload(&embed, None);
+        assert!(payload.get("content").is_none());
+        assert!(payload.get("allowed_mentions").is_none());
+        assert!(validate_discord_payload(&payload).is_empty());
+    }
+
+    #[test]
+    fn test_notification_records_a_short_dmo_burst_and_serializes_results() {
+        let raw_header = test_notification_header(&header::relay_station_id("TestRelay"));
+        assert!(raw_header.starts_with("ZCZC-EAS-DMO-000000+0015-"));
+        assert!(raw_header.ends_with("-TESTRELA-"));
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("test.wav");
+        write_test_recording(&path, &raw_header).expect("recording");
+        let reader = hound::WavReader::open(&path).expect("wav");
+        assert_eq!(reader.spec().sample_rate, TEST_NOTIFICATION_SAMPLE_RATE);
+        let secs = reader.duration() as f64 / TEST_NOTIFICATION_SAMPLE_RATE as f64;
+        assert!(secs > 1.0 && secs < 10.0, "unexpected duration {secs}");
+
+        let summary = DeliverySummary {
+            results: vec![EndpointResult {
+                endpoint: "smtp-0011aabb".to_string(),
+                outcome: DeliveryOutcome::DeliveredWithoutAttachment,
+            }],
+        };
+        assert_eq!(
+            serde_json::to_value(&summary).expect("json"),
+            json!({
+                "results": [{ "endpoint": "smtp-0011aabb", "outcome": "delivered_without_attachment" }]
+            })
+        );
+    }
+
+    #[test]
+    fn discord_rate_limit_delay_reads_headers_and_body() {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", "2".parse().expect("header"));
+        let (delay, global) =
+            discord_rate_limit_delay(&headers, r#"{"retry_after": 2.5, "global": false}"#);
+        assert_eq!(delay, Duration::from_millis(2500));
+        assert!(!global);
+
+        headers.insert("x-ratelimit-global", "true".parse().expect("header"));
+        let (delay, global) = discord_rate_limit_delay(&headers, "not json");
+        assert_eq!(delay, Duration::from_secs(2));
+        assert!(global);
+
+        let (delay, global) =
+            discord_rate_limit_delay(&HeaderMap::new(), r#"{"retry_after": 900}"#);
+        assert_eq!(delay, DISCORD_RATE_LIMIT_MAX_WAIT);
+        assert!(!global);
+        assert_eq!(
+            discord_rate_limit_delay(&HeaderMap::new(), "").0,
+            DISCORD_RATE_LIMIT_FALLBACK_WAIT
+        );
+
+        let mut bucket = HeaderMap::new();
+        bucket.insert("x-ratelimit-remaining", "1".parse().expect("header"));
+        bucket.insert("x-ratelimit-reset-after", "0.75".parse().expect("header"));
+        assert_eq!(discord_bucket_delay(&bucket), None);
+        bucket.insert("x-ratelimit-remaining", "0".parse().expect("header"));
+        assert_eq!(
+            discord_bucket_delay(&bucket),
+            Some(Duration::from_millis(750))
+        );
+    }
+
+    #[tokio::test]
+    async fn discord_sends_wait_out_rate_limits_then_give_up() {
+        use axum::http::StatusCode;
+        use axum::routing::post;
+        use std::sync::atomic::AtomicUsize;
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let app = axum::Router::new().route(
+            "/hook",
+            post({
+                let hits = hits.clone();
+                move || async move {
+                    if hits.fetch_add(1, Ordering::SeqCst).is_multiple_of(2) {
+                        (
+                            StatusCode::TOO_MANY_REQUESTS,
+                            r#"{"message": "You are being rate limited.", "retry_after": 0.05, "global": false}"#,
+                        )
+                    } else {
+                        (StatusCode::NO_CONTENT, "")
+                    }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let url = format!("http://{}/hook", listener.local_addr().expect("addr"));
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("serve");
+        });
+
+        let client = Client::new();
+        let form = || multipart::Form::new().text("payload_json", "{}");
+        match send_discord_rate_limited(&client, &url, "discord://test", 2, form).await {
+            DiscordSendOutcome::Response(response) => {
+                assert_eq!(response.status(), reqwest::StatusCode::NO_CONTENT)
+            }
+            _ => panic!("expected the retry to succeed"),
+        }
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+        assert!(matches!(
+            send_discord_rate_limited(&client, &url, "discord://test", 0, form).await,
+            DiscordSendOutcome::RateLimited
+        ));
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn apprise_api_tries_formats_and_reports_failure() {
+        use axum::http::StatusCode;
+        use axum::routing::post;
+        use axum::Json;
+
+        let formats = Arc::new(Mutex::new(Vec::new()));
+        let app = axum::Router::new().route(
+            "/notify/",
+            post({
+                let formats = formats.clone();
+                move |Json(payload): Json<serde_json::Value>| async move {
+                    let format = payload["format"].as_str().unwrap_or_default().to_string();
+                    assert_eq!(payload["urls"], "mailto://a,tgram://b");
+                    formats.lock().expect("formats").push(format.clone());
+                    if format == "html" {
+                        StatusCode::OK
+                    } else {
+                        StatusCode::FAILED_DEPENDENCY
+                    }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let api_url = format!("http://{}", listener.local_addr().expect("addr"));
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("serve");
+        });
+
+        assert_eq!(
+            apprise_api_notify_url("http://apprise:8000/"),
+            "http://apprise:8000/notify/"
+        );
+        assert_eq!(
+            apprise_api_notify_url("http://apprise:8000/notify"),
+            "http://apprise:8000/notify/"
+        );
+
+        let bodies = [
+            ("markdown", "**Test**".to_string()),
+            ("html", "<b>Test</b>".to_string()),
+            ("text", "Test".to_string()),
+        ];
+        let targets = ["mailto://a", "tgram://b"];
+        assert!(run_apprise_api(&api_url, "Title", &bodies, None, &targets).await);
+        assert_eq!(*formats.lock().expect("formats"), vec!["markdown", "html"]);
+
+        assert!(!run_apprise_api(&api_url, "Title", &bodies[..1], None, &targets).await);
+        assert!(!run_apprise_api("http://127.0.0.1:9", "Title", &bodies, None, &targets).await);
+    }
+
+    #[test]
+    fn delivery_summary_counts_and_describes_each_endpoint() {
+        let summary = DeliverySummary {
+            results: vec![
+                EndpointResult {
+                    endpoint: "discord-0a1b2c3d".to_string(),
+                    outcome: DeliveryOutcome::Delivered,
+                },
+                EndpointResult {
+                    endpoint: "tgram-11223344".to_string(),
+                    outcome: DeliveryOutcome::DeliveredWithoutAttachment,
+                },
+                EndpointResult {
+                    endpoint: "mailto-55667788".to_string(),
+                    outcome: DeliveryOutcome::Failed,
+                },
+            ],
+        };
+        assert_eq!(summary.delivered(), 2);
+        assert_eq!(
+            summary.to_string(),
+            "2/3 endpoint(s) delivered; discord-0a1b2c3d ok; tgram-11223344 ok without attachment; mailto-55667788 failed"
+        );
+    }
 }
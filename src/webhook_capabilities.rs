@@ -0,0 +1,217 @@
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+const CAPABILITY_STORE_FILE: &str = "webhook_capabilities.json";
+pub const ATTACHMENT_FAILURE_LIMIT: u32 = 3;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LearnedCapability {
+    pub attachment_failures: u32,
+    pub text_only: bool,
+    pub last_attachment_failure: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DestinationStatus {
+    pub id: String,
+    pub index: usize,
+    pub configured: Option<bool>,
+    pub learned: LearnedCapability,
+    pub supports_attachments: bool,
+}
+
+struct CapabilityStore {
+    path: PathBuf,
+    learned: BTreeMap<String, LearnedCapability>,
+}
+
+impl CapabilityStore {
+    fn load(path: PathBuf) -> Self {
+        let learned = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|err| {
+                warn!(
+                    "Ignoring unreadable webhook capability store {}: {}",
+                    path.display(),
+                    err
+                );
+                BTreeMap::new()
+            }),
+            Err(_) => BTreeMap::new(),
+        };
+        Self { path, learned }
+    }
+
+    fn persist(&self) {
+        let result = serde_json::to_vec_pretty(&self.learned)
+            .map_err(anyhow::Error::from)
+            .and_then(|payload| {
+                let tmp_path = self.path.with_extension("json.tmp");
+                std::fs::write(&tmp_path, payload)?;
+                std::fs::rename(&tmp_path, &self.path)?;
+                Ok(())
+            });
+        if let Err(err) = result {
+            warn!(
+                "Failed to persist webhook capability store {}: {}",
+                self.path.display(),
+                err
+            );
+        }
+    }
+}
+
+static STORE: Lazy<Mutex<Option<CapabilityStore>>> = Lazy::new(|| Mutex::new(None));
+
+fn with_store<R>(state_dir: &Path, update: impl FnOnce(&mut CapabilityStore) -> R) -> R {
+    let path = state_dir.join(CAPABILITY_STORE_FILE);
+    let mut guard = STORE
+        .lock()
+        .expect("webhook capability store lock poisoned");
+    if guard.as_ref().map(|store| &store.path) != Some(&path) {
+        *guard = Some(CapabilityStore::load(path));
+    }
+    update(guard.as_mut().expect("store loaded above"))
+}
+
+pub fn destination_id(url: &str) -> String {
+    let scheme = url
+        .split_once("://")
+        .map_or("unknown", |(scheme, _)| scheme);
+    let digest = Sha256::digest(url.trim().as_bytes());
+    let hash: String = digest[..4]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    format!("{scheme}-{hash}")
+}
+
+fn configured_support(configured: &[(String, bool)], url: &str) -> Option<bool> {
+    configured
+        .iter()
+        .filter(|(prefix, _)| url.starts_with(prefix.as_str()))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, supported)| *supported)
+}
+
+pub fn supports_attachments(state_dir: &Path, configured: &[(String, bool)], url: &str) -> bool {
+    if let Some(supported) = configured_support(configured, url) {
+        return supported;
+    }
+    let id = destination_id(url);
+    with_store(state_dir, |store| {
+        !store
+            .learned
+            .get(&id)
+            .is_some_and(|learned| learned.text_only)
+    })
+}
+
+pub fn record_attachment_failure(state_dir: &Path, url: &str) {
+    let id = destination_id(url);
+    with_store(state_dir, |store| {
+        let learned = store.learned.entry(id.clone()).or_default();
+        learned.attachment_failures = learned.attachment_failures.saturating_add(1);
+        learned.last_attachment_failure = Some(Utc::now());
+        if !learned.text_only && learned.attachment_failures >= ATTACHMENT_FAILURE_LIMIT {
+            learned.text_only = true;
+            info!(
+                "AppRise destination {} failed with attachments {} times; sending it text only from now on",
+                id, learned.attachment_failures
+            );
+        }
+        store.persist();
+    });
+}
+
+pub fn record_attachment_success(state_dir: &Path, url: &str) {
+    let id = destination_id(url);
+    with_store(state_dir, |store| {
+        if store.learned.remove(&id).is_some() {
+            store.persist();
+        }
+    });
+}
+
+pub fn destination_statuses(
+    state_dir: &Path,
+    configured: &[(String, bool)],
+    urls: &[String],
+) -> Vec<DestinationStatus> {
+    with_store(state_dir, |store| {
+        urls.iter()
+            .enumerate()
+            .map(|(index, url)| {
+                let id = destination_id(url);
+                let learned = store.learned.get(&id).cloned().unwrap_or_default();
+                let configured = configured_support(configured, url);
+                DestinationStatus {
+                    supports_attachments: configured.unwrap_or(!learned.text_only),
+                    id,
+                    index: index + 1,
+                    configured,
+                    learned,
+                }
+            })
+            .collect()
+    })
+}
+
+pub fn reset_learned(state_dir: &Path, id: Option<&str>) -> usize {
+    with_store(state_dir, |store| {
+        let removed = match id {
+            Some(id) => usize::from(store.learned.remove(id).is_some()),
+            None => std::mem::take(&mut store.learned).len(),
+        };
+        if removed > 0 {
+            store.persist();
+        }
+        removed
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn destinations_learn_text_only_and_can_be_reset() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let sms = "sns://AKIAEXAMPLE/secret/us-east-1/+15551234567";
+        let chat = "tgram://bottoken/ChatID";
+        let configured = vec![("tgram://".to_string(), false)];
+
+        assert!(supports_attachments(dir.path(), &configured, sms));
+        assert!(!supports_attachments(dir.path(), &configured, chat));
+
+        for _ in 0..ATTACHMENT_FAILURE_LIMIT {
+            assert!(supports_attachments(dir.path(), &configured, sms));
+            record_attachment_failure(dir.path(), sms);
+        }
+        assert!(!supports_attachments(dir.path(), &configured, sms));
+
+        let persisted = std::fs::read_to_string(dir.path().join(CAPABILITY_STORE_FILE))
+            .expect("persisted store");
+        assert!(!persisted.contains("secret"));
+
+        let statuses = destination_statuses(
+            dir.path(),
+            &configured,
+            &[sms.to_string(), chat.to_string()],
+        );
+        assert_eq!(statuses[0].id, destination_id(sms));
+        assert!(statuses[0].id.starts_with("sns-"));
+        assert!(statuses[0].learned.text_only);
+        assert_eq!(statuses[1].configured, Some(false));
+        assert_eq!(statuses[1].index, 2);
+
+        assert_eq!(reset_learned(dir.path(), Some(&destination_id(sms))), 1);
+        assert!(supports_attachments(dir.path(), &configured, sms));
+        assert_eq!(reset_learned(dir.path(), None), 0);
+    }
+}
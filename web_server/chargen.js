@@ -636,7 +636,8 @@
         }
 
         if (payload.type === "Alerts") {
-            applyLatestAlert(Array.isArray(payload.payload) ? payload.payload : []);
+            const alerts = Array.isArray(payload.payload) ? payload.payload : payload.payload?.alerts;
+            applyLatestAlert(Array.isArray(alerts) ? alerts : []);
         }
     }
 
@@ -668,10 +669,12 @@
 
     function connectWebSocket() {
         const protocol = window.location.protocol === "https:" ? "wss" : "ws";
-        const wsUrl = `${protocol}://${apiBase}/ws?auth=${encodeURIComponent(token)}`;
+        const wsUrl = `${protocol}://${apiBase}/ws`;
+        // The token travels as a subprotocol so it stays out of proxy logs and history.
+        const encodedToken = btoa(token).replace(/\+/g, "-").replace(/\//g, "_").replace(/=+$/, "");
 
         try {
-            state.ws = new WebSocket(wsUrl);
+            state.ws = new WebSocket(wsUrl, ["eas-listener", `eas-auth.${encodedToken}`]);
         } catch (_error) {
             scheduleReconnect();
             return;
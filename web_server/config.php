@@ -100,8 +100,30 @@ if (!function_exists("app_dashboard_password")) {
     }
 }
 
+if (!function_exists("app_dashboard_password_hash")) {
+    function app_dashboard_password_hash(): string {
+        return trim(app_string("DASHBOARD_PASSWORD_HASH", ""));
+    }
+}
+
+if (!function_exists("app_verify_dashboard_credentials")) {
+    function app_verify_dashboard_credentials(string $username, string $password): bool {
+        $userMatches = hash_equals(app_dashboard_username(), $username);
+        $hash = app_dashboard_password_hash();
+        if ($hash !== "") {
+            return password_verify($password, $hash) && $userMatches;
+        }
+        return hash_equals(app_dashboard_password(), $password) && $userMatches;
+    }
+}
+
 if (!function_exists("app_auth_token")) {
     function app_auth_token(): string {
+        // With a hashed password only the login form ever sees the plain text, so the
+        // token it builds is kept in the session.
+        if (app_dashboard_password_hash() !== "") {
+            return (string) ($_SESSION["auth_token"] ?? "");
+        }
         return base64_encode(app_dashboard_username() . ":" . app_dashboard_password());
     }
 }
@@ -116,12 +138,16 @@ if (!function_exists("app_request_is_authorized")) {
             }
         }
 
-        if ($provided === null) {
+        if ($provided === null || strncmp($provided, "Bearer ", 7) !== 0) {
             return false;
         }
 
-        $expected = "Bearer " . app_auth_token();
-        return hash_equals($expected, $provided);
+        $decoded = base64_decode(substr($provided, 7), true);
+        if ($decoded === false || !str_contains($decoded, ":")) {
+            return false;
+        }
+        [$username, $password] = explode(":", $decoded, 2);
+        return app_verify_dashboard_credentials($username, $password);
     }
 }
 
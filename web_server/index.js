@@ -101,6 +101,8 @@
 
     const elements = {
         wsStatus: document.getElementById("wsStatus"),
+        diskStatus: document.getElementById("diskStatus"),
+        uptimeStatus: document.getElementById("uptimeStatus"),
         streamGrid: document.getElementById("streamGrid"),
         streamCount: document.getElementById("streamCount"),
         alertList: document.getElementById("alertList"),
@@ -117,6 +119,69 @@
         elements.wsStatus.className = `ws-status ${statusClass || ""}`.trim();
     }
 
+    function formatBytes(bytes) {
+        const units = ["B", "KiB", "MiB", "GiB", "TiB"];
+        let value = bytes;
+        let unit = 0;
+        while (value >= 1024 && unit < units.length - 1) {
+            value /= 1024;
+            unit += 1;
+        }
+        return `${value.toFixed(unit === 0 ? 0 : 1)} ${units[unit]}`;
+    }
+
+    // Free space on the recording volume, from the periodic Resources message.
+    function renderDiskStatus(snapshot) {
+        const el = elements.diskStatus;
+        if (!el) return;
+        const disk = (snapshot?.disks || []).find((entry) => entry.name === "recordings");
+        if (!disk || typeof disk.available_bytes !== "number") {
+            el.hidden = true;
+            return;
+        }
+        let text = `Recordings: ${formatBytes(disk.available_bytes)} free`;
+        if (typeof disk.total_bytes === "number" && disk.total_bytes > 0) {
+            text += ` (${Math.round((disk.available_bytes / disk.total_bytes) * 100)}%)`;
+        }
+        const low = typeof disk.min_free_bytes === "number" && disk.available_bytes < disk.min_free_bytes;
+        el.textContent = text;
+        el.title = low
+            ? `Below the ${formatBytes(disk.min_free_bytes)} needed to start a recording`
+            : disk.path;
+        el.classList.toggle("low", low);
+        el.hidden = false;
+    }
+
+    const SHUTDOWN_KIND_LABELS = {
+        signal: "a requested shutdown",
+        task_exited: "a task exit",
+        panic: "a panic",
+        unclean: "an unclean exit",
+    };
+
+    // Uptime from /api/status, flagged when the previous instance did not stop on request.
+    function renderUptime(payload) {
+        const el = elements.uptimeStatus;
+        if (!el || typeof payload.uptime_seconds !== "number") return;
+        const previous = payload.previous_shutdown;
+        const unexpected = Boolean(previous && previous.kind !== "signal");
+        let text = `Up ${formatDuration(Math.max(payload.uptime_seconds, 1))}`;
+        if (unexpected) {
+            text += ` · restarted after ${SHUTDOWN_KIND_LABELS[previous.kind] || previous.kind}`;
+        }
+        const lines = [`Started ${formatTimestamp(payload.started_at)}`];
+        if (previous) {
+            lines.push(`Previous instance: ${previous.reason}`);
+            if (previous.panic && previous.kind !== "panic") {
+                lines.push(`Last panic: ${previous.panic}`);
+            }
+        }
+        el.textContent = text;
+        el.title = lines.join("\n");
+        el.classList.toggle("unexpected", unexpected);
+        el.hidden = false;
+    }
+
     function formatDuration(seconds) {
         if (!seconds || seconds <= 0) return "—";
         const abs = Math.floor(seconds);
@@ -188,6 +253,7 @@
         if (payload.cap_status !== undefined) {
             state.capStatus = payload.cap_status;
         }
+        renderUptime(payload);
         renderStreams();
         renderAlerts();
         renderCapStatus();
@@ -474,6 +540,10 @@
             ? formatDuration(stream.uptime_seconds)
             : "-";
 
+        const availability = typeof stream.availability_percent_24h === "number"
+            ? `${stream.availability_percent_24h.toFixed(1)}%`
+            : "-";
+
         const lastActivity = stream.last_activity
             ? formatTimestamp(stream.last_activity * 1000)
             : "Never";
@@ -511,6 +581,7 @@
                 <div><strong>Last audio:</strong> ${lastActivity}</div>
                 <div><strong>Last disconnect:</strong> ${lastDisconnect}</div>
                 <div><strong>Attempts:</strong> ${stream.connection_attempts}</div>
+                <div><strong>Availability (24h):</strong> ${availability}</div>
                 <div><strong>Last error:</strong> ${safeLastError}</div>
                 <div><strong>Alerts received:</strong> ${stream.alerts_received}</div>
                 <div><strong>Last alert received:</strong> ${safeLastAlertCode ? `${safeLastAlertCode} at ${lastAlertReceived}` : "-"} </div>
@@ -1304,18 +1375,27 @@
                         applyLogs([payload.payload]);
                     }
                     break;
-                case "Alerts":
-                    if (Array.isArray(payload.payload)) {
-                        setActiveAlerts(payload.payload);
+                case "Alerts": {
+                    // {alerts, reason, source_stream, triggering_event_code}; older
+                    // backends sent the bare array.
+                    const alerts = Array.isArray(payload.payload)
+                        ? payload.payload
+                        : payload.payload?.alerts;
+                    if (Array.isArray(alerts)) {
+                        setActiveAlerts(alerts);
                         renderAlerts();
                     }
                     break;
+                }
                 case "CapStatus":
                     if (payload.payload && typeof payload.payload === "object") {
                         state.capStatus = payload.payload;
                         renderCapStatus();
                     }
                     break;
+                case "Resources":
+                    renderDiskStatus(payload.payload);
+                    break;
                 default:
                     console.warn("Unhandled WS message type", payload.type);
             }
@@ -1330,11 +1410,13 @@
 
     function connectWebSocket() {
         const protocol = window.location.protocol === "https:" ? "wss" : "ws";
-        const url = `${protocol}://${window.API_BASE}/ws?auth=${encodeURIComponent(window.TOKEN)}`;
+        const url = `${protocol}://${window.API_BASE}/ws`;
+        // The token travels as a subprotocol so it stays out of proxy logs and history.
+        const encodedToken = btoa(window.TOKEN).replace(/\+/g, "-").replace(/\//g, "_").replace(/=+$/, "");
         setWsStatus("Connecting...", "");
 
         try {
-            ws = new WebSocket(url);
+            ws = new WebSocket(url, ["eas-listener", `eas-auth.${encodedToken}`]);
         } catch (err) {
             console.error("WebSocket init failed", err);
             scheduleReconnect();
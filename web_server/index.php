@@ -24,11 +24,9 @@ if (!session_id()) {
 }
 
 if (!empty($_POST["username"]) && !empty($_POST["password"])) {
-    $valid_user = app_dashboard_username();
-    $valid_pass = app_dashboard_password();
-
-    if ($_POST["username"] === $valid_user && $_POST["password"] === $valid_pass) {
+    if (app_verify_dashboard_credentials((string) $_POST["username"], (string) $_POST["password"])) {
         $_SESSION['authed'] = true;
+        $_SESSION['auth_token'] = base64_encode($_POST["username"] . ":" . $_POST["password"]);
         $redirect_target = $_SESSION['redirect'] ?? basename($_SERVER["SCRIPT_FILENAME"]);
         header("Location: " . basename($redirect_target), true, 303);
         exit();
@@ -125,6 +123,8 @@ if (!isset($_SESSION['authed'])) {
             <h1><img src="assets/favicon-96x96.png" alt="EAS Logo" class="logo" />EAS Monitoring Dashboard</h1>
             <div id="header-right">
                 <span id="wsStatus" class="ws-status">Connecting...</span>
+                <span id="diskStatus" class="pill disk-status" hidden></span>
+                <span id="uptimeStatus" class="pill uptime-status" hidden></span>
                 <div id="logout-container">
                     <button id="testAlertButton" class="custom-button button">Send Test Alert</button>
                     <button id="reloadButton" class="custom-button button">Reload Config/Backend</button>